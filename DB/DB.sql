@@ -64,10 +64,116 @@ CREATE TYPE public.user_status AS ENUM (
 -- Pending: В ожидании
 -- In_Progress: В процессе
 -- Done: Выполнено
+-- Cancelled: Отменена
+-- Paused: Приостановлена (учёт времени остановлен до возобновления)
 CREATE TYPE public.work_status AS ENUM (
     'Pending',
     'In_Progress',
-    'Done'
+    'Done',
+    'Cancelled',
+    'Paused'
+);
+
+-- Причины отмены заказа
+-- Client_Refused: Клиент отказался от ремонта
+-- Too_Expensive: Клиента не устроила стоимость
+-- Parts_Unavailable: Нет возможности получить запчасти
+-- Duplicate: Заказ создан ошибочно или повторно
+-- Other: Другая причина (описывается в комментарии)
+CREATE TYPE public.cancel_reason AS ENUM (
+    'Client_Refused',
+    'Too_Expensive',
+    'Parts_Unavailable',
+    'Duplicate',
+    'Other'
+);
+
+-- Типы движений по складу
+-- Receipt: Поступление
+-- Issue: Выдача в заказ
+-- Return: Возврат на склад
+-- Write_Off: Списание
+-- Correction: Корректировка по инвентаризации
+CREATE TYPE public.stock_movement_type AS ENUM (
+    'Receipt',
+    'Issue',
+    'Return',
+    'Write_Off',
+    'Correction'
+);
+
+-- Статусы заказов поставщикам
+-- Draft: Черновик
+-- Sent: Отправлен поставщику
+-- Partially_Received: Получен частично
+-- Received: Получен полностью
+CREATE TYPE public.purchase_order_status AS ENUM (
+    'Draft',
+    'Sent',
+    'Partially_Received',
+    'Received'
+);
+
+-- Способы оплаты
+-- Cash: Наличные
+-- Card: Банковская карта
+-- Transfer: Безналичный перевод на расчётный счёт
+CREATE TYPE public.payment_method AS ENUM (
+    'Cash',
+    'Card',
+    'Transfer'
+);
+
+-- Виды платежей по заказу
+-- Prepayment: Предоплата при приёмке автомобиля
+-- Payment: Оплата при выдаче автомобиля
+-- Refund: Возврат денег клиенту
+CREATE TYPE public.payment_kind AS ENUM (
+    'Prepayment',
+    'Payment',
+    'Refund'
+);
+
+-- Схемы оплаты труда слесарей
+-- Labour_Share: Процент от стоимости выполненных работ
+-- Norm_Hour_Rate: Ставка за нормо-час выполненных работ
+-- Salary_Bonus: Оклад за период плюс процент от стоимости выполненных работ
+CREATE TYPE public.pay_scheme AS ENUM (
+    'Labour_Share',
+    'Norm_Hour_Rate',
+    'Salary_Bonus'
+);
+
+-- Результат контроля качества заказа
+-- Passed: Все проверки пройдены, заказ готов к выдаче
+-- Failed: Есть непройденные проверки, работы возвращены на переделку
+CREATE TYPE public.qc_result AS ENUM (
+    'Passed',
+    'Failed'
+);
+
+-- Действия с неисправностями заказа при диагностике
+-- Added: Неисправность добавлена
+-- Updated: Изменён тип неисправности или комментарий диагноста
+-- Removed: Неисправность удалена вместе с предложенными по ней работами
+CREATE TYPE public.defect_change_action AS ENUM (
+    'Added',
+    'Updated',
+    'Removed'
+);
+
+-- Роль услуги для типа неисправности: обязательная работа создаётся при диагностике,
+-- рекомендуемая и дополнительная предлагаются мастеру на выбор
+CREATE TYPE public.defect_service_role AS ENUM (
+    'Mandatory',
+    'Recommended',
+    'Optional'
+);
+
+-- Тип значения замера: число с порогами или текст (например, коды ошибок OBD)
+CREATE TYPE public.measurement_value_type AS ENUM (
+    'Number',
+    'Text'
 );
 
 -- Таблица автомобилей клиентов
@@ -141,7 +247,8 @@ ALTER SEQUENCE public.defect_nodes_id_seq OWNED BY public.defect_nodes.id;
 CREATE TABLE public.defect_type_services (
     id integer NOT NULL, -- Уникальный идентификатор связи
     defect_type_id integer, -- Ссылка на тип неисправности
-    service_id integer -- Ссылка на услугу
+    service_id integer, -- Ссылка на услугу
+    role public.defect_service_role DEFAULT 'Mandatory'::public.defect_service_role NOT NULL -- Роль услуги: обязательная, рекомендуемая или дополнительная
 );
 
 -- Последовательность для генерации ID связей
@@ -210,11 +317,17 @@ CREATE TABLE public.order_parts (
     quantity integer DEFAULT 1, -- Количество запчастей
     price_per_unit numeric(10,2) NOT NULL, -- Цена за единицу
     source_type public.part_source DEFAULT 'Stock'::public.part_source, -- Источник запчасти (со склада или у поставщика)
-    is_issued boolean DEFAULT false, -- Выдана ли запчасть в производство
+    is_issued boolean DEFAULT false, -- Выдана ли запчасть в производство полностью
     issued_by integer, -- Кто выдал запчасть
     issued_at timestamp without time zone, -- Когда запчасть была выдана
     defect_id integer, -- Ссылка на неисправность, для устранения которой требовалась запчасть
-    is_confirmed boolean DEFAULT false -- Подтверждена ли необходимость запчасти клиентом
+    is_confirmed boolean DEFAULT false, -- Подтверждена ли необходимость запчасти клиентом
+    returned_at timestamp without time zone, -- Когда выданная запчасть была возвращена на склад
+    issued_quantity integer DEFAULT 0 NOT NULL, -- Количество, выданное в цех и не возвращённое на склад
+    issued_to integer, -- Ссылка на работника, которому выдана запчасть
+    supplier_id integer, -- Ссылка на поставщика из справочника (если поставщик в нём есть)
+    received_at timestamp without time zone, -- Когда заказанная у поставщика запчасть поступила на склад
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL -- Скидка на запчасть, в процентах
 );
 
 -- Последовательность для генерации ID запчастей по заказам
@@ -241,7 +354,10 @@ CREATE TABLE public.order_works (
     status public.work_status DEFAULT 'Pending'::public.work_status, -- Статус работы
     started_at timestamp without time zone, -- Время начала работы
     finished_at timestamp without time zone, -- Время окончания работы
-    is_confirmed boolean DEFAULT false -- Подтверждена ли работа клиентом
+    is_confirmed boolean DEFAULT false, -- Подтверждена ли работа клиентом
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL, -- Скидка на работу, в процентах
+    first_finished_at timestamp without time zone, -- Время первого завершения работы; по нему работа попадает в расчётную ведомость, переделка не оплачивается повторно
+    first_worker_id integer -- Исполнитель на момент первого завершения работы, которому начисляется оплата
 );
 
 -- Таблица связи работ и неисправностей
@@ -288,7 +404,16 @@ CREATE TABLE public.orders (
     total_amount numeric(10,2) DEFAULT 0.00, -- Общая сумма заказа
     created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата создания заказа
     completed_at timestamp without time zone, -- Дата завершения заказа
-    worker_id integer -- Ссылка на работника, который непосредственно выполняет заказ
+    worker_id integer, -- Ссылка на работника, который непосредственно выполняет заказ
+    cancel_reason public.cancel_reason, -- Причина отмены заказа
+    cancel_comment text, -- Комментарий к отмене заказа
+    cancelled_at timestamp without time zone, -- Дата отмены заказа
+    cancelled_by integer, -- Ссылка на пользователя, отменившего заказ
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL, -- Скидка на весь заказ, в процентах
+    subtotal numeric(10,2) DEFAULT 0.00 NOT NULL, -- Сумма подтверждённых работ и запчастей с учётом скидок по строкам
+    discount_amount numeric(10,2) DEFAULT 0.00 NOT NULL, -- Сумма скидки на заказ
+    vat_rate numeric(5,2) DEFAULT 0 NOT NULL, -- Ставка НДС, по которой рассчитан заказ, в процентах
+    vat_amount numeric(10,2) DEFAULT 0.00 NOT NULL -- Сумма НДС
 );
 
 -- Последовательность для генерации ID заказов
@@ -379,7 +504,10 @@ CREATE TABLE public.warehouse (
     quantity integer DEFAULT 0, -- Количество запчастей на складе
     min_quantity integer DEFAULT 2, -- Минимальное количество для уведомления о необходимости пополнения
     purchase_price numeric(10,2), -- Цена покупки запчасти
-    selling_price numeric(10,2) -- Цена продажи запчасти
+    selling_price numeric(10,2), -- Цена продажи запчасти
+    is_archived boolean DEFAULT false, -- Выведена ли позиция из оборота (архивная позиция не показывается в списке склада)
+    archived_at timestamp without time zone, -- Дата перевода позиции в архив
+    supplier_id integer -- Ссылка на поставщика, у которого позиция закупалась последней
 );
 
 -- Последовательность для генерации ID элементов склада
@@ -393,6 +521,540 @@ CREATE SEQUENCE public.warehouse_id_seq
 
 ALTER SEQUENCE public.warehouse_id_seq OWNED BY public.warehouse.id;
 
+-- Таблица сессий пользователей
+-- Хранит токены входа в систему, чтобы сессии переживали перезапуск приложения и были общими для всех рабочих мест
+CREATE TABLE public.sessions (
+    id integer NOT NULL, -- Уникальный идентификатор сессии
+    token character varying(64) NOT NULL, -- Токен сессии, выдаваемый клиенту при входе
+    user_id integer NOT NULL, -- Ссылка на пользователя, которому принадлежит сессия
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Время входа в систему
+    last_seen_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Время последнего обращения по сессии
+    expires_at timestamp without time zone NOT NULL, -- Абсолютное время истечения сессии
+    revoked_at timestamp without time zone, -- Время отзыва сессии (выход или принудительное завершение)
+    revoked_by integer -- Кто отозвал сессию (для принудительного завершения администратором)
+);
+
+-- Последовательность для генерации ID сессий
+CREATE SEQUENCE public.sessions_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.sessions_id_seq OWNED BY public.sessions.id;
+
+-- Журнал движений по складу
+-- Каждое изменение остатка позиции склада записывается отдельной строкой; warehouse.quantity равен сумме движений
+CREATE TABLE public.stock_movements (
+    id integer NOT NULL, -- Уникальный идентификатор движения
+    warehouse_item_id integer NOT NULL, -- Ссылка на позицию склада
+    movement_type public.stock_movement_type NOT NULL, -- Тип движения
+    quantity integer NOT NULL, -- Количество со знаком (приход положительный, расход отрицательный)
+    balance_after integer NOT NULL, -- Остаток позиции после движения
+    unit_cost numeric(10,2), -- Себестоимость единицы на момент движения
+    user_id integer, -- Ссылка на пользователя, выполнившего движение
+    order_part_id integer, -- Ссылка на запчасть заказа (для выдачи и возврата)
+    reason text, -- Причина или комментарий к движению
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата движения
+);
+
+-- Последовательность для генерации ID движений по складу
+CREATE SEQUENCE public.stock_movements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.stock_movements_id_seq OWNED BY public.stock_movements.id;
+
+-- Резервы запчастей склада под заказы
+-- Резерв создаётся при подтверждении клиентом запчасти со склада; зарезервированным считается ещё не выданное количество
+CREATE TABLE public.stock_reservations (
+    id integer NOT NULL, -- Уникальный идентификатор резерва
+    warehouse_item_id integer NOT NULL, -- Ссылка на позицию склада
+    order_part_id integer NOT NULL, -- Ссылка на запчасть заказа
+    quantity integer NOT NULL, -- Зарезервированное количество
+    created_by integer, -- Ссылка на пользователя, подтвердившего запчасть
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата создания резерва
+    released_at timestamp without time zone, -- Дата снятия резерва
+    release_reason character varying(100) -- Причина снятия резерва
+);
+
+-- Последовательность для генерации ID резервов
+CREATE SEQUENCE public.stock_reservations_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.stock_reservations_id_seq OWNED BY public.stock_reservations.id;
+
+-- Таблица поставщиков
+-- Содержит контактные данные поставщиков запчастей
+CREATE TABLE public.suppliers (
+    id integer NOT NULL, -- Уникальный идентификатор поставщика
+    name character varying(100) NOT NULL, -- Название поставщика
+    contact_person character varying(100), -- Контактное лицо
+    phone character varying(20), -- Телефон
+    email character varying(100), -- Электронная почта
+    notes text, -- Примечания (условия работы, сроки поставки)
+    is_active boolean DEFAULT true, -- Работаем ли с поставщиком
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата добавления поставщика
+    price_list_mapping jsonb, -- Сопоставление колонок прайс-листа поставщика, использованное при последнем импорте
+    price_list_imported_at timestamp without time zone -- Дата последнего импорта прайс-листа
+);
+
+-- Последовательность для генерации ID поставщиков
+CREATE SEQUENCE public.suppliers_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.suppliers_id_seq OWNED BY public.suppliers.id;
+
+-- Таблица заказов поставщикам
+-- Содержит заказы запчастей у поставщиков и их статусы
+CREATE TABLE public.purchase_orders (
+    id integer NOT NULL, -- Уникальный идентификатор заказа поставщику
+    supplier_id integer NOT NULL, -- Ссылка на поставщика
+    status public.purchase_order_status DEFAULT 'Draft'::public.purchase_order_status, -- Статус заказа поставщику
+    expected_date date, -- Ожидаемая дата поставки
+    comment text, -- Комментарий к заказу
+    created_by integer, -- Ссылка на пользователя, создавшего заказ
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата создания заказа
+    sent_at timestamp without time zone, -- Дата отправки заказа поставщику
+    received_at timestamp without time zone -- Дата полного получения заказа
+);
+
+-- Последовательность для генерации ID заказов поставщикам
+CREATE SEQUENCE public.purchase_orders_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.purchase_orders_id_seq OWNED BY public.purchase_orders.id;
+
+-- Таблица строк заказов поставщикам
+-- Каждая строка - запчасть, заказанная у поставщика на склад или под конкретный заказ клиента
+CREATE TABLE public.purchase_order_lines (
+    id integer NOT NULL, -- Уникальный идентификатор строки
+    purchase_order_id integer NOT NULL, -- Ссылка на заказ поставщику
+    warehouse_item_id integer, -- Ссылка на позицию склада (заполняется не позднее приёмки)
+    order_part_id integer, -- Ссылка на запчасть заказа клиента, под которую заказана позиция
+    name character varying(150) NOT NULL, -- Название запчасти
+    brand character varying(50), -- Бренд запчасти
+    article character varying(50), -- Артикул запчасти
+    quantity integer NOT NULL, -- Заказанное количество
+    received_quantity integer DEFAULT 0 NOT NULL, -- Полученное количество
+    unit_price numeric(10,2) -- Закупочная цена за единицу
+);
+
+-- Последовательность для генерации ID строк заказов поставщикам
+CREATE SEQUENCE public.purchase_order_lines_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.purchase_order_lines_id_seq OWNED BY public.purchase_order_lines.id;
+
+-- Таблица позиций прайс-листов поставщиков
+-- Содержит последний импортированный прайс-лист каждого поставщика
+CREATE TABLE public.supplier_price_items (
+    id integer NOT NULL, -- Уникальный идентификатор позиции прайс-листа
+    supplier_id integer NOT NULL, -- Ссылка на поставщика
+    brand character varying(50), -- Бренд запчасти
+    article character varying(50) NOT NULL, -- Артикул в том виде, в котором он указан в прайс-листе
+    article_normalized character varying(50) NOT NULL, -- Артикул без пробелов, дефисов и прочих разделителей в верхнем регистре
+    name character varying(150), -- Наименование запчасти
+    price numeric(10,2) NOT NULL, -- Цена поставщика
+    delivery_days integer DEFAULT 0 NOT NULL, -- Срок поставки в днях
+    stock_quantity integer, -- Наличие у поставщика (если указано в прайс-листе)
+    imported_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата импорта прайс-листа
+);
+
+-- Последовательность для генерации ID позиций прайс-листов
+CREATE SEQUENCE public.supplier_price_items_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.supplier_price_items_id_seq OWNED BY public.supplier_price_items.id;
+
+-- Таблица кросс-номеров (аналогов) запчастей
+-- Каждая строка объявляет взаимозаменяемыми две запчасти (бренд + артикул); связь действует в обе стороны
+CREATE TABLE public.part_cross_references (
+    id integer NOT NULL, -- Уникальный идентификатор связи
+    brand_a character varying(50) NOT NULL, -- Бренд первой запчасти (в верхнем регистре)
+    article_a character varying(50) NOT NULL, -- Артикул первой запчасти в исходном виде
+    article_a_normalized character varying(50) NOT NULL, -- Нормализованный артикул первой запчасти
+    brand_b character varying(50) NOT NULL, -- Бренд второй запчасти (в верхнем регистре)
+    article_b character varying(50) NOT NULL, -- Артикул второй запчасти в исходном виде
+    article_b_normalized character varying(50) NOT NULL, -- Нормализованный артикул второй запчасти
+    created_by integer, -- Ссылка на пользователя, добавившего связь
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата добавления связи
+);
+
+-- Последовательность для генерации ID связей кросс-номеров
+CREATE SEQUENCE public.part_cross_references_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.part_cross_references_id_seq OWNED BY public.part_cross_references.id;
+
+-- Таблица настроек системы (реквизиты компании, график работы, папка документов).
+-- Хранится одной строкой с id = 1, настройки - JSON-документ.
+CREATE TABLE public.system_settings (
+    id integer DEFAULT 1 NOT NULL, -- Идентификатор строки настроек (всегда 1)
+    settings jsonb NOT NULL, -- Настройки системы в формате JSON
+    updated_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата последнего изменения настроек
+    updated_by integer -- Ссылка на пользователя, изменившего настройки
+);
+
+-- Таблица платежей по заказам (предоплаты, оплаты и возвраты).
+-- Остаток к оплате = сумма заказа - (платежи - возвраты).
+CREATE TABLE public.payments (
+    id integer NOT NULL, -- Уникальный идентификатор платежа
+    order_id integer NOT NULL, -- Ссылка на заказ
+    kind public.payment_kind NOT NULL, -- Вид платежа
+    method public.payment_method NOT NULL, -- Способ оплаты
+    amount numeric(10,2) NOT NULL, -- Сумма платежа (для возврата - возвращённая сумма, всегда положительная)
+    refund_of integer, -- Ссылка на платёж, по которому сделан возврат
+    cashier_id integer, -- Ссылка на пользователя, принявшего платёж или оформившего возврат
+    comment text, -- Комментарий (для возврата - причина)
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата и время платежа
+);
+
+-- Последовательность для генерации ID платежей
+CREATE SEQUENCE public.payments_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.payments_id_seq OWNED BY public.payments.id;
+
+-- Интервалы выполнения работ слесарями
+-- Открытый интервал (finished_at IS NULL) означает, что работа выполняется прямо сейчас
+CREATE TABLE public.work_time_intervals (
+    id integer NOT NULL, -- Уникальный идентификатор интервала
+    work_id integer NOT NULL, -- Ссылка на работу заказа
+    worker_id integer NOT NULL, -- Слесарь, выполнявший работу
+    started_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP NOT NULL, -- Начало или возобновление работы
+    finished_at timestamp without time zone -- Пауза или завершение работы
+);
+
+-- Последовательность для генерации ID интервалов работ
+CREATE SEQUENCE public.work_time_intervals_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.work_time_intervals_id_seq OWNED BY public.work_time_intervals.id;
+
+-- Таблица схем оплаты труда сотрудников (одна действующая схема на сотрудника).
+-- Используется при расчёте ведомости заработной платы за период.
+CREATE TABLE public.worker_pay_schemes (
+    id integer NOT NULL, -- Уникальный идентификатор записи
+    worker_id integer NOT NULL, -- Ссылка на сотрудника
+    scheme public.pay_scheme NOT NULL, -- Схема оплаты
+    labour_share_percent numeric(5,2) DEFAULT 0 NOT NULL, -- Доля от стоимости работ, % (Labour_Share)
+    norm_hour_rate numeric(10,2) DEFAULT 0 NOT NULL, -- Ставка за нормо-час (Norm_Hour_Rate)
+    salary numeric(10,2) DEFAULT 0 NOT NULL, -- Оклад за месяц (Salary_Bonus)
+    bonus_percent numeric(5,2) DEFAULT 0 NOT NULL, -- Премия, % от стоимости работ (Salary_Bonus)
+    updated_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP, -- Дата последнего изменения схемы
+    updated_by integer -- Ссылка на пользователя, изменившего схему
+);
+
+-- Последовательность для генерации ID схем оплаты
+CREATE SEQUENCE public.worker_pay_schemes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.worker_pay_schemes_id_seq OWNED BY public.worker_pay_schemes.id;
+
+-- Таблица пунктов чек-листа контроля качества.
+-- Пункт относится к услуге, к узлу неисправностей или (если оба поля пусты) ко всем заказам.
+CREATE TABLE public.qc_checklist_items (
+    id integer NOT NULL, -- Уникальный идентификатор пункта
+    title character varying(255) NOT NULL, -- Что проверяется
+    service_id integer, -- Ссылка на услугу, после которой выполняется проверка
+    defect_node_id integer, -- Ссылка на узел неисправностей, к которому относится проверка
+    sort_order integer DEFAULT 0 NOT NULL, -- Порядок пункта в чек-листе
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата создания пункта
+);
+
+-- Последовательность для генерации ID пунктов чек-листа контроля качества
+CREATE SEQUENCE public.qc_checklist_items_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_checklist_items_id_seq OWNED BY public.qc_checklist_items.id;
+
+-- Таблица проверок контроля качества по заказам.
+-- Каждая приёмка работ мастером - отдельная запись, по ним прослеживаются возвраты на переделку.
+CREATE TABLE public.qc_inspections (
+    id integer NOT NULL, -- Уникальный идентификатор проверки
+    order_id integer NOT NULL, -- Ссылка на заказ
+    inspector_id integer, -- Ссылка на мастера, проводившего проверку
+    result public.qc_result NOT NULL, -- Итог проверки
+    comment text, -- Комментарий мастера
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата и время проверки
+);
+
+-- Последовательность для генерации ID проверок контроля качества
+CREATE SEQUENCE public.qc_inspections_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_inspections_id_seq OWNED BY public.qc_inspections.id;
+
+-- Таблица результатов по пунктам проверки контроля качества.
+-- Название пункта сохраняется на момент проверки, чтобы история не менялась при правке чек-листа.
+CREATE TABLE public.qc_inspection_results (
+    id integer NOT NULL, -- Уникальный идентификатор результата
+    inspection_id integer NOT NULL, -- Ссылка на проверку
+    checklist_item_id integer, -- Ссылка на пункт чек-листа
+    title character varying(255) NOT NULL, -- Название пункта на момент проверки
+    work_id integer, -- Ссылка на проверяемую работу заказа
+    passed boolean NOT NULL, -- Пройдена ли проверка
+    comment text, -- Замечание мастера
+    rework_worker_id integer -- Ссылка на слесаря, которому работа передана на переделку
+);
+
+-- Последовательность для генерации ID результатов проверок контроля качества
+CREATE SEQUENCE public.qc_inspection_results_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_inspection_results_id_seq OWNED BY public.qc_inspection_results.id;
+
+-- Таблица истории изменений дефектовочной ведомости заказа.
+-- Фиксирует, кто и когда добавил, изменил или удалил неисправность при диагностике.
+CREATE TABLE public.order_defect_changes (
+    id integer NOT NULL, -- Уникальный идентификатор записи
+    order_id integer NOT NULL, -- Ссылка на заказ
+    defect_id integer, -- ID неисправности (без внешнего ключа: удалённые неисправности остаются в истории)
+    action public.defect_change_action NOT NULL, -- Действие
+    details text NOT NULL, -- Что изменилось
+    changed_by integer, -- Ссылка на пользователя, внёсшего изменение
+    changed_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP -- Дата и время изменения
+);
+
+-- Последовательность для генерации ID записей истории изменений неисправностей
+CREATE SEQUENCE public.order_defect_changes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_defect_changes_id_seq OWNED BY public.order_defect_changes.id;
+
+-- Запчасти по умолчанию для связи тип неисправности - услуга
+-- Предлагаются в заказ вместе с работой
+CREATE TABLE public.defect_service_part_templates (
+    id integer NOT NULL, -- Уникальный идентификатор шаблона
+    defect_type_service_id integer NOT NULL, -- Ссылка на связь тип неисправности - услуга
+    article character varying(50) NOT NULL, -- Артикул запчасти (ищется на складе)
+    part_name character varying(150), -- Название запчасти, если её нет на складе
+    quantity integer DEFAULT 1 NOT NULL -- Количество
+);
+
+-- Последовательность для генерации ID шаблонов запчастей
+CREATE SEQUENCE public.defect_service_part_templates_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.defect_service_part_templates_id_seq OWNED BY public.defect_service_part_templates.id;
+
+-- Шаблоны замеров для типов неисправностей
+-- Задают единицу измерения и допустимый диапазон значения
+CREATE TABLE public.defect_type_measurements (
+    id integer NOT NULL, -- Уникальный идентификатор шаблона
+    defect_type_id integer NOT NULL, -- Ссылка на тип неисправности
+    name character varying(100) NOT NULL, -- Что измеряется, например 'Толщина колодок'
+    value_type public.measurement_value_type DEFAULT 'Number'::public.measurement_value_type NOT NULL, -- Тип значения
+    unit character varying(20), -- Единица измерения
+    min_value numeric(12,3), -- Минимально допустимое значение (для чисел)
+    max_value numeric(12,3), -- Максимально допустимое значение (для чисел)
+    flag_if_present boolean DEFAULT false NOT NULL, -- Для текста: любое заполненное значение считается отклонением
+    sort_order integer DEFAULT 0 NOT NULL -- Порядок в форме диагностики
+);
+
+-- Последовательность для генерации ID шаблонов замеров
+CREATE SEQUENCE public.defect_type_measurements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.defect_type_measurements_id_seq OWNED BY public.defect_type_measurements.id;
+
+-- Значения замеров по неисправностям заказа
+-- Название, единица и пороги копируются из шаблона на момент замера
+CREATE TABLE public.order_defect_measurements (
+    id integer NOT NULL, -- Уникальный идентификатор замера
+    order_defect_id integer NOT NULL, -- Ссылка на неисправность заказа
+    template_id integer, -- Ссылка на шаблон замера
+    name character varying(100) NOT NULL, -- Название замера
+    unit character varying(20), -- Единица измерения
+    min_value numeric(12,3), -- Нижний порог на момент замера
+    max_value numeric(12,3), -- Верхний порог на момент замера
+    numeric_value numeric(12,3), -- Числовое значение
+    text_value text, -- Текстовое значение
+    is_out_of_range boolean DEFAULT false NOT NULL, -- Значение вне допустимого диапазона
+    measured_by integer, -- Диагност, выполнивший замер
+    measured_at timestamp without time zone DEFAULT now() NOT NULL -- Время замера
+);
+
+-- Последовательность для генерации ID замеров
+CREATE SEQUENCE public.order_defect_measurements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_defect_measurements_id_seq OWNED BY public.order_defect_measurements.id;
+
+-- Справочник кодов неисправностей OBD-II (DTC)
+-- Заполняется стандартными кодами SAE из поставки приложения и импортом из CSV
+CREATE TABLE public.dtc_codes (
+    id integer NOT NULL, -- Уникальный идентификатор кода
+    code character varying(5) NOT NULL, -- Код неисправности, например P0300
+    description text NOT NULL, -- Расшифровка кода
+    updated_at timestamp without time zone DEFAULT now() NOT NULL -- Дата последнего обновления расшифровки
+);
+
+-- Последовательность для генерации ID кодов неисправностей OBD-II
+CREATE SEQUENCE public.dtc_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.dtc_codes_id_seq OWNED BY public.dtc_codes.id;
+
+-- Связь кодов OBD-II с типами неисправностей
+-- Через типы неисправностей код приводит к услугам из defect_type_services
+CREATE TABLE public.dtc_code_defect_types (
+    id integer NOT NULL, -- Уникальный идентификатор связи
+    dtc_code_id integer NOT NULL, -- Ссылка на код OBD-II
+    defect_type_id integer NOT NULL -- Ссылка на предлагаемый тип неисправности
+);
+
+-- Последовательность для генерации ID связей кодов OBD-II с неисправностями
+CREATE SEQUENCE public.dtc_code_defect_types_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.dtc_code_defect_types_id_seq OWNED BY public.dtc_code_defect_types.id;
+
+-- Коды OBD-II, считанные с автомобиля при диагностике заказа
+-- Код и расшифровка копируются на момент считывания
+CREATE TABLE public.order_dtc_codes (
+    id integer NOT NULL, -- Уникальный идентификатор записи
+    order_id integer NOT NULL, -- Ссылка на заказ
+    dtc_code_id integer, -- Ссылка на код в справочнике (если код известен)
+    code character varying(5) NOT NULL, -- Считанный код
+    description text, -- Расшифровка на момент считывания
+    added_by integer, -- Диагност, добавивший код
+    added_at timestamp without time zone DEFAULT now() NOT NULL -- Время добавления
+);
+
+-- Последовательность для генерации ID записей кодов OBD-II заказов
+CREATE SEQUENCE public.order_dtc_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_dtc_codes_id_seq OWNED BY public.order_dtc_codes.id;
+
+-- Запрещает слесарю вести одновременно несколько работ (открытых интервалов учёта времени).
+-- Параллельные вставки дополнительно отсекает уникальный индекс work_time_intervals_running_worker_idx
+CREATE FUNCTION public.check_work_overlap() RETURNS trigger
+    LANGUAGE plpgsql
+    AS $$
+DECLARE
+    overlap_count INTEGER;
+BEGIN
+    -- Проверяем что у слесаря нет других выполняемых работ
+    IF NEW.finished_at IS NULL THEN
+        SELECT COUNT(*) INTO overlap_count
+        FROM work_time_intervals 
+        WHERE worker_id = NEW.worker_id 
+          AND finished_at IS NULL 
+          AND id != COALESCE(NEW.id, 0);
+          
+        IF overlap_count > 0 THEN
+            RAISE EXCEPTION 'Слесарь уже выполняет другую работу';
+        END IF;
+    END IF;
+    
+    RETURN NEW;
+END;
+$$;
+
 -- Установка DEFAULT для столбцов ID с использованием соответствующих последовательностей
 ALTER TABLE ONLY public.cars ALTER COLUMN id SET DEFAULT nextval('public.cars_id_seq'::regclass);
 ALTER TABLE ONLY public.clients ALTER COLUMN id SET DEFAULT nextval('public.clients_id_seq'::regclass);
@@ -408,6 +1070,27 @@ ALTER TABLE ONLY public.services_reference ALTER COLUMN id SET DEFAULT nextval('
 ALTER TABLE ONLY public.system_logs ALTER COLUMN id SET DEFAULT nextval('public.system_logs_id_seq'::regclass);
 ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_id_seq'::regclass);
 ALTER TABLE ONLY public.warehouse ALTER COLUMN id SET DEFAULT nextval('public.warehouse_id_seq'::regclass);
+ALTER TABLE ONLY public.sessions ALTER COLUMN id SET DEFAULT nextval('public.sessions_id_seq'::regclass);
+ALTER TABLE ONLY public.stock_movements ALTER COLUMN id SET DEFAULT nextval('public.stock_movements_id_seq'::regclass);
+ALTER TABLE ONLY public.stock_reservations ALTER COLUMN id SET DEFAULT nextval('public.stock_reservations_id_seq'::regclass);
+ALTER TABLE ONLY public.suppliers ALTER COLUMN id SET DEFAULT nextval('public.suppliers_id_seq'::regclass);
+ALTER TABLE ONLY public.purchase_orders ALTER COLUMN id SET DEFAULT nextval('public.purchase_orders_id_seq'::regclass);
+ALTER TABLE ONLY public.purchase_order_lines ALTER COLUMN id SET DEFAULT nextval('public.purchase_order_lines_id_seq'::regclass);
+ALTER TABLE ONLY public.supplier_price_items ALTER COLUMN id SET DEFAULT nextval('public.supplier_price_items_id_seq'::regclass);
+ALTER TABLE ONLY public.part_cross_references ALTER COLUMN id SET DEFAULT nextval('public.part_cross_references_id_seq'::regclass);
+ALTER TABLE ONLY public.payments ALTER COLUMN id SET DEFAULT nextval('public.payments_id_seq'::regclass);
+ALTER TABLE ONLY public.work_time_intervals ALTER COLUMN id SET DEFAULT nextval('public.work_time_intervals_id_seq'::regclass);
+ALTER TABLE ONLY public.worker_pay_schemes ALTER COLUMN id SET DEFAULT nextval('public.worker_pay_schemes_id_seq'::regclass);
+ALTER TABLE ONLY public.qc_checklist_items ALTER COLUMN id SET DEFAULT nextval('public.qc_checklist_items_id_seq'::regclass);
+ALTER TABLE ONLY public.qc_inspections ALTER COLUMN id SET DEFAULT nextval('public.qc_inspections_id_seq'::regclass);
+ALTER TABLE ONLY public.qc_inspection_results ALTER COLUMN id SET DEFAULT nextval('public.qc_inspection_results_id_seq'::regclass);
+ALTER TABLE ONLY public.order_defect_changes ALTER COLUMN id SET DEFAULT nextval('public.order_defect_changes_id_seq'::regclass);
+ALTER TABLE ONLY public.defect_service_part_templates ALTER COLUMN id SET DEFAULT nextval('public.defect_service_part_templates_id_seq'::regclass);
+ALTER TABLE ONLY public.defect_type_measurements ALTER COLUMN id SET DEFAULT nextval('public.defect_type_measurements_id_seq'::regclass);
+ALTER TABLE ONLY public.order_defect_measurements ALTER COLUMN id SET DEFAULT nextval('public.order_defect_measurements_id_seq'::regclass);
+ALTER TABLE ONLY public.dtc_codes ALTER COLUMN id SET DEFAULT nextval('public.dtc_codes_id_seq'::regclass);
+ALTER TABLE ONLY public.dtc_code_defect_types ALTER COLUMN id SET DEFAULT nextval('public.dtc_code_defect_types_id_seq'::regclass);
+ALTER TABLE ONLY public.order_dtc_codes ALTER COLUMN id SET DEFAULT nextval('public.order_dtc_codes_id_seq'::regclass);
 
 -- Создание первичных ключей для всех таблиц
 ALTER TABLE ONLY public.cars ADD CONSTRAINT cars_pkey PRIMARY KEY (id);
@@ -428,6 +1111,66 @@ ALTER TABLE ONLY public.system_logs ADD CONSTRAINT system_logs_pkey PRIMARY KEY
 ALTER TABLE ONLY public.users ADD CONSTRAINT users_login_key UNIQUE (login);
 ALTER TABLE ONLY public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
 ALTER TABLE ONLY public.warehouse ADD CONSTRAINT warehouse_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.sessions ADD CONSTRAINT sessions_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.sessions ADD CONSTRAINT sessions_token_key UNIQUE (token);
+ALTER TABLE ONLY public.stock_movements ADD CONSTRAINT stock_movements_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.stock_movements ADD CONSTRAINT stock_movements_quantity_check CHECK (quantity <> 0);
+ALTER TABLE ONLY public.order_parts ADD CONSTRAINT order_parts_issued_quantity_check CHECK (issued_quantity >= 0 AND issued_quantity <= quantity);
+ALTER TABLE ONLY public.stock_reservations ADD CONSTRAINT stock_reservations_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.stock_reservations ADD CONSTRAINT stock_reservations_quantity_check CHECK (quantity > 0);
+ALTER TABLE ONLY public.suppliers ADD CONSTRAINT suppliers_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.suppliers ADD CONSTRAINT suppliers_name_key UNIQUE (name);
+ALTER TABLE ONLY public.purchase_orders ADD CONSTRAINT purchase_orders_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.purchase_order_lines ADD CONSTRAINT purchase_order_lines_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.purchase_order_lines ADD CONSTRAINT purchase_order_lines_quantity_check CHECK (quantity > 0 AND received_quantity >= 0 AND received_quantity <= quantity);
+ALTER TABLE ONLY public.supplier_price_items ADD CONSTRAINT supplier_price_items_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.part_cross_references ADD CONSTRAINT part_cross_references_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.part_cross_references ADD CONSTRAINT part_cross_references_pair_key UNIQUE (brand_a, article_a_normalized, brand_b, article_b_normalized);
+ALTER TABLE ONLY public.part_cross_references ADD CONSTRAINT part_cross_references_distinct_check CHECK (brand_a <> brand_b OR article_a_normalized <> article_b_normalized);
+ALTER TABLE ONLY public.system_settings ADD CONSTRAINT system_settings_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.system_settings ADD CONSTRAINT system_settings_single_row CHECK (id = 1);
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_amount_positive CHECK (amount > (0)::numeric);
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_refund_link CHECK (((kind = 'Refund'::public.payment_kind) = (refund_of IS NOT NULL)));
+ALTER TABLE ONLY public.order_works ADD CONSTRAINT order_works_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+ALTER TABLE ONLY public.order_parts ADD CONSTRAINT order_parts_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+ALTER TABLE ONLY public.work_time_intervals ADD CONSTRAINT work_time_intervals_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.work_time_intervals ADD CONSTRAINT work_time_intervals_period CHECK (((finished_at IS NULL) OR (finished_at >= started_at)));
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_worker_id_key UNIQUE (worker_id);
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_percents CHECK (labour_share_percent BETWEEN 0 AND 100 AND bonus_percent BETWEEN 0 AND 100);
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_amounts CHECK (norm_hour_rate >= 0 AND salary >= 0);
+ALTER TABLE ONLY public.qc_checklist_items ADD CONSTRAINT qc_checklist_items_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.qc_checklist_items ADD CONSTRAINT qc_checklist_items_scope CHECK (service_id IS NULL OR defect_node_id IS NULL);
+ALTER TABLE ONLY public.qc_inspections ADD CONSTRAINT qc_inspections_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.qc_inspection_results ADD CONSTRAINT qc_inspection_results_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.order_defect_changes ADD CONSTRAINT order_defect_changes_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.defect_service_part_templates ADD CONSTRAINT defect_service_part_templates_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.defect_service_part_templates ADD CONSTRAINT defect_service_part_templates_link_article_key UNIQUE (defect_type_service_id, article);
+ALTER TABLE ONLY public.defect_service_part_templates ADD CONSTRAINT defect_service_part_templates_quantity_check CHECK (quantity > 0);
+ALTER TABLE ONLY public.defect_type_measurements ADD CONSTRAINT defect_type_measurements_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.defect_type_measurements ADD CONSTRAINT defect_type_measurements_range_check CHECK (min_value IS NULL OR max_value IS NULL OR min_value <= max_value);
+ALTER TABLE ONLY public.order_defect_measurements ADD CONSTRAINT order_defect_measurements_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.order_defect_measurements ADD CONSTRAINT order_defect_measurements_defect_template_key UNIQUE (order_defect_id, template_id);
+ALTER TABLE ONLY public.dtc_codes ADD CONSTRAINT dtc_codes_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.dtc_codes ADD CONSTRAINT dtc_codes_code_key UNIQUE (code);
+ALTER TABLE ONLY public.dtc_code_defect_types ADD CONSTRAINT dtc_code_defect_types_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.dtc_code_defect_types ADD CONSTRAINT dtc_code_defect_types_dtc_code_id_defect_type_id_key UNIQUE (dtc_code_id, defect_type_id);
+ALTER TABLE ONLY public.order_dtc_codes ADD CONSTRAINT order_dtc_codes_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.order_dtc_codes ADD CONSTRAINT order_dtc_codes_order_id_code_key UNIQUE (order_id, code);
+CREATE INDEX stock_movements_warehouse_item_id_idx ON public.stock_movements USING btree (warehouse_item_id, created_at);
+CREATE UNIQUE INDEX stock_reservations_active_order_part_idx ON public.stock_reservations USING btree (order_part_id) WHERE (released_at IS NULL);
+CREATE INDEX stock_reservations_warehouse_item_id_idx ON public.stock_reservations USING btree (warehouse_item_id) WHERE (released_at IS NULL);
+CREATE INDEX supplier_price_items_article_normalized_idx ON public.supplier_price_items USING btree (article_normalized);
+CREATE INDEX supplier_price_items_supplier_id_idx ON public.supplier_price_items USING btree (supplier_id);
+CREATE INDEX part_cross_references_article_b_idx ON public.part_cross_references USING btree (article_b_normalized);
+CREATE INDEX idx_payments_order_id ON public.payments USING btree (order_id);
+CREATE UNIQUE INDEX work_time_intervals_running_worker_idx ON public.work_time_intervals USING btree (worker_id) WHERE (finished_at IS NULL);
+CREATE UNIQUE INDEX work_time_intervals_running_work_idx ON public.work_time_intervals USING btree (work_id) WHERE (finished_at IS NULL);
+CREATE INDEX qc_inspections_order_id_idx ON public.qc_inspections USING btree (order_id);
+CREATE INDEX qc_inspection_results_inspection_id_idx ON public.qc_inspection_results USING btree (inspection_id);
+CREATE INDEX order_defect_changes_order_id_idx ON public.order_defect_changes USING btree (order_id);
 
 -- Создание внешних ключей для обеспечения целостности данных
 ALTER TABLE ONLY public.cars ADD CONSTRAINT cars_client_id_fkey FOREIGN KEY (client_id) REFERENCES public.clients(id) ON DELETE CASCADE;
@@ -450,4 +1193,53 @@ ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_car_id_fkey FOREIGN KEY (ca
 ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_client_id_fkey FOREIGN KEY (client_id) REFERENCES public.clients(id) ON DELETE RESTRICT;
 ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_master_id_fkey FOREIGN KEY (master_id) REFERENCES public.users(id);
 ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_worker_id_fkey FOREIGN KEY (worker_id) REFERENCES public.users(id);
-ALTER TABLE ONLY public.system_logs ADD CONSTRAINT system_logs_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
\ No newline at end of file
+ALTER TABLE ONLY public.system_logs ADD CONSTRAINT system_logs_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.sessions ADD CONSTRAINT sessions_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.sessions ADD CONSTRAINT sessions_revoked_by_fkey FOREIGN KEY (revoked_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.orders ADD CONSTRAINT orders_cancelled_by_fkey FOREIGN KEY (cancelled_by) REFERENCES public.users(id);
+ALTER TABLE ONLY public.stock_movements ADD CONSTRAINT stock_movements_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+ALTER TABLE ONLY public.stock_movements ADD CONSTRAINT stock_movements_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.stock_movements ADD CONSTRAINT stock_movements_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_parts ADD CONSTRAINT order_parts_issued_to_fkey FOREIGN KEY (issued_to) REFERENCES public.users(id);
+ALTER TABLE ONLY public.stock_reservations ADD CONSTRAINT stock_reservations_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+ALTER TABLE ONLY public.stock_reservations ADD CONSTRAINT stock_reservations_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.stock_reservations ADD CONSTRAINT stock_reservations_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.purchase_orders ADD CONSTRAINT purchase_orders_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+ALTER TABLE ONLY public.purchase_orders ADD CONSTRAINT purchase_orders_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.purchase_order_lines ADD CONSTRAINT purchase_order_lines_purchase_order_id_fkey FOREIGN KEY (purchase_order_id) REFERENCES public.purchase_orders(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.purchase_order_lines ADD CONSTRAINT purchase_order_lines_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+ALTER TABLE ONLY public.purchase_order_lines ADD CONSTRAINT purchase_order_lines_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_parts ADD CONSTRAINT order_parts_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+ALTER TABLE ONLY public.warehouse ADD CONSTRAINT warehouse_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+ALTER TABLE ONLY public.supplier_price_items ADD CONSTRAINT supplier_price_items_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.part_cross_references ADD CONSTRAINT part_cross_references_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.system_settings ADD CONSTRAINT system_settings_updated_by_fkey FOREIGN KEY (updated_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_refund_of_fkey FOREIGN KEY (refund_of) REFERENCES public.payments(id);
+ALTER TABLE ONLY public.payments ADD CONSTRAINT payments_cashier_id_fkey FOREIGN KEY (cashier_id) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.work_time_intervals ADD CONSTRAINT work_time_intervals_work_id_fkey FOREIGN KEY (work_id) REFERENCES public.order_works(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.work_time_intervals ADD CONSTRAINT work_time_intervals_worker_id_fkey FOREIGN KEY (worker_id) REFERENCES public.users(id);
+CREATE TRIGGER work_time_intervals_check_overlap BEFORE INSERT OR UPDATE ON public.work_time_intervals FOR EACH ROW EXECUTE FUNCTION public.check_work_overlap();
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_worker_id_fkey FOREIGN KEY (worker_id) REFERENCES public.users(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.worker_pay_schemes ADD CONSTRAINT worker_pay_schemes_updated_by_fkey FOREIGN KEY (updated_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.qc_checklist_items ADD CONSTRAINT qc_checklist_items_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services_reference(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.qc_checklist_items ADD CONSTRAINT qc_checklist_items_defect_node_id_fkey FOREIGN KEY (defect_node_id) REFERENCES public.defect_nodes(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.qc_inspections ADD CONSTRAINT qc_inspections_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.qc_inspections ADD CONSTRAINT qc_inspections_inspector_id_fkey FOREIGN KEY (inspector_id) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.qc_inspection_results ADD CONSTRAINT qc_inspection_results_inspection_id_fkey FOREIGN KEY (inspection_id) REFERENCES public.qc_inspections(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.qc_inspection_results ADD CONSTRAINT qc_inspection_results_checklist_item_id_fkey FOREIGN KEY (checklist_item_id) REFERENCES public.qc_checklist_items(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.qc_inspection_results ADD CONSTRAINT qc_inspection_results_work_id_fkey FOREIGN KEY (work_id) REFERENCES public.order_works(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.qc_inspection_results ADD CONSTRAINT qc_inspection_results_rework_worker_id_fkey FOREIGN KEY (rework_worker_id) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_defect_changes ADD CONSTRAINT order_defect_changes_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.order_defect_changes ADD CONSTRAINT order_defect_changes_changed_by_fkey FOREIGN KEY (changed_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.defect_service_part_templates ADD CONSTRAINT defect_service_part_templates_link_fkey FOREIGN KEY (defect_type_service_id) REFERENCES public.defect_type_services(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.defect_type_measurements ADD CONSTRAINT defect_type_measurements_defect_type_id_fkey FOREIGN KEY (defect_type_id) REFERENCES public.defect_types(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.order_defect_measurements ADD CONSTRAINT order_defect_measurements_order_defect_id_fkey FOREIGN KEY (order_defect_id) REFERENCES public.order_defects(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.order_defect_measurements ADD CONSTRAINT order_defect_measurements_template_id_fkey FOREIGN KEY (template_id) REFERENCES public.defect_type_measurements(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_defect_measurements ADD CONSTRAINT order_defect_measurements_measured_by_fkey FOREIGN KEY (measured_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.dtc_code_defect_types ADD CONSTRAINT dtc_code_defect_types_dtc_code_id_fkey FOREIGN KEY (dtc_code_id) REFERENCES public.dtc_codes(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.dtc_code_defect_types ADD CONSTRAINT dtc_code_defect_types_defect_type_id_fkey FOREIGN KEY (defect_type_id) REFERENCES public.defect_types(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.order_dtc_codes ADD CONSTRAINT order_dtc_codes_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+ALTER TABLE ONLY public.order_dtc_codes ADD CONSTRAINT order_dtc_codes_dtc_code_id_fkey FOREIGN KEY (dtc_code_id) REFERENCES public.dtc_codes(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_dtc_codes ADD CONSTRAINT order_dtc_codes_added_by_fkey FOREIGN KEY (added_by) REFERENCES public.users(id) ON DELETE SET NULL;
+ALTER TABLE ONLY public.order_works ADD CONSTRAINT order_works_first_worker_id_fkey FOREIGN KEY (first_worker_id) REFERENCES public.users(id) ON DELETE SET NULL;
@@ -36,7 +36,72 @@ CREATE TYPE public.user_status AS ENUM (
 CREATE TYPE public.work_status AS ENUM (
     'Pending',
     'In_Progress',
-    'Done'
+    'Done',
+    'Cancelled',
+    'Paused'
+);
+
+CREATE TYPE public.cancel_reason AS ENUM (
+    'Client_Refused',
+    'Too_Expensive',
+    'Parts_Unavailable',
+    'Duplicate',
+    'Other'
+);
+
+CREATE TYPE public.stock_movement_type AS ENUM (
+    'Receipt',
+    'Issue',
+    'Return',
+    'Write_Off',
+    'Correction'
+);
+
+CREATE TYPE public.purchase_order_status AS ENUM (
+    'Draft',
+    'Sent',
+    'Partially_Received',
+    'Received'
+);
+
+CREATE TYPE public.payment_method AS ENUM (
+    'Cash',
+    'Card',
+    'Transfer'
+);
+
+CREATE TYPE public.payment_kind AS ENUM (
+    'Prepayment',
+    'Payment',
+    'Refund'
+);
+
+CREATE TYPE public.pay_scheme AS ENUM (
+    'Labour_Share',
+    'Norm_Hour_Rate',
+    'Salary_Bonus'
+);
+
+CREATE TYPE public.qc_result AS ENUM (
+    'Passed',
+    'Failed'
+);
+
+CREATE TYPE public.defect_change_action AS ENUM (
+    'Added',
+    'Updated',
+    'Removed'
+);
+
+CREATE TYPE public.defect_service_role AS ENUM (
+    'Mandatory',
+    'Recommended',
+    'Optional'
+);
+
+CREATE TYPE public.measurement_value_type AS ENUM (
+    'Number',
+    'Text'
 );
 
 CREATE FUNCTION public.calculate_warranty() RETURNS trigger
@@ -104,12 +169,12 @@ CREATE FUNCTION public.check_work_overlap() RETURNS trigger
 DECLARE
     overlap_count INTEGER;
 BEGIN
-    -- Проверяем что у слесаря нет активных работ в это же время
-    IF TG_OP = 'INSERT' AND NEW.end_time IS NULL THEN
+    -- Проверяем что у слесаря нет других выполняемых работ
+    IF NEW.finished_at IS NULL THEN
         SELECT COUNT(*) INTO overlap_count
-        FROM defect_work_log 
+        FROM work_time_intervals 
         WHERE worker_id = NEW.worker_id 
-          AND end_time IS NULL 
+          AND finished_at IS NULL 
           AND id != COALESCE(NEW.id, 0);
           
         IF overlap_count > 0 THEN
@@ -181,7 +246,8 @@ ALTER SEQUENCE public.defect_nodes_id_seq OWNED BY public.defect_nodes.id;
 CREATE TABLE public.defect_type_services (
     id integer NOT NULL,
     defect_type_id integer,
-    service_id integer
+    service_id integer,
+    role public.defect_service_role DEFAULT 'Mandatory'::public.defect_service_role NOT NULL
 );
 
 CREATE SEQUENCE public.defect_type_services_id_seq
@@ -245,7 +311,13 @@ CREATE TABLE public.order_parts (
     issued_by integer,
     issued_at timestamp without time zone,
     defect_id integer,
-    is_confirmed boolean DEFAULT false
+    is_confirmed boolean DEFAULT false,
+    returned_at timestamp without time zone,
+    issued_quantity integer DEFAULT 0 NOT NULL,
+    issued_to integer,
+    supplier_id integer,
+    received_at timestamp without time zone,
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL
 );
 
 CREATE SEQUENCE public.order_parts_id_seq
@@ -269,7 +341,10 @@ CREATE TABLE public.order_works (
     status public.work_status DEFAULT 'Pending'::public.work_status,
     started_at timestamp without time zone,
     finished_at timestamp without time zone,
-    is_confirmed boolean DEFAULT false
+    is_confirmed boolean DEFAULT false,
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL,
+    first_finished_at timestamp without time zone,
+    first_worker_id integer
 );
 
 CREATE TABLE public.order_works_defects (
@@ -310,7 +385,16 @@ CREATE TABLE public.orders (
     total_amount numeric(10,2) DEFAULT 0.00,
     created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
     completed_at timestamp without time zone,
-    worker_id integer
+    worker_id integer,
+    cancel_reason public.cancel_reason,
+    cancel_comment text,
+    cancelled_at timestamp without time zone,
+    cancelled_by integer,
+    discount_percent numeric(5,2) DEFAULT 0 NOT NULL,
+    subtotal numeric(10,2) DEFAULT 0.00 NOT NULL,
+    discount_amount numeric(10,2) DEFAULT 0.00 NOT NULL,
+    vat_rate numeric(5,2) DEFAULT 0 NOT NULL,
+    vat_amount numeric(10,2) DEFAULT 0.00 NOT NULL
 );
 
 CREATE SEQUENCE public.orders_id_seq
@@ -389,7 +473,10 @@ CREATE TABLE public.warehouse (
     quantity integer DEFAULT 0,
     min_quantity integer DEFAULT 2,
     purchase_price numeric(10,2),
-    selling_price numeric(10,2)
+    selling_price numeric(10,2),
+    is_archived boolean DEFAULT false,
+    archived_at timestamp without time zone,
+    supplier_id integer
 );
 
 CREATE SEQUENCE public.warehouse_id_seq
@@ -402,6 +489,450 @@ CREATE SEQUENCE public.warehouse_id_seq
 
 ALTER SEQUENCE public.warehouse_id_seq OWNED BY public.warehouse.id;
 
+CREATE TABLE public.sessions (
+    id integer NOT NULL,
+    token character varying(64) NOT NULL,
+    user_id integer NOT NULL,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    expires_at timestamp without time zone NOT NULL,
+    revoked_at timestamp without time zone,
+    revoked_by integer
+);
+
+CREATE SEQUENCE public.sessions_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.sessions_id_seq OWNED BY public.sessions.id;
+
+CREATE TABLE public.stock_movements (
+    id integer NOT NULL,
+    warehouse_item_id integer NOT NULL,
+    movement_type public.stock_movement_type NOT NULL,
+    quantity integer NOT NULL,
+    balance_after integer NOT NULL,
+    unit_cost numeric(10,2),
+    user_id integer,
+    order_part_id integer,
+    reason text,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.stock_movements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.stock_movements_id_seq OWNED BY public.stock_movements.id;
+
+CREATE TABLE public.stock_reservations (
+    id integer NOT NULL,
+    warehouse_item_id integer NOT NULL,
+    order_part_id integer NOT NULL,
+    quantity integer NOT NULL,
+    created_by integer,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    released_at timestamp without time zone,
+    release_reason character varying(100)
+);
+
+CREATE SEQUENCE public.stock_reservations_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.stock_reservations_id_seq OWNED BY public.stock_reservations.id;
+
+CREATE TABLE public.suppliers (
+    id integer NOT NULL,
+    name character varying(100) NOT NULL,
+    contact_person character varying(100),
+    phone character varying(20),
+    email character varying(100),
+    notes text,
+    is_active boolean DEFAULT true,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    price_list_mapping jsonb,
+    price_list_imported_at timestamp without time zone
+);
+
+CREATE SEQUENCE public.suppliers_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.suppliers_id_seq OWNED BY public.suppliers.id;
+
+CREATE TABLE public.purchase_orders (
+    id integer NOT NULL,
+    supplier_id integer NOT NULL,
+    status public.purchase_order_status DEFAULT 'Draft'::public.purchase_order_status,
+    expected_date date,
+    comment text,
+    created_by integer,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    sent_at timestamp without time zone,
+    received_at timestamp without time zone
+);
+
+CREATE SEQUENCE public.purchase_orders_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.purchase_orders_id_seq OWNED BY public.purchase_orders.id;
+
+CREATE TABLE public.purchase_order_lines (
+    id integer NOT NULL,
+    purchase_order_id integer NOT NULL,
+    warehouse_item_id integer,
+    order_part_id integer,
+    name character varying(150) NOT NULL,
+    brand character varying(50),
+    article character varying(50),
+    quantity integer NOT NULL,
+    received_quantity integer DEFAULT 0 NOT NULL,
+    unit_price numeric(10,2)
+);
+
+CREATE SEQUENCE public.purchase_order_lines_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.purchase_order_lines_id_seq OWNED BY public.purchase_order_lines.id;
+
+CREATE TABLE public.supplier_price_items (
+    id integer NOT NULL,
+    supplier_id integer NOT NULL,
+    brand character varying(50),
+    article character varying(50) NOT NULL,
+    article_normalized character varying(50) NOT NULL,
+    name character varying(150),
+    price numeric(10,2) NOT NULL,
+    delivery_days integer DEFAULT 0 NOT NULL,
+    stock_quantity integer,
+    imported_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.supplier_price_items_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.supplier_price_items_id_seq OWNED BY public.supplier_price_items.id;
+
+CREATE TABLE public.part_cross_references (
+    id integer NOT NULL,
+    brand_a character varying(50) NOT NULL,
+    article_a character varying(50) NOT NULL,
+    article_a_normalized character varying(50) NOT NULL,
+    brand_b character varying(50) NOT NULL,
+    article_b character varying(50) NOT NULL,
+    article_b_normalized character varying(50) NOT NULL,
+    created_by integer,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.part_cross_references_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.part_cross_references_id_seq OWNED BY public.part_cross_references.id;
+
+CREATE TABLE public.system_settings (
+    id integer DEFAULT 1 NOT NULL,
+    settings jsonb NOT NULL,
+    updated_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    updated_by integer
+);
+
+CREATE TABLE public.payments (
+    id integer NOT NULL,
+    order_id integer NOT NULL,
+    kind public.payment_kind NOT NULL,
+    method public.payment_method NOT NULL,
+    amount numeric(10,2) NOT NULL,
+    refund_of integer,
+    cashier_id integer,
+    comment text,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.payments_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.payments_id_seq OWNED BY public.payments.id;
+
+CREATE TABLE public.work_time_intervals (
+    id integer NOT NULL,
+    work_id integer NOT NULL,
+    worker_id integer NOT NULL,
+    started_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    finished_at timestamp without time zone
+);
+
+CREATE SEQUENCE public.work_time_intervals_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.work_time_intervals_id_seq OWNED BY public.work_time_intervals.id;
+
+CREATE TABLE public.worker_pay_schemes (
+    id integer NOT NULL,
+    worker_id integer NOT NULL,
+    scheme public.pay_scheme NOT NULL,
+    labour_share_percent numeric(5,2) DEFAULT 0 NOT NULL,
+    norm_hour_rate numeric(10,2) DEFAULT 0 NOT NULL,
+    salary numeric(10,2) DEFAULT 0 NOT NULL,
+    bonus_percent numeric(5,2) DEFAULT 0 NOT NULL,
+    updated_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP,
+    updated_by integer
+);
+
+CREATE SEQUENCE public.worker_pay_schemes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.worker_pay_schemes_id_seq OWNED BY public.worker_pay_schemes.id;
+
+CREATE TABLE public.qc_checklist_items (
+    id integer NOT NULL,
+    title character varying(255) NOT NULL,
+    service_id integer,
+    defect_node_id integer,
+    sort_order integer DEFAULT 0 NOT NULL,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.qc_checklist_items_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_checklist_items_id_seq OWNED BY public.qc_checklist_items.id;
+
+CREATE TABLE public.qc_inspections (
+    id integer NOT NULL,
+    order_id integer NOT NULL,
+    inspector_id integer,
+    result public.qc_result NOT NULL,
+    comment text,
+    created_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.qc_inspections_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_inspections_id_seq OWNED BY public.qc_inspections.id;
+
+CREATE TABLE public.qc_inspection_results (
+    id integer NOT NULL,
+    inspection_id integer NOT NULL,
+    checklist_item_id integer,
+    title character varying(255) NOT NULL,
+    work_id integer,
+    passed boolean NOT NULL,
+    comment text,
+    rework_worker_id integer
+);
+
+CREATE SEQUENCE public.qc_inspection_results_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.qc_inspection_results_id_seq OWNED BY public.qc_inspection_results.id;
+
+CREATE TABLE public.order_defect_changes (
+    id integer NOT NULL,
+    order_id integer NOT NULL,
+    defect_id integer,
+    action public.defect_change_action NOT NULL,
+    details text NOT NULL,
+    changed_by integer,
+    changed_at timestamp without time zone DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE SEQUENCE public.order_defect_changes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_defect_changes_id_seq OWNED BY public.order_defect_changes.id;
+
+CREATE TABLE public.defect_service_part_templates (
+    id integer NOT NULL,
+    defect_type_service_id integer NOT NULL,
+    article character varying(50) NOT NULL,
+    part_name character varying(150),
+    quantity integer DEFAULT 1 NOT NULL
+);
+
+CREATE SEQUENCE public.defect_service_part_templates_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.defect_service_part_templates_id_seq OWNED BY public.defect_service_part_templates.id;
+
+CREATE TABLE public.defect_type_measurements (
+    id integer NOT NULL,
+    defect_type_id integer NOT NULL,
+    name character varying(100) NOT NULL,
+    value_type public.measurement_value_type DEFAULT 'Number'::public.measurement_value_type NOT NULL,
+    unit character varying(20),
+    min_value numeric(12,3),
+    max_value numeric(12,3),
+    flag_if_present boolean DEFAULT false NOT NULL,
+    sort_order integer DEFAULT 0 NOT NULL
+);
+
+CREATE SEQUENCE public.defect_type_measurements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.defect_type_measurements_id_seq OWNED BY public.defect_type_measurements.id;
+
+CREATE TABLE public.order_defect_measurements (
+    id integer NOT NULL,
+    order_defect_id integer NOT NULL,
+    template_id integer,
+    name character varying(100) NOT NULL,
+    unit character varying(20),
+    min_value numeric(12,3),
+    max_value numeric(12,3),
+    numeric_value numeric(12,3),
+    text_value text,
+    is_out_of_range boolean DEFAULT false NOT NULL,
+    measured_by integer,
+    measured_at timestamp without time zone DEFAULT now() NOT NULL
+);
+
+CREATE SEQUENCE public.order_defect_measurements_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_defect_measurements_id_seq OWNED BY public.order_defect_measurements.id;
+
+CREATE TABLE public.dtc_codes (
+    id integer NOT NULL,
+    code character varying(5) NOT NULL,
+    description text NOT NULL,
+    updated_at timestamp without time zone DEFAULT now() NOT NULL
+);
+
+CREATE SEQUENCE public.dtc_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.dtc_codes_id_seq OWNED BY public.dtc_codes.id;
+
+CREATE TABLE public.dtc_code_defect_types (
+    id integer NOT NULL,
+    dtc_code_id integer NOT NULL,
+    defect_type_id integer NOT NULL
+);
+
+CREATE SEQUENCE public.dtc_code_defect_types_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.dtc_code_defect_types_id_seq OWNED BY public.dtc_code_defect_types.id;
+
+CREATE TABLE public.order_dtc_codes (
+    id integer NOT NULL,
+    order_id integer NOT NULL,
+    dtc_code_id integer,
+    code character varying(5) NOT NULL,
+    description text,
+    added_by integer,
+    added_at timestamp without time zone DEFAULT now() NOT NULL
+);
+
+CREATE SEQUENCE public.order_dtc_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER SEQUENCE public.order_dtc_codes_id_seq OWNED BY public.order_dtc_codes.id;
+
 ALTER TABLE ONLY public.cars ALTER COLUMN id SET DEFAULT nextval('public.cars_id_seq'::regclass);
 
 ALTER TABLE ONLY public.clients ALTER COLUMN id SET DEFAULT nextval('public.clients_id_seq'::regclass);
@@ -430,6 +961,48 @@ ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_
 
 ALTER TABLE ONLY public.warehouse ALTER COLUMN id SET DEFAULT nextval('public.warehouse_id_seq'::regclass);
 
+ALTER TABLE ONLY public.sessions ALTER COLUMN id SET DEFAULT nextval('public.sessions_id_seq'::regclass);
+
+ALTER TABLE ONLY public.stock_movements ALTER COLUMN id SET DEFAULT nextval('public.stock_movements_id_seq'::regclass);
+
+ALTER TABLE ONLY public.stock_reservations ALTER COLUMN id SET DEFAULT nextval('public.stock_reservations_id_seq'::regclass);
+
+ALTER TABLE ONLY public.suppliers ALTER COLUMN id SET DEFAULT nextval('public.suppliers_id_seq'::regclass);
+
+ALTER TABLE ONLY public.purchase_orders ALTER COLUMN id SET DEFAULT nextval('public.purchase_orders_id_seq'::regclass);
+
+ALTER TABLE ONLY public.purchase_order_lines ALTER COLUMN id SET DEFAULT nextval('public.purchase_order_lines_id_seq'::regclass);
+
+ALTER TABLE ONLY public.supplier_price_items ALTER COLUMN id SET DEFAULT nextval('public.supplier_price_items_id_seq'::regclass);
+
+ALTER TABLE ONLY public.part_cross_references ALTER COLUMN id SET DEFAULT nextval('public.part_cross_references_id_seq'::regclass);
+
+ALTER TABLE ONLY public.payments ALTER COLUMN id SET DEFAULT nextval('public.payments_id_seq'::regclass);
+
+ALTER TABLE ONLY public.work_time_intervals ALTER COLUMN id SET DEFAULT nextval('public.work_time_intervals_id_seq'::regclass);
+
+ALTER TABLE ONLY public.worker_pay_schemes ALTER COLUMN id SET DEFAULT nextval('public.worker_pay_schemes_id_seq'::regclass);
+
+ALTER TABLE ONLY public.qc_checklist_items ALTER COLUMN id SET DEFAULT nextval('public.qc_checklist_items_id_seq'::regclass);
+
+ALTER TABLE ONLY public.qc_inspections ALTER COLUMN id SET DEFAULT nextval('public.qc_inspections_id_seq'::regclass);
+
+ALTER TABLE ONLY public.qc_inspection_results ALTER COLUMN id SET DEFAULT nextval('public.qc_inspection_results_id_seq'::regclass);
+
+ALTER TABLE ONLY public.order_defect_changes ALTER COLUMN id SET DEFAULT nextval('public.order_defect_changes_id_seq'::regclass);
+
+ALTER TABLE ONLY public.defect_service_part_templates ALTER COLUMN id SET DEFAULT nextval('public.defect_service_part_templates_id_seq'::regclass);
+
+ALTER TABLE ONLY public.defect_type_measurements ALTER COLUMN id SET DEFAULT nextval('public.defect_type_measurements_id_seq'::regclass);
+
+ALTER TABLE ONLY public.order_defect_measurements ALTER COLUMN id SET DEFAULT nextval('public.order_defect_measurements_id_seq'::regclass);
+
+ALTER TABLE ONLY public.dtc_codes ALTER COLUMN id SET DEFAULT nextval('public.dtc_codes_id_seq'::regclass);
+
+ALTER TABLE ONLY public.dtc_code_defect_types ALTER COLUMN id SET DEFAULT nextval('public.dtc_code_defect_types_id_seq'::regclass);
+
+ALTER TABLE ONLY public.order_dtc_codes ALTER COLUMN id SET DEFAULT nextval('public.order_dtc_codes_id_seq'::regclass);
+
 ALTER TABLE ONLY public.cars
     ADD CONSTRAINT cars_pkey PRIMARY KEY (id);
 
@@ -484,6 +1057,174 @@ ALTER TABLE ONLY public.users
 ALTER TABLE ONLY public.warehouse
     ADD CONSTRAINT warehouse_pkey PRIMARY KEY (id);
 
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_token_key UNIQUE (token);
+
+ALTER TABLE ONLY public.stock_movements
+    ADD CONSTRAINT stock_movements_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.stock_movements
+    ADD CONSTRAINT stock_movements_quantity_check CHECK (quantity <> 0);
+
+ALTER TABLE ONLY public.order_parts
+    ADD CONSTRAINT order_parts_issued_quantity_check CHECK (issued_quantity >= 0 AND issued_quantity <= quantity);
+
+ALTER TABLE ONLY public.stock_reservations
+    ADD CONSTRAINT stock_reservations_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.stock_reservations
+    ADD CONSTRAINT stock_reservations_quantity_check CHECK (quantity > 0);
+
+ALTER TABLE ONLY public.suppliers
+    ADD CONSTRAINT suppliers_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.suppliers
+    ADD CONSTRAINT suppliers_name_key UNIQUE (name);
+
+ALTER TABLE ONLY public.purchase_orders
+    ADD CONSTRAINT purchase_orders_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.purchase_order_lines
+    ADD CONSTRAINT purchase_order_lines_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.purchase_order_lines
+    ADD CONSTRAINT purchase_order_lines_quantity_check CHECK (quantity > 0 AND received_quantity >= 0 AND received_quantity <= quantity);
+
+ALTER TABLE ONLY public.supplier_price_items
+    ADD CONSTRAINT supplier_price_items_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.part_cross_references
+    ADD CONSTRAINT part_cross_references_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.part_cross_references
+    ADD CONSTRAINT part_cross_references_pair_key UNIQUE (brand_a, article_a_normalized, brand_b, article_b_normalized);
+
+ALTER TABLE ONLY public.part_cross_references
+    ADD CONSTRAINT part_cross_references_distinct_check CHECK (brand_a <> brand_b OR article_a_normalized <> article_b_normalized);
+
+ALTER TABLE ONLY public.system_settings
+    ADD CONSTRAINT system_settings_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.system_settings
+    ADD CONSTRAINT system_settings_single_row CHECK (id = 1);
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_amount_positive CHECK (amount > (0)::numeric);
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_refund_link CHECK (((kind = 'Refund'::public.payment_kind) = (refund_of IS NOT NULL)));
+
+ALTER TABLE ONLY public.order_works
+    ADD CONSTRAINT order_works_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+
+ALTER TABLE ONLY public.order_parts
+    ADD CONSTRAINT order_parts_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+
+ALTER TABLE ONLY public.orders
+    ADD CONSTRAINT orders_discount_percent_range CHECK (((discount_percent >= (0)::numeric) AND (discount_percent <= (100)::numeric)));
+
+ALTER TABLE ONLY public.work_time_intervals
+    ADD CONSTRAINT work_time_intervals_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.work_time_intervals
+    ADD CONSTRAINT work_time_intervals_period CHECK (((finished_at IS NULL) OR (finished_at >= started_at)));
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_worker_id_key UNIQUE (worker_id);
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_percents CHECK (labour_share_percent BETWEEN 0 AND 100 AND bonus_percent BETWEEN 0 AND 100);
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_amounts CHECK (norm_hour_rate >= 0 AND salary >= 0);
+
+ALTER TABLE ONLY public.qc_checklist_items
+    ADD CONSTRAINT qc_checklist_items_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.qc_checklist_items
+    ADD CONSTRAINT qc_checklist_items_scope CHECK (service_id IS NULL OR defect_node_id IS NULL);
+
+ALTER TABLE ONLY public.qc_inspections
+    ADD CONSTRAINT qc_inspections_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.qc_inspection_results
+    ADD CONSTRAINT qc_inspection_results_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.order_defect_changes
+    ADD CONSTRAINT order_defect_changes_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.defect_service_part_templates
+    ADD CONSTRAINT defect_service_part_templates_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.defect_service_part_templates
+    ADD CONSTRAINT defect_service_part_templates_link_article_key UNIQUE (defect_type_service_id, article);
+
+ALTER TABLE ONLY public.defect_service_part_templates
+    ADD CONSTRAINT defect_service_part_templates_quantity_check CHECK (quantity > 0);
+
+ALTER TABLE ONLY public.defect_type_measurements
+    ADD CONSTRAINT defect_type_measurements_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.defect_type_measurements
+    ADD CONSTRAINT defect_type_measurements_range_check CHECK (min_value IS NULL OR max_value IS NULL OR min_value <= max_value);
+
+ALTER TABLE ONLY public.order_defect_measurements
+    ADD CONSTRAINT order_defect_measurements_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.order_defect_measurements
+    ADD CONSTRAINT order_defect_measurements_defect_template_key UNIQUE (order_defect_id, template_id);
+
+ALTER TABLE ONLY public.dtc_codes
+    ADD CONSTRAINT dtc_codes_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.dtc_codes
+    ADD CONSTRAINT dtc_codes_code_key UNIQUE (code);
+
+ALTER TABLE ONLY public.dtc_code_defect_types
+    ADD CONSTRAINT dtc_code_defect_types_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.dtc_code_defect_types
+    ADD CONSTRAINT dtc_code_defect_types_dtc_code_id_defect_type_id_key UNIQUE (dtc_code_id, defect_type_id);
+
+ALTER TABLE ONLY public.order_dtc_codes
+    ADD CONSTRAINT order_dtc_codes_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.order_dtc_codes
+    ADD CONSTRAINT order_dtc_codes_order_id_code_key UNIQUE (order_id, code);
+
+CREATE INDEX stock_movements_warehouse_item_id_idx ON public.stock_movements USING btree (warehouse_item_id, created_at);
+
+CREATE UNIQUE INDEX stock_reservations_active_order_part_idx ON public.stock_reservations USING btree (order_part_id) WHERE (released_at IS NULL);
+
+CREATE INDEX stock_reservations_warehouse_item_id_idx ON public.stock_reservations USING btree (warehouse_item_id) WHERE (released_at IS NULL);
+
+CREATE INDEX supplier_price_items_article_normalized_idx ON public.supplier_price_items USING btree (article_normalized);
+
+CREATE INDEX supplier_price_items_supplier_id_idx ON public.supplier_price_items USING btree (supplier_id);
+
+CREATE INDEX part_cross_references_article_b_idx ON public.part_cross_references USING btree (article_b_normalized);
+
+CREATE INDEX idx_payments_order_id ON public.payments USING btree (order_id);
+
+CREATE UNIQUE INDEX work_time_intervals_running_worker_idx ON public.work_time_intervals USING btree (worker_id) WHERE (finished_at IS NULL);
+
+CREATE UNIQUE INDEX work_time_intervals_running_work_idx ON public.work_time_intervals USING btree (work_id) WHERE (finished_at IS NULL);
+
+CREATE INDEX qc_inspections_order_id_idx ON public.qc_inspections USING btree (order_id);
+
+CREATE INDEX qc_inspection_results_inspection_id_idx ON public.qc_inspection_results USING btree (inspection_id);
+
+CREATE INDEX order_defect_changes_order_id_idx ON public.order_defect_changes USING btree (order_id);
+
 ALTER TABLE ONLY public.cars
     ADD CONSTRAINT cars_client_id_fkey FOREIGN KEY (client_id) REFERENCES public.clients(id) ON DELETE CASCADE;
 
@@ -547,4 +1288,148 @@ ALTER TABLE ONLY public.orders
 ALTER TABLE ONLY public.system_logs
     ADD CONSTRAINT system_logs_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
 
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
 
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_revoked_by_fkey FOREIGN KEY (revoked_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.orders
+    ADD CONSTRAINT orders_cancelled_by_fkey FOREIGN KEY (cancelled_by) REFERENCES public.users(id);
+
+ALTER TABLE ONLY public.stock_movements
+    ADD CONSTRAINT stock_movements_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+
+ALTER TABLE ONLY public.stock_movements
+    ADD CONSTRAINT stock_movements_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.stock_movements
+    ADD CONSTRAINT stock_movements_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_parts
+    ADD CONSTRAINT order_parts_issued_to_fkey FOREIGN KEY (issued_to) REFERENCES public.users(id);
+
+ALTER TABLE ONLY public.stock_reservations
+    ADD CONSTRAINT stock_reservations_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+
+ALTER TABLE ONLY public.stock_reservations
+    ADD CONSTRAINT stock_reservations_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.stock_reservations
+    ADD CONSTRAINT stock_reservations_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.purchase_orders
+    ADD CONSTRAINT purchase_orders_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+
+ALTER TABLE ONLY public.purchase_orders
+    ADD CONSTRAINT purchase_orders_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.purchase_order_lines
+    ADD CONSTRAINT purchase_order_lines_purchase_order_id_fkey FOREIGN KEY (purchase_order_id) REFERENCES public.purchase_orders(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.purchase_order_lines
+    ADD CONSTRAINT purchase_order_lines_warehouse_item_id_fkey FOREIGN KEY (warehouse_item_id) REFERENCES public.warehouse(id);
+
+ALTER TABLE ONLY public.purchase_order_lines
+    ADD CONSTRAINT purchase_order_lines_order_part_id_fkey FOREIGN KEY (order_part_id) REFERENCES public.order_parts(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_parts
+    ADD CONSTRAINT order_parts_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+
+ALTER TABLE ONLY public.warehouse
+    ADD CONSTRAINT warehouse_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id);
+
+ALTER TABLE ONLY public.supplier_price_items
+    ADD CONSTRAINT supplier_price_items_supplier_id_fkey FOREIGN KEY (supplier_id) REFERENCES public.suppliers(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.part_cross_references
+    ADD CONSTRAINT part_cross_references_created_by_fkey FOREIGN KEY (created_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.system_settings
+    ADD CONSTRAINT system_settings_updated_by_fkey FOREIGN KEY (updated_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_refund_of_fkey FOREIGN KEY (refund_of) REFERENCES public.payments(id);
+
+ALTER TABLE ONLY public.payments
+    ADD CONSTRAINT payments_cashier_id_fkey FOREIGN KEY (cashier_id) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.work_time_intervals
+    ADD CONSTRAINT work_time_intervals_work_id_fkey FOREIGN KEY (work_id) REFERENCES public.order_works(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.work_time_intervals
+    ADD CONSTRAINT work_time_intervals_worker_id_fkey FOREIGN KEY (worker_id) REFERENCES public.users(id);
+
+CREATE TRIGGER work_time_intervals_check_overlap BEFORE INSERT OR UPDATE ON public.work_time_intervals FOR EACH ROW EXECUTE FUNCTION public.check_work_overlap();
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_worker_id_fkey FOREIGN KEY (worker_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.worker_pay_schemes
+    ADD CONSTRAINT worker_pay_schemes_updated_by_fkey FOREIGN KEY (updated_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.qc_checklist_items
+    ADD CONSTRAINT qc_checklist_items_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services_reference(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.qc_checklist_items
+    ADD CONSTRAINT qc_checklist_items_defect_node_id_fkey FOREIGN KEY (defect_node_id) REFERENCES public.defect_nodes(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.qc_inspections
+    ADD CONSTRAINT qc_inspections_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.qc_inspections
+    ADD CONSTRAINT qc_inspections_inspector_id_fkey FOREIGN KEY (inspector_id) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.qc_inspection_results
+    ADD CONSTRAINT qc_inspection_results_inspection_id_fkey FOREIGN KEY (inspection_id) REFERENCES public.qc_inspections(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.qc_inspection_results
+    ADD CONSTRAINT qc_inspection_results_checklist_item_id_fkey FOREIGN KEY (checklist_item_id) REFERENCES public.qc_checklist_items(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.qc_inspection_results
+    ADD CONSTRAINT qc_inspection_results_work_id_fkey FOREIGN KEY (work_id) REFERENCES public.order_works(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.qc_inspection_results
+    ADD CONSTRAINT qc_inspection_results_rework_worker_id_fkey FOREIGN KEY (rework_worker_id) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_defect_changes
+    ADD CONSTRAINT order_defect_changes_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.order_defect_changes
+    ADD CONSTRAINT order_defect_changes_changed_by_fkey FOREIGN KEY (changed_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.defect_service_part_templates
+    ADD CONSTRAINT defect_service_part_templates_link_fkey FOREIGN KEY (defect_type_service_id) REFERENCES public.defect_type_services(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.defect_type_measurements
+    ADD CONSTRAINT defect_type_measurements_defect_type_id_fkey FOREIGN KEY (defect_type_id) REFERENCES public.defect_types(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.order_defect_measurements
+    ADD CONSTRAINT order_defect_measurements_order_defect_id_fkey FOREIGN KEY (order_defect_id) REFERENCES public.order_defects(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.order_defect_measurements
+    ADD CONSTRAINT order_defect_measurements_template_id_fkey FOREIGN KEY (template_id) REFERENCES public.defect_type_measurements(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_defect_measurements
+    ADD CONSTRAINT order_defect_measurements_measured_by_fkey FOREIGN KEY (measured_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.dtc_code_defect_types
+    ADD CONSTRAINT dtc_code_defect_types_dtc_code_id_fkey FOREIGN KEY (dtc_code_id) REFERENCES public.dtc_codes(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.dtc_code_defect_types
+    ADD CONSTRAINT dtc_code_defect_types_defect_type_id_fkey FOREIGN KEY (defect_type_id) REFERENCES public.defect_types(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.order_dtc_codes
+    ADD CONSTRAINT order_dtc_codes_order_id_fkey FOREIGN KEY (order_id) REFERENCES public.orders(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.order_dtc_codes
+    ADD CONSTRAINT order_dtc_codes_dtc_code_id_fkey FOREIGN KEY (dtc_code_id) REFERENCES public.dtc_codes(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_dtc_codes
+    ADD CONSTRAINT order_dtc_codes_added_by_fkey FOREIGN KEY (added_by) REFERENCES public.users(id) ON DELETE SET NULL;
+
+ALTER TABLE ONLY public.order_works
+    ADD CONSTRAINT order_works_first_worker_id_fkey FOREIGN KEY (first_worker_id) REFERENCES public.users(id) ON DELETE SET NULL;
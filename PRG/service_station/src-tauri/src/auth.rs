@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::database::Database;
+use crate::sessions;
+use crate::User;
+
+// Роли пользователей (соответствует ENUM user_role в БД)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserRole {
+    Admin,
+    Master,
+    Diagnostician,
+    Storekeeper,
+    Worker,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Admin => "Admin",
+            UserRole::Master => "Master",
+            UserRole::Diagnostician => "Diagnostician",
+            UserRole::Storekeeper => "Storekeeper",
+            UserRole::Worker => "Worker",
+        }
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Admin" => Ok(UserRole::Admin),
+            "Master" => Ok(UserRole::Master),
+            "Diagnostician" => Ok(UserRole::Diagnostician),
+            "Storekeeper" => Ok(UserRole::Storekeeper),
+            "Worker" => Ok(UserRole::Worker),
+            _ => Err(format!("Неизвестная роль пользователя: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for UserRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Права на группы команд
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ViewAllOrders,     // Общий список заказов и поиск
+    ViewOrderDetails,  // Работы, запчасти и неисправности заказа
+    ManageOrders,      // Создание заказов
+    ChangeOrderStatus, // Смена статуса (допустимые переходы проверяет order_status)
+    ViewClients,       // Клиенты и автомобили
+    ManageClients,     // Регистрация клиентов и автомобилей
+    Diagnose,          // Очередь диагностики и дефектовочная ведомость
+    SelectParts,       // Очередь кладовщика и подбор запчастей
+    ManageWarehouse,   // Складские позиции
+    ApproveOrders,     // Согласование работ и запчастей с клиентом
+    AssignWorkers,     // Назначение исполнителей
+    InspectQuality,    // Контроль качества и приёмка выполненных работ
+    ExecuteWorks,      // Выполнение работ в цехе
+    ViewCatalog,       // Справочники неисправностей и услуг
+    ManageCatalog,     // Редактирование справочников
+    ViewArchive,       // Архив заказов
+    ManageUsers,       // Учётные записи и сессии сотрудников
+    ViewLogs,          // Журнал событий
+    ManagePayments,    // Приём оплат, предоплат и оформление возвратов
+    ManagePayroll,     // Схемы оплаты труда и расчётные ведомости
+    ViewSettings,
+    ManageSettings,
+    Debug,             // Диагностические команды для отладки БД
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Матрица прав по ролям. Администратор имеет все права.
+pub fn role_has_permission(role: UserRole, permission: Permission) -> bool {
+    use Permission::*;
+
+    match role {
+        UserRole::Admin => true,
+        UserRole::Master => matches!(
+            permission,
+            ViewAllOrders
+                | ViewOrderDetails
+                | ManageOrders
+                | ChangeOrderStatus
+                | ViewClients
+                | ManageClients
+                | SelectParts
+                | ApproveOrders
+                | AssignWorkers
+                | InspectQuality
+                | ViewCatalog
+                | ViewArchive
+                | ViewSettings
+                | ManagePayments
+        ),
+        UserRole::Diagnostician => matches!(
+            permission,
+            ViewOrderDetails | ChangeOrderStatus | ViewClients | Diagnose | ViewCatalog | ViewSettings
+        ),
+        UserRole::Storekeeper => matches!(
+            permission,
+            ViewOrderDetails | ChangeOrderStatus | ViewClients | SelectParts | ManageWarehouse | ViewCatalog | ViewSettings
+        ),
+        UserRole::Worker => matches!(
+            permission,
+            ChangeOrderStatus | ViewClients | ExecuteWorks | ViewCatalog | ViewSettings
+        ),
+    }
+}
+
+// Определяет пользователя по сессии и проверяет его право на выполнение команды.
+// Отказы в доступе записываются в system_logs.
+pub async fn authorize(
+    state: &tauri::State<'_, Database>,
+    session_token: &str,
+    permission: Permission,
+    command: &str,
+) -> Result<User, String> {
+    let user = sessions::require_user(&state.pool, session_token).await?;
+
+    let allowed = user
+        .role
+        .parse::<UserRole>()
+        .map(|role| role_has_permission(role, permission))
+        .unwrap_or(false);
+
+    if allowed {
+        return Ok(user);
+    }
+
+    Err(deny(state, &user, command, &format!("требуется право {}", permission)).await)
+}
+
+// Записывает отказ в доступе в system_logs и возвращает текст ошибки для интерфейса
+pub async fn deny(state: &tauri::State<'_, Database>, user: &User, command: &str, reason: &str) -> String {
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Access_Denied".to_string(),
+        format!("Отказано в доступе: пользователь '{}' с ролью '{}' вызвал команду '{}' ({})",
+                user.full_name, user.role, command, reason),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging access denial: {}", e);
+    }
+
+    "Недостаточно прав для выполнения операции".to_string()
+}
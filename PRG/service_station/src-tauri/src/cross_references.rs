@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::{escape_like, Database};
+use crate::parts_providers::normalize_article;
+use crate::price_lists::{self, RejectedRow, INSERT_BATCH_SIZE, MAX_REPORTED_REJECTIONS};
+
+// Ограничение списка связей в окне справочника
+const MAX_LISTED_REFERENCES: i64 = 500;
+const MAX_BRAND_LENGTH: usize = 50;
+const MAX_ARTICLE_LENGTH: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CrossReference {
+    id: i32,
+    brand_a: String,
+    article_a: String,
+    brand_b: String,
+    article_b: String,
+    created_by_name: Option<String>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct CrossReferenceRequest {
+    #[serde(rename = "brandA")]
+    brand_a: String,
+    #[serde(rename = "articleA")]
+    article_a: String,
+    #[serde(rename = "brandB")]
+    brand_b: String,
+    #[serde(rename = "articleB")]
+    article_b: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CrossReferenceImportReport {
+    total_rows: usize,
+    imported: usize,
+    duplicates: usize, // Связи, которые уже были в справочнике или повторяются в файле
+    rejected_count: usize,
+    rejected: Vec<RejectedRow>,
+}
+
+// Запчасть стороны связи: бренд в верхнем регистре, артикул в исходном и нормализованном виде
+struct PartKey {
+    brand: String,
+    article: String,
+    article_normalized: String,
+}
+
+// Проверенная связь, стороны упорядочены так, чтобы пара A-B и B-A хранилась одной строкой
+struct Pair {
+    a: PartKey,
+    b: PartKey,
+}
+
+fn part_key(brand: &str, article: &str) -> Result<PartKey, String> {
+    let brand = brand.trim().to_uppercase();
+    let article = article.trim();
+    if brand.is_empty() {
+        return Err("Не указан бренд".to_string());
+    }
+    if brand.chars().count() > MAX_BRAND_LENGTH {
+        return Err(format!("Бренд длиннее {} символов", MAX_BRAND_LENGTH));
+    }
+    if article.chars().count() > MAX_ARTICLE_LENGTH {
+        return Err(format!("Артикул длиннее {} символов", MAX_ARTICLE_LENGTH));
+    }
+    let article_normalized = normalize_article(article);
+    if article_normalized.is_empty() {
+        return Err(format!("Некорректный артикул '{}' бренда {}", article, brand));
+    }
+
+    Ok(PartKey { brand, article: article.to_string(), article_normalized })
+}
+
+fn make_pair(brand_a: &str, article_a: &str, brand_b: &str, article_b: &str) -> Result<Pair, String> {
+    let first = part_key(brand_a, article_a)?;
+    let second = part_key(brand_b, article_b)?;
+
+    let first_key = (&first.brand, &first.article_normalized);
+    let second_key = (&second.brand, &second.article_normalized);
+    if first_key == second_key {
+        return Err("Запчасть не может быть аналогом самой себя".to_string());
+    }
+
+    Ok(if first_key < second_key { Pair { a: first, b: second } } else { Pair { a: second, b: first } })
+}
+
+// Аналоги артикула (любого бренда): пары (бренд, нормализованный артикул) с другой стороны связей
+pub async fn find_analogs(pool: &PgPool, article_normalized: &str) -> Result<Vec<(String, String)>, String> {
+    if article_normalized.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = "SELECT brand_b AS brand, article_b_normalized AS article FROM part_cross_references WHERE article_a_normalized = $1
+                 UNION
+                 SELECT brand_a, article_a_normalized FROM part_cross_references WHERE article_b_normalized = $1";
+    let rows = sqlx::query(query)
+        .bind(article_normalized)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| (row.get("brand"), row.get("article"))).collect())
+}
+
+#[tauri::command]
+pub async fn get_cross_references(session_token: String, search: Option<String>, state: tauri::State<'_, Database>) -> Result<Vec<CrossReference>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "get_cross_references").await?;
+
+    let search = search.as_deref().map(str::trim).unwrap_or("").to_string();
+    let query = "SELECT c.id, c.brand_a, c.article_a, c.brand_b, c.article_b, u.full_name AS created_by_name, c.created_at::text AS created_at
+                 FROM part_cross_references c
+                 LEFT JOIN users u ON c.created_by = u.id
+                 WHERE $1 = '' OR c.brand_a ILIKE '%' || $1 || '%' OR c.brand_b ILIKE '%' || $1 || '%'
+                    OR (length($2) > 0 AND (c.article_a_normalized LIKE '%' || $2 || '%' OR c.article_b_normalized LIKE '%' || $2 || '%'))
+                 ORDER BY c.brand_a, c.article_a_normalized, c.brand_b
+                 LIMIT $3";
+    let rows = sqlx::query(query)
+        .bind(escape_like(&search))
+        .bind(normalize_article(&search))
+        .bind(MAX_LISTED_REFERENCES)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut references = Vec::new();
+    for row in rows {
+        references.push(CrossReference {
+            id: row.get("id"),
+            brand_a: row.get("brand_a"),
+            article_a: row.get("article_a"),
+            brand_b: row.get("brand_b"),
+            article_b: row.get("article_b"),
+            created_by_name: row.get("created_by_name"),
+            created_at: row.get("created_at"),
+        });
+    }
+
+    Ok(references)
+}
+
+#[tauri::command]
+pub async fn add_cross_reference(session_token: String, request: CrossReferenceRequest, state: tauri::State<'_, Database>) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "add_cross_reference").await?;
+
+    let pair = make_pair(&request.brand_a, &request.article_a, &request.brand_b, &request.article_b)?;
+
+    let query = "INSERT INTO part_cross_references (brand_a, article_a, article_a_normalized, brand_b, article_b, article_b_normalized, created_by)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (brand_a, article_a_normalized, brand_b, article_b_normalized) DO NOTHING
+                 RETURNING id";
+    let row = sqlx::query(query)
+        .bind(&pair.a.brand)
+        .bind(&pair.a.article)
+        .bind(&pair.a.article_normalized)
+        .bind(&pair.b.brand)
+        .bind(&pair.b.article)
+        .bind(&pair.b.article_normalized)
+        .bind(user.id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Связь {} {} - {} {} уже есть в справочнике", pair.a.brand, pair.a.article, pair.b.brand, pair.b.article))?;
+
+    let cross_reference_id: i32 = row.get("id");
+
+    // Логируем добавление аналога
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Add_Cross_Reference".to_string(),
+        format!("Добавлен аналог: {} {} = {} {}", pair.a.brand, pair.a.article, pair.b.brand, pair.b.article),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging cross reference creation: {}", e);
+    }
+
+    Ok(cross_reference_id)
+}
+
+#[tauri::command]
+pub async fn delete_cross_reference(session_token: String, cross_reference_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "delete_cross_reference").await?;
+
+    let row = sqlx::query("DELETE FROM part_cross_references WHERE id = $1 RETURNING brand_a, article_a, brand_b, article_b")
+        .bind(cross_reference_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Cross reference {} not found", cross_reference_id))?;
+
+    let brand_a: String = row.get("brand_a");
+    let article_a: String = row.get("article_a");
+    let brand_b: String = row.get("brand_b");
+    let article_b: String = row.get("article_b");
+
+    // Логируем удаление аналога
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Delete_Cross_Reference".to_string(),
+        format!("Удалён аналог: {} {} = {} {}", brand_a, article_a, brand_b, article_b),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging cross reference deletion: {}", e);
+    }
+
+    Ok(format!("Cross reference {} deleted", cross_reference_id))
+}
+
+// Импорт кросс-номеров из CSV/XLSX. Колонки по порядку: бренд 1, артикул 1, бренд 2, артикул 2.
+// Первая строка пропускается, если это заголовок. Существующие связи не удаляются.
+#[tauri::command]
+pub async fn import_cross_references(
+    session_token: String,
+    file_name: String,
+    content: Vec<u8>,
+    state: tauri::State<'_, Database>
+) -> Result<CrossReferenceImportReport, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "import_cross_references").await?;
+
+    price_lists::check_file_size(&content, "кросс-номеров")?;
+    let rows = price_lists::read_rows(&file_name, &content)?;
+
+    let has_header = rows.first().is_some_and(|row| {
+        let first = row.first().map(|v| v.trim().to_lowercase()).unwrap_or_default();
+        first == "brand" || first.starts_with("бренд") || first.starts_with("производитель")
+    });
+
+    let mut pairs: Vec<Pair> = Vec::new();
+    let mut rejected: Vec<RejectedRow> = Vec::new();
+    let mut total_rows = 0;
+
+    for (index, row) in rows.iter().enumerate().skip(if has_header { 1 } else { 0 }) {
+        if row.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        total_rows += 1;
+
+        let get = |col: usize| row.get(col).map(String::as_str).unwrap_or("");
+        match make_pair(get(0), get(1), get(2), get(3)) {
+            Ok(pair) => pairs.push(pair),
+            Err(reason) => rejected.push(RejectedRow { row: index + 1, reason }),
+        }
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let mut imported = 0;
+    for batch in pairs.chunks(INSERT_BATCH_SIZE) {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO part_cross_references (brand_a, article_a, article_a_normalized, brand_b, article_b, article_b_normalized, created_by) "
+        );
+        builder.push_values(batch, |mut b, pair| {
+            b.push_bind(&pair.a.brand)
+                .push_bind(&pair.a.article)
+                .push_bind(&pair.a.article_normalized)
+                .push_bind(&pair.b.brand)
+                .push_bind(&pair.b.article)
+                .push_bind(&pair.b.article_normalized)
+                .push_bind(user.id);
+        });
+        builder.push(" ON CONFLICT (brand_a, article_a_normalized, brand_b, article_b_normalized) DO NOTHING");
+
+        let result = builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error importing cross references: {}", e))?;
+        imported += result.rows_affected() as usize;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    let duplicates = pairs.len() - imported;
+    let rejected_count = rejected.len();
+    rejected.truncate(MAX_REPORTED_REJECTIONS);
+
+    // Логируем импорт кросс-номеров
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Import_Cross_References".to_string(),
+        format!("Импорт аналогов из файла '{}': добавлено {}, уже были {}, отклонено {}", file_name, imported, duplicates, rejected_count),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging cross references import: {}", e);
+    }
+
+    Ok(CrossReferenceImportReport {
+        total_rows,
+        imported,
+        duplicates,
+        rejected_count,
+        rejected,
+    })
+}
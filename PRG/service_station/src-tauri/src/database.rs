@@ -31,4 +31,21 @@ impl Database {
 
         Ok(Self { pool })
     }
-}
\ No newline at end of file
+}
+
+// Экранирует спецсимволы шаблона LIKE, чтобы введённые '%' и '_' искались как обычные символы.
+// Обратная косая черта — escape-символ LIKE в PostgreSQL по умолчанию.
+pub fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_like;
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_backslash() {
+        assert_eq!(escape_like("100%_a\\b"), "100\\%\\_a\\\\b");
+        assert_eq!(escape_like("масло 5W-40"), "масло 5W-40");
+    }
+}
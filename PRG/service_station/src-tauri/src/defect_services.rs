@@ -0,0 +1,423 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::order_status::OrderStatus;
+
+// Роли услуги для типа неисправности (соответствует ENUM defect_service_role в БД).
+// Обязательные работы создаются при диагностике, остальные предлагаются мастеру.
+const ROLE_MANDATORY: &str = "Mandatory";
+const SERVICE_ROLES: [&str; 3] = [ROLE_MANDATORY, "Recommended", "Optional"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PartTemplate {
+    id: i32,
+    article: String,
+    part_name: Option<String>,
+    quantity: i32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PartTemplateInput {
+    article: String,
+    part_name: Option<String>,
+    quantity: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DefectServiceLink {
+    id: i32,
+    defect_type_id: i32,
+    service_id: i32,
+    service_name: String,
+    base_price: Money,
+    norm_hours: String,
+    role: String,
+    parts: Vec<PartTemplate>,
+}
+
+// Рекомендуемая или дополнительная работа по неисправности заказа, которую мастер может добавить
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkSuggestion {
+    defect_id: i32,
+    defect_description: String,
+    link_id: i32,
+    service_id: i32,
+    service_name: String,
+    role: String,
+    price: Money,
+    norm_hours: String,
+    parts: Vec<PartTemplate>,
+}
+
+async fn load_part_templates(conn: &mut PgConnection, link_id: i32) -> Result<Vec<PartTemplate>, String> {
+    let rows = sqlx::query("SELECT id, article, part_name, quantity FROM defect_service_part_templates WHERE defect_type_service_id = $1 ORDER BY id")
+        .bind(link_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| PartTemplate {
+        id: row.get("id"),
+        article: row.get("article"),
+        part_name: row.get("part_name"),
+        quantity: row.get("quantity"),
+    }).collect())
+}
+
+// Предлагает запчасти по шаблонам связи. Артикул ищется на складе: найденная позиция подставляется
+// с ценой продажи, иначе запчасть добавляется как заказ у поставщика с нулевой ценой до подбора кладовщиком.
+async fn propose_template_parts(conn: &mut PgConnection, order_id: i32, defect_id: i32, link_id: i32) -> Result<(), String> {
+    for template in load_part_templates(conn, link_id).await? {
+        let item = sqlx::query("SELECT id, name, brand, selling_price, supplier_id FROM warehouse
+                                WHERE LOWER(article) = LOWER($1) AND NOT COALESCE(is_archived, false)
+                                ORDER BY id LIMIT 1")
+            .bind(&template.article)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let query = "INSERT INTO order_parts (order_id, warehouse_item_id, part_name_snapshot, brand, supplier_id, quantity, price_per_unit, source_type, defect_id, is_confirmed)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8::part_source, $9, false)";
+        let insert = sqlx::query(query).bind(order_id);
+        let insert = match item {
+            Some(item) => insert
+                .bind(Some(item.get::<i32, _>("id")))
+                .bind(item.get::<String, _>("name"))
+                .bind(item.get::<Option<String>, _>("brand").unwrap_or_default())
+                .bind(item.get::<Option<i32>, _>("supplier_id"))
+                .bind(template.quantity)
+                .bind(item.get::<Option<Money>, _>("selling_price").unwrap_or_else(Money::zero))
+                .bind("Stock"),
+            None => insert
+                .bind(None::<i32>)
+                .bind(template.part_name.clone().unwrap_or_else(|| template.article.clone()))
+                .bind(String::new())
+                .bind(None::<i32>)
+                .bind(template.quantity)
+                .bind(Money::zero())
+                .bind("Supplier"),
+        };
+        insert
+            .bind(defect_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error inserting proposed part: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// Создаёт работу по связи тип неисправности - услуга вместе с запчастями из шаблонов
+async fn create_link_work(conn: &mut PgConnection, order_id: i32, defect_id: i32, link_id: i32) -> Result<i32, String> {
+    let query = "INSERT INTO order_works (order_id, service_id, service_name_snapshot, price, norm_hours, is_confirmed)
+                 SELECT $1, s.id, s.name, s.base_price, s.norm_hours, false
+                 FROM defect_type_services dts
+                 JOIN services_reference s ON dts.service_id = s.id
+                 WHERE dts.id = $2
+                 RETURNING id";
+    let work_id: i32 = sqlx::query(query)
+        .bind(order_id)
+        .bind(link_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error inserting work: {}", e))?
+        .ok_or(format!("Связь неисправности с услугой {} не найдена", link_id))?
+        .get("id");
+
+    link_work(conn, work_id, defect_id).await?;
+    propose_template_parts(conn, order_id, defect_id, link_id).await?;
+
+    Ok(work_id)
+}
+
+async fn link_work(conn: &mut PgConnection, work_id: i32, defect_id: i32) -> Result<(), String> {
+    // Создаем связь между работой и неисправностью в отдельной таблице
+    sqlx::query("INSERT INTO order_works_defects (work_id, defect_id) VALUES ($1, $2)")
+        .bind(work_id)
+        .bind(defect_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error linking work to defect: {}", e))?;
+
+    Ok(())
+}
+
+// Работы по неисправности при диагностике: все обязательные услуги из справочника связей.
+// Если тип неисправности не связан ни с одной услугой, создаётся условная работа с нулевой ценой до согласования.
+pub async fn create_defect_works(conn: &mut PgConnection, order_id: i32, defect_id: i32, defect_type_id: i32, defect_description: &str) -> Result<(), String> {
+    let links = sqlx::query("SELECT id, role::text AS role FROM defect_type_services WHERE defect_type_id = $1 ORDER BY id")
+        .bind(defect_type_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error fetching services for defect: {}", e))?;
+
+    if links.is_empty() {
+        let query = "INSERT INTO order_works (order_id, service_id, service_name_snapshot, price, norm_hours, is_confirmed) VALUES ($1, NULL, $2, $3, $4, false) RETURNING id";
+        let work_id: i32 = sqlx::query(query)
+            .bind(order_id)
+            .bind(defect_description) // используем узел/неисправность как название работы
+            .bind(Money::zero()) // базовая цена 0 до согласования
+            .bind(Decimal::ONE) // условная норма часов
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error inserting work: {}", e))?
+            .get("id");
+        return link_work(conn, work_id, defect_id).await;
+    }
+
+    for link in links.iter().filter(|link| link.get::<String, _>("role") == ROLE_MANDATORY) {
+        create_link_work(conn, order_id, defect_id, link.get("id")).await?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_defect_type_services(session_token: String, defect_type_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectServiceLink>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_defect_type_services").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database error: {}", e))?;
+    let query = "SELECT dts.id, dts.defect_type_id, s.id AS service_id, s.name AS service_name, s.base_price, s.norm_hours::text AS norm_hours, dts.role::text AS role
+                 FROM defect_type_services dts
+                 JOIN services_reference s ON dts.service_id = s.id
+                 WHERE dts.defect_type_id = $1
+                 ORDER BY dts.role, s.name";
+    let rows = sqlx::query(query)
+        .bind(defect_type_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut links = Vec::new();
+    for row in rows {
+        let id: i32 = row.get("id");
+        links.push(DefectServiceLink {
+            id,
+            defect_type_id: row.get("defect_type_id"),
+            service_id: row.get("service_id"),
+            service_name: row.get("service_name"),
+            base_price: row.get("base_price"),
+            norm_hours: row.get("norm_hours"),
+            role: row.get("role"),
+            parts: load_part_templates(&mut conn, id).await?,
+        });
+    }
+
+    Ok(links)
+}
+
+// Создаёт или обновляет связь типа неисправности с услугой; шаблоны запчастей заменяются целиком
+#[tauri::command]
+pub async fn set_defect_type_service(
+    session_token: String,
+    defect_type_id: i32,
+    service_id: i32,
+    role: String,
+    parts: Vec<PartTemplateInput>,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "set_defect_type_service").await?;
+
+    if !SERVICE_ROLES.contains(&role.as_str()) {
+        return Err(format!("Неизвестная роль услуги: {}", role));
+    }
+    for part in &parts {
+        if part.article.trim().is_empty() {
+            return Err("Укажите артикул запчасти".to_string());
+        }
+        if part.quantity <= 0 {
+            return Err(format!("Количество запчасти {} должно быть больше нуля", part.article));
+        }
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let query = "INSERT INTO defect_type_services (defect_type_id, service_id, role) VALUES ($1, $2, $3::defect_service_role)
+                 ON CONFLICT (defect_type_id, service_id) DO UPDATE SET role = EXCLUDED.role
+                 RETURNING id";
+    let link_id: i32 = sqlx::query(query)
+        .bind(defect_type_id)
+        .bind(service_id)
+        .bind(&role)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error linking service to defect type: {}", e))?
+        .get("id");
+
+    sqlx::query("DELETE FROM defect_service_part_templates WHERE defect_type_service_id = $1")
+        .bind(link_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    for part in &parts {
+        sqlx::query("INSERT INTO defect_service_part_templates (defect_type_service_id, article, part_name, quantity) VALUES ($1, $2, $3, $4)")
+            .bind(link_id)
+            .bind(part.article.trim())
+            .bind(part.part_name.as_deref().map(str::trim).filter(|name| !name.is_empty()))
+            .bind(part.quantity)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error inserting part template: {}", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем изменение справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Set_Defect_Type_Service".to_string(),
+        format!("Тип неисправности {} связан с услугой {} (роль: {}, запчастей по умолчанию: {})", defect_type_id, service_id, role, parts.len()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging defect type service link: {}", e);
+    }
+
+    Ok(link_id)
+}
+
+#[tauri::command]
+pub async fn remove_defect_type_service(session_token: String, link_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "remove_defect_type_service").await?;
+
+    let result = sqlx::query("DELETE FROM defect_type_services WHERE id = $1")
+        .bind(link_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Связь неисправности с услугой {} не найдена", link_id));
+    }
+
+    // Логируем изменение справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Remove_Defect_Type_Service".to_string(),
+        format!("Удалена связь неисправности с услугой {}", link_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging defect type service removal: {}", e);
+    }
+
+    Ok(format!("Связь {} удалена", link_id))
+}
+
+// Рекомендуемые и дополнительные работы по неисправностям заказа, которые ещё не добавлены
+#[tauri::command]
+pub async fn get_order_work_suggestions(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<WorkSuggestion>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_work_suggestions").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database error: {}", e))?;
+    let query = "SELECT d.id AS defect_id, d.defect_description, dts.id AS link_id, s.id AS service_id, s.name AS service_name,
+                        dts.role::text AS role, s.base_price, s.norm_hours::text AS norm_hours
+                 FROM order_defects d
+                 JOIN defect_type_services dts ON dts.defect_type_id = d.defect_type_id
+                 JOIN services_reference s ON dts.service_id = s.id
+                 WHERE d.order_id = $1 AND dts.role <> 'Mandatory'
+                   AND NOT EXISTS (SELECT 1 FROM order_works_defects wd
+                                   JOIN order_works w ON wd.work_id = w.id
+                                   WHERE wd.defect_id = d.id AND w.service_id = s.id)
+                 ORDER BY d.id, dts.role, s.name";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut suggestions = Vec::new();
+    for row in rows {
+        let link_id: i32 = row.get("link_id");
+        suggestions.push(WorkSuggestion {
+            defect_id: row.get("defect_id"),
+            defect_description: row.get("defect_description"),
+            link_id,
+            service_id: row.get("service_id"),
+            service_name: row.get("service_name"),
+            role: row.get("role"),
+            price: row.get("base_price"),
+            norm_hours: row.get("norm_hours"),
+            parts: load_part_templates(&mut conn, link_id).await?,
+        });
+    }
+
+    Ok(suggestions)
+}
+
+// Мастер добавляет предложенную работу (и запчасти по шаблону) до согласования с клиентом
+#[tauri::command]
+pub async fn add_suggested_work(session_token: String, order_id: i32, defect_id: i32, link_id: i32, state: tauri::State<'_, Database>) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ApproveOrders, "add_suggested_work").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let status: String = sqlx::query("SELECT status::text AS status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?
+        .get("status");
+    let allowed = [OrderStatus::Diagnostics, OrderStatus::PartsSelection, OrderStatus::Approval];
+    if !allowed.iter().any(|s| s.as_str() == status) {
+        return Err(format!("Работы в заказ {} можно добавлять только до согласования с клиентом (статус '{}')", order_id, status));
+    }
+
+    let query = "SELECT s.name AS service_name,
+                        EXISTS (SELECT 1 FROM order_works_defects wd
+                                JOIN order_works w ON wd.work_id = w.id
+                                WHERE wd.defect_id = d.id AND w.service_id = s.id) AS already_added
+                 FROM order_defects d
+                 JOIN defect_type_services dts ON dts.defect_type_id = d.defect_type_id
+                 JOIN services_reference s ON dts.service_id = s.id
+                 WHERE d.id = $1 AND d.order_id = $2 AND dts.id = $3";
+    let row = sqlx::query(query)
+        .bind(defect_id)
+        .bind(order_id)
+        .bind(link_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Услуга не предлагается для неисправности {} заказа {}", defect_id, order_id))?;
+    let service_name: String = row.get("service_name");
+    if row.get::<bool, _>("already_added") {
+        return Err(format!("Работа '{}' уже добавлена в заказ", service_name));
+    }
+
+    let work_id = create_link_work(&mut tx, order_id, defect_id, link_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем добавление работы
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Add_Suggested_Work".to_string(),
+        format!("В заказ {} добавлена предложенная работа '{}' по неисправности {}", order_id, service_name, defect_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging suggested work: {}", e);
+    }
+
+    Ok(work_id)
+}
@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission, UserRole};
+use crate::database::Database;
+use crate::defect_services::create_defect_works;
+use crate::measurements::{self, MeasurementInput};
+use crate::order_status::OrderStatus;
+
+// Действия с неисправностями (соответствует ENUM defect_change_action в БД)
+const ACTION_ADDED: &str = "Added";
+const ACTION_UPDATED: &str = "Updated";
+const ACTION_REMOVED: &str = "Removed";
+
+// Неисправность в составе дефектовочной ведомости, которую сохраняет диагност
+#[derive(Deserialize, Clone)]
+pub struct DiagnosticDefectInput {
+    id: Option<i32>, // ID неисправности заказа при редактировании; null - новая неисправность
+    defect_type_id: i32,
+    comment: Option<String>, // Свободное заключение диагноста
+    #[serde(default)]
+    measurements: Vec<MeasurementInput>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DefectChange {
+    id: i32,
+    defect_id: Option<i32>,
+    action: String,
+    details: String,
+    changed_by_name: Option<String>,
+    changed_at: String,
+}
+
+struct ExistingDefect {
+    id: i32,
+    defect_type_id: Option<i32>,
+    description: String,
+    comment: Option<String>,
+}
+
+struct DefectTypeInfo {
+    description: String, // "Узел: Неисправность"
+}
+
+fn normalize_comment(comment: Option<String>) -> Option<String> {
+    comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+async fn load_defect_type(conn: &mut PgConnection, defect_type_id: i32) -> Result<DefectTypeInfo, String> {
+    let query = "SELECT dt.name, dn.name AS node_name
+                 FROM defect_types dt
+                 JOIN defect_nodes dn ON dt.node_id = dn.id
+                 WHERE dt.id = $1";
+    let row = sqlx::query(query)
+        .bind(defect_type_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error fetching defect type: {}", e))?
+        .ok_or(format!("Тип неисправности {} не найден", defect_type_id))?;
+
+    let name: String = row.get("name");
+    let node_name: String = row.get("node_name");
+    Ok(DefectTypeInfo {
+        description: format!("{}: {}", node_name, name), // формируем полное описание узел/неисправность
+    })
+}
+
+// Удаляет работы, предложенные только по этой неисправности
+async fn delete_defect_works(conn: &mut PgConnection, defect_id: i32) -> Result<u64, String> {
+    let query = "DELETE FROM order_works w
+                 WHERE w.id IN (SELECT work_id FROM order_works_defects WHERE defect_id = $1)
+                   AND NOT EXISTS (SELECT 1 FROM order_works_defects o WHERE o.work_id = w.id AND o.defect_id <> $1)";
+    let result = sqlx::query(query)
+        .bind(defect_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error deleting defect works: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+// Удаляет запчасти, предложенные по неисправности и ещё не согласованные, не выданные и не зарезервированные
+async fn delete_defect_parts(conn: &mut PgConnection, defect_id: i32) -> Result<u64, String> {
+    let query = "DELETE FROM order_parts p
+                 WHERE p.defect_id = $1 AND NOT COALESCE(p.is_confirmed, false) AND p.issued_quantity = 0
+                   AND NOT EXISTS (SELECT 1 FROM stock_reservations r WHERE r.order_part_id = p.id AND r.released_at IS NULL)";
+    let result = sqlx::query(query)
+        .bind(defect_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error deleting defect parts: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+// Запчасти, оставшиеся у неисправности после delete_defect_parts: согласованные, выданные или зарезервированные
+async fn count_defect_parts(conn: &mut PgConnection, defect_id: i32) -> Result<i64, String> {
+    let row = sqlx::query("SELECT COUNT(*) AS count FROM order_parts WHERE defect_id = $1")
+        .bind(defect_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(row.get("count"))
+}
+
+async fn record_change(conn: &mut PgConnection, order_id: i32, defect_id: i32, action: &str, details: &str, user_id: i32) -> Result<(), String> {
+    let query = "INSERT INTO order_defect_changes (order_id, defect_id, action, details, changed_by) VALUES ($1, $2, $3::defect_change_action, $4, $5)";
+    sqlx::query(query)
+        .bind(order_id)
+        .bind(defect_id)
+        .bind(action)
+        .bind(details)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+// Сохраняет дефектовочную ведомость целиком: переданный список - итоговый состав неисправностей заказа.
+// Повторный вызов с тем же списком ничего не меняет; неисправности без ID сопоставляются с уже
+// сохранёнными по типу, поэтому повторная отправка формы не дублирует работы.
+#[tauri::command]
+pub async fn save_diagnostic_results(
+    session_token: String,
+    order_id: i32,
+    diagnostician_id: i32,
+    defects: Vec<DiagnosticDefectInput>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::Diagnose, "save_diagnostic_results").await?;
+
+    // Диагност сохраняет результаты только от своего имени
+    if user.role == UserRole::Diagnostician.as_str() && user.id != diagnostician_id {
+        return Err(auth::deny(&state, &user, "save_diagnostic_results", "диагностика от имени другого пользователя").await);
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let order = sqlx::query("SELECT status::text AS status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+    let status: String = order.get("status");
+    if status != OrderStatus::Diagnostics.as_str() {
+        return Err(format!("Неисправности заказа {} можно менять только на этапе диагностики (статус '{}')", order_id, status));
+    }
+
+    let rows = sqlx::query("SELECT id, defect_type_id, defect_description, diagnostician_comment FROM order_defects WHERE order_id = $1 ORDER BY id")
+        .bind(order_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut existing: Vec<Option<ExistingDefect>> = rows.iter().map(|row| Some(ExistingDefect {
+        id: row.get("id"),
+        defect_type_id: row.get("defect_type_id"),
+        description: row.get("defect_description"),
+        comment: row.get("diagnostician_comment"),
+    })).collect();
+
+    // Сопоставляем переданные неисправности с сохранёнными: сначала по ID, затем по типу
+    let mut matched: Vec<(DiagnosticDefectInput, Option<ExistingDefect>)> = Vec::new();
+    let (with_id, without_id): (Vec<_>, Vec<_>) = defects.into_iter().partition(|d| d.id.is_some());
+    for input in with_id {
+        let position = existing.iter()
+            .position(|e| e.as_ref().is_some_and(|e| Some(e.id) == input.id))
+            .ok_or(format!("Неисправность {} не относится к заказу {} или указана дважды", input.id.unwrap_or_default(), order_id))?;
+        let defect = existing[position].take();
+        matched.push((input, defect));
+    }
+    for input in without_id {
+        let position = existing.iter()
+            .position(|e| e.as_ref().is_some_and(|e| e.defect_type_id == Some(input.defect_type_id)));
+        let defect = position.and_then(|p| existing[p].take());
+        matched.push((input, defect));
+    }
+
+    let (mut added, mut updated, mut removed) = (0, 0, 0);
+
+    // Неисправности, которых нет в новом списке, удаляются вместе с работами по ним
+    for defect in existing.into_iter().flatten() {
+        delete_defect_parts(&mut tx, defect.id).await?;
+        if count_defect_parts(&mut tx, defect.id).await? > 0 {
+            return Err(format!("К неисправности '{}' уже подобраны запчасти, удалить её нельзя", defect.description));
+        }
+
+        let works = delete_defect_works(&mut tx, defect.id).await?;
+        sqlx::query("DELETE FROM order_defects WHERE id = $1")
+            .bind(defect.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error deleting defect: {}", e))?;
+        record_change(&mut tx, order_id, defect.id, ACTION_REMOVED,
+                      &format!("Удалена неисправность '{}' (удалено работ: {})", defect.description, works), user.id).await?;
+        removed += 1;
+    }
+
+    for (input, defect) in matched {
+        let defect_type = load_defect_type(&mut tx, input.defect_type_id).await?;
+        // В комментарий попадает только заключение диагноста, описание из справочника не копируется
+        let comment = normalize_comment(input.comment);
+
+        let (defect_id, defect_changed) = match defect {
+            None => {
+                let defect_query = "INSERT INTO order_defects (order_id, diagnostician_id, defect_description, diagnostician_comment, is_confirmed, defect_type_id)
+                                    VALUES ($1, $2, $3, $4, false, $5) RETURNING id";
+                let row = sqlx::query(defect_query)
+                    .bind(order_id)
+                    .bind(diagnostician_id)
+                    .bind(&defect_type.description)
+                    .bind(&comment)
+                    .bind(input.defect_type_id)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Database error inserting defect: {}", e))?;
+                let defect_id: i32 = row.get("id");
+
+                create_defect_works(&mut tx, order_id, defect_id, input.defect_type_id, &defect_type.description).await?;
+                record_change(&mut tx, order_id, defect_id, ACTION_ADDED,
+                              &format!("Добавлена неисправность '{}'", defect_type.description), user.id).await?;
+                added += 1;
+                (defect_id, true)
+            }
+            Some(defect) if defect.defect_type_id != Some(input.defect_type_id) => {
+                // Сменился тип неисправности: предложенные работы пересоздаются по новому типу.
+                // Подобранные запчасти относятся к прежней неисправности, поэтому как и при удалении смена типа запрещена
+                delete_defect_parts(&mut tx, defect.id).await?;
+                if count_defect_parts(&mut tx, defect.id).await? > 0 {
+                    return Err(format!("К неисправности '{}' уже подобраны запчасти, сменить её тип нельзя", defect.description));
+                }
+                sqlx::query("UPDATE order_defects SET defect_type_id = $2, defect_description = $3, diagnostician_comment = $4 WHERE id = $1")
+                    .bind(defect.id)
+                    .bind(input.defect_type_id)
+                    .bind(&defect_type.description)
+                    .bind(&comment)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Database error updating defect: {}", e))?;
+                delete_defect_works(&mut tx, defect.id).await?;
+                create_defect_works(&mut tx, order_id, defect.id, input.defect_type_id, &defect_type.description).await?;
+                record_change(&mut tx, order_id, defect.id, ACTION_UPDATED,
+                              &format!("Неисправность '{}' заменена на '{}'", defect.description, defect_type.description), user.id).await?;
+                updated += 1;
+                (defect.id, true)
+            }
+            Some(defect) if defect.comment != comment => {
+                sqlx::query("UPDATE order_defects SET diagnostician_comment = $2 WHERE id = $1")
+                    .bind(defect.id)
+                    .bind(&comment)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Database error updating defect: {}", e))?;
+                record_change(&mut tx, order_id, defect.id, ACTION_UPDATED,
+                              &format!("Изменён комментарий к неисправности '{}': '{}' -> '{}'", defect.description,
+                                       defect.comment.as_deref().unwrap_or(""), comment.as_deref().unwrap_or("")), user.id).await?;
+                updated += 1;
+                (defect.id, true)
+            }
+            Some(defect) => (defect.id, false),
+        };
+
+        let saved = measurements::save_defect_measurements(&mut tx, defect_id, input.defect_type_id, &input.measurements, user.id).await?;
+        if saved.changed > 0 {
+            let mut details = format!("Обновлены замеры по неисправности '{}' (изменено: {})", defect_type.description, saved.changed);
+            if !saved.out_of_range.is_empty() {
+                details.push_str(&format!(", вне нормы: {}", saved.out_of_range.join("; ")));
+            }
+            record_change(&mut tx, order_id, defect_id, ACTION_UPDATED, &details, user.id).await?;
+            if !defect_changed {
+                updated += 1;
+            }
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем сохранение диагностики
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Save_Diagnostic_Results".to_string(),
+        format!("Сохранена диагностика заказа {}: добавлено неисправностей {}, изменено {}, удалено {}", order_id, added, updated, removed),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging diagnostic results: {}", e);
+    }
+
+    Ok(format!("Diagnostic results saved for order {}: {} added, {} updated, {} removed", order_id, added, updated, removed))
+}
+
+#[tauri::command]
+pub async fn get_order_defect_changes(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectChange>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_defect_changes").await?;
+
+    let query = "SELECT c.id, c.defect_id, c.action::text AS action, c.details, u.full_name AS changed_by_name, c.changed_at::text AS changed_at
+                 FROM order_defect_changes c
+                 LEFT JOIN users u ON u.id = c.changed_by
+                 WHERE c.order_id = $1
+                 ORDER BY c.changed_at, c.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| DefectChange {
+        id: row.get("id"),
+        defect_id: row.get("defect_id"),
+        action: row.get("action"),
+        details: row.get("details"),
+        changed_by_name: row.get("changed_by_name"),
+        changed_at: row.get("changed_at"),
+    }).collect())
+}
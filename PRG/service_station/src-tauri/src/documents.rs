@@ -0,0 +1,555 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+use serde::Deserialize;
+use serde_json::Value;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::order_totals::{self, OrderTotals, Selection, PART_AMOUNT_SQL, WORK_AMOUNT_SQL};
+use crate::settings;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const FONT_SIZE: f32 = 10.0;
+// Средняя ширина символа относительно кегля; точной метрики шрифта у printpdf нет
+const CHAR_WIDTH_RATIO: f32 = 0.55;
+const PT_TO_MM: f32 = 0.3528;
+
+// Шрифты с кириллицей, которые ищутся, если путь не указан в настройках или PDF_FONT_PATH
+const FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+];
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+pub enum DocumentType {
+    Intake,                // Акт приёмки автомобиля
+    WorkOrder,             // Заказ-наряд
+    Invoice,               // Счёт на оплату
+    AcceptanceCertificate, // Акт выполненных работ
+}
+
+impl DocumentType {
+    fn title(self) -> &'static str {
+        match self {
+            DocumentType::Intake => "АКТ ПРИЁМКИ АВТОМОБИЛЯ",
+            DocumentType::WorkOrder => "ЗАКАЗ-НАРЯД",
+            DocumentType::Invoice => "СЧЁТ НА ОПЛАТУ",
+            DocumentType::AcceptanceCertificate => "АКТ ВЫПОЛНЕННЫХ РАБОТ",
+        }
+    }
+
+    fn file_prefix(self) -> &'static str {
+        match self {
+            DocumentType::Intake => "intake_act",
+            DocumentType::WorkOrder => "work_order",
+            DocumentType::Invoice => "invoice",
+            DocumentType::AcceptanceCertificate => "acceptance_certificate",
+        }
+    }
+}
+
+struct OrderHeader {
+    id: i32,
+    complaint: Option<String>,
+    current_mileage: Option<i32>,
+    prepayment: Money,
+    paid: Money, // Все платежи за вычетом возвратов
+    created_at: String,
+    completed_at: Option<String>,
+    client_name: String,
+    client_phone: String,
+    client_address: Option<String>,
+    car_make: String,
+    car_model: String,
+    car_year: Option<i32>,
+    car_vin: Option<String>,
+    car_plate: Option<String>,
+    master_name: Option<String>,
+}
+
+struct DocumentLine {
+    name: String,
+    brand: Option<String>,
+    quantity: i32,
+    price: Money,
+    discount_percent: String,
+    amount: Money, // Сумма строки с учётом скидки, считается в SQL так же, как итог заказа
+}
+
+struct DocumentData {
+    order: OrderHeader,
+    works: Vec<DocumentLine>,
+    parts: Vec<DocumentLine>,
+    totals: Option<OrderTotals>, // В акте приёмки итогов нет
+}
+
+fn is_zero(amount: &str) -> bool {
+    amount.parse::<Decimal>().map(|d| d.is_zero()).unwrap_or(true)
+}
+
+async fn load_document_data(conn: &mut PgConnection, order_id: i32, document_type: DocumentType) -> Result<DocumentData, String> {
+    let row = sqlx::query(
+        "SELECT o.id, o.complaint, o.current_mileage, COALESCE(o.prepayment, 0) AS prepayment,
+                ROUND(COALESCE((SELECT SUM(CASE WHEN p.kind = 'Refund' THEN -p.amount ELSE p.amount END) FROM payments p WHERE p.order_id = o.id), 0), 2) AS paid,
+                TO_CHAR(o.created_at, 'DD.MM.YYYY HH24:MI') AS created_at, TO_CHAR(o.completed_at, 'DD.MM.YYYY HH24:MI') AS completed_at,
+                cl.full_name AS client_name, cl.phone AS client_phone, cl.address AS client_address,
+                c.make, c.model, c.production_year, c.vin, c.license_plate, m.full_name AS master_name
+         FROM orders o
+         JOIN clients cl ON cl.id = o.client_id
+         JOIN cars c ON c.id = o.car_id
+         LEFT JOIN users m ON m.id = o.master_id
+         WHERE o.id = $1"
+    )
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    let order = OrderHeader {
+        id: row.get("id"),
+        complaint: row.get("complaint"),
+        current_mileage: row.get("current_mileage"),
+        prepayment: row.get("prepayment"),
+        paid: row.get("paid"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+        client_name: row.get("client_name"),
+        client_phone: row.get("client_phone"),
+        client_address: row.get("client_address"),
+        car_make: row.get("make"),
+        car_model: row.get("model"),
+        car_year: row.get("production_year"),
+        car_vin: row.get("vin"),
+        car_plate: row.get("license_plate"),
+        master_name: row.get("master_name"),
+    };
+
+    // В акт приёмки работы и запчасти не входят - они появляются после диагностики
+    if document_type == DocumentType::Intake {
+        return Ok(DocumentData { order, works: Vec::new(), parts: Vec::new(), totals: None });
+    }
+
+    // Строки отбираются так же, как при расчёте суммы заказа
+    let works_query = format!(
+        "SELECT service_name_snapshot, price, discount_percent::text AS discount_percent, {} AS amount
+         FROM order_works WHERE order_id = $1 AND is_confirmed AND status <> 'Cancelled' ORDER BY id",
+        WORK_AMOUNT_SQL
+    );
+    let works = sqlx::query(&works_query)
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .map(|row| DocumentLine {
+            name: row.get::<Option<String>, _>("service_name_snapshot").unwrap_or_default(),
+            brand: None,
+            quantity: 1,
+            price: row.get("price"),
+            discount_percent: row.get("discount_percent"),
+            amount: row.get("amount"),
+        })
+        .collect();
+
+    let parts_query = format!(
+        "SELECT part_name_snapshot, brand, quantity, price_per_unit AS price, discount_percent::text AS discount_percent, {} AS amount
+         FROM order_parts WHERE order_id = $1 AND is_confirmed ORDER BY id",
+        PART_AMOUNT_SQL
+    );
+    let parts = sqlx::query(&parts_query)
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .map(|row| DocumentLine {
+            name: row.get::<Option<String>, _>("part_name_snapshot").unwrap_or_default(),
+            brand: row.get("brand"),
+            quantity: row.get::<Option<i32>, _>("quantity").unwrap_or(1),
+            price: row.get("price"),
+            discount_percent: row.get("discount_percent"),
+            amount: row.get("amount"),
+        })
+        .collect();
+
+    let totals = order_totals::compute(conn, order_id, Selection::Confirmed).await?;
+
+    Ok(DocumentData { order, works, parts, totals: Some(totals) })
+}
+
+// Шрифт должен содержать кириллицу, встроенные шрифты PDF её не поддерживают
+fn find_font(company: &Value) -> Result<PathBuf, String> {
+    if let Some(path) = settings::text(company, "pdf_font_path")
+        .or_else(|| std::env::var("PDF_FONT_PATH").ok().filter(|s| !s.trim().is_empty()))
+    {
+        let path = PathBuf::from(path);
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(format!("Шрифт для документов не найден: {}", path.display()))
+        };
+    }
+
+    FONT_CANDIDATES.iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .ok_or("Не найден шрифт с кириллицей для документов: укажите путь к TTF-файлу в настройках (pdf_font_path)".to_string())
+}
+
+struct TableColumn {
+    title: &'static str,
+    width: f32,
+    align_right: bool,
+}
+
+// Простая вёрстка сверху вниз: текущая позиция y отсчитывается от нижнего края страницы
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    pages: usize,
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new(title: &str, font_path: &Path) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Страница 1");
+        let font_file = fs::File::open(font_path).map_err(|e| format!("Ошибка чтения шрифта {}: {}", font_path.display(), e))?;
+        let font = doc.add_external_font(font_file).map_err(|e| format!("Ошибка загрузки шрифта: {}", e))?;
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(PdfWriter { doc, layer, font, pages: 1, y: PAGE_HEIGHT - MARGIN })
+    }
+
+    fn line_height(size: f32) -> f32 {
+        size * PT_TO_MM * 1.4
+    }
+
+    fn text_width(text: &str, size: f32) -> f32 {
+        text.chars().count() as f32 * size * PT_TO_MM * CHAR_WIDTH_RATIO
+    }
+
+    // Перенос по словам под заданную ширину
+    fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut current = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+                if !current.is_empty() && Self::text_width(&candidate, size) > width {
+                    lines.push(std::mem::replace(&mut current, word.to_string()));
+                } else {
+                    current = candidate;
+                }
+            }
+            lines.push(current);
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+        self.pages += 1;
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), format!("Страница {}", self.pages));
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn paragraph(&mut self, text: &str, size: f32) {
+        let height = Self::line_height(size);
+        for line in Self::wrap(text, size, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.ensure_space(height);
+            self.y -= height;
+            self.layer.use_text(line, size, Mm(MARGIN), Mm(self.y), &self.font);
+        }
+    }
+
+    fn centered(&mut self, text: &str, size: f32) {
+        let height = Self::line_height(size);
+        self.ensure_space(height);
+        self.y -= height;
+        let x = ((PAGE_WIDTH - Self::text_width(text, size)) / 2.0).max(MARGIN);
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), &self.font);
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn rule(&mut self) {
+        self.ensure_space(2.0);
+        self.y -= 1.5;
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn table_row(&mut self, columns: &[TableColumn], cells: &[String], size: f32) {
+        let height = Self::line_height(size);
+        let wrapped: Vec<Vec<String>> = columns.iter()
+            .zip(cells)
+            .map(|(column, cell)| Self::wrap(cell, size, column.width - 2.0))
+            .collect();
+        let rows = wrapped.iter().map(|lines| lines.len()).max().unwrap_or(1);
+        self.ensure_space(height * rows as f32);
+
+        let top = self.y;
+        let mut x = MARGIN;
+        for (column, lines) in columns.iter().zip(&wrapped) {
+            for (i, line) in lines.iter().enumerate() {
+                let text_x = if column.align_right {
+                    x + column.width - 1.0 - Self::text_width(line, size)
+                } else {
+                    x + 1.0
+                };
+                self.layer.use_text(line.clone(), size, Mm(text_x), Mm(top - height * (i + 1) as f32), &self.font);
+            }
+            x += column.width;
+        }
+        self.y = top - height * rows as f32;
+    }
+
+    fn table(&mut self, columns: &[TableColumn], rows: &[Vec<String>]) {
+        let header: Vec<String> = columns.iter().map(|c| c.title.to_string()).collect();
+        self.table_row(columns, &header, FONT_SIZE - 1.0);
+        self.rule();
+        for row in rows {
+            self.table_row(columns, row, FONT_SIZE - 1.0);
+        }
+        self.rule();
+    }
+
+    // Строка итогов, выровненная по правому краю
+    fn total_line(&mut self, label: &str, value: Money) {
+        let text = format!("{}: {}", label, value);
+        let height = Self::line_height(FONT_SIZE);
+        self.ensure_space(height);
+        self.y -= height;
+        let x = PAGE_WIDTH - MARGIN - 1.0 - Self::text_width(&text, FONT_SIZE);
+        self.layer.use_text(text, FONT_SIZE, Mm(x), Mm(self.y), &self.font);
+    }
+
+    fn signatures(&mut self, left: &str, right: &str) {
+        self.gap(12.0);
+        let height = Self::line_height(FONT_SIZE);
+        self.ensure_space(height);
+        self.y -= height;
+        self.layer.use_text(format!("{} ____________", left), FONT_SIZE, Mm(MARGIN), Mm(self.y), &self.font);
+        self.layer.use_text(format!("{} ____________", right), FONT_SIZE, Mm(PAGE_WIDTH / 2.0 + 5.0), Mm(self.y), &self.font);
+    }
+
+    fn finish(self) -> Result<Vec<u8>, String> {
+        self.doc.save_to_bytes().map_err(|e| format!("Ошибка формирования PDF: {}", e))
+    }
+}
+
+fn lines_table(writer: &mut PdfWriter, lines: &[DocumentLine], with_quantity: bool) {
+    let columns = [
+        TableColumn { title: "№", width: 10.0, align_right: false },
+        TableColumn { title: "Наименование", width: 80.0, align_right: false },
+        TableColumn { title: "Кол-во", width: 15.0, align_right: true },
+        TableColumn { title: "Цена", width: 25.0, align_right: true },
+        TableColumn { title: "Скидка, %", width: 20.0, align_right: true },
+        TableColumn { title: "Сумма", width: 30.0, align_right: true },
+    ];
+    let rows: Vec<Vec<String>> = lines.iter().enumerate().map(|(i, line)| {
+        let name = match &line.brand {
+            Some(brand) if !brand.is_empty() => format!("{} ({})", line.name, brand),
+            _ => line.name.clone(),
+        };
+        vec![
+            (i + 1).to_string(),
+            name,
+            if with_quantity { line.quantity.to_string() } else { "1".to_string() },
+            line.price.amount_text(),
+            if is_zero(&line.discount_percent) { String::new() } else { line.discount_percent.clone() },
+            line.amount.amount_text(),
+        ]
+    }).collect();
+    writer.table(&columns, &rows);
+}
+
+fn render(document_type: DocumentType, data: &DocumentData, company: &Value, font_path: &Path) -> Result<Vec<u8>, String> {
+    let order = &data.order;
+    let title = format!("{} № {}", document_type.title(), order.id);
+    let mut writer = PdfWriter::new(&title, font_path)?;
+
+    // Реквизиты исполнителя
+    let company_name = settings::text(company, "legal_name")
+        .or_else(|| settings::text(company, "company_name"))
+        .unwrap_or_default();
+    writer.paragraph(&company_name, FONT_SIZE + 1.0);
+    if let Some(tax_id) = settings::text(company, "tax_id") {
+        writer.paragraph(&format!("УНП: {}", tax_id), FONT_SIZE);
+    }
+    if let Some(address) = settings::text(company, "address") {
+        writer.paragraph(&format!("Адрес: {}", address), FONT_SIZE);
+    }
+    if let Some(phone) = settings::text(company, "phone") {
+        writer.paragraph(&format!("Телефон: {}", phone), FONT_SIZE);
+    }
+    if document_type == DocumentType::Invoice {
+        if let Some(bank_details) = settings::text(company, "bank_details") {
+            writer.paragraph(&format!("Банковские реквизиты: {}", bank_details), FONT_SIZE);
+        }
+    }
+    writer.rule();
+
+    writer.gap(4.0);
+    writer.centered(&title, FONT_SIZE + 4.0);
+    let document_date = chrono::Local::now().format("%d.%m.%Y").to_string();
+    writer.centered(&format!("от {}", document_date), FONT_SIZE);
+    writer.gap(4.0);
+
+    // Заказчик и автомобиль
+    let client_label = if document_type == DocumentType::Invoice { "Плательщик" } else { "Заказчик" };
+    writer.paragraph(&format!("{}: {}, тел. {}", client_label, order.client_name, order.client_phone), FONT_SIZE);
+    if let Some(address) = order.client_address.as_deref().filter(|a| !a.is_empty()) {
+        writer.paragraph(&format!("Адрес заказчика: {}", address), FONT_SIZE);
+    }
+    let mut car = format!("Автомобиль: {} {}", order.car_make, order.car_model);
+    if let Some(year) = order.car_year {
+        car.push_str(&format!(", {} г.в.", year));
+    }
+    writer.paragraph(&car, FONT_SIZE);
+    writer.paragraph(&format!(
+        "VIN: {}    Гос. номер: {}    Пробег: {}",
+        order.car_vin.as_deref().unwrap_or("—"),
+        order.car_plate.as_deref().unwrap_or("—"),
+        order.current_mileage.map(|m| format!("{} км", m)).unwrap_or("—".to_string())
+    ), FONT_SIZE);
+    writer.paragraph(&format!("Заказ № {} от {}", order.id, order.created_at), FONT_SIZE);
+    if let Some(master) = &order.master_name {
+        writer.paragraph(&format!("Мастер-приёмщик: {}", master), FONT_SIZE);
+    }
+    writer.gap(3.0);
+
+    if document_type == DocumentType::Intake {
+        writer.paragraph("Причина обращения (со слов заказчика):", FONT_SIZE);
+        writer.paragraph(order.complaint.as_deref().unwrap_or("—"), FONT_SIZE);
+        writer.gap(3.0);
+        if !order.prepayment.is_zero() {
+            writer.paragraph(&format!("Внесена предоплата: {}", order.prepayment), FONT_SIZE);
+        }
+        writer.paragraph(
+            "Автомобиль принят на диагностику и ремонт. Перечень и стоимость работ согласовываются с заказчиком после диагностики.",
+            FONT_SIZE - 1.0,
+        );
+        writer.signatures("Принял", "Сдал");
+        return writer.finish();
+    }
+
+    let totals = data.totals.as_ref().ok_or("Не рассчитаны итоги заказа".to_string())?;
+
+    writer.paragraph("Работы:", FONT_SIZE + 1.0);
+    lines_table(&mut writer, &data.works, false);
+    writer.total_line("Итого по работам", totals.works_total);
+    writer.gap(3.0);
+
+    writer.paragraph("Запчасти и материалы:", FONT_SIZE + 1.0);
+    lines_table(&mut writer, &data.parts, true);
+    writer.total_line("Итого по запчастям", totals.parts_total);
+    writer.gap(3.0);
+
+    if !totals.discount_amount.is_zero() {
+        writer.total_line("Сумма без скидки на заказ", totals.subtotal);
+        writer.total_line(&format!("Скидка {}%", totals.discount_percent), totals.discount_amount);
+    }
+    if !is_zero(&totals.vat_rate) {
+        let vat_label = if totals.prices_include_vat { "В том числе НДС" } else { "НДС" };
+        writer.total_line(&format!("{} {}%", vat_label, totals.vat_rate), totals.vat_amount);
+    }
+    writer.total_line("ВСЕГО", totals.total_amount);
+    if document_type != DocumentType::WorkOrder {
+        writer.total_line("Оплачено", order.paid);
+        writer.total_line("К оплате", totals.total_amount - order.paid);
+    }
+
+    match document_type {
+        DocumentType::WorkOrder => {
+            writer.gap(3.0);
+            writer.paragraph("Заказчик согласен с перечнем и стоимостью работ и запчастей.", FONT_SIZE - 1.0);
+            writer.signatures("Исполнитель", "Заказчик");
+        }
+        DocumentType::Invoice => {
+            writer.gap(12.0);
+            writer.paragraph("Счёт выставил ____________", FONT_SIZE);
+        }
+        DocumentType::AcceptanceCertificate => {
+            writer.gap(3.0);
+            if let Some(completed_at) = &order.completed_at {
+                writer.paragraph(&format!("Дата завершения работ: {}", completed_at), FONT_SIZE);
+            }
+            writer.paragraph(
+                "Работы выполнены полностью и в срок. Заказчик претензий по объёму, качеству и срокам выполнения работ не имеет.",
+                FONT_SIZE - 1.0,
+            );
+            writer.signatures("Исполнитель", "Заказчик");
+        }
+        DocumentType::Intake => {}
+    }
+
+    writer.finish()
+}
+
+// Формирует PDF-документ по заказу и сохраняет его в папку документов; возвращает путь к файлу
+#[tauri::command]
+pub async fn generate_order_document(session_token: String, order_id: i32, document_type: DocumentType, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "generate_order_document").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    let company = settings::load(&mut *conn).await?;
+    let data = load_document_data(&mut conn, order_id, document_type).await?;
+    drop(conn);
+    let font_path = find_font(&company)?;
+    let content = render(document_type, &data, &company, &font_path)?;
+
+    // Документы заказа складываются в отдельную подпапку, чтобы их было удобно архивировать
+    let dir = settings::documents_dir(&company).join(format!("order_{}", order_id));
+    fs::create_dir_all(&dir).map_err(|e| format!("Ошибка создания папки {}: {}", dir.display(), e))?;
+    let file_name = format!(
+        "{}_{}_{}.pdf",
+        document_type.file_prefix(),
+        order_id,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let path = dir.join(file_name);
+    fs::write(&path, content).map_err(|e| format!("Ошибка сохранения документа {}: {}", path.display(), e))?;
+    let path = fs::canonicalize(&path).unwrap_or(path);
+
+    // Логируем формирование документа
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Generate_Document".to_string(),
+        format!("Сформирован документ '{}' по заказу {}: {}", document_type.title(), order_id, path.display()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging document generation: {}", e);
+    }
+
+    Ok(path.display().to_string())
+}
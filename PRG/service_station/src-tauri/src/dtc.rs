@@ -0,0 +1,491 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+
+use crate::auth::{self, Permission};
+use crate::database::{escape_like, Database};
+use crate::order_status::OrderStatus;
+use crate::price_lists::{self, RejectedRow, INSERT_BATCH_SIZE, MAX_REPORTED_REJECTIONS};
+
+// Стандартные коды SAE, поставляемые с приложением
+const BUNDLED_CODES_FILE: &str = "dtc_generic.csv";
+const BUNDLED_CODES: &[u8] = include_bytes!("../resources/dtc_generic.csv");
+// Стартовое сопоставление стандартных кодов с типами неисправностей: код, узел, тип неисправности
+const BUNDLED_DEFECT_TYPES_FILE: &str = "dtc_defect_types.csv";
+const BUNDLED_DEFECT_TYPES: &[u8] = include_bytes!("../resources/dtc_defect_types.csv");
+
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DtcDefectType {
+    id: i32,
+    node_name: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DtcCode {
+    id: i32,
+    code: String,
+    description: String,
+    defect_types: Vec<DtcDefectType>, // Предлагаемые типы неисправностей
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OrderDtcCode {
+    id: i32,
+    order_id: i32,
+    code: String,
+    description: Option<String>,
+    added_by_name: Option<String>,
+    added_at: String,
+    defect_types: Vec<DtcDefectType>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DtcImportReport {
+    total_rows: usize,
+    imported: usize, // Добавленные и обновлённые коды
+    rejected_count: usize,
+    rejected: Vec<RejectedRow>,
+    linked_defect_types: usize, // Новые связи из стартового сопоставления (только для стандартных кодов)
+}
+
+// Код приводится к виду P0300: буква системы (P, C, B, U), цифра 0-3 и три шестнадцатеричных символа
+fn normalize_code(value: &str) -> Result<String, String> {
+    let code: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+
+    let mut chars = code.chars();
+    let valid = code.len() == 5
+        && chars.next().is_some_and(|c| matches!(c, 'P' | 'C' | 'B' | 'U'))
+        && chars.next().is_some_and(|c| matches!(c, '0'..='3'))
+        && chars.all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!("Некорректный код OBD-II '{}': ожидается формат P0300", value.trim()));
+    }
+    Ok(code)
+}
+
+async fn load_defect_types(pool: &PgPool, dtc_code_ids: &[i32]) -> Result<HashMap<i32, Vec<DtcDefectType>>, String> {
+    let query = "SELECT l.dtc_code_id, dt.id, dn.name AS node_name, dt.name
+                 FROM dtc_code_defect_types l
+                 JOIN defect_types dt ON l.defect_type_id = dt.id
+                 JOIN defect_nodes dn ON dt.node_id = dn.id
+                 WHERE l.dtc_code_id = ANY($1)
+                 ORDER BY dn.name, dt.name";
+    let rows = sqlx::query(query)
+        .bind(dtc_code_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut defect_types: HashMap<i32, Vec<DtcDefectType>> = HashMap::new();
+    for row in rows {
+        defect_types.entry(row.get("dtc_code_id")).or_default().push(DtcDefectType {
+            id: row.get("id"),
+            node_name: row.get("node_name"),
+            name: row.get("name"),
+        });
+    }
+    Ok(defect_types)
+}
+
+// Загружает коды из строк файла: колонки по порядку - код и расшифровка, первая строка пропускается,
+// если это заголовок. При overwrite расшифровки уже известных кодов заменяются.
+async fn import_rows(pool: &PgPool, rows: &[Vec<String>], overwrite: bool) -> Result<DtcImportReport, String> {
+    let has_header = rows.first().is_some_and(|row| {
+        let first = row.first().map(|v| v.trim()).unwrap_or_default();
+        normalize_code(first).is_err()
+    });
+
+    let mut codes: Vec<(String, String)> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut rejected: Vec<RejectedRow> = Vec::new();
+    let mut total_rows = 0;
+
+    for (index, row) in rows.iter().enumerate().skip(if has_header { 1 } else { 0 }) {
+        if row.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        total_rows += 1;
+        let row_number = index + 1;
+
+        let code = match normalize_code(row.first().map(String::as_str).unwrap_or("")) {
+            Ok(code) => code,
+            Err(reason) => {
+                rejected.push(RejectedRow { row: row_number, reason });
+                continue;
+            }
+        };
+        let description = row.get(1).map(|v| v.trim().to_string()).unwrap_or_default();
+        if description.is_empty() {
+            rejected.push(RejectedRow { row: row_number, reason: format!("Нет расшифровки кода {}", code) });
+            continue;
+        }
+        if let Some(first_row) = seen.get(&code) {
+            rejected.push(RejectedRow { row: row_number, reason: format!("Код {} уже указан в строке {}", code, first_row) });
+            continue;
+        }
+        seen.insert(code.clone(), row_number);
+        codes.push((code, description));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let mut imported = 0;
+    for batch in codes.chunks(INSERT_BATCH_SIZE) {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("INSERT INTO dtc_codes (code, description) ");
+        builder.push_values(batch, |mut b, (code, description)| {
+            b.push_bind(code).push_bind(description);
+        });
+        if overwrite {
+            builder.push(" ON CONFLICT (code) DO UPDATE SET description = EXCLUDED.description, updated_at = NOW()
+                          WHERE dtc_codes.description IS DISTINCT FROM EXCLUDED.description");
+        } else {
+            builder.push(" ON CONFLICT (code) DO NOTHING");
+        }
+
+        let result = builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error importing DTC codes: {}", e))?;
+        imported += result.rows_affected() as usize;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    let rejected_count = rejected.len();
+    rejected.truncate(MAX_REPORTED_REJECTIONS);
+
+    Ok(DtcImportReport {
+        total_rows,
+        imported,
+        rejected_count,
+        rejected,
+        linked_defect_types: 0,
+    })
+}
+
+// Связывает стандартные коды с типами неисправностей справочника, совпадающими по названию узла и типа
+// (без учёта регистра). Типов, которых в справочнике нет, сопоставление не создаёт; существующие связи не удаляются.
+async fn link_bundled_defect_types(pool: &PgPool) -> Result<usize, String> {
+    let rows = price_lists::read_rows(BUNDLED_DEFECT_TYPES_FILE, BUNDLED_DEFECT_TYPES)?;
+
+    let mut codes = Vec::new();
+    let mut nodes = Vec::new();
+    let mut defect_types = Vec::new();
+    for row in rows.iter().skip(1) {
+        if let [code, node, defect_type, ..] = row.as_slice() {
+            codes.push(code.trim().to_string());
+            nodes.push(node.trim().to_string());
+            defect_types.push(defect_type.trim().to_string());
+        }
+    }
+
+    let query = "INSERT INTO dtc_code_defect_types (dtc_code_id, defect_type_id)
+                 SELECT d.id, dt.id
+                 FROM UNNEST($1::text[], $2::text[], $3::text[]) AS m(code, node, defect_type)
+                 JOIN dtc_codes d ON d.code = m.code
+                 JOIN defect_nodes dn ON lower(trim(dn.name)) = lower(m.node)
+                 JOIN defect_types dt ON dt.node_id = dn.id AND lower(trim(dt.name)) = lower(m.defect_type)
+                 ON CONFLICT DO NOTHING";
+    let result = sqlx::query(query)
+        .bind(&codes)
+        .bind(&nodes)
+        .bind(&defect_types)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Database error linking DTC codes to defect types: {}", e))?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+// Поиск по началу кода или по тексту расшифровки
+#[tauri::command]
+pub async fn search_dtc_codes(session_token: String, query: String, state: tauri::State<'_, Database>) -> Result<Vec<DtcCode>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "search_dtc_codes").await?;
+
+    let search = query.trim();
+    let sql = "SELECT id, code, description FROM dtc_codes
+               WHERE $1 = '' OR code ILIKE $2 || '%' OR description ILIKE '%' || $1 || '%'
+               ORDER BY code
+               LIMIT $3";
+    let rows = sqlx::query(sql)
+        .bind(escape_like(search))
+        .bind(escape_like(&search.replace([' ', '-'], "").to_uppercase()))
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let ids: Vec<i32> = rows.iter().map(|row| row.get("id")).collect();
+    let mut defect_types = load_defect_types(&state.pool, &ids).await?;
+
+    Ok(rows.iter().map(|row| {
+        let id: i32 = row.get("id");
+        DtcCode {
+            id,
+            code: row.get("code"),
+            description: row.get("description"),
+            defect_types: defect_types.remove(&id).unwrap_or_default(),
+        }
+    }).collect())
+}
+
+// Загружает в справочник стандартные коды SAE; расшифровки, изменённые вручную или импортом, сохраняются.
+// Повторная загрузка добавляет связи с типами неисправностей, появившимися в справочнике после прошлой загрузки.
+#[tauri::command]
+pub async fn load_bundled_dtc_codes(session_token: String, state: tauri::State<'_, Database>) -> Result<DtcImportReport, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "load_bundled_dtc_codes").await?;
+
+    let rows = price_lists::read_rows(BUNDLED_CODES_FILE, BUNDLED_CODES)?;
+    let mut report = import_rows(&state.pool, &rows, false).await?;
+    report.linked_defect_types = link_bundled_defect_types(&state.pool).await?;
+
+    // Логируем загрузку справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Load_Bundled_DTC_Codes".to_string(),
+        format!("Загружены стандартные коды OBD-II: добавлено {} из {}, связей с типами неисправностей {}",
+                report.imported, report.total_rows, report.linked_defect_types),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging DTC codes load: {}", e);
+    }
+
+    Ok(report)
+}
+
+// Импорт кодов из CSV/XLSX. Колонки по порядку: код, расшифровка. Расшифровки известных кодов обновляются.
+#[tauri::command]
+pub async fn import_dtc_codes(
+    session_token: String,
+    file_name: String,
+    content: Vec<u8>,
+    state: tauri::State<'_, Database>
+) -> Result<DtcImportReport, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "import_dtc_codes").await?;
+
+    price_lists::check_file_size(&content, "кодов DTC")?;
+    let rows = price_lists::read_rows(&file_name, &content)?;
+    let report = import_rows(&state.pool, &rows, true).await?;
+
+    // Логируем импорт кодов
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Import_DTC_Codes".to_string(),
+        format!("Импорт кодов OBD-II из файла '{}': добавлено или обновлено {}, отклонено {}", file_name, report.imported, report.rejected_count),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging DTC codes import: {}", e);
+    }
+
+    Ok(report)
+}
+
+// Задаёт полный список типов неисправностей, предлагаемых по коду
+#[tauri::command]
+pub async fn set_dtc_defect_types(session_token: String, dtc_code_id: i32, defect_type_ids: Vec<i32>, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "set_dtc_defect_types").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let code: String = sqlx::query("SELECT code FROM dtc_codes WHERE id = $1")
+        .bind(dtc_code_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Код OBD-II {} не найден", dtc_code_id))?
+        .get("code");
+
+    sqlx::query("DELETE FROM dtc_code_defect_types WHERE dtc_code_id = $1")
+        .bind(dtc_code_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    for defect_type_id in &defect_type_ids {
+        sqlx::query("INSERT INTO dtc_code_defect_types (dtc_code_id, defect_type_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(dtc_code_id)
+            .bind(defect_type_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error linking DTC code to defect type: {}", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем изменение справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Set_DTC_Defect_Types".to_string(),
+        format!("Код {} связан с {} типами неисправностей", code, defect_type_ids.len()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging DTC defect types: {}", e);
+    }
+
+    Ok(format!("Код {} связан с {} типами неисправностей", code, defect_type_ids.len()))
+}
+
+#[tauri::command]
+pub async fn get_order_dtc_codes(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderDtcCode>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_dtc_codes").await?;
+
+    let query = "SELECT o.id, o.order_id, o.dtc_code_id, o.code, o.description, u.full_name AS added_by_name, o.added_at::text AS added_at
+                 FROM order_dtc_codes o
+                 LEFT JOIN users u ON o.added_by = u.id
+                 WHERE o.order_id = $1
+                 ORDER BY o.added_at, o.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let ids: Vec<i32> = rows.iter().filter_map(|row| row.get::<Option<i32>, _>("dtc_code_id")).collect();
+    let defect_types = load_defect_types(&state.pool, &ids).await?;
+
+    Ok(rows.iter().map(|row| {
+        let dtc_code_id: Option<i32> = row.get("dtc_code_id");
+        OrderDtcCode {
+            id: row.get("id"),
+            order_id: row.get("order_id"),
+            code: row.get("code"),
+            description: row.get("description"),
+            added_by_name: row.get("added_by_name"),
+            added_at: row.get("added_at"),
+            defect_types: dtc_code_id.and_then(|id| defect_types.get(&id).cloned()).unwrap_or_default(),
+        }
+    }).collect())
+}
+
+async fn check_diagnostics_status(tx: &mut sqlx::PgConnection, order_id: i32) -> Result<(), String> {
+    let status: String = sqlx::query("SELECT status::text AS status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?
+        .get("status");
+    if status != OrderStatus::Diagnostics.as_str() {
+        return Err(format!("Коды ошибок заказа {} можно менять только на этапе диагностики (статус '{}')", order_id, status));
+    }
+    Ok(())
+}
+
+// Прикрепляет к заказу считанный сканером код. Расшифровка берётся из справочника,
+// для неизвестного кода можно указать её вручную.
+#[tauri::command]
+pub async fn add_order_dtc_code(session_token: String, order_id: i32, code: String, description: Option<String>, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::Diagnose, "add_order_dtc_code").await?;
+
+    let code = normalize_code(&code)?;
+    let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+    check_diagnostics_status(&mut tx, order_id).await?;
+
+    let query = "INSERT INTO order_dtc_codes (order_id, dtc_code_id, code, description, added_by)
+                 SELECT $1, d.id, $2, COALESCE(d.description, $3), $4
+                 FROM (SELECT 1) AS one
+                 LEFT JOIN dtc_codes d ON d.code = $2
+                 ON CONFLICT (order_id, code) DO NOTHING";
+    let result = sqlx::query(query)
+        .bind(order_id)
+        .bind(&code)
+        .bind(&description)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Код {} уже добавлен в заказ {}", code, order_id));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем добавление кода
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Add_Order_DTC_Code".to_string(),
+        format!("В заказ {} добавлен код неисправности {}", order_id, code),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging order DTC code: {}", e);
+    }
+
+    Ok(code)
+}
+
+#[tauri::command]
+pub async fn remove_order_dtc_code(session_token: String, order_dtc_code_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::Diagnose, "remove_order_dtc_code").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let row = sqlx::query("SELECT order_id, code FROM order_dtc_codes WHERE id = $1")
+        .bind(order_dtc_code_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Код неисправности {} не найден", order_dtc_code_id))?;
+    let order_id: i32 = row.get("order_id");
+    let code: String = row.get("code");
+    check_diagnostics_status(&mut tx, order_id).await?;
+
+    sqlx::query("DELETE FROM order_dtc_codes WHERE id = $1")
+        .bind(order_dtc_code_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем удаление кода
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Remove_Order_DTC_Code".to_string(),
+        format!("Из заказа {} удалён код неисправности {}", order_id, code),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging order DTC code removal: {}", e);
+    }
+
+    Ok(code)
+}
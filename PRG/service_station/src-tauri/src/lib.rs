@@ -1,11 +1,64 @@
 use tauri::{Manager, async_runtime::block_on};
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
-use sqlx::types::BigDecimal;
+use rust_decimal::Decimal;
+use std::str::FromStr;
 
 mod database;
 use database::Database;
 
+mod auth;
+use auth::{Permission, UserRole};
+
+mod order_status;
+use order_status::OrderStatus;
+
+mod sessions;
+
+mod warehouse;
+
+mod stock;
+use stock::{MovementType, NewMovement};
+
+mod parts_issue;
+
+mod reservations;
+
+mod reorder;
+
+mod purchasing;
+
+mod parts_providers;
+
+mod price_lists;
+
+mod cross_references;
+
+mod settings;
+
+mod documents;
+
+mod payments;
+
+mod order_totals;
+
+mod money;
+use money::Money;
+
+mod work_time;
+
+mod payroll;
+
+mod qc;
+
+mod diagnostics;
+
+mod defect_services;
+
+mod measurements;
+
+mod dtc;
+
 // Define data structures
 #[derive(Serialize, Deserialize, Clone)]
 struct User {
@@ -28,8 +81,8 @@ struct Order {
     status: String, // We keep as String for compatibility with frontend
     complaint: Option<String>,
     current_mileage: Option<i32>,
-    prepayment: Option<String>, // Decimal as string
-    total_amount: Option<String>, // Decimal as string
+    prepayment: Option<Money>,
+    total_amount: Option<Money>,
     created_at: String,
     completed_at: Option<String>,
 }
@@ -65,6 +118,8 @@ struct OrderDefect {
     defect_description: String,
     diagnostician_comment: Option<String>, // Может быть пустым
     is_confirmed: bool,
+    defect_type_id: Option<i32>, // Тип неисправности из справочника
+    has_out_of_range: bool, // Есть замеры вне допустимого диапазона
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -73,10 +128,12 @@ struct OrderWork {
     order_id: i32,
     service_id: Option<i32>, // Может быть null
     service_name_snapshot: String,
-    price: String, // Decimal as string for compatibility
+    price: Money,
     worker_id: Option<i32>, // Может быть null
-    status: String, // Статус работы (Pending, In_Progress, Done)
+    status: String, // Статус работы (Pending, In_Progress, Paused, Done)
     is_confirmed: bool, // Подтверждено ли клиентом
+    discount_percent: String, // Скидка на работу, %
+    worked_seconds: i64, // Учтённое время по интервалам работы, включая текущий
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -86,9 +143,11 @@ struct OrderPart {
     warehouse_item_id: Option<i32>, // Может быть null (если не со склада)
     part_name_snapshot: String,
     brand: String,
-    price_per_unit: String, // Decimal as string for compatibility
+    price_per_unit: Money,
     quantity: i32,
     is_confirmed: bool, // Подтверждено ли клиентом
+    discount_percent: String, // Скидка на запчасть, %
+    is_returned: bool, // Запчасть полностью возвращена на склад
 }
 
 
@@ -138,11 +197,7 @@ async fn login_user(username: String, password: String, state: tauri::State<'_,
                         };
 
                         // Create a session token
-                        let session_token = Uuid::new_v4().to_string();
-                        {
-                            let mut sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-                            sessions.insert(session_token.clone(), user.clone());
-                        }
+                        let session_token = sessions::create_session(&state.pool, user.id).await?;
 
                         // Логируем успешный вход пользователя
                         let log_result = log_event(
@@ -198,11 +253,7 @@ async fn login_worker(pin: String, state: tauri::State<'_, Database>) -> Result<
         };
 
         // Create a session token
-        let session_token = Uuid::new_v4().to_string();
-        {
-            let mut sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-            sessions.insert(session_token.clone(), user.clone());
-        }
+        let session_token = sessions::create_session(&state.pool, user.id).await?;
 
         // Логируем успешный вход работника
         let log_result = log_event(
@@ -223,38 +274,45 @@ async fn login_worker(pin: String, state: tauri::State<'_, Database>) -> Result<
     }
 }
 
-// In-memory session storage (in a real application, you'd use a proper session store)
-use std::collections::HashMap;
-use std::sync::Mutex;
-use uuid::Uuid;
-
-// Simple session store - in a real application, use a more secure approach
-static SESSIONS: once_cell::sync::Lazy<Mutex<HashMap<String, User>>> =
-    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
-
-
 #[tauri::command]
-async fn get_user_session(session_token: Option<String>) -> Result<Option<User>, String> {
+async fn get_user_session(session_token: Option<String>, state: tauri::State<'_, Database>) -> Result<Option<User>, String> {
     if let Some(token) = session_token {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        Ok(sessions.get(&token).cloned())
+        sessions::get_session_user(&state.pool, &token).await
     } else {
         Ok(None)
     }
 }
 
 #[tauri::command]
-async fn logout_user(session_token: String) -> Result<String, String> {
-    let mut sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-    sessions.remove(&session_token);
+async fn logout_user(session_token: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    let user_id = sessions::revoke_session(&state.pool, &session_token).await?;
+
+    // Логируем выход пользователя
+    if let Some(user_id) = user_id {
+        let log_result = log_event(
+            Some(user_id),
+            "Logout".to_string(),
+            "Выход пользователя из системы".to_string(),
+            None, // IP-адрес пока не реализован
+            state.clone()
+        ).await;
+
+        if let Err(e) = log_result {
+            eprintln!("Error logging user logout: {}", e);
+        }
+    }
+
     Ok("User logged out successfully".to_string())
 }
 
 // Order management
 #[tauri::command]
-async fn get_orders_for_master(state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+async fn get_orders_for_master(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewAllOrders, "get_orders_for_master").await?;
+
     // Query all orders for the master from the database (excluding Closed and Cancelled)
-    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE status != 'Closed' AND status != 'Cancelled' ORDER BY created_at DESC";
+    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders WHERE status != 'Closed' AND status != 'Cancelled' ORDER BY created_at DESC";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
         .await
@@ -287,7 +345,10 @@ async fn get_orders_for_master(state: tauri::State<'_, Database>) -> Result<Vec<
 }
 
 #[tauri::command]
-async fn get_client_by_id(client_id: i32, state: tauri::State<'_, Database>) -> Result<Option<Client>, String> {
+async fn get_client_by_id(session_token: String, client_id: i32, state: tauri::State<'_, Database>) -> Result<Option<Client>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewClients, "get_client_by_id").await?;
+
     let query = "SELECT id, full_name, phone, address, created_at::text FROM clients WHERE id = $1";
     let row = sqlx::query(query)
         .bind(client_id)
@@ -309,7 +370,10 @@ async fn get_client_by_id(client_id: i32, state: tauri::State<'_, Database>) ->
 }
 
 #[tauri::command]
-async fn get_car_by_id(car_id: i32, state: tauri::State<'_, Database>) -> Result<Option<Car>, String> {
+async fn get_car_by_id(session_token: String, car_id: i32, state: tauri::State<'_, Database>) -> Result<Option<Car>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewClients, "get_car_by_id").await?;
+
     let query = "SELECT id, client_id, vin, license_plate, make, model, production_year, mileage, last_visit_date::text, created_at::text FROM cars WHERE id = $1";
     let row = sqlx::query(query)
         .bind(car_id)
@@ -336,11 +400,14 @@ async fn get_car_by_id(car_id: i32, state: tauri::State<'_, Database>) -> Result
 }
 
 #[tauri::command]
-async fn search_orders_clients_cars(query: String, state: tauri::State<'_, Database>) -> Result<(Vec<Order>, Vec<Client>, Vec<Car>), String> {
+async fn search_orders_clients_cars(session_token: String, query: String, state: tauri::State<'_, Database>) -> Result<(Vec<Order>, Vec<Client>, Vec<Car>), String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewAllOrders, "search_orders_clients_cars").await?;
+
     let query_lower = format!("%{}%", query.to_lowercase());
 
     // Search for orders by ID
-    let order_query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE id::text LIKE $1";
+    let order_query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders WHERE id::text LIKE $1";
     let order_results = sqlx::query(order_query)
         .bind(&query)
         .fetch_all(&state.pool)
@@ -412,12 +479,22 @@ async fn search_orders_clients_cars(query: String, state: tauri::State<'_, Datab
 }
 
 #[tauri::command]
-async fn get_order_works_by_order_id(order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderWork>, String> {
+async fn get_order_works_by_order_id(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderWork>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_works_by_order_id").await?;
+
+    load_order_works(&state.pool, order_id).await
+}
+
+async fn load_order_works(pool: &sqlx::PgPool, order_id: i32) -> Result<Vec<OrderWork>, String> {
     // Запрос для получения работ по ID заказа
-    let query = "SELECT id, order_id, service_id, service_name_snapshot, price::text, worker_id, status::text as status, is_confirmed FROM order_works WHERE order_id = $1";
+    let query = "SELECT id, order_id, service_id, service_name_snapshot, price, worker_id, status::text as status, is_confirmed, discount_percent::text,
+                        (SELECT COALESCE(SUM(EXTRACT(EPOCH FROM COALESCE(t.finished_at, LOCALTIMESTAMP) - t.started_at)), 0)::bigint
+                         FROM work_time_intervals t WHERE t.work_id = order_works.id) AS worked_seconds
+                 FROM order_works WHERE order_id = $1";
     let rows = sqlx::query(query)
         .bind(order_id)
-        .fetch_all(&state.pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
@@ -432,6 +509,8 @@ async fn get_order_works_by_order_id(order_id: i32, state: tauri::State<'_, Data
             worker_id: row.get("worker_id"),
             status: row.get("status"),
             is_confirmed: row.get("is_confirmed"),
+            discount_percent: row.get("discount_percent"),
+            worked_seconds: row.get("worked_seconds"),
         });
     }
 
@@ -439,12 +518,21 @@ async fn get_order_works_by_order_id(order_id: i32, state: tauri::State<'_, Data
 }
 
 #[tauri::command]
-async fn get_order_parts_by_order_id(order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderPart>, String> {
+async fn get_order_parts_by_order_id(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderPart>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_parts_by_order_id").await?;
+
+    load_order_parts(&state.pool, order_id).await
+}
+
+async fn load_order_parts(pool: &sqlx::PgPool, order_id: i32) -> Result<Vec<OrderPart>, String> {
     // Запрос для получения запчастей по ID заказа
-    let query = "SELECT id, order_id, warehouse_item_id, part_name_snapshot, brand, price_per_unit::text, quantity, is_confirmed FROM order_parts WHERE order_id = $1";
+    let query = "SELECT id, order_id, warehouse_item_id, part_name_snapshot, brand, price_per_unit, quantity, is_confirmed, discount_percent::text,
+                        (returned_at IS NOT NULL AND issued_quantity = 0) AS is_returned
+                 FROM order_parts WHERE order_id = $1";
     let rows = sqlx::query(query)
         .bind(order_id)
-        .fetch_all(&state.pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
@@ -459,6 +547,8 @@ async fn get_order_parts_by_order_id(order_id: i32, state: tauri::State<'_, Data
             price_per_unit: row.get("price_per_unit"),
             quantity: row.get("quantity"),
             is_confirmed: row.get("is_confirmed"),
+            discount_percent: row.get("discount_percent"),
+            is_returned: row.get("is_returned"),
         });
     }
 
@@ -466,7 +556,10 @@ async fn get_order_parts_by_order_id(order_id: i32, state: tauri::State<'_, Data
 }
 
 #[tauri::command]
-async fn get_cars_by_client_id(client_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<Car>, String> {
+async fn get_cars_by_client_id(session_token: String, client_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<Car>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewClients, "get_cars_by_client_id").await?;
+
     let query = "SELECT id, client_id, vin, license_plate, make, model, production_year, mileage, last_visit_date::text, created_at::text FROM cars WHERE client_id=$1";
     let rows = sqlx::query(query)
         .bind(client_id)
@@ -493,11 +586,112 @@ async fn get_cars_by_client_id(client_id: i32, state: tauri::State<'_, Database>
     Ok(cars)
 }
 
+// Заказ из истории обслуживания автомобиля
+#[derive(Serialize, Deserialize, Clone)]
+struct CarHistoryOrder {
+    id: i32,
+    status: String,
+    complaint: Option<String>,
+    current_mileage: Option<i32>,
+    total_amount: Option<Money>,
+    created_at: String,
+    completed_at: Option<String>,
+    defects: Vec<OrderDefect>, // Подтверждённые неисправности
+    works: Vec<OrderWork>,     // Выполненные (подтверждённые и не отменённые) работы
+    parts: Vec<OrderPart>,     // Установленные запчасти
+    works_total: Money,
+    parts_total: Money,
+}
+
 #[tauri::command]
-async fn get_orders_for_storekeeper(state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+async fn get_car_service_history(session_token: String, car_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<CarHistoryOrder>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_car_service_history").await?;
+
+    let car_exists = sqlx::query("SELECT id FROM cars WHERE id = $1")
+        .bind(car_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if car_exists.is_none() {
+        return Err(format!("Car {} not found", car_id));
+    }
+
+    // Суммы считаются по тем же условиям, по которым отбираются работы и запчасти ниже:
+    // только выполненные работы и запчасти, не возвращённые на склад полностью
+    let query = "SELECT o.id, o.status::text AS status, o.complaint, o.current_mileage, o.total_amount,
+                        o.created_at::text AS created_at, o.completed_at::text AS completed_at,
+                        COALESCE((SELECT SUM(w.price) FROM order_works w
+                                  WHERE w.order_id = o.id AND w.is_confirmed = true AND w.status = 'Done'), 0) AS works_total,
+                        COALESCE((SELECT SUM(p.price_per_unit * COALESCE(p.quantity, 1)) FROM order_parts p
+                                  WHERE p.order_id = o.id AND p.is_confirmed = true
+                                    AND NOT (p.returned_at IS NOT NULL AND p.issued_quantity = 0)
+                                    AND o.status <> 'Cancelled'), 0) AS parts_total
+                 FROM orders o
+                 WHERE o.car_id = $1
+                 ORDER BY o.created_at, o.id";
+    let rows = sqlx::query(query)
+        .bind(car_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        let order_id: i32 = row.get("id");
+        let status: String = row.get("status");
+
+        let defects = load_diagnostic_results(&state.pool, order_id)
+            .await?
+            .into_iter()
+            .filter(|d| d.is_confirmed)
+            .collect();
+
+        let works = load_order_works(&state.pool, order_id)
+            .await?
+            .into_iter()
+            .filter(|w| w.is_confirmed && w.status == "Done")
+            .collect();
+
+        // При отмене заказа выданные запчасти возвращаются на склад, поэтому установленных запчастей у него нет
+        let parts = if status == OrderStatus::Cancelled.as_str() {
+            Vec::new()
+        } else {
+            load_order_parts(&state.pool, order_id)
+                .await?
+                .into_iter()
+                .filter(|p| p.is_confirmed && !p.is_returned)
+                .collect()
+        };
+
+        history.push(CarHistoryOrder {
+            id: order_id,
+            status,
+            complaint: row.get("complaint"),
+            current_mileage: row.get("current_mileage"),
+            total_amount: row.get("total_amount"),
+            created_at: row.get("created_at"),
+            completed_at: row.get("completed_at"),
+            defects,
+            works,
+            parts,
+            works_total: row.get("works_total"),
+            parts_total: row.get("parts_total"),
+        });
+    }
+
+    Ok(history)
+}
+
+#[tauri::command]
+async fn get_orders_for_storekeeper(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "get_orders_for_storekeeper").await?;
+
     // In a real application, this would query the database for orders that need storekeeper attention
     // For now, returning hardcoded data for testing purposes
-    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE status IN ('Parts_Selection', 'Approval', 'In_Work')";
+    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders WHERE status IN ('Parts_Selection', 'Approval', 'In_Work')";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
         .await
@@ -530,9 +724,20 @@ async fn get_orders_for_storekeeper(state: tauri::State<'_, Database>) -> Result
 }
 
 #[tauri::command]
-async fn get_orders_for_worker(worker_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
-    // Query to get orders assigned to a specific worker
-    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE worker_id = $1 AND status IN ('In_Work')";
+async fn get_orders_for_worker(session_token: String, worker_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "get_orders_for_worker").await?;
+
+    // Работник может просматривать только свои заказы
+    if user.role == UserRole::Worker.as_str() && user.id != worker_id {
+        return Err(auth::deny(&state, &user, "get_orders_for_worker", "заказы другого работника").await);
+    }
+
+    // Заказы, где работник - основной исполнитель или исполнитель отдельных работ (в том числе переделки после контроля качества)
+    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text
+                 FROM orders
+                 WHERE (worker_id = $1 OR EXISTS (SELECT 1 FROM order_works w WHERE w.order_id = orders.id AND w.worker_id = $1))
+                   AND status IN ('In_Work')";
     let rows = sqlx::query(query)
         .bind(worker_id)
         .fetch_all(&state.pool)
@@ -567,7 +772,10 @@ async fn get_orders_for_worker(worker_id: i32, state: tauri::State<'_, Database>
 
 // Service-DefectType relationship functions
 #[tauri::command]
-async fn get_service_defect_types(service_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+async fn get_service_defect_types(session_token: String, service_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_service_defect_types").await?;
+
     let query = "SELECT dt.id, dt.node_id, dn.name as node_name, dt.name, dt.description
                  FROM defect_type_services dts
                  JOIN defect_types dt ON dts.defect_type_id = dt.id
@@ -596,23 +804,28 @@ async fn get_service_defect_types(service_id: i32, state: tauri::State<'_, Datab
 
 #[tauri::command]
 async fn link_service_to_defect_type(
+    session_token: String,
     service_id: i32,
     defect_type_ids: Vec<i32>,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageCatalog, "link_service_to_defect_type").await?;
+
     let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
 
-    // Удаляем существующие связи для этой услуги
-    let delete_query = "DELETE FROM defect_type_services WHERE service_id = $1";
+    // Удаляем связи с типами, которых нет в новом списке; у оставшихся сохраняются роль и шаблоны запчастей
+    let delete_query = "DELETE FROM defect_type_services WHERE service_id = $1 AND NOT (defect_type_id = ANY($2))";
     sqlx::query(delete_query)
         .bind(service_id)
+        .bind(&defect_type_ids)
         .execute(&mut *tx)
         .await
         .map_err(|e| format!("Database error deleting old links: {}", e))?;
 
     // Создаем новые связи
     for defect_type_id in &defect_type_ids {
-        let insert_query = "INSERT INTO defect_type_services (defect_type_id, service_id) VALUES ($1, $2)";
+        let insert_query = "INSERT INTO defect_type_services (defect_type_id, service_id) VALUES ($1, $2) ON CONFLICT (defect_type_id, service_id) DO NOTHING";
         sqlx::query(insert_query)
             .bind(defect_type_id)
             .bind(service_id)
@@ -629,7 +842,10 @@ async fn link_service_to_defect_type(
 }
 
 #[tauri::command]
-async fn get_all_defect_types_grouped(state: tauri::State<'_, Database>) -> Result<Vec<DefectNodeWithTypes>, String> {
+async fn get_all_defect_types_grouped(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<DefectNodeWithTypes>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_all_defect_types_grouped").await?;
+
     // Сначала получим все узлы
     let node_query = "SELECT id, name, description FROM defect_nodes ORDER BY name";
     let node_rows = sqlx::query(node_query)
@@ -683,14 +899,18 @@ struct DefectNodeWithTypes {
 
 #[tauri::command]
 async fn get_archived_orders(
+    session_token: String,
     status_filter: String,
     period_start: String,
     period_end: String,
     search_query: String,
     state: tauri::State<'_, Database>
 ) -> Result<Vec<Order>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewArchive, "get_archived_orders").await?;
+
     // Query archived orders based on filters
-    let mut query_builder = sqlx::QueryBuilder::new("SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders");
+    let mut query_builder = sqlx::QueryBuilder::new("SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders");
 
     // Add initial WHERE clause based on status filter
     if status_filter != "All" {
@@ -752,13 +972,16 @@ async fn get_archived_orders(
 struct Service {
     id: i32,
     name: String,
-    base_price: String, // DECIMAL as string
+    base_price: Money,
     norm_hours: String, // DECIMAL as string
 }
 
 #[tauri::command]
-async fn get_all_services(state: tauri::State<'_, Database>) -> Result<Vec<Service>, String> {
-    let query = "SELECT id, name, base_price::text, norm_hours::text FROM services_reference ORDER BY name";
+async fn get_all_services(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<Service>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_all_services").await?;
+
+    let query = "SELECT id, name, base_price, norm_hours::text FROM services_reference ORDER BY name";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
         .await
@@ -777,27 +1000,38 @@ async fn get_all_services(state: tauri::State<'_, Database>) -> Result<Vec<Servi
     Ok(services)
 }
 
+// Норма часов услуги: колонка numeric(4,2), от 0 до 99.99
+fn parse_norm_hours(text: &str) -> Result<Decimal, String> {
+    let value = Decimal::from_str(&text.trim().replace(',', "."))
+        .map_err(|_| format!("Некорректная норма часов: '{}'", text))?;
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err("Норма часов не может быть отрицательной".to_string());
+    }
+    if value.scale() > 2 || value >= Decimal::ONE_HUNDRED {
+        return Err("Норма часов указывается не больше 99.99 и не более чем с двумя знаками после запятой".to_string());
+    }
+    Ok(value)
+}
+
 #[tauri::command]
 async fn create_service(
     session_token: String,
     name: String,
-    base_price: f64,
-    norm_hours: f64,
+    base_price: Money,
+    norm_hours: String,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "create_service").await?;
+
     // Validate inputs
     if name.trim().is_empty() {
         return Err("Название услуги не может быть пустым".to_string());
     }
-
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    let norm_hours = parse_norm_hours(&norm_hours)?;
 
     // Insert the new service into the database
-    let query = "INSERT INTO services_reference (name, base_price, norm_hours) VALUES ($1, $2::numeric, $3::numeric) RETURNING id";
+    let query = "INSERT INTO services_reference (name, base_price, norm_hours) VALUES ($1, $2, $3) RETURNING id";
     let row = sqlx::query(query)
         .bind(&name)
         .bind(base_price)
@@ -829,20 +1063,18 @@ async fn update_service(
     session_token: String,
     service_id: i32,
     name: String,
-    base_price: f64,
-    norm_hours: f64,
+    base_price: Money,
+    norm_hours: String,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "update_service").await?;
+
     // Validate inputs
     if name.trim().is_empty() {
         return Err("Название услуги не может быть пустым".to_string());
     }
-
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    let norm_hours = parse_norm_hours(&norm_hours)?;
 
     // Get the existing service name for logging
     let existing_query = "SELECT name FROM services_reference WHERE id = $1";
@@ -857,7 +1089,7 @@ async fn update_service(
     }
 
     // Update the service in the database
-    let query = "UPDATE services_reference SET name = $1, base_price = $2::numeric, norm_hours = $3::numeric WHERE id = $4";
+    let query = "UPDATE services_reference SET name = $1, base_price = $2, norm_hours = $3 WHERE id = $4";
     let result = sqlx::query(query)
         .bind(&name)
         .bind(base_price)
@@ -889,6 +1121,9 @@ async fn update_service(
 
 #[tauri::command]
 async fn delete_service(session_token: String, service_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "delete_service").await?;
+
     // First, check if there are any order_works associated with this service
     let check_query = "SELECT COUNT(*) as count FROM order_works WHERE service_id = $1";
     let row = sqlx::query(check_query)
@@ -902,12 +1137,6 @@ async fn delete_service(session_token: String, service_id: i32, state: tauri::St
         return Err("Невозможно удалить услугу, так как на неё ссылаются заказы".to_string());
     }
 
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
-
     // Get the existing service name for logging
     let existing_query = "SELECT name FROM services_reference WHERE id = $1";
     let existing_row = sqlx::query(existing_query)
@@ -949,22 +1178,32 @@ async fn delete_service(session_token: String, service_id: i32, state: tauri::St
 }
 
 #[tauri::command]
-async fn get_order_details_for_worker(order_id: i32, worker_id: i32, state: tauri::State<'_, Database>) -> Result<(Order, Vec<OrderWork>, Vec<OrderPart>, Vec<OrderDefect>), String> {
-    // Проверяем, что заказ назначен этому работнику
-    let check_query = "SELECT worker_id FROM orders WHERE id = $1";
+async fn get_order_details_for_worker(session_token: String, order_id: i32, worker_id: i32, state: tauri::State<'_, Database>) -> Result<(Order, Vec<OrderWork>, Vec<OrderPart>, Vec<OrderDefect>), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "get_order_details_for_worker").await?;
+
+    // Работник может просматривать только свои заказы
+    if user.role == UserRole::Worker.as_str() && user.id != worker_id {
+        return Err(auth::deny(&state, &user, "get_order_details_for_worker", "заказ другого работника").await);
+    }
+
+    // Проверяем, что заказ или одна из его работ назначены этому работнику
+    let check_query = "SELECT worker_id = $2 OR EXISTS (SELECT 1 FROM order_works w WHERE w.order_id = orders.id AND w.worker_id = $2) AS assigned
+                       FROM orders WHERE id = $1";
     let row = sqlx::query(check_query)
         .bind(order_id)
+        .bind(worker_id)
         .fetch_one(&state.pool)
         .await
         .map_err(|e| format!("Database error checking order assignment: {}", e))?;
 
-    let assigned_worker_id: Option<i32> = row.get("worker_id");
-    if assigned_worker_id != Some(worker_id) {
+    let assigned: Option<bool> = row.get("assigned");
+    if assigned != Some(true) {
         return Err(format!("Order {} is not assigned to worker {}", order_id, worker_id));
     }
 
     // Получаем информацию о заказе
-    let order_query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE id = $1";
+    let order_query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders WHERE id = $1";
     let order_row = sqlx::query(order_query)
         .bind(order_id)
         .fetch_one(&state.pool)
@@ -987,21 +1226,24 @@ async fn get_order_details_for_worker(order_id: i32, worker_id: i32, state: taur
     };
 
     // Получаем работы по заказу
-    let works = get_order_works_by_order_id(order_id, state.clone()).await?;
+    let works = load_order_works(&state.pool, order_id).await?;
 
     // Получаем запчасти по заказу
-    let parts = get_order_parts_by_order_id(order_id, state.clone()).await?;
+    let parts = load_order_parts(&state.pool, order_id).await?;
 
     // Получаем неисправности для отчета диагностики
-    let defects = get_diagnostic_results_by_order_id(order_id, state).await?;
+    let defects = load_diagnostic_results(&state.pool, order_id).await?;
 
     Ok((order, works, parts, defects))
 }
 
 #[tauri::command]
-async fn get_orders_for_diagnostician(state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+async fn get_orders_for_diagnostician(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<Order>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::Diagnose, "get_orders_for_diagnostician").await?;
+
     // Query to get orders that need diagnostics from the database
-    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment::text, total_amount::text, created_at::text, completed_at::text FROM orders WHERE status = 'Diagnostics'";
+    let query = "SELECT id, client_id, car_id, master_id, worker_id, status::text, complaint, current_mileage, prepayment, total_amount, created_at::text, completed_at::text FROM orders WHERE status = 'Diagnostics'";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
         .await
@@ -1034,15 +1276,13 @@ async fn get_orders_for_diagnostician(state: tauri::State<'_, Database>) -> Resu
 }
 
 #[tauri::command]
-async fn add_part_to_order(session_token: String, order_id: i32, part_name: String, brand: String, supplier: String, price: f64, _availability: String, _part_number: String, state: tauri::State<'_, Database>) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+async fn add_part_to_order(session_token: String, order_id: i32, part_name: String, brand: String, supplier: String, price: Money, _availability: String, _part_number: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::SelectParts, "add_part_to_order").await?;
 
-    // Добавляем запчасть в таблицу order_parts
-    let query = "INSERT INTO order_parts (order_id, part_name_snapshot, brand, supplier, price_per_unit, source_type) VALUES ($1, $2, $3, $4, $5::numeric, 'Supplier')";
+    // Добавляем запчасть в таблицу order_parts; если поставщик есть в справочнике, сохраняем ссылку на него
+    let query = "INSERT INTO order_parts (order_id, part_name_snapshot, brand, supplier, supplier_id, price_per_unit, source_type)
+                 VALUES ($1, $2, $3, $4, (SELECT id FROM suppliers WHERE LOWER(name) = LOWER(TRIM($4))), $5, 'Supplier')";
     sqlx::query(query)
         .bind(order_id)
         .bind(&part_name)
@@ -1057,7 +1297,7 @@ async fn add_part_to_order(session_token: String, order_id: i32, part_name: Stri
     let log_result = log_event(
         Some(user.id),
         "Add_Part_To_Order".to_string(),
-        format!("Добавлена запчасть '{}' (бренд: {}, поставщик: {}) в заказ {} за {} руб.",
+        format!("Добавлена запчасть '{}' (бренд: {}, поставщик: {}) в заказ {} за {}",
                 part_name, brand, supplier, order_id, price),
         None, // IP-адрес пока не реализован
         state.clone()
@@ -1087,9 +1327,9 @@ struct AddWarehouseItemRequest {
     #[serde(rename = "minQuantity")]
     min_quantity: i32,
     #[serde(rename = "purchasePrice")]
-    purchase_price: f64,
+    purchase_price: Money,
     #[serde(rename = "sellingPrice")]
-    selling_price: f64,
+    selling_price: Money,
 }
 
 #[tauri::command]
@@ -1120,39 +1360,57 @@ async fn add_warehouse_item(
     location_cell: String,
     quantity: i32,
     min_quantity: i32,
-    purchase_price: f64,
-    selling_price: f64,
+    purchase_price: Money,
+    selling_price: Money,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "add_warehouse_item").await?;
 
-    // Добавляем новую позицию на склад
-    let query = "INSERT INTO warehouse (name, brand, article, location_cell, quantity, min_quantity, purchase_price, selling_price) VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric) RETURNING id";
+    if quantity < 0 {
+        return Err("Количество не может быть отрицательным".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Добавляем новую позицию на склад с нулевым остатком, начальный остаток проводится как поступление
+    let query = "INSERT INTO warehouse (name, brand, article, location_cell, quantity, min_quantity, purchase_price, selling_price) VALUES ($1, $2, $3, $4, 0, $5, $6, $7) RETURNING id";
     let row = sqlx::query(query)
         .bind(&name)
         .bind(&brand)
         .bind(&article)
         .bind(&location_cell)
-        .bind(quantity)
         .bind(min_quantity)
         .bind(purchase_price)
         .bind(selling_price)
-        .fetch_one(&state.pool)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
     let new_id: i32 = row.get("id");
 
+    if quantity > 0 {
+        stock::record_movement(&mut tx, &NewMovement {
+            warehouse_item_id: new_id,
+            movement_type: MovementType::Receipt,
+            quantity,
+            unit_cost: Some(purchase_price),
+            user_id: Some(user.id),
+            order_part_id: None,
+            reason: Some("Начальный остаток при добавлении позиции"),
+        }).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
     // Логируем добавление новой позиции на склад
     let log_result = log_event(
         Some(user.id),
         "Add_Warehouse_Item".to_string(),
-        format!("Добавлена новая позиция на склад: '{}' (бренд: {}, артикул: {}) с ID {}",
-                name, brand, article, new_id),
+        format!("Добавлена новая позиция на склад: '{}' (бренд: {}, артикул: {}) с ID {}, начальный остаток: {}",
+                name, brand, article, new_id, quantity),
         None, // IP-адрес пока не реализован
         state.clone()
     ).await;
@@ -1166,33 +1424,70 @@ async fn add_warehouse_item(
 
 #[tauri::command]
 async fn confirm_order_parts_and_works(
+    session_token: String,
     order_id: i32,
     confirmed_works: Vec<i32>,
     confirmed_parts: Vec<i32>,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ApproveOrders, "confirm_order_parts_and_works").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
     // Обновляем статус у работ в заказе
     for work_id in &confirmed_works {
         let query = "UPDATE order_works SET is_confirmed = true WHERE id = $1 AND order_id = $2";
         sqlx::query(query)
             .bind(work_id)
             .bind(order_id)
-            .execute(&state.pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| format!("Database error updating work: {}", e))?;
     }
 
-    // Обновляем статус у запчастей в заказе
+    // Запчасти, с которых клиент снял отметку, перестают быть подтверждёнными и освобождают резерв
+    let deselect_query = "UPDATE order_parts SET is_confirmed = false
+                          WHERE order_id = $1 AND is_confirmed = true AND NOT (id = ANY($2))
+                          RETURNING id, issued_quantity, part_name_snapshot";
+    let deselected = sqlx::query(deselect_query)
+        .bind(order_id)
+        .bind(&confirmed_parts)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error updating part: {}", e))?;
+
+    for part in &deselected {
+        let issued_quantity: i32 = part.get("issued_quantity");
+        if issued_quantity > 0 {
+            let name: String = part.get("part_name_snapshot");
+            return Err(format!("Запчасть '{}' уже выдана в цех, сначала верните её на склад", name));
+        }
+        reservations::release_part(&mut tx, part.get("id"), "Отказ клиента от запчасти").await?;
+    }
+
+    // Обновляем статус у запчастей в заказе и резервируем запчасти со склада
     for part_id in &confirmed_parts {
         let query = "UPDATE order_parts SET is_confirmed = true WHERE id = $1 AND order_id = $2";
-        sqlx::query(query)
+        let result = sqlx::query(query)
             .bind(part_id)
             .bind(order_id)
-            .execute(&state.pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| format!("Database error updating part: {}", e))?;
+
+        if result.rows_affected() > 0 {
+            reservations::reserve_part(&mut tx, *part_id, user.id).await?;
+        }
     }
 
+    // Сумма заказа считается только по подтверждённым строкам
+    order_totals::recalculate(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
     // Независимо от того, есть ли подтвержденные работы или запчасти,
     // заказ остается в статусе "Approval", чтобы дать возможность назначить работников
     // Статус изменится на "In_Work" только при назначении работников через assign_workers_to_order
@@ -1203,7 +1498,10 @@ async fn confirm_order_parts_and_works(
 }
 
 #[tauri::command]
-async fn get_available_workers(state: tauri::State<'_, Database>) -> Result<Vec<User>, String> {
+async fn get_available_workers(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<User>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::AssignWorkers, "get_available_workers").await?;
+
     let query = "SELECT id, full_name, role::text, login, password_hash, pin_code, status::text FROM users WHERE role = 'Worker' AND status = 'Active'";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
@@ -1227,7 +1525,10 @@ async fn get_available_workers(state: tauri::State<'_, Database>) -> Result<Vec<
 }
 
 #[tauri::command]
-async fn check_database_triggers(state: tauri::State<'_, Database>) -> Result<String, String> {
+async fn check_database_triggers(session_token: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::Debug, "check_database_triggers").await?;
+
     let query = "
         SELECT 
             t.tgname as trigger_name,
@@ -1284,7 +1585,10 @@ async fn check_database_triggers(state: tauri::State<'_, Database>) -> Result<St
 }
 
 #[tauri::command]
-async fn debug_order_status(order_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+async fn debug_order_status(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::Debug, "debug_order_status").await?;
+
     // Получаем информацию о заказе
     let order_query = "SELECT id, worker_id, status::text FROM orders WHERE id = $1";
     let order_row = sqlx::query(order_query)
@@ -1331,11 +1635,27 @@ async fn assign_workers_to_order(
     main_worker_id: Option<i32>, // Optional main worker for the entire order
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::AssignWorkers, "assign_workers_to_order").await?;
+
+    // Проверяем, что заказ можно перевести в работу.
+    // Заказ на согласовании после подтверждения клиентом проходит через статус "Approved"
+    let status_row = sqlx::query("SELECT status::text FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+    let current_status: String = status_row.get("status");
+
+    if current_status == OrderStatus::Approval.as_str() {
+        order_status::check_transition_str(&current_status, OrderStatus::Approved.as_str(), &user.role)
+            .and_then(|_| order_status::check_transition_str(OrderStatus::Approved.as_str(), OrderStatus::InWork.as_str(), &user.role))
+            .map_err(|e| e.to_string())?;
+    } else {
+        order_status::check_transition_str(&current_status, OrderStatus::InWork.as_str(), &user.role)
+            .map_err(|e| e.to_string())?;
+    }
 
     // Назначаем работников на работы
     for (work_id, worker_id) in &work_assignments {
@@ -1353,14 +1673,19 @@ async fn assign_workers_to_order(
     let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
 
     // Сначала обновляем статус заказа
-    let status_query = "UPDATE orders SET status = $1::order_status WHERE id = $2";
-    sqlx::query(status_query)
-        .bind("In_Work")
+    let status_query = "UPDATE orders SET status = $1::order_status WHERE id = $2 AND status = $3::order_status";
+    let updated = sqlx::query(status_query)
+        .bind(OrderStatus::InWork.as_str())
         .bind(order_id)
+        .bind(&current_status)
         .execute(&mut *tx)
         .await
         .map_err(|e| format!("Database error updating order status: {}", e))?;
 
+    if updated.rows_affected() == 0 {
+        return Err(format!("Статус заказа {} был изменён другим пользователем, обновите данные", order_id));
+    }
+
     // Затем, если указан основной исполнитель, назначаем его
     if let Some(worker_id) = main_worker_id {
         let worker_query = "UPDATE orders SET worker_id = $1 WHERE id = $2";
@@ -1402,11 +1727,8 @@ async fn assign_workers_to_order(
 
 #[tauri::command]
 async fn create_order(session_token: String, client_id: i32, car_id: i32, complaint: Option<String>, current_mileage: Option<i32>, state: tauri::State<'_, Database>) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageOrders, "create_order").await?;
 
     // Insert a new order into the database with status 'Diagnostics'
     let query = "INSERT INTO orders (client_id, car_id, master_id, status, complaint, current_mileage, prepayment, total_amount, created_at) VALUES ($1, $2, NULL, 'Diagnostics', $3, $4, 0, 0, NOW()) RETURNING id";
@@ -1445,11 +1767,8 @@ async fn create_client(
     address: Option<String>,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageClients, "create_client").await?;
 
     // Проверяем, что обязательные поля заполнены
     if full_name.trim().is_empty() {
@@ -1565,7 +1884,10 @@ async fn create_client_with_json(
 }
 
 #[tauri::command]
-async fn get_all_clients(state: tauri::State<'_, Database>) -> Result<Vec<Client>, String> {
+async fn get_all_clients(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<Client>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewClients, "get_all_clients").await?;
+
     let query = "SELECT id, full_name, phone, address, created_at::text FROM clients ORDER BY full_name";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
@@ -1598,11 +1920,8 @@ async fn create_car(
     mileage: i32,
     state: tauri::State<'_, Database>
 ) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageClients, "create_car").await?;
 
     // Проверяем, что обязательные поля заполнены
     if make.trim().is_empty() {
@@ -1698,12 +2017,21 @@ async fn create_car(
 }
 
 #[tauri::command]
-async fn get_diagnostic_results_by_order_id(order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderDefect>, String> {
+async fn get_diagnostic_results_by_order_id(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderDefect>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_diagnostic_results_by_order_id").await?;
+
+    load_diagnostic_results(&state.pool, order_id).await
+}
+
+async fn load_diagnostic_results(pool: &sqlx::PgPool, order_id: i32) -> Result<Vec<OrderDefect>, String> {
     // Query to get diagnostic results for a specific order from the correct table
-    let query = "SELECT id, order_id, diagnostician_id, defect_description, diagnostician_comment, is_confirmed FROM order_defects WHERE order_id = $1";
+    let query = "SELECT id, order_id, diagnostician_id, defect_description, diagnostician_comment, is_confirmed, defect_type_id,
+                        EXISTS (SELECT 1 FROM order_defect_measurements m WHERE m.order_defect_id = order_defects.id AND m.is_out_of_range) AS has_out_of_range
+                 FROM order_defects WHERE order_id = $1 ORDER BY id";
     let rows = sqlx::query(query)
         .bind(order_id)
-        .fetch_all(&state.pool)
+        .fetch_all(pool)
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
@@ -1716,72 +2044,43 @@ async fn get_diagnostic_results_by_order_id(order_id: i32, state: tauri::State<'
             defect_description: row.get("defect_description"),
             diagnostician_comment: row.get("diagnostician_comment"),
             is_confirmed: row.get("is_confirmed"),
+            defect_type_id: row.get("defect_type_id"),
+            has_out_of_range: row.get("has_out_of_range"),
         });
     }
 
     Ok(results)
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct Part {
-    id: i32,
-    name: String,
-    brand: String,
-    supplier: String,
-    article: String,
-    price: f64,
-    availability: String, // Срок поставки
-}
-
 #[tauri::command]
-async fn search_parts_by_vin(_vin: String, _query: String, _state: tauri::State<'_, Database>) -> Result<Vec<Part>, String> {
-    // В реальном приложении поиск будет происходить по внешним API и внутреннему складу
-    // Для демонстрации возвращаем фиктивные данные
-    let parts = vec![
-        Part {
-            id: 1,
-            name: "Рычаг передний левый".to_string(),
-            brand: "Lemforder".to_string(),
-            supplier: "Склад СТО".to_string(),
-            article: "30333 01".to_string(),
-            price: 250.00,
-            availability: "В наличии".to_string(),
-        },
-        Part {
-            id: 2,
-            name: "Рычаг передний левый".to_string(),
-            brand: "TRW".to_string(),
-            supplier: "Армтек".to_string(),
-            article: "JTC1001".to_string(),
-            price: 240.00,
-            availability: "1 дн.".to_string(),
-        },
-        Part {
-            id: 3,
-            name: "Рычаг передний левый".to_string(),
-            brand: "Patron".to_string(),
-            supplier: "Шате-М".to_string(),
-            article: "PS5005".to_string(),
-            price: 120.00,
-            availability: "1 дн.".to_string(),
-        },
-        Part {
-            id: 4,
-            name: "Рычаг передний левый".to_string(),
-            brand: "Stellox".to_string(),
-            supplier: "Мотекс".to_string(),
-            article: "57-0001".to_string(),
-            price: 110.00,
-            availability: "2 дн.".to_string(),
-        },
-    ];
+async fn search_parts_by_vin(session_token: String, vin: String, query: String, state: tauri::State<'_, Database>) -> Result<Vec<parts_providers::PartOffer>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "search_parts_by_vin").await?;
 
-    Ok(parts)
+    if query.trim().is_empty() {
+        return Err("Введите артикул или название запчасти".to_string());
+    }
+
+    // Запрошенный артикул дополняется аналогами из справочника кросс-номеров
+    let analogs = cross_references::find_analogs(&state.pool, &parts_providers::normalize_article(&query)).await?;
+
+    // Поиск по собственному складу и прайс-листам поставщиков
+    let providers = parts_providers::default_providers(&state.pool);
+    let parts_query = parts_providers::PartsQuery {
+        vin: Some(vin.trim().to_string()).filter(|v| !v.is_empty()),
+        text: query,
+        analogs,
+    };
+
+    Ok(parts_providers::search_all(&providers, &parts_query).await)
 }
 
 // User management
 #[tauri::command]
-async fn get_all_users(state: tauri::State<'_, Database>) -> Result<Vec<User>, String> {
+async fn get_all_users(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<User>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageUsers, "get_all_users").await?;
+
     let query = "SELECT id, full_name, role::text, login, password_hash, pin_code, status::text FROM users";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
@@ -1808,11 +2107,8 @@ async fn get_all_users(state: tauri::State<'_, Database>) -> Result<Vec<User>, S
 async fn create_user(session_token: String, user_data: User, state: tauri::State<'_, Database>) -> Result<User, String> {
     use bcrypt::hash;
 
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageUsers, "create_user").await?;
 
     // Hash the password if it exists
     let password_hash = if let Some(password) = &user_data.password_hash {
@@ -1868,11 +2164,8 @@ async fn create_user(session_token: String, user_data: User, state: tauri::State
 async fn update_user(session_token: String, user_id: i32, user_data: User, state: tauri::State<'_, Database>) -> Result<String, String> {
     use bcrypt::hash;
 
-    // Получаем информацию о пользователе из сессии
-    let session_user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let session_user = auth::authorize(&state, &session_token, Permission::ManageUsers, "update_user").await?;
 
     // Получаем существующие данные пользователя для логирования
     let existing_user_query = "SELECT full_name FROM users WHERE id = $1";
@@ -1928,11 +2221,8 @@ async fn update_user(session_token: String, user_id: i32, user_data: User, state
 
 #[tauri::command]
 async fn delete_user(session_token: String, user_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let session_user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let session_user = auth::authorize(&state, &session_token, Permission::ManageUsers, "delete_user").await?;
 
     // Получаем существующие данные пользователя для логирования
     let existing_user_query = "SELECT full_name FROM users WHERE id = $1";
@@ -1987,7 +2277,10 @@ struct DefectType {
 }
 
 #[tauri::command]
-async fn get_defect_nodes(state: tauri::State<'_, Database>) -> Result<Vec<DefectNode>, String> {
+async fn get_defect_nodes(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<DefectNode>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_defect_nodes").await?;
+
     let query = "SELECT id, name, description FROM defect_nodes ORDER BY name";
     let rows = sqlx::query(query)
         .fetch_all(&state.pool)
@@ -2007,7 +2300,10 @@ async fn get_defect_nodes(state: tauri::State<'_, Database>) -> Result<Vec<Defec
 }
 
 #[tauri::command]
-async fn get_defect_types_by_node(node_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+async fn get_defect_types_by_node(session_token: String, node_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_defect_types_by_node").await?;
+
     let query = "SELECT dt.id, dt.node_id, dn.name as node_name, dt.name, dt.description
                  FROM defect_types dt
                  JOIN defect_nodes dn ON dt.node_id = dn.id
@@ -2034,7 +2330,10 @@ async fn get_defect_types_by_node(node_id: i32, state: tauri::State<'_, Database
 }
 
 #[tauri::command]
-async fn get_all_defect_types(state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+async fn get_all_defect_types(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<DefectType>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_all_defect_types").await?;
+
     let query = "SELECT dt.id, dt.node_id, dn.name as node_name, dt.name, dt.description
                  FROM defect_types dt
                  JOIN defect_nodes dn ON dt.node_id = dn.id
@@ -2065,17 +2364,14 @@ async fn create_defect_node(
     description: Option<String>,
     state: tauri::State<'_, Database>
 ) -> Result<DefectNode, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "create_defect_node").await?;
+
     // Validate inputs
     if name.trim().is_empty() {
         return Err("Название узла не может быть пустым".to_string());
     }
 
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
-
     // Insert a new defect node into the database
     let query = "INSERT INTO defect_nodes (name, description) VALUES ($1, $2) RETURNING id";
     let row = sqlx::query(query)
@@ -2115,6 +2411,9 @@ async fn create_defect_type(
     description: Option<String>,
     state: tauri::State<'_, Database>
 ) -> Result<DefectType, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "create_defect_type").await?;
+
     // Validate inputs
     if name.trim().is_empty() {
         return Err("Название типа неисправности не может быть пустым".to_string());
@@ -2132,12 +2431,6 @@ async fn create_defect_type(
         return Err(format!("Узел неисправности с ID {} не найден", node_id));
     }
 
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
-
     // Insert a new defect type into the database
     let query = "INSERT INTO defect_types (node_id, name, description) VALUES ($1, $2, $3) RETURNING id";
     let row = sqlx::query(query)
@@ -2175,119 +2468,10 @@ async fn create_defect_type(
     })
 }
 
-#[tauri::command]
-async fn save_diagnostic_results(order_id: i32, diagnostician_id: i32, defect_type_ids: Vec<i32>, state: tauri::State<'_, Database>) -> Result<String, String> {
-    let defects_count = defect_type_ids.len();
-    for defect_type_id in &defect_type_ids {
-        // Получаем информацию о типе неисправности
-        let defect_type_query = "SELECT dt.name, dt.description, dn.name as node_name
-                                 FROM defect_types dt
-                                 JOIN defect_nodes dn ON dt.node_id = dn.id
-                                 WHERE dt.id = $1";
-        let defect_type_row = sqlx::query(defect_type_query)
-            .bind(defect_type_id)
-            .fetch_one(&state.pool)
-            .await
-            .map_err(|e| format!("Database error fetching defect type: {}", e))?;
-
-        let defect_name: String = defect_type_row.get("name");
-        let defect_description: String = defect_type_row.get("description");
-        let node_name: String = defect_type_row.get("node_name");
-
-        // Вставляем неисправность, связанную с типом неисправности
-        let defect_query = "INSERT INTO order_defects (order_id, diagnostician_id, defect_description, diagnostician_comment, is_confirmed, defect_type_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id";
-        let defect_row = sqlx::query(defect_query)
-            .bind(order_id)
-            .bind(diagnostician_id)
-            .bind(format!("{}: {}", node_name, defect_name)) // формируем полное описание узел/неисправность
-            .bind(defect_description) // подробное описание
-            .bind(false) // is_confirmed пока false
-            .bind(defect_type_id)
-            .fetch_one(&state.pool)
-            .await
-            .map_err(|e| format!("Database error inserting defect: {}", e))?;
-
-        let defect_id: i32 = defect_row.get("id");
-
-        // Находим связанную услугу для этого типа неисправности
-        let service_query = "SELECT s.id, s.name, s.base_price, s.norm_hours
-                             FROM defect_type_services dts
-                             JOIN services_reference s ON dts.service_id = s.id
-                             WHERE dts.defect_type_id = $1
-                             LIMIT 1";
-        let service_row = sqlx::query(service_query)
-            .bind(defect_type_id)
-            .fetch_optional(&state.pool)
-            .await
-            .map_err(|e| format!("Database error fetching service for defect: {}", e))?;
-
-        // Если найдена связанная услуга, создаем работу на основе этой услуги
-        if let Some(service_row) = service_row {
-            let service_id: i32 = service_row.get("id");
-            let service_name: String = service_row.get("name");
-            let base_price: BigDecimal = service_row.get("base_price");
-            let norm_hours: BigDecimal = service_row.get("norm_hours");
-
-            // Создаем работу, связанную с этой услугой
-            let work_query = "INSERT INTO order_works (order_id, service_id, service_name_snapshot, price, norm_hours, is_confirmed) VALUES ($1, $2, $3, $4::numeric, $5::numeric, $6) RETURNING id";
-            let work_row = sqlx::query(work_query)
-                .bind(order_id)
-                .bind(service_id)
-                .bind(service_name) // используем имя услуги как название работы
-                .bind(base_price) // используем базовую цену из справочника
-                .bind(norm_hours)
-                .bind(false) // is_confirmed пока false
-                .fetch_one(&state.pool)
-                .await
-                .map_err(|e| format!("Database error inserting work: {}", e))?;
-
-            // Получаем ID созданной работы
-            let work_id: i32 = work_row.get("id");
-
-            // Создаем связь между работой и неисправностью в отдельной таблице
-            let work_defect_query = "INSERT INTO order_works_defects (work_id, defect_id) VALUES ($1, $2)";
-            sqlx::query(work_defect_query)
-                .bind(work_id)
-                .bind(defect_id)
-                .execute(&state.pool)
-                .await
-                .map_err(|e| format!("Database error linking work to defect: {}", e))?;
-        } else {
-            // Если нет связанной услуги, создаем работу с базовыми параметрами
-            let work_query = "INSERT INTO order_works (order_id, service_name_snapshot, price, norm_hours, is_confirmed) VALUES ($1, $2, $3::numeric, $4::numeric, $5) RETURNING id";
-            let work_row = sqlx::query(work_query)
-                .bind(order_id)
-                .bind(format!("{}: {}", node_name, defect_name)) // используем узел/неисправность как название работы
-                .bind(BigDecimal::from(0)) // базовая цена 0 до согласования
-                .bind(BigDecimal::from(1)) // условная норма часов
-                .bind(false) // is_confirmed пока false
-                .fetch_one(&state.pool)
-                .await
-                .map_err(|e| format!("Database error inserting work: {}", e))?;
-
-            // Получаем ID созданной работы
-            let work_id: i32 = work_row.get("id");
-
-            // Создаем связь между работой и неисправностью в отдельной таблице
-            let work_defect_query = "INSERT INTO order_works_defects (work_id, defect_id) VALUES ($1, $2)";
-            sqlx::query(work_defect_query)
-                .bind(work_id)
-                .bind(defect_id)
-                .execute(&state.pool)
-                .await
-                .map_err(|e| format!("Database error linking work to defect: {}", e))?;
-        }
-    }
-    Ok(format!("{} diagnostic results saved for order {}, with corresponding works", defects_count, order_id))
-}
-
 #[tauri::command]
 async fn update_order_status(session_token: String, order_id: i32, new_status: String, state: tauri::State<'_, Database>) -> Result<String, String> {
-    // Получаем информацию о пользователе из сессии
-    let user = {
-        let sessions = SESSIONS.lock().map_err(|_| "Session lock error")?;
-        sessions.get(&session_token).cloned().ok_or("Invalid session token")?
-    };
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ChangeOrderStatus, "update_order_status").await?;
 
     println!("Updating order {} status to {}", order_id, new_status);
 
@@ -2312,18 +2496,57 @@ async fn update_order_status(session_token: String, order_id: i32, new_status: S
         }
     };
 
+    // Отмена требует указания причины и возврата запчастей, поэтому выполняется только через cancel_order
+    if new_status == OrderStatus::Cancelled.as_str() {
+        return Err("Для отмены заказа используйте команду отмены с указанием причины".to_string());
+    }
+
+    // Готовность к выдаче подтверждается только проверкой по чек-листу контроля качества
+    if current_status == OrderStatus::QualityControl.as_str() && new_status == OrderStatus::Ready.as_str() {
+        return Err("Для приёмки работ пройдите чек-лист контроля качества".to_string());
+    }
+
+    // Проверяем переход по таблице допустимых переходов статусов
+    if let Err(e) = order_status::check_transition_str(&current_status, &new_status, &user.role) {
+        eprintln!("Rejected status change of order {}: {}", order_id, e);
+        return Err(e.to_string());
+    }
+
+    // Заказ уходит из работы только после остановки учёта времени по всем работам
+    if current_status == OrderStatus::InWork.as_str() {
+        let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+        work_time::check_no_running_works(&mut conn, order_id).await?;
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Закрыть заказ можно только после полной оплаты. Заказ блокируется до конца транзакции,
+    // чтобы параллельная оплата или возврат не изменили остаток между проверкой и сменой статуса
+    if new_status == OrderStatus::Closed.as_str() {
+        payments::lock_order(&mut tx, order_id).await?;
+        let balance = payments::outstanding_balance(&mut tx, order_id).await?;
+        if !balance.is_zero() {
+            return Err(format!("Заказ {} нельзя закрыть: остаток к оплате {}", order_id, balance));
+        }
+    }
+
     // Update the order status in the database
+    // Условие по текущему статусу защищает от одновременного изменения заказа из другого окна
     println!("About to execute update query with status: {} for order: {}", new_status, order_id);
-    let query = "UPDATE orders SET status = $1::order_status WHERE id = $2";
+    let query = "UPDATE orders SET status = $1::order_status WHERE id = $2 AND status = $3::order_status";
     let result = sqlx::query(query)
         .bind(&new_status)
         .bind(order_id)
-        .execute(&state.pool)
+        .bind(&current_status)
+        .execute(&mut *tx)
         .await;
 
     match result {
         Ok(updated) => {
             println!("Order {} status updated to {}, rows affected: {}", order_id, new_status, updated.rows_affected());
+            if updated.rows_affected() == 0 {
+                return Err(format!("Статус заказа {} был изменён другим пользователем, обновите данные", order_id));
+            }
         },
         Err(e) => {
             println!("Error during update: {}", e);
@@ -2331,6 +2554,15 @@ async fn update_order_status(session_token: String, order_id: i32, new_status: S
         }
     }
 
+    // После закрытия заказа невыданные запчасти больше не держат резерв
+    if new_status == OrderStatus::Closed.as_str() {
+        reservations::release_order(&mut tx, order_id, "Заказ закрыт").await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
     // Проверим, действительно ли статус изменился
     let verify_query = "SELECT status::text FROM orders WHERE id = $1";
     let verify_row = sqlx::query(verify_query)
@@ -2358,34 +2590,186 @@ async fn update_order_status(session_token: String, order_id: i32, new_status: S
     Ok(format!("Order {} status updated to {}", order_id, updated_status))
 }
 
+// Коды причин отмены заказа (соответствует ENUM cancel_reason в БД)
+const CANCEL_REASONS: &[&str] = &["Client_Refused", "Too_Expensive", "Parts_Unavailable", "Duplicate", "Other"];
+
+#[tauri::command]
+async fn cancel_order(
+    session_token: String,
+    order_id: i32,
+    reason: String,
+    comment: Option<String>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageOrders, "cancel_order").await?;
+
+    if !CANCEL_REASONS.contains(&reason.as_str()) {
+        return Err(format!("Неизвестная причина отмены заказа: {}", reason));
+    }
+
+    let comment = comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+
+    // Для причины "Другое" комментарий обязателен
+    if reason == "Other" && comment.is_none() {
+        return Err("Для причины отмены \"Другое\" необходимо указать комментарий".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Блокируем заказ до конца транзакции, чтобы его статус не изменили параллельно
+    let row = sqlx::query("SELECT status::text FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    let current_status: String = row.get("status");
+
+    if current_status == OrderStatus::Payment.as_str() || current_status == OrderStatus::Closed.as_str() {
+        return Err(format!("Заказ {} в статусе '{}' не может быть отменён", order_id, current_status));
+    }
+
+    if let Err(e) = order_status::check_transition_str(&current_status, OrderStatus::Cancelled.as_str(), &user.role) {
+        return Err(e.to_string());
+    }
+
+    // Возвращаем выданные со склада запчасти обратно на склад
+    // Количество берётся до обновления: UPDATE ... FROM видит старую строку в подзапросе
+    let issued_query = "UPDATE order_parts p SET issued_quantity = 0, is_issued = false, returned_at = NOW()
+                        FROM (SELECT id, issued_quantity FROM order_parts
+                              WHERE order_id = $1 AND issued_quantity > 0 AND warehouse_item_id IS NOT NULL
+                              FOR UPDATE) old
+                        WHERE p.id = old.id
+                        RETURNING p.id, p.warehouse_item_id, old.issued_quantity AS quantity";
+    let issued_parts = sqlx::query(issued_query)
+        .bind(order_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error updating order parts: {}", e))?;
+
+    let return_reason = format!("Отмена заказа {}", order_id);
+    for part in &issued_parts {
+        stock::record_movement(&mut tx, &NewMovement {
+            warehouse_item_id: part.get("warehouse_item_id"),
+            movement_type: MovementType::Return,
+            quantity: part.get("quantity"),
+            unit_cost: None,
+            user_id: Some(user.id),
+            order_part_id: Some(part.get("id")),
+            reason: Some(&return_reason),
+        }).await?;
+    }
+
+    reservations::release_order(&mut tx, order_id, &return_reason).await?;
+
+    // Отменяем все незавершённые работы по заказу, учёт времени по ним останавливается
+    work_time::stop_order_timers(&mut tx, order_id).await?;
+    let works_result = sqlx::query("UPDATE order_works SET status = 'Cancelled' WHERE order_id = $1 AND status IN ('Pending', 'In_Progress', 'Paused')")
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error cancelling order works: {}", e))?;
+
+    let order_query = "UPDATE orders
+                       SET status = 'Cancelled', cancel_reason = $2::cancel_reason, cancel_comment = $3,
+                           cancelled_at = NOW(), cancelled_by = $4
+                       WHERE id = $1";
+    sqlx::query(order_query)
+        .bind(order_id)
+        .bind(&reason)
+        .bind(&comment)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error cancelling order: {}", e))?;
+
+    // Отменённые работы больше не входят в сумму заказа
+    order_totals::recalculate(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем отмену заказа
+    let log_result = log_event(
+        Some(user.id),
+        "Cancel_Order".to_string(),
+        format!("Отменен заказ {} (статус до отмены: '{}', причина: {}, комментарий: {}). Возвращено на склад позиций: {}, отменено работ: {}",
+                order_id, current_status, reason, comment.as_deref().unwrap_or("-"),
+                issued_parts.len(), works_result.rows_affected()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging order cancellation: {}", e);
+    }
+
+    Ok(format!("Order {} cancelled", order_id))
+}
+
+#[tauri::command]
+async fn get_allowed_order_transitions(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<OrderStatus>, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ChangeOrderStatus, "get_allowed_order_transitions").await?;
+
+    let query = "SELECT status::text FROM orders WHERE id = $1";
+    let row = sqlx::query(query)
+        .bind(order_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    let current_status: OrderStatus = row.get::<String, _>("status").parse().map_err(|e: order_status::TransitionError| e.to_string())?;
+    let role: UserRole = user.role.parse()?;
+
+    Ok(order_status::allowed_next_statuses(current_status, role))
+}
+
 // System settings
 #[tauri::command]
-async fn get_system_settings(_state: tauri::State<'_, Database>) -> Result<String, String> {
-    // For now, return a placeholder JSON string
-    // In a real implementation, this would retrieve actual system settings from the database
-    Ok(r#"{
-        "company_name": "ООО 'АвтоСервис Про'",
-        "address": "г. Минск, ул. Ленина, 1",
-        "phone": "+375 () ___-__-__",
-        "diagnostics_cost": 500,
-        "work_schedule": {
-            "mon_to_fri": "09:00 - 18:00",
-            "saturday": "10:00 - 15:00",
-            "sunday": "Выходной"
-        }
-    }"#.to_string())
+async fn get_system_settings(session_token: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewSettings, "get_system_settings").await?;
+
+    let settings = settings::load(&state.pool).await?;
+    Ok(settings.to_string())
 }
 
 #[tauri::command]
-async fn save_system_settings(_settings: String, _state: tauri::State<'_, Database>) -> Result<String, String> {
-    // For now, just return success
-    // In a real implementation, this would save the settings to the database
+async fn save_system_settings(session_token: String, settings: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageSettings, "save_system_settings").await?;
+
+    settings::save(&state.pool, &settings, user.id).await?;
+
+    // Логируем изменение настроек
+    let log_result = log_event(
+        Some(user.id),
+        "Update_Settings".to_string(),
+        "Изменены настройки системы".to_string(),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging settings update: {}", e);
+    }
+
     Ok("System settings saved successfully".to_string())
 }
 
 // Event logs
 #[tauri::command]
-async fn get_system_logs(filters: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+async fn get_system_logs(session_token: String, filters: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewLogs, "get_system_logs").await?;
+
     use serde_json::Value;
 
     // Парсим фильтры из JSON строки
@@ -2448,7 +2832,7 @@ async fn get_system_logs(filters: String, state: tauri::State<'_, Database>) ->
     serde_json::to_string(&logs_json).map_err(|e| format!("JSON serialization error: {}", e))
 }
 
-#[tauri::command]
+// Запись события в журнал system_logs (используется другими командами, не вызывается из интерфейса)
 async fn log_event(
     user_id: Option<i32>,
     event_type: String,
@@ -2489,11 +2873,33 @@ pub fn run() {
             login_worker,
             get_user_session,
             logout_user,
+            sessions::get_active_sessions,
+            sessions::force_logout_user,
             get_orders_for_master,
             get_orders_for_storekeeper,
             get_orders_for_diagnostician,
-            save_diagnostic_results,
+            diagnostics::save_diagnostic_results,
+            diagnostics::get_order_defect_changes,
+            defect_services::get_defect_type_services,
+            defect_services::set_defect_type_service,
+            defect_services::remove_defect_type_service,
+            defect_services::get_order_work_suggestions,
+            defect_services::add_suggested_work,
+            measurements::get_defect_type_measurements,
+            measurements::add_defect_type_measurement,
+            measurements::delete_defect_type_measurement,
+            measurements::get_order_defect_measurements,
+            dtc::search_dtc_codes,
+            dtc::load_bundled_dtc_codes,
+            dtc::import_dtc_codes,
+            dtc::set_dtc_defect_types,
+            dtc::get_order_dtc_codes,
+            dtc::add_order_dtc_code,
+            dtc::remove_order_dtc_code,
             update_order_status,
+            get_allowed_order_transitions,
+            cancel_order,
+            get_car_service_history,
             get_client_by_id,
             get_car_by_id,
             get_cars_by_client_id,
@@ -2518,6 +2924,57 @@ pub fn run() {
             add_part_to_order,
             add_warehouse_item,
             add_warehouse_item_with_json,
+            warehouse::get_warehouse_items,
+            warehouse::update_warehouse_item,
+            warehouse::adjust_warehouse_quantity,
+            warehouse::set_warehouse_item_archived,
+            stock::get_stock_movements,
+            stock::get_stock_discrepancies,
+            stock::reconcile_stock_ledger,
+            parts_issue::get_order_issue_sheet,
+            parts_issue::issue_order_parts,
+            parts_issue::return_order_parts,
+            reservations::get_item_reservations,
+            reorder::get_reorder_proposals,
+            purchasing::get_suppliers,
+            purchasing::create_supplier,
+            purchasing::update_supplier,
+            purchasing::get_purchase_orders,
+            purchasing::get_purchase_order,
+            purchasing::create_purchase_order,
+            purchasing::send_purchase_order,
+            purchasing::delete_purchase_order,
+            purchasing::receive_purchase_order,
+            price_lists::get_price_list_mapping,
+            price_lists::import_price_list,
+            cross_references::get_cross_references,
+            cross_references::add_cross_reference,
+            cross_references::delete_cross_reference,
+            cross_references::import_cross_references,
+            documents::generate_order_document,
+            payments::get_order_payments,
+            payments::register_prepayment,
+            payments::register_payment,
+            payments::refund_payment,
+            order_totals::get_order_totals,
+            order_totals::preview_order_totals,
+            order_totals::set_order_discount,
+            order_totals::set_order_line_discount,
+            work_time::get_work_time,
+            work_time::start_work,
+            work_time::pause_work,
+            work_time::resume_work,
+            work_time::finish_work,
+            payroll::get_pay_schemes,
+            payroll::set_pay_scheme,
+            payroll::get_payroll_statement,
+            payroll::export_payroll_csv,
+            qc::get_qc_checklist_items,
+            qc::add_qc_checklist_item,
+            qc::delete_qc_checklist_item,
+            qc::get_order_qc_checklist,
+            qc::submit_qc_inspection,
+            qc::get_order_qc_history,
             confirm_order_parts_and_works,
             get_available_workers,
             assign_workers_to_order,
@@ -2541,9 +2998,28 @@ pub fn run() {
             create_user,
             update_user,
             delete_user,
-            get_system_logs,
-            log_event
+            get_system_logs
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_norm_hours_accepts_decimal_text() {
+        assert_eq!(parse_norm_hours("1.5").unwrap(), Decimal::from_str("1.5").unwrap());
+        assert_eq!(parse_norm_hours(" 0,25 ").unwrap(), Decimal::from_str("0.25").unwrap());
+        assert_eq!(parse_norm_hours("99.99").unwrap(), Decimal::from_str("99.99").unwrap());
+    }
+
+    #[test]
+    fn parse_norm_hours_rejects_invalid_values() {
+        assert!(parse_norm_hours("").is_err());
+        assert!(parse_norm_hours("-1").is_err());
+        assert!(parse_norm_hours("1.125").is_err());
+        assert!(parse_norm_hours("100").is_err());
+    }
+}
@@ -0,0 +1,416 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+
+// Типы значений замеров (соответствует ENUM measurement_value_type в БД)
+const VALUE_NUMBER: &str = "Number";
+const VALUE_TYPES: [&str; 2] = [VALUE_NUMBER, "Text"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MeasurementTemplate {
+    id: i32,
+    defect_type_id: i32,
+    name: String,
+    value_type: String,
+    unit: Option<String>,
+    min_value: Option<String>,
+    max_value: Option<String>,
+    flag_if_present: bool,
+    sort_order: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DefectMeasurement {
+    id: i32,
+    order_defect_id: i32,
+    template_id: Option<i32>,
+    name: String,
+    unit: Option<String>,
+    min_value: Option<String>,
+    max_value: Option<String>,
+    value: String,
+    is_out_of_range: bool,
+    measured_by_name: Option<String>,
+    measured_at: String,
+}
+
+// Значение замера из формы диагностики; пустое значение удаляет замер
+#[derive(Deserialize, Clone)]
+pub struct MeasurementInput {
+    template_id: i32,
+    value: Option<String>,
+}
+
+// Новый шаблон замера из справочника; пороги задаются только для числовых замеров
+#[derive(Deserialize)]
+pub struct MeasurementTemplateInput {
+    name: String,
+    #[serde(rename = "valueType")]
+    value_type: String,
+    unit: Option<String>,
+    #[serde(rename = "minValue")]
+    min_value: Option<String>,
+    #[serde(rename = "maxValue")]
+    max_value: Option<String>,
+    #[serde(rename = "flagIfPresent", default)]
+    flag_if_present: bool,
+    #[serde(rename = "sortOrder", default)]
+    sort_order: i32,
+}
+
+// Итог сохранения замеров одной неисправности
+pub struct SavedMeasurements {
+    pub changed: usize,
+    pub out_of_range: Vec<String>,
+}
+
+struct TemplateLimits {
+    id: i32,
+    name: String,
+    value_type: String,
+    unit: Option<String>,
+    min_value: Option<Decimal>,
+    max_value: Option<Decimal>,
+    flag_if_present: bool,
+}
+
+fn parse_number(text: &str, label: &str) -> Result<Decimal, String> {
+    Decimal::from_str(text.trim().replace(',', ".").as_str())
+        .map_err(|_| format!("Некорректное значение '{}' для замера '{}'", text, label))
+}
+
+fn parse_limit(value: Option<String>, label: &str) -> Result<Option<Decimal>, String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_number(&v, label))
+        .transpose()
+}
+
+// Числовое значение выходит за пороги шаблона; текстовое считается отклонением, если шаблон так настроен
+// (например, любой считанный код ошибки OBD)
+fn is_out_of_range(template: &TemplateLimits, number: Option<Decimal>, text: Option<&str>) -> bool {
+    match number {
+        Some(value) => template.min_value.is_some_and(|min| value < min) || template.max_value.is_some_and(|max| value > max),
+        None => template.flag_if_present && text.is_some(),
+    }
+}
+
+async fn load_type_templates(conn: &mut PgConnection, defect_type_id: i32) -> Result<Vec<TemplateLimits>, String> {
+    let query = "SELECT id, name, value_type::text AS value_type, unit, min_value, max_value, flag_if_present
+                 FROM defect_type_measurements WHERE defect_type_id = $1";
+    let rows = sqlx::query(query)
+        .bind(defect_type_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| TemplateLimits {
+        id: row.get("id"),
+        name: row.get("name"),
+        value_type: row.get("value_type"),
+        unit: row.get("unit"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+        flag_if_present: row.get("flag_if_present"),
+    }).collect())
+}
+
+// Замеры, которые удаляются при сохранении: по шаблонам другого типа (после смены типа неисправности).
+// Замеры, шаблон которых удалён из справочника (template_id стал NULL), остаются в истории заказа.
+fn stale_measurements(saved: &[(i32, Option<i32>)], template_ids: &[i32]) -> Vec<i32> {
+    saved
+        .iter()
+        .filter(|(_, template_id)| template_id.is_some_and(|id| !template_ids.contains(&id)))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+// Сохраняет замеры неисправности целиком: переданный список - итоговые значения по шаблонам её типа.
+// Замеры по шаблонам другого типа (после смены типа неисправности) удаляются.
+pub async fn save_defect_measurements(
+    conn: &mut PgConnection,
+    order_defect_id: i32,
+    defect_type_id: i32,
+    inputs: &[MeasurementInput],
+    user_id: i32
+) -> Result<SavedMeasurements, String> {
+    let templates = load_type_templates(conn, defect_type_id).await?;
+    if let Some(input) = inputs.iter().find(|input| !templates.iter().any(|t| t.id == input.template_id)) {
+        return Err(format!("Замер {} не относится к типу неисправности {}", input.template_id, defect_type_id));
+    }
+
+    let template_ids: Vec<i32> = templates.iter().map(|t| t.id).collect();
+    let rows = sqlx::query("SELECT id, template_id FROM order_defect_measurements WHERE order_defect_id = $1")
+        .bind(order_defect_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let existing: Vec<(i32, Option<i32>)> = rows.iter().map(|row| (row.get("id"), row.get("template_id"))).collect();
+    let stale = stale_measurements(&existing, &template_ids);
+    if !stale.is_empty() {
+        sqlx::query("DELETE FROM order_defect_measurements WHERE id = ANY($1)")
+            .bind(&stale)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error deleting measurements: {}", e))?;
+    }
+
+    let mut saved = SavedMeasurements { changed: stale.len(), out_of_range: Vec::new() };
+
+    for template in &templates {
+        let value = inputs.iter()
+            .find(|input| input.template_id == template.id)
+            .and_then(|input| input.value.as_deref())
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+
+        let existing = sqlx::query("SELECT numeric_value, text_value, is_out_of_range FROM order_defect_measurements WHERE order_defect_id = $1 AND template_id = $2")
+            .bind(order_defect_id)
+            .bind(template.id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let Some(value) = value else {
+            if existing.is_some() {
+                sqlx::query("DELETE FROM order_defect_measurements WHERE order_defect_id = $1 AND template_id = $2")
+                    .bind(order_defect_id)
+                    .bind(template.id)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Database error deleting measurement: {}", e))?;
+                saved.changed += 1;
+            }
+            continue;
+        };
+
+        let (number, text) = if template.value_type == VALUE_NUMBER {
+            (Some(parse_number(value, &template.name)?), None)
+        } else {
+            (None, Some(value))
+        };
+        let out_of_range = is_out_of_range(template, number, text);
+        if out_of_range {
+            saved.out_of_range.push(format!("{}: {}{}", template.name, value, template.unit.as_deref().map(|u| format!(" {}", u)).unwrap_or_default()));
+        }
+
+        // Пороги могли измениться в справочнике, поэтому сравнивается и флаг отклонения
+        let unchanged = existing.is_some_and(|row| {
+            row.get::<Option<Decimal>, _>("numeric_value") == number
+                && row.get::<Option<String>, _>("text_value").as_deref() == text
+                && row.get::<bool, _>("is_out_of_range") == out_of_range
+        });
+        if unchanged {
+            continue;
+        }
+
+        let query = "INSERT INTO order_defect_measurements (order_defect_id, template_id, name, unit, min_value, max_value, numeric_value, text_value, is_out_of_range, measured_by)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                     ON CONFLICT (order_defect_id, template_id) DO UPDATE
+                     SET name = EXCLUDED.name, unit = EXCLUDED.unit, min_value = EXCLUDED.min_value, max_value = EXCLUDED.max_value,
+                         numeric_value = EXCLUDED.numeric_value, text_value = EXCLUDED.text_value, is_out_of_range = EXCLUDED.is_out_of_range,
+                         measured_by = EXCLUDED.measured_by, measured_at = NOW()";
+        sqlx::query(query)
+            .bind(order_defect_id)
+            .bind(template.id)
+            .bind(&template.name)
+            .bind(&template.unit)
+            .bind(template.min_value)
+            .bind(template.max_value)
+            .bind(number)
+            .bind(text)
+            .bind(out_of_range)
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error saving measurement: {}", e))?;
+        saved.changed += 1;
+    }
+
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn get_defect_type_measurements(session_token: String, defect_type_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<MeasurementTemplate>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_defect_type_measurements").await?;
+
+    let query = "SELECT id, defect_type_id, name, value_type::text AS value_type, unit, min_value::text AS min_value, max_value::text AS max_value,
+                        flag_if_present, sort_order
+                 FROM defect_type_measurements
+                 WHERE defect_type_id = $1
+                 ORDER BY sort_order, id";
+    let rows = sqlx::query(query)
+        .bind(defect_type_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| MeasurementTemplate {
+        id: row.get("id"),
+        defect_type_id: row.get("defect_type_id"),
+        name: row.get("name"),
+        value_type: row.get("value_type"),
+        unit: row.get("unit"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+        flag_if_present: row.get("flag_if_present"),
+        sort_order: row.get("sort_order"),
+    }).collect())
+}
+
+#[tauri::command]
+pub async fn add_defect_type_measurement(
+    session_token: String,
+    defect_type_id: i32,
+    template: MeasurementTemplateInput,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "add_defect_type_measurement").await?;
+
+    let MeasurementTemplateInput { name, value_type, unit, min_value, max_value, flag_if_present, sort_order } = template;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Укажите, что измеряется".to_string());
+    }
+    if !VALUE_TYPES.contains(&value_type.as_str()) {
+        return Err(format!("Неизвестный тип значения замера: {}", value_type));
+    }
+    let min_value = parse_limit(min_value, &name)?;
+    let max_value = parse_limit(max_value, &name)?;
+    if value_type != VALUE_NUMBER && (min_value.is_some() || max_value.is_some()) {
+        return Err(format!("Для текстового замера '{}' нельзя задать минимум и максимум", name));
+    }
+    if let (Some(min), Some(max)) = (min_value, max_value) {
+        if min > max {
+            return Err("Минимальное значение не может быть больше максимального".to_string());
+        }
+    }
+    let unit = unit.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+
+    let query = "INSERT INTO defect_type_measurements (defect_type_id, name, value_type, unit, min_value, max_value, flag_if_present, sort_order)
+                 VALUES ($1, $2, $3::measurement_value_type, $4, $5, $6, $7, $8) RETURNING id";
+    let id: i32 = sqlx::query(query)
+        .bind(defect_type_id)
+        .bind(&name)
+        .bind(&value_type)
+        .bind(&unit)
+        .bind(min_value)
+        .bind(max_value)
+        .bind(flag_if_present && value_type != VALUE_NUMBER)
+        .bind(sort_order)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .get("id");
+
+    // Логируем изменение справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Add_Defect_Type_Measurement".to_string(),
+        format!("Добавлен замер '{}' для типа неисправности {}", name, defect_type_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging measurement template: {}", e);
+    }
+
+    Ok(id)
+}
+
+// Удаление шаблона не затрагивает сохранённые замеры: они хранят копию названия и порогов
+#[tauri::command]
+pub async fn delete_defect_type_measurement(session_token: String, template_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "delete_defect_type_measurement").await?;
+
+    let result = sqlx::query("DELETE FROM defect_type_measurements WHERE id = $1")
+        .bind(template_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Шаблон замера {} не найден", template_id));
+    }
+
+    // Логируем изменение справочника
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Delete_Defect_Type_Measurement".to_string(),
+        format!("Удалён шаблон замера {}", template_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging measurement template removal: {}", e);
+    }
+
+    Ok(format!("Шаблон замера {} удалён", template_id))
+}
+
+#[tauri::command]
+pub async fn get_order_defect_measurements(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<DefectMeasurement>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_defect_measurements").await?;
+
+    let query = "SELECT m.id, m.order_defect_id, m.template_id, m.name, m.unit, m.min_value::text AS min_value, m.max_value::text AS max_value,
+                        COALESCE(m.numeric_value::text, m.text_value, '') AS value, m.is_out_of_range,
+                        u.full_name AS measured_by_name, m.measured_at::text AS measured_at
+                 FROM order_defect_measurements m
+                 JOIN order_defects d ON m.order_defect_id = d.id
+                 LEFT JOIN defect_type_measurements t ON m.template_id = t.id
+                 LEFT JOIN users u ON m.measured_by = u.id
+                 WHERE d.order_id = $1
+                 ORDER BY m.order_defect_id, t.sort_order, m.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| DefectMeasurement {
+        id: row.get("id"),
+        order_defect_id: row.get("order_defect_id"),
+        template_id: row.get("template_id"),
+        name: row.get("name"),
+        unit: row.get("unit"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+        value: row.get("value"),
+        is_out_of_range: row.get("is_out_of_range"),
+        measured_by_name: row.get("measured_by_name"),
+        measured_at: row.get("measured_at"),
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurements_of_another_type_are_stale() {
+        // Тип неисправности сменился: у нового типа шаблоны 5 и 6
+        let saved = vec![(1, Some(2)), (2, Some(5)), (3, Some(3))];
+
+        assert_eq!(stale_measurements(&saved, &[5, 6]), vec![1, 3]);
+    }
+
+    #[test]
+    fn measurements_of_deleted_template_survive_resave() {
+        // Шаблон удалён из справочника (template_id стал NULL), затем ведомость сохранена повторно
+        let saved = vec![(1, None), (2, Some(5))];
+
+        assert!(stale_measurements(&saved, &[5]).is_empty());
+        assert_eq!(stale_measurements(&saved, &[]), vec![2]);
+    }
+}
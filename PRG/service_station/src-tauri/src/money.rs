@@ -0,0 +1,234 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres};
+use sqlx::{Decode, Encode, Type};
+
+// Валюта, в которой хранятся все суммы в БД (колонки numeric без кода валюты)
+pub const BASE_CURRENCY: &str = "BYN";
+const SUPPORTED_CURRENCIES: &[&str] = &[BASE_CURRENCY];
+// Суммы хранятся с точностью до копеек
+const SCALE: u32 = 2;
+
+// Денежная сумма: точное десятичное значение и код валюты.
+// Во фронтенд передаётся как { amount: "12.50", currency: "BYN" }.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money {
+    amount: Decimal,
+    currency: &'static str,
+}
+
+impl Money {
+    pub fn zero() -> Self {
+        Money { amount: Decimal::ZERO.round_dp(SCALE), currency: BASE_CURRENCY }
+    }
+
+    // Сумма, введённая пользователем или пришедшая из файла: не отрицательная, не больше двух знаков после запятой
+    pub fn new(amount: Decimal, currency: &str) -> Result<Self, String> {
+        let currency = SUPPORTED_CURRENCIES
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(currency.trim()))
+            .ok_or(format!("Валюта '{}' не поддерживается, суммы указываются в {}", currency, BASE_CURRENCY))?;
+        if amount.is_sign_negative() && !amount.is_zero() {
+            return Err(format!("Сумма не может быть отрицательной: {}", amount));
+        }
+        if amount.normalize().scale() > SCALE {
+            return Err(format!("Сумма указывается не более чем с {} знаками после запятой: {}", SCALE, amount));
+        }
+        Ok(Money::exact(amount, currency))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let amount = Decimal::from_str(text.trim()).map_err(|_| format!("Некорректная сумма: '{}'", text))?;
+        Money::new(amount, BASE_CURRENCY)
+    }
+
+    // Значение из БД или результат расчёта: округляется до копеек без проверок
+    fn exact(amount: Decimal, currency: &'static str) -> Self {
+        let mut amount = amount.round_dp_with_strategy(SCALE, RoundingStrategy::MidpointAwayFromZero);
+        amount.rescale(SCALE);
+        Money { amount, currency }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.amount > Decimal::ZERO
+    }
+
+    // Сумма, умноженная на количество (например, ставка за нормо-час на число нормо-часов)
+    pub fn multiply(&self, factor: Decimal) -> Self {
+        Money::exact(self.amount * factor, self.currency)
+    }
+
+    // Доля суммы в процентах, округлённая до копеек
+    pub fn percent(&self, percent: Decimal) -> Self {
+        Money::exact(self.amount * percent / Decimal::from(100), self.currency)
+    }
+
+    // Сумма без знака валюты, для SQL-шаблонов и полей ввода
+    pub fn amount_text(&self) -> String {
+        format!("{:.2}", self.amount)
+    }
+}
+
+impl Default for Money {
+    fn default() -> Self {
+        Money::zero()
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} {}", self.amount, self.currency)
+    }
+}
+
+// В журналах сумма выводится так же, как в интерфейсе
+impl fmt::Debug for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money::exact(self.amount + other.amount, self.currency)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money::exact(self.amount - other.amount, self.currency)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Money", 2)?;
+        state.serialize_field("amount", &self.amount_text())?;
+        state.serialize_field("currency", self.currency)?;
+        state.end()
+    }
+}
+
+// Фронтенд может прислать сумму строкой, числом или объектом { amount, currency }
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AmountInput {
+    Text(String),
+    Number(serde_json::Number),
+}
+
+impl AmountInput {
+    fn into_decimal(self) -> Result<Decimal, String> {
+        let text = match self {
+            AmountInput::Text(text) => text,
+            AmountInput::Number(number) => number.to_string(),
+        };
+        Decimal::from_str(text.trim().replace(',', ".").as_str())
+            .or_else(|_| Decimal::from_scientific(text.trim()))
+            .map_err(|_| format!("Некорректная сумма: '{}'", text))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyInput {
+    Plain(AmountInput),
+    Object { amount: AmountInput, currency: Option<String> },
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (amount, currency) = match MoneyInput::deserialize(deserializer)? {
+            MoneyInput::Plain(amount) => (amount, None),
+            MoneyInput::Object { amount, currency } => (amount, currency),
+        };
+        let amount = amount.into_decimal().map_err(serde::de::Error::custom)?;
+        Money::new(amount, currency.as_deref().unwrap_or(BASE_CURRENCY)).map_err(serde::de::Error::custom)
+    }
+}
+
+// В БД сумма хранится как numeric в базовой валюте
+impl Type<Postgres> for Money {
+    fn type_info() -> PgTypeInfo {
+        <Decimal as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Decimal as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for Money {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <Decimal as Encode<'q, Postgres>>::encode_by_ref(&self.amount, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Money {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let amount = <Decimal as Decode<'r, Postgres>>::decode(value)?;
+        Ok(Money::exact(amount, BASE_CURRENCY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_amount_with_kopecks() {
+        let money = Money::parse(" 12.5 ").unwrap();
+        assert_eq!(money.amount_text(), "12.50");
+        assert_eq!(money.to_string(), "12.50 BYN");
+        assert!(Money::parse("0").unwrap().is_zero());
+    }
+
+    #[test]
+    fn parse_rejects_negative_amount() {
+        assert!(Money::parse("-0.01").is_err());
+        assert!(Money::parse("-10").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_more_than_two_decimals() {
+        assert!(Money::parse("10.005").is_err());
+        // Незначащие нули после копеек допустимы
+        assert_eq!(Money::parse("10.500").unwrap().amount_text(), "10.50");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_text() {
+        assert!(Money::parse("").is_err());
+        assert!(Money::parse("12 BYN").is_err());
+    }
+
+    #[test]
+    fn new_rejects_unsupported_currency() {
+        let amount = Decimal::from_str("10.00").unwrap();
+        assert!(Money::new(amount, "USD").is_err());
+        assert_eq!(Money::new(amount, "byn").unwrap(), Money::parse("10").unwrap());
+    }
+
+    #[test]
+    fn deserialize_rejects_unsupported_currency() {
+        let result: Result<Money, _> = serde_json::from_str(r#"{"amount": "10.00", "currency": "EUR"}"#);
+        assert!(result.is_err());
+        let money: Money = serde_json::from_str(r#"{"amount": 10.5, "currency": "BYN"}"#).unwrap();
+        assert_eq!(money.amount_text(), "10.50");
+    }
+}
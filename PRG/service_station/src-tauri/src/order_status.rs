@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::auth::UserRole;
+
+// Статусы заказа (соответствует ENUM order_status в БД)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    New,
+    Diagnostics,
+    #[serde(rename = "Parts_Selection")]
+    PartsSelection,
+    Approval,
+    Approved,
+    Agreed,
+    #[serde(rename = "In_Work")]
+    InWork,
+    #[serde(rename = "Quality_Control")]
+    QualityControl,
+    Ready,
+    Payment,
+    Closed,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::New => "New",
+            OrderStatus::Diagnostics => "Diagnostics",
+            OrderStatus::PartsSelection => "Parts_Selection",
+            OrderStatus::Approval => "Approval",
+            OrderStatus::Approved => "Approved",
+            OrderStatus::Agreed => "Agreed",
+            OrderStatus::InWork => "In_Work",
+            OrderStatus::QualityControl => "Quality_Control",
+            OrderStatus::Ready => "Ready",
+            OrderStatus::Payment => "Payment",
+            OrderStatus::Closed => "Closed",
+            OrderStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = TransitionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "New" => Ok(OrderStatus::New),
+            "Diagnostics" => Ok(OrderStatus::Diagnostics),
+            "Parts_Selection" => Ok(OrderStatus::PartsSelection),
+            "Approval" => Ok(OrderStatus::Approval),
+            "Approved" => Ok(OrderStatus::Approved),
+            "Agreed" => Ok(OrderStatus::Agreed),
+            "In_Work" => Ok(OrderStatus::InWork),
+            "Quality_Control" => Ok(OrderStatus::QualityControl),
+            "Ready" => Ok(OrderStatus::Ready),
+            "Payment" => Ok(OrderStatus::Payment),
+            "Closed" => Ok(OrderStatus::Closed),
+            "Cancelled" => Ok(OrderStatus::Cancelled),
+            _ => Err(TransitionError::UnknownStatus(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    UnknownStatus(String),
+    UnknownRole(String),
+    NotAllowed { from: OrderStatus, to: OrderStatus },
+    Forbidden { from: OrderStatus, to: OrderStatus, role: UserRole },
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::UnknownStatus(status) => {
+                write!(f, "Неизвестный статус заказа: {}", status)
+            }
+            TransitionError::UnknownRole(role) => {
+                write!(f, "Неизвестная роль пользователя: {}", role)
+            }
+            TransitionError::NotAllowed { from, to } => {
+                write!(f, "Недопустимый переход статуса заказа: '{}' -> '{}'", from, to)
+            }
+            TransitionError::Forbidden { from, to, role } => {
+                write!(f, "Роль '{}' не может переводить заказ из '{}' в '{}'", role, from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+struct Transition {
+    from: OrderStatus,
+    to: OrderStatus,
+    roles: &'static [UserRole],
+}
+
+use OrderStatus::*;
+use UserRole::{Diagnostician, Master, Storekeeper, Worker};
+
+// Таблица допустимых переходов между статусами заказа и ролей, которые могут их выполнять.
+// Администратор может выполнить любой переход из таблицы (но не переход вне её).
+const TRANSITIONS: &[Transition] = &[
+    Transition { from: New, to: Diagnostics, roles: &[Master] },
+    Transition { from: Diagnostics, to: PartsSelection, roles: &[Diagnostician, Master] },
+    Transition { from: PartsSelection, to: Approval, roles: &[Storekeeper, Master] },
+    Transition { from: Approval, to: Approved, roles: &[Master] },
+    Transition { from: Approval, to: Agreed, roles: &[Master] },
+    Transition { from: Approved, to: InWork, roles: &[Master] },
+    Transition { from: Agreed, to: InWork, roles: &[Master] },
+    Transition { from: InWork, to: QualityControl, roles: &[Worker, Master] },
+    Transition { from: QualityControl, to: InWork, roles: &[Master] },
+    Transition { from: QualityControl, to: Ready, roles: &[Master] },
+    Transition { from: Ready, to: Payment, roles: &[Master] },
+    Transition { from: Payment, to: Closed, roles: &[Master] },
+    // Отмена возможна на любом этапе до оплаты
+    Transition { from: New, to: Cancelled, roles: &[Master] },
+    Transition { from: Diagnostics, to: Cancelled, roles: &[Master] },
+    Transition { from: PartsSelection, to: Cancelled, roles: &[Master] },
+    Transition { from: Approval, to: Cancelled, roles: &[Master] },
+    Transition { from: Approved, to: Cancelled, roles: &[Master] },
+    Transition { from: Agreed, to: Cancelled, roles: &[Master] },
+    Transition { from: InWork, to: Cancelled, roles: &[Master] },
+    Transition { from: QualityControl, to: Cancelled, roles: &[Master] },
+    Transition { from: Ready, to: Cancelled, roles: &[Master] },
+];
+
+fn find_transition(from: OrderStatus, to: OrderStatus) -> Option<&'static Transition> {
+    TRANSITIONS.iter().find(|t| t.from == from && t.to == to)
+}
+
+fn role_may_fire(transition: &Transition, role: UserRole) -> bool {
+    role == UserRole::Admin || transition.roles.contains(&role)
+}
+
+// Проверяет, может ли пользователь с указанной ролью перевести заказ из одного статуса в другой
+pub fn check_transition(from: OrderStatus, to: OrderStatus, role: UserRole) -> Result<(), TransitionError> {
+    let transition = find_transition(from, to).ok_or(TransitionError::NotAllowed { from, to })?;
+
+    if role_may_fire(transition, role) {
+        Ok(())
+    } else {
+        Err(TransitionError::Forbidden { from, to, role })
+    }
+}
+
+// Вариант check_transition для строковых значений, приходящих из БД и фронтенда
+pub fn check_transition_str(from: &str, to: &str, role: &str) -> Result<(OrderStatus, OrderStatus), TransitionError> {
+    let from: OrderStatus = from.parse()?;
+    let to: OrderStatus = to.parse()?;
+    let role: UserRole = role
+        .parse()
+        .map_err(|_| TransitionError::UnknownRole(role.to_string()))?;
+
+    check_transition(from, to, role)?;
+    Ok((from, to))
+}
+
+// Список статусов, в которые пользователь с указанной ролью может перевести заказ
+pub fn allowed_next_statuses(from: OrderStatus, role: UserRole) -> Vec<OrderStatus> {
+    TRANSITIONS
+        .iter()
+        .filter(|t| t.from == from && role_may_fire(t, role))
+        .map(|t| t.to)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::UserRole::Admin;
+
+    const ALL_STATUSES: [OrderStatus; 12] = [
+        New, Diagnostics, PartsSelection, Approval, Approved, Agreed, InWork, QualityControl, Ready, Payment, Closed, Cancelled,
+    ];
+
+    #[test]
+    fn roles_may_fire_their_transitions() {
+        assert_eq!(check_transition(New, Diagnostics, Master), Ok(()));
+        assert_eq!(check_transition(Diagnostics, PartsSelection, Diagnostician), Ok(()));
+        assert_eq!(check_transition(PartsSelection, Approval, Storekeeper), Ok(()));
+        assert_eq!(check_transition(InWork, QualityControl, Worker), Ok(()));
+        assert_eq!(check_transition(Payment, Closed, Master), Ok(()));
+    }
+
+    #[test]
+    fn other_roles_are_forbidden() {
+        assert_eq!(
+            check_transition(New, Diagnostics, Worker),
+            Err(TransitionError::Forbidden { from: New, to: Diagnostics, role: Worker })
+        );
+        assert!(matches!(check_transition(Diagnostics, PartsSelection, Storekeeper), Err(TransitionError::Forbidden { .. })));
+        assert!(matches!(check_transition(QualityControl, Ready, Worker), Err(TransitionError::Forbidden { .. })));
+        assert!(matches!(check_transition(Payment, Closed, Diagnostician), Err(TransitionError::Forbidden { .. })));
+    }
+
+    #[test]
+    fn admin_may_fire_any_listed_transition_only() {
+        for transition in TRANSITIONS {
+            assert_eq!(check_transition(transition.from, transition.to, Admin), Ok(()));
+        }
+        assert_eq!(
+            check_transition(New, Closed, Admin),
+            Err(TransitionError::NotAllowed { from: New, to: Closed })
+        );
+    }
+
+    #[test]
+    fn cancellation_is_possible_only_before_payment() {
+        for from in ALL_STATUSES {
+            let expected = !matches!(from, Payment | Closed | Cancelled);
+            assert_eq!(check_transition(from, Cancelled, Master).is_ok(), expected, "{} -> Cancelled", from);
+            assert!(check_transition(from, Cancelled, Worker).is_err());
+        }
+        // Из отменённого заказа выйти нельзя
+        assert!(allowed_next_statuses(Cancelled, Admin).is_empty());
+    }
+
+    #[test]
+    fn string_statuses_and_roles_are_parsed() {
+        assert_eq!(check_transition_str("In_Work", "Quality_Control", "Worker"), Ok((InWork, QualityControl)));
+        assert_eq!(
+            check_transition_str("In_Work", "Unknown", "Worker"),
+            Err(TransitionError::UnknownStatus("Unknown".to_string()))
+        );
+        assert_eq!(
+            check_transition_str("In_Work", "Quality_Control", "Guest"),
+            Err(TransitionError::UnknownRole("Guest".to_string()))
+        );
+    }
+
+    #[test]
+    fn allowed_next_statuses_depend_on_role() {
+        assert_eq!(allowed_next_statuses(Approval, Master), vec![Approved, Agreed, Cancelled]);
+        assert_eq!(allowed_next_statuses(Approval, Storekeeper), Vec::<OrderStatus>::new());
+        assert_eq!(allowed_next_statuses(InWork, Worker), vec![QualityControl]);
+    }
+}
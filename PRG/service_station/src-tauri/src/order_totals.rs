@@ -0,0 +1,326 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::settings;
+
+// Сумма строки работы с учётом скидки по строке (копейки округляются по каждой строке)
+pub const WORK_AMOUNT_SQL: &str = "ROUND(price * (100 - discount_percent) / 100, 2)";
+// Сумма строки запчасти с учётом количества и скидки по строке
+pub const PART_AMOUNT_SQL: &str = "ROUND(price_per_unit * COALESCE(quantity, 1) * (100 - discount_percent) / 100, 2)";
+
+// Статусы, в которых состав и скидки заказа уже не меняются
+const LOCKED_STATUSES: &[&str] = &["Payment", "Closed", "Cancelled"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OrderTotals {
+    pub works_total: Money,
+    pub parts_total: Money,
+    pub subtotal: Money, // Работы и запчасти с учётом скидок по строкам
+    pub discount_percent: String,
+    pub discount_amount: Money,
+    pub vat_rate: String,
+    pub prices_include_vat: bool,
+    pub vat_amount: Money,
+    pub total_amount: Money,
+}
+
+// Какие строки заказа входят в расчёт
+pub enum Selection<'a> {
+    Confirmed,
+    // Предварительный расчёт для отмеченных клиентом строк, ещё не подтверждённых
+    Preview { works: &'a [i32], parts: &'a [i32] },
+}
+
+struct VatSettings {
+    rate: Decimal,
+    included: bool, // Цены в справочниках и заказах указаны с НДС
+}
+
+fn parse_decimal(text: &str) -> Result<Decimal, String> {
+    Decimal::from_str(text.trim()).map_err(|e| format!("Некорректное число '{}': {}", text, e))
+}
+
+// Процент скидки: от 0 до 100, не больше двух знаков после запятой
+pub fn parse_percent(percent: &str) -> Result<Decimal, String> {
+    let value = Decimal::from_str(&percent.trim().replace(',', "."))
+        .map_err(|_| format!("Некорректный процент скидки: '{}'", percent))?;
+    if value < Decimal::ZERO || value > Decimal::ONE_HUNDRED {
+        return Err("Скидка должна быть от 0 до 100%".to_string());
+    }
+    if value.scale() > 2 {
+        return Err("Скидка указывается не более чем с двумя знаками после запятой".to_string());
+    }
+    Ok(value)
+}
+
+fn vat_settings(settings: &Value) -> Result<VatSettings, String> {
+    let rate = match settings.get("vat_rate") {
+        Some(Value::Number(n)) => parse_decimal(&n.to_string())?,
+        Some(Value::String(s)) if !s.trim().is_empty() => parse_decimal(s)?,
+        _ => Decimal::ZERO,
+    };
+    if rate < Decimal::ZERO || rate > Decimal::from(100) {
+        return Err(format!("Некорректная ставка НДС в настройках: {}", rate));
+    }
+    let included = settings.get("prices_include_vat").and_then(|v| v.as_bool()).unwrap_or(true);
+    Ok(VatSettings { rate, included })
+}
+
+pub async fn compute(conn: &mut PgConnection, order_id: i32, selection: Selection<'_>) -> Result<OrderTotals, String> {
+    let order = sqlx::query("SELECT discount_percent::text AS discount_percent FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+    let discount_percent = parse_decimal(&order.get::<String, _>("discount_percent"))?;
+
+    let (line_filter, work_ids, part_ids) = match selection {
+        Selection::Confirmed => ("is_confirmed", Vec::new(), Vec::new()),
+        Selection::Preview { works, parts } => ("id = ANY($2)", works.to_vec(), parts.to_vec()),
+    };
+
+    // Отменённые работы в сумму не входят; $2 в запросе есть только при предварительном расчёте
+    let works_query = format!(
+        "SELECT COALESCE(SUM({}), 0) AS total FROM order_works WHERE order_id = $1 AND status <> 'Cancelled' AND {}",
+        WORK_AMOUNT_SQL, line_filter
+    );
+    let parts_query = format!(
+        "SELECT COALESCE(SUM({}), 0) AS total FROM order_parts WHERE order_id = $1 AND {}",
+        PART_AMOUNT_SQL, line_filter
+    );
+
+    let mut works_request = sqlx::query(&works_query).bind(order_id);
+    let mut parts_request = sqlx::query(&parts_query).bind(order_id);
+    if let Selection::Preview { .. } = selection {
+        works_request = works_request.bind(&work_ids);
+        parts_request = parts_request.bind(&part_ids);
+    }
+
+    let works_total: Money = works_request
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .get("total");
+    let parts_total: Money = parts_request
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .get("total");
+
+    let vat = vat_settings(&settings::load(&mut *conn).await?)?;
+
+    let subtotal = works_total + parts_total;
+    let discount_amount = subtotal.percent(discount_percent);
+    let net = subtotal - discount_amount;
+
+    // Если цены указаны с НДС, налог выделяется из суммы, иначе начисляется сверху
+    let hundred = Decimal::from(100);
+    let (vat_amount, total_amount) = if vat.included {
+        (net.percent(vat.rate * hundred / (hundred + vat.rate)), net)
+    } else {
+        let vat_amount = net.percent(vat.rate);
+        (vat_amount, net + vat_amount)
+    };
+
+    Ok(OrderTotals {
+        works_total,
+        parts_total,
+        subtotal,
+        discount_percent: discount_percent.to_string(),
+        discount_amount,
+        vat_rate: vat.rate.to_string(),
+        prices_include_vat: vat.included,
+        vat_amount,
+        total_amount,
+    })
+}
+
+// Пересчитывает итоги по подтверждённым строкам и сохраняет их в заказе.
+// Вызывается в той же транзакции, в которой меняются строки или скидки заказа.
+pub async fn recalculate(conn: &mut PgConnection, order_id: i32) -> Result<OrderTotals, String> {
+    let totals = compute(conn, order_id, Selection::Confirmed).await?;
+
+    let query = "UPDATE orders SET subtotal = $2, discount_amount = $3, vat_rate = $4::numeric,
+                                   vat_amount = $5, total_amount = $6
+                 WHERE id = $1";
+    sqlx::query(query)
+        .bind(order_id)
+        .bind(totals.subtotal)
+        .bind(totals.discount_amount)
+        .bind(&totals.vat_rate)
+        .bind(totals.vat_amount)
+        .bind(totals.total_amount)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(totals)
+}
+
+async fn lock_editable_order(conn: &mut PgConnection, order_id: i32) -> Result<(), String> {
+    let row = sqlx::query("SELECT status::text AS status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    let status: String = row.get("status");
+    if LOCKED_STATUSES.contains(&status.as_str()) {
+        return Err(format!("Скидки по заказу {} в статусе '{}' не меняются", order_id, status));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_order_totals(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<OrderTotals, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_totals").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    compute(&mut conn, order_id, Selection::Confirmed).await
+}
+
+// Итог для отмеченных при согласовании строк, до их подтверждения
+#[tauri::command]
+pub async fn preview_order_totals(
+    session_token: String,
+    order_id: i32,
+    works: Vec<i32>,
+    parts: Vec<i32>,
+    state: tauri::State<'_, Database>
+) -> Result<OrderTotals, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "preview_order_totals").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    compute(&mut conn, order_id, Selection::Preview { works: &works, parts: &parts }).await
+}
+
+#[tauri::command]
+pub async fn set_order_discount(session_token: String, order_id: i32, discount_percent: String, state: tauri::State<'_, Database>) -> Result<OrderTotals, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ApproveOrders, "set_order_discount").await?;
+
+    let discount = parse_percent(&discount_percent)?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    lock_editable_order(&mut tx, order_id).await?;
+
+    sqlx::query("UPDATE orders SET discount_percent = $2::numeric WHERE id = $1")
+        .bind(order_id)
+        .bind(discount.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let totals = recalculate(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем изменение скидки
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Set_Order_Discount".to_string(),
+        format!("Скидка на заказ {} установлена {}%, итог {}", order_id, discount, totals.total_amount),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging order discount: {}", e);
+    }
+
+    Ok(totals)
+}
+
+// Скидка на отдельную строку заказа: line_type "work" или "part"
+#[tauri::command]
+pub async fn set_order_line_discount(
+    session_token: String,
+    order_id: i32,
+    line_type: String,
+    line_id: i32,
+    discount_percent: String,
+    state: tauri::State<'_, Database>
+) -> Result<OrderTotals, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ApproveOrders, "set_order_line_discount").await?;
+
+    let discount = parse_percent(&discount_percent)?;
+    let table = match line_type.as_str() {
+        "work" => "order_works",
+        "part" => "order_parts",
+        _ => return Err(format!("Неизвестный тип строки заказа: {}", line_type)),
+    };
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    lock_editable_order(&mut tx, order_id).await?;
+
+    let query = format!("UPDATE {} SET discount_percent = $3::numeric WHERE id = $1 AND order_id = $2", table);
+    let result = sqlx::query(&query)
+        .bind(line_id)
+        .bind(order_id)
+        .bind(discount.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Строка {} не найдена в заказе {}", line_id, order_id));
+    }
+
+    let totals = recalculate(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем изменение скидки
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Set_Order_Discount".to_string(),
+        format!("Скидка на строку {} ({}) заказа {} установлена {}%, итог {}", line_id, line_type, order_id, discount, totals.total_amount),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging order line discount: {}", e);
+    }
+
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_percent_accepts_decimal_text() {
+        assert_eq!(parse_percent("12.5").unwrap(), Decimal::from_str("12.5").unwrap());
+        assert_eq!(parse_percent(" 7,25 ").unwrap(), Decimal::from_str("7.25").unwrap());
+        assert_eq!(parse_percent("100").unwrap(), Decimal::ONE_HUNDRED);
+    }
+
+    #[test]
+    fn parse_percent_rejects_invalid_values() {
+        assert!(parse_percent("").is_err());
+        assert!(parse_percent("abc").is_err());
+        assert!(parse_percent("-1").is_err());
+        assert!(parse_percent("100.01").is_err());
+        assert!(parse_percent("10.125").is_err());
+    }
+}
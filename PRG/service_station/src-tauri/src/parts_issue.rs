@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::order_status::OrderStatus;
+use crate::reservations;
+use crate::stock::{self, MovementType, NewMovement};
+
+// Статусы заказа, в которых запчасти можно выдавать в цех и возвращать на склад
+const ISSUE_STATUSES: &[OrderStatus] = &[
+    OrderStatus::Approved,
+    OrderStatus::Agreed,
+    OrderStatus::InWork,
+    OrderStatus::QualityControl,
+];
+
+// Запчасть заказа в ведомости выдачи
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PartToIssue {
+    id: i32,
+    warehouse_item_id: i32,
+    part_name_snapshot: String,
+    brand: Option<String>,
+    quantity: i32,
+    issued_quantity: i32,
+    location_cell: Option<String>,
+    on_hand: i32, // Текущий остаток позиции на складе
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IssueSheet {
+    order_id: i32,
+    worker_id: Option<i32>,
+    worker_name: Option<String>,
+    parts: Vec<PartToIssue>,
+}
+
+#[derive(Deserialize)]
+pub struct PartIssueItem {
+    #[serde(rename = "orderPartId")]
+    order_part_id: i32,
+    #[serde(rename = "quantity")]
+    quantity: i32,
+}
+
+#[tauri::command]
+pub async fn get_order_issue_sheet(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<IssueSheet, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_order_issue_sheet").await?;
+
+    let order_row = sqlx::query("SELECT o.worker_id, u.full_name FROM orders o LEFT JOIN users u ON o.worker_id = u.id WHERE o.id = $1")
+        .bind(order_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    // В ведомость попадают только подтверждённые клиентом запчасти со склада
+    let query = "SELECT p.id, p.warehouse_item_id, p.part_name_snapshot, p.brand, COALESCE(p.quantity, 1) AS quantity,
+                        p.issued_quantity, w.location_cell, COALESCE(w.quantity, 0) AS on_hand
+                 FROM order_parts p
+                 JOIN warehouse w ON p.warehouse_item_id = w.id
+                 WHERE p.order_id = $1 AND p.is_confirmed = true
+                 ORDER BY p.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut parts = Vec::new();
+    for row in rows {
+        parts.push(PartToIssue {
+            id: row.get("id"),
+            warehouse_item_id: row.get("warehouse_item_id"),
+            part_name_snapshot: row.get("part_name_snapshot"),
+            brand: row.get("brand"),
+            quantity: row.get("quantity"),
+            issued_quantity: row.get("issued_quantity"),
+            location_cell: row.get("location_cell"),
+            on_hand: row.get("on_hand"),
+        });
+    }
+
+    Ok(IssueSheet {
+        order_id,
+        worker_id: order_row.get("worker_id"),
+        worker_name: order_row.get("full_name"),
+        parts,
+    })
+}
+
+// Блокирует заказ и проверяет, что по нему можно выдавать запчасти. Возвращает ID исполнителя заказа.
+async fn lock_order_for_issue(conn: &mut PgConnection, order_id: i32) -> Result<Option<i32>, String> {
+    let row = sqlx::query("SELECT status::text AS status, worker_id FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    let status: String = row.get("status");
+    if !ISSUE_STATUSES.iter().any(|s| s.as_str() == status) {
+        return Err(format!("Заказ {} в статусе '{}': выдача и возврат запчастей недоступны", order_id, status));
+    }
+
+    Ok(row.get("worker_id"))
+}
+
+// Блокирует запчасть заказа и возвращает (warehouse_item_id, quantity, issued_quantity, название)
+async fn lock_order_part(conn: &mut PgConnection, order_id: i32, order_part_id: i32) -> Result<(i32, i32, i32, String), String> {
+    let query = "SELECT warehouse_item_id, COALESCE(quantity, 1) AS quantity, issued_quantity, is_confirmed, part_name_snapshot
+                 FROM order_parts WHERE id = $1 AND order_id = $2 FOR UPDATE";
+    let row = sqlx::query(query)
+        .bind(order_part_id)
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Запчасть {} не найдена в заказе {}", order_part_id, order_id))?;
+
+    let name: String = row.get("part_name_snapshot");
+    let is_confirmed: bool = row.get("is_confirmed");
+    if !is_confirmed {
+        return Err(format!("Запчасть '{}' не подтверждена клиентом", name));
+    }
+
+    let warehouse_item_id: Option<i32> = row.get("warehouse_item_id");
+    let warehouse_item_id = warehouse_item_id.ok_or(format!("Запчасть '{}' не связана с позицией склада", name))?;
+
+    Ok((warehouse_item_id, row.get("quantity"), row.get("issued_quantity"), name))
+}
+
+#[tauri::command]
+pub async fn issue_order_parts(
+    session_token: String,
+    order_id: i32,
+    items: Vec<PartIssueItem>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "issue_order_parts").await?;
+
+    if items.is_empty() {
+        return Err("Не выбраны запчасти для выдачи".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let worker_id = lock_order_for_issue(&mut tx, order_id)
+        .await?
+        .ok_or(format!("Заказу {} не назначен исполнитель", order_id))?;
+
+    let reason = format!("Выдача в цех по заказу {}", order_id);
+    let mut issued = Vec::new();
+    for item in &items {
+        let (warehouse_item_id, quantity, issued_quantity, name) = lock_order_part(&mut tx, order_id, item.order_part_id).await?;
+
+        if item.quantity <= 0 {
+            return Err(format!("Количество к выдаче запчасти '{}' должно быть положительным", name));
+        }
+        if issued_quantity + item.quantity > quantity {
+            return Err(format!("Запчасть '{}': к выдаче осталось {} шт., запрошено {}", name, quantity - issued_quantity, item.quantity));
+        }
+
+        // Нельзя выдать то, что зарезервировано под другие заказы
+        let available = reservations::available_for_part(&mut tx, warehouse_item_id, item.order_part_id).await?;
+        if available < item.quantity {
+            return Err(format!("Недостаточно свободного остатка для запчасти '{}': запрошено {} шт., доступно {} шт.",
+                               name, item.quantity, available.max(0)));
+        }
+
+        // Списание со склада; при недостатке остатка вся выдача отменяется
+        stock::record_movement(&mut tx, &NewMovement {
+            warehouse_item_id,
+            movement_type: MovementType::Issue,
+            quantity: -item.quantity,
+            unit_cost: None,
+            user_id: Some(user.id),
+            order_part_id: Some(item.order_part_id),
+            reason: Some(&reason),
+        }).await?;
+
+        let update_query = "UPDATE order_parts
+                            SET issued_quantity = issued_quantity + $2, is_issued = (issued_quantity + $2 >= COALESCE(quantity, 1)),
+                                issued_by = $3, issued_at = NOW(), issued_to = $4, returned_at = NULL
+                            WHERE id = $1";
+        sqlx::query(update_query)
+            .bind(item.order_part_id)
+            .bind(item.quantity)
+            .bind(user.id)
+            .bind(worker_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error updating order part: {}", e))?;
+
+        issued.push(format!("'{}' x{}", name, item.quantity));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем выдачу запчастей
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Issue_Order_Parts".to_string(),
+        format!("Выданы запчасти по заказу {} работнику с ID {}: {}", order_id, worker_id, issued.join(", ")),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging parts issuance: {}", e);
+    }
+
+    Ok(format!("Выдано позиций по заказу {}: {}", order_id, issued.len()))
+}
+
+#[tauri::command]
+pub async fn return_order_parts(
+    session_token: String,
+    order_id: i32,
+    items: Vec<PartIssueItem>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "return_order_parts").await?;
+
+    if items.is_empty() {
+        return Err("Не выбраны запчасти для возврата".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    lock_order_for_issue(&mut tx, order_id).await?;
+
+    let reason = format!("Возврат неиспользованной запчасти по заказу {}", order_id);
+    let mut returned = Vec::new();
+    for item in &items {
+        let (warehouse_item_id, _, issued_quantity, name) = lock_order_part(&mut tx, order_id, item.order_part_id).await?;
+
+        if item.quantity <= 0 {
+            return Err(format!("Количество к возврату запчасти '{}' должно быть положительным", name));
+        }
+        if item.quantity > issued_quantity {
+            return Err(format!("Запчасть '{}': выдано {} шт., к возврату запрошено {}", name, issued_quantity, item.quantity));
+        }
+
+        stock::record_movement(&mut tx, &NewMovement {
+            warehouse_item_id,
+            movement_type: MovementType::Return,
+            quantity: item.quantity,
+            unit_cost: None,
+            user_id: Some(user.id),
+            order_part_id: Some(item.order_part_id),
+            reason: Some(&reason),
+        }).await?;
+
+        let update_query = "UPDATE order_parts
+                            SET issued_quantity = issued_quantity - $2, is_issued = false, returned_at = NOW()
+                            WHERE id = $1";
+        sqlx::query(update_query)
+            .bind(item.order_part_id)
+            .bind(item.quantity)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error updating order part: {}", e))?;
+
+        returned.push(format!("'{}' x{}", name, item.quantity));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем возврат запчастей
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Return_Order_Parts".to_string(),
+        format!("Возвращены на склад запчасти по заказу {}: {}", order_id, returned.join(", ")),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging parts return: {}", e);
+    }
+
+    Ok(format!("Возвращено позиций по заказу {}: {}", order_id, returned.len()))
+}
@@ -0,0 +1,556 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use crate::database::escape_like;
+use crate::money::Money;
+
+// Ограничение числа предложений от одного источника
+const MAX_OFFERS_PER_PROVIDER: usize = 200;
+
+// Время ожидания ответа API поставщика, чтобы медленный источник не задерживал поиск
+const HTTP_PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Предложение запчасти от источника (склад, прайс-лист, API поставщика)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PartOffer {
+    pub id: i32,
+    pub name: String,
+    pub brand: String,
+    pub supplier: String,
+    pub article: String,
+    pub price: Money,
+    pub availability: String, // Срок поставки в виде текста для отображения
+    pub delivery_days: i32,
+    pub warehouse_item_id: Option<i32>, // Заполняется для предложений с собственного склада
+    pub is_analog: bool, // Найдено через справочник кросс-номеров, а не по самому запросу
+    pub in_stock: bool,  // Такая запчасть (бренд + артикул) есть на нашем складе
+}
+
+// Параметры поиска. VIN передаётся для источников, умеющих подбирать запчасти по автомобилю.
+pub struct PartsQuery {
+    pub vin: Option<String>,
+    pub text: String,
+    // Аналоги запрошенного артикула: (бренд в верхнем регистре, нормализованный артикул)
+    pub analogs: Vec<(String, String)>,
+}
+
+impl PartsQuery {
+    pub fn normalized_article(&self) -> String {
+        normalize_article(&self.text)
+    }
+
+    // Пустой запрос иначе совпал бы с каждой позицией по ILIKE '%%'
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.analogs.is_empty()
+    }
+
+    pub fn analog_brands(&self) -> Vec<String> {
+        self.analogs.iter().map(|(brand, _)| brand.clone()).collect()
+    }
+
+    pub fn analog_articles(&self) -> Vec<String> {
+        self.analogs.iter().map(|(_, article)| article.clone()).collect()
+    }
+}
+
+// Источник предложений запчастей. Новые поставщики (в том числе HTTP API) добавляются реализацией этого трейта.
+#[async_trait]
+pub trait PartsProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn search(&self, query: &PartsQuery) -> Result<Vec<PartOffer>, String>;
+}
+
+// Приводит артикул к виду для сравнения: без пробелов, дефисов, точек и слэшей, в верхнем регистре
+pub fn normalize_article(article: &str) -> String {
+    article
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+pub fn availability_text(delivery_days: i32) -> String {
+    if delivery_days <= 0 {
+        "В наличии".to_string()
+    } else {
+        format!("{} дн.", delivery_days)
+    }
+}
+
+// Собственный склад: поиск по артикулу и названию среди неархивных позиций с остатком
+pub struct WarehouseProvider {
+    pool: PgPool,
+}
+
+impl WarehouseProvider {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PartsProvider for WarehouseProvider {
+    fn name(&self) -> &str {
+        "Склад СТО"
+    }
+
+    async fn search(&self, query: &PartsQuery) -> Result<Vec<PartOffer>, String> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = "SELECT id, name, COALESCE(brand, '') AS brand, COALESCE(article, '') AS article,
+                          COALESCE(selling_price, purchase_price, 0) AS price, COALESCE(quantity, 0) AS quantity
+                   FROM warehouse
+                   WHERE COALESCE(is_archived, false) = false AND COALESCE(quantity, 0) > 0
+                     AND ((length($1) > 0 AND regexp_replace(upper(COALESCE(article, '')), '[^[:alnum:]]', '', 'g') LIKE '%' || $1 || '%')
+                          OR (length($2) > 0 AND name ILIKE '%' || $2 || '%')
+                          OR (upper(trim(COALESCE(brand, ''))), regexp_replace(upper(COALESCE(article, '')), '[^[:alnum:]]', '', 'g'))
+                             IN (SELECT * FROM UNNEST($4::text[], $5::text[])))
+                   ORDER BY name
+                   LIMIT $3";
+        let rows = sqlx::query(sql)
+            .bind(query.normalized_article())
+            .bind(escape_like(query.text.trim()))
+            .bind(MAX_OFFERS_PER_PROVIDER as i64)
+            .bind(query.analog_brands())
+            .bind(query.analog_articles())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let mut offers = Vec::new();
+        for row in rows {
+            let quantity: i32 = row.get("quantity");
+            offers.push(PartOffer {
+                id: 0,
+                name: row.get("name"),
+                brand: row.get("brand"),
+                supplier: self.name().to_string(),
+                article: row.get("article"),
+                price: row.get("price"),
+                availability: format!("В наличии ({} шт.)", quantity),
+                delivery_days: 0,
+                warehouse_item_id: Some(row.get("id")),
+                is_analog: false,
+                in_stock: true,
+            });
+        }
+
+        Ok(offers)
+    }
+}
+
+// Импортированные прайс-листы поставщиков из справочника (таблица supplier_price_items)
+pub struct SupplierCatalogProvider {
+    pool: PgPool,
+}
+
+impl SupplierCatalogProvider {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PartsProvider for SupplierCatalogProvider {
+    fn name(&self) -> &str {
+        "Прайс-листы поставщиков"
+    }
+
+    async fn search(&self, query: &PartsQuery) -> Result<Vec<PartOffer>, String> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = "SELECT s.name AS supplier, COALESCE(i.brand, '') AS brand, i.article, COALESCE(i.name, '') AS name,
+                          i.price, i.delivery_days
+                   FROM supplier_price_items i
+                   JOIN suppliers s ON i.supplier_id = s.id
+                   WHERE COALESCE(s.is_active, true)
+                     AND ((length($1) > 0 AND i.article_normalized LIKE '%' || $1 || '%') OR (length($2) > 0 AND i.name ILIKE '%' || $2 || '%')
+                          OR (upper(trim(COALESCE(i.brand, ''))), i.article_normalized) IN (SELECT * FROM UNNEST($4::text[], $5::text[])))
+                   ORDER BY i.price, i.delivery_days
+                   LIMIT $3";
+        let rows = sqlx::query(sql)
+            .bind(query.normalized_article())
+            .bind(escape_like(query.text.trim()))
+            .bind(MAX_OFFERS_PER_PROVIDER as i64)
+            .bind(query.analog_brands())
+            .bind(query.analog_articles())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let mut offers = Vec::new();
+        for row in rows {
+            let delivery_days: i32 = row.get("delivery_days");
+            offers.push(PartOffer {
+                id: 0,
+                name: row.get("name"),
+                brand: row.get("brand"),
+                supplier: row.get("supplier"),
+                article: row.get("article"),
+                price: row.get("price"),
+                availability: availability_text(delivery_days),
+                delivery_days,
+                warehouse_item_id: None,
+                is_analog: false,
+                in_stock: false,
+            });
+        }
+
+        Ok(offers)
+    }
+}
+
+// Строка ответа API поставщика
+#[derive(Deserialize)]
+struct HttpOffer {
+    brand: String,
+    article: String,
+    #[serde(default)]
+    name: String,
+    price: Money,
+    #[serde(default)]
+    delivery_days: i32,
+}
+
+// API поставщика по HTTP: GET {base_url}/search?article=...&text=...&vin=... возвращает JSON-массив предложений
+pub struct HttpSupplierProvider {
+    name: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpSupplierProvider {
+    pub fn new(name: &str, base_url: &str) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_PROVIDER_TIMEOUT)
+            .build()
+            .map_err(|e| format!("HTTP client error: {}", e))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl PartsProvider for HttpSupplierProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn search(&self, query: &PartsQuery) -> Result<Vec<PartOffer>, String> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut params = vec![("article", query.normalized_article()), ("text", query.text.trim().to_string())];
+        if let Some(vin) = query.vin.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            params.push(("vin", vin.to_string()));
+        }
+
+        let response = self.client
+            .get(format!("{}/search", self.base_url))
+            .query(&params)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request error: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("HTTP response error: {}", e))?;
+        let rows: Vec<HttpOffer> = response
+            .json()
+            .await
+            .map_err(|e| format!("HTTP response parse error: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .take(MAX_OFFERS_PER_PROVIDER)
+            .map(|row| PartOffer {
+                id: 0,
+                name: row.name,
+                brand: row.brand,
+                supplier: self.name.clone(),
+                article: row.article,
+                price: row.price,
+                availability: availability_text(row.delivery_days),
+                delivery_days: row.delivery_days,
+                warehouse_item_id: None,
+                is_analog: false,
+                in_stock: false,
+            })
+            .collect())
+    }
+}
+
+// Источники, участвующие в поиске запчастей.
+// Прайс-листы поставщиков загружаются импортом (price_lists.rs) и ищутся через SupplierCatalogProvider.
+// API поставщика подключается переменной окружения PARTS_SUPPLIER_API_URL (и PARTS_SUPPLIER_API_NAME).
+pub fn default_providers(pool: &PgPool) -> Vec<Box<dyn PartsProvider>> {
+    let mut providers: Vec<Box<dyn PartsProvider>> = vec![
+        Box::new(WarehouseProvider::new(pool.clone())),
+        Box::new(SupplierCatalogProvider::new(pool.clone())),
+    ];
+
+    if let Ok(base_url) = env::var("PARTS_SUPPLIER_API_URL") {
+        let name = env::var("PARTS_SUPPLIER_API_NAME").unwrap_or_else(|_| "API поставщика".to_string());
+        match HttpSupplierProvider::new(&name, &base_url) {
+            Ok(provider) => providers.push(Box::new(provider)),
+            Err(e) => eprintln!("Parts provider '{}' is not available: {}", name, e),
+        }
+    }
+
+    providers
+}
+
+// Опрашивает все источники, оставляет по одному предложению на бренд+артикул
+// и сортирует по цене и сроку поставки. Позиция со склада всегда важнее предложения поставщика:
+// её можно выдать сразу, а заказ у поставщика при наличии на складе не нужен.
+// Среди поставщиков выбирается лучшее по цене и сроку. Ошибка одного источника не прерывает поиск.
+pub async fn search_all(providers: &[Box<dyn PartsProvider>], query: &PartsQuery) -> Vec<PartOffer> {
+    let mut best: HashMap<(String, String), PartOffer> = HashMap::new();
+
+    for provider in providers {
+        let offers = match provider.search(query).await {
+            Ok(offers) => offers,
+            Err(e) => {
+                eprintln!("Parts provider '{}' failed: {}", provider.name(), e);
+                continue;
+            }
+        };
+
+        for offer in offers {
+            let key = (offer.brand.trim().to_uppercase(), normalize_article(&offer.article));
+            let replace = match best.get(&key) {
+                Some(current) => {
+                    (!offer.in_stock, offer.price, offer.delivery_days) < (!current.in_stock, current.price, current.delivery_days)
+                }
+                None => true,
+            };
+            if replace {
+                best.insert(key, offer);
+            }
+        }
+    }
+
+    let article_query = query.normalized_article();
+    let mut offers: Vec<PartOffer> = best
+        .into_iter()
+        .map(|(key, mut offer)| {
+            offer.is_analog = query.analogs.contains(&key) && (article_query.is_empty() || !key.1.contains(&article_query));
+            offer
+        })
+        .collect();
+    offers.sort_by(|a, b| {
+        a.price
+            .cmp(&b.price)
+            .then(a.delivery_days.cmp(&b.delivery_days))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    // Идентификаторы нужны интерфейсу для выбора строк
+    for (index, offer) in offers.iter_mut().enumerate() {
+        offer.id = index as i32 + 1;
+    }
+
+    offers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Источник с заранее заданными предложениями вместо склада или API поставщика
+    struct StubProvider {
+        name: String,
+        offers: Result<Vec<PartOffer>, String>,
+    }
+
+    #[async_trait]
+    impl PartsProvider for StubProvider {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn search(&self, _query: &PartsQuery) -> Result<Vec<PartOffer>, String> {
+            self.offers.clone()
+        }
+    }
+
+    fn stub(name: &str, offers: Vec<PartOffer>) -> Box<dyn PartsProvider> {
+        Box::new(StubProvider { name: name.to_string(), offers: Ok(offers) })
+    }
+
+    fn offer(supplier: &str, brand: &str, article: &str, price: &str, delivery_days: i32) -> PartOffer {
+        PartOffer {
+            id: 0,
+            name: format!("{} {}", brand, article),
+            brand: brand.to_string(),
+            supplier: supplier.to_string(),
+            article: article.to_string(),
+            price: Money::parse(price).unwrap(),
+            availability: availability_text(delivery_days),
+            delivery_days,
+            warehouse_item_id: None,
+            is_analog: false,
+            in_stock: supplier == "Склад",
+        }
+    }
+
+    fn query(text: &str) -> PartsQuery {
+        PartsQuery { vin: None, text: text.to_string(), analogs: Vec::new() }
+    }
+
+    #[test]
+    fn normalize_article_strips_separators_and_uppercases() {
+        assert_eq!(normalize_article("oc 90-1.a/b"), "OC901AB");
+        assert_eq!(normalize_article("  "), "");
+    }
+
+    #[test]
+    fn empty_query_is_detected() {
+        assert!(query("   ").is_empty());
+        assert!(!query("OC90").is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_all_keeps_warehouse_offer_per_brand_and_article() {
+        let providers = vec![
+            stub("Склад", vec![offer("Склад", "Knecht", "OC 90", "500.00", 0)]),
+            stub("Поставщик А", vec![
+                offer("Поставщик А", "KNECHT", "OC-90", "450.00", 2),
+                offer("Поставщик А", "Mann", "W 712/73", "400.00", 1),
+            ]),
+            stub("Поставщик Б", vec![offer("Поставщик Б", "Knecht", "OC90", "450.00", 1)]),
+        ];
+
+        let offers = search_all(&providers, &query("OC90")).await;
+
+        assert_eq!(offers.len(), 2);
+        assert_eq!(offers[0].article, "W 712/73");
+        // Складская позиция остаётся, хотя поставщики предлагают дешевле
+        assert_eq!(offers[1].supplier, "Склад");
+        assert_eq!(offers[1].price, Money::parse("500.00").unwrap());
+        assert!(offers[1].in_stock);
+        assert!(!offers[0].in_stock);
+        assert_eq!(offers.iter().map(|o| o.id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn search_all_skips_failed_provider() {
+        let providers: Vec<Box<dyn PartsProvider>> = vec![
+            Box::new(StubProvider { name: "Недоступный API".to_string(), offers: Err("timeout".to_string()) }),
+            stub("Поставщик А", vec![offer("Поставщик А", "Bosch", "0 986 452 041", "300.00", 3)]),
+        ];
+
+        let offers = search_all(&providers, &query("0986452041")).await;
+
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].brand, "Bosch");
+    }
+
+    #[tokio::test]
+    async fn search_all_marks_analogs() {
+        let mut analog_query = query("OC90");
+        analog_query.analogs.push(("MAHLE".to_string(), "OC47".to_string()));
+        let providers = vec![stub("Поставщик А", vec![
+            offer("Поставщик А", "Mahle", "OC 47", "350.00", 2),
+            offer("Поставщик А", "Knecht", "OC 90", "450.00", 2),
+        ])];
+
+        let offers = search_all(&providers, &analog_query).await;
+
+        assert_eq!(offers.len(), 2);
+        assert!(offers[0].is_analog);
+        assert!(!offers[1].is_analog);
+    }
+
+    #[tokio::test]
+    async fn search_all_keeps_best_supplier_offer() {
+        let providers = vec![
+            stub("Поставщик А", vec![offer("Поставщик А", "Knecht", "OC 90", "450.00", 2)]),
+            stub("Поставщик Б", vec![offer("Поставщик Б", "KNECHT", "OC-90", "450.00", 1)]),
+            stub("Поставщик В", vec![offer("Поставщик В", "Knecht", "OC90", "480.00", 0)]),
+        ];
+
+        let offers = search_all(&providers, &query("OC90")).await;
+
+        // При равной цене выигрывает более короткий срок поставки
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].supplier, "Поставщик Б");
+    }
+
+    // Отвечает на один HTTP-запрос заданным телом и возвращает строку запроса
+    async fn mock_server(status: &'static str, body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let read = socket.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let request = String::from_utf8_lossy(&request).to_string();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+
+        (base_url, handle)
+    }
+
+    #[tokio::test]
+    async fn http_provider_maps_api_offers() {
+        let (base_url, server) = mock_server(
+            "200 OK",
+            r#"[{"brand": "Knecht", "article": "OC 90", "name": "Фильтр масляный", "price": "470.50", "delivery_days": 3}]"#,
+        )
+        .await;
+        let provider = HttpSupplierProvider::new("API поставщика", &base_url).unwrap();
+        let mut api_query = query("oc-90");
+        api_query.vin = Some("XTA21099043576182".to_string());
+
+        let offers = provider.search(&api_query).await.unwrap();
+
+        let request_line = server.await.unwrap();
+        assert!(request_line.starts_with("GET /search?"));
+        assert!(request_line.contains("article=OC90"));
+        assert!(request_line.contains("vin=XTA21099043576182"));
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].supplier, "API поставщика");
+        assert_eq!(offers[0].price, Money::parse("470.50").unwrap());
+        assert_eq!(offers[0].availability, "3 дн.");
+        assert!(offers[0].warehouse_item_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn http_provider_reports_server_error() {
+        let (base_url, server) = mock_server("503 Service Unavailable", "[]").await;
+        let provider = HttpSupplierProvider::new("API поставщика", &base_url).unwrap();
+
+        let result = provider.search(&query("OC90")).await;
+
+        server.await.unwrap();
+        assert!(result.is_err());
+    }
+}
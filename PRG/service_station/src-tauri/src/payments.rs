@@ -0,0 +1,388 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+
+// Виды платежей (соответствует ENUM payment_kind в БД)
+const KIND_PREPAYMENT: &str = "Prepayment";
+const KIND_PAYMENT: &str = "Payment";
+const KIND_REFUND: &str = "Refund";
+
+// Способы оплаты (соответствует ENUM payment_method в БД)
+const PAYMENT_METHODS: &[&str] = &["Cash", "Card", "Transfer"];
+
+// Статусы заказа, в которых принимается окончательная оплата
+const PAYABLE_STATUSES: &[&str] = &["Ready", "Payment"];
+// Статусы, после которых деньги по заказу уже не принимаются
+const FINAL_STATUSES: &[&str] = &["Closed", "Cancelled"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Payment {
+    id: i32,
+    order_id: i32,
+    kind: String,
+    method: String,
+    amount: Money,
+    refund_of: Option<i32>,
+    refunded: Money, // Сколько уже возвращено по этому платежу
+    cashier_name: Option<String>,
+    comment: Option<String>,
+    created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OrderPayments {
+    order_id: i32,
+    total_amount: Money,
+    paid: Money, // Платежи за вычетом возвратов
+    balance: Money, // Остаток к оплате
+    payments: Vec<Payment>,
+}
+
+// Знак и точность суммы проверяются при разборе Money, здесь отсекаются нулевые платежи
+fn check_amount(amount: &Money) -> Result<(), String> {
+    if amount.is_positive() {
+        Ok(())
+    } else {
+        Err("Сумма платежа должна быть больше нуля".to_string())
+    }
+}
+
+fn check_method(method: &str) -> Result<(), String> {
+    if PAYMENT_METHODS.contains(&method) {
+        Ok(())
+    } else {
+        Err(format!("Неизвестный способ оплаты: {}", method))
+    }
+}
+
+// Блокирует заказ до конца транзакции, чтобы параллельные платежи не разошлись с остатком
+pub async fn lock_order(conn: &mut PgConnection, order_id: i32) -> Result<String, String> {
+    let row = sqlx::query("SELECT status::text AS status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    Ok(row.get("status"))
+}
+
+// Остаток к оплате: сумма заказа минус платежи с учётом возвратов
+pub async fn outstanding_balance(conn: &mut PgConnection, order_id: i32) -> Result<Money, String> {
+    let query = "SELECT COALESCE(o.total_amount, 0) - COALESCE((
+                     SELECT SUM(CASE WHEN p.kind = 'Refund' THEN -p.amount ELSE p.amount END) FROM payments p WHERE p.order_id = o.id
+                 ), 0) AS balance
+                 FROM orders o WHERE o.id = $1";
+    let row = sqlx::query(query)
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+
+    Ok(row.get("balance"))
+}
+
+// orders.prepayment хранит внесённую предоплату за вычетом возвратов по ней
+async fn sync_prepayment(conn: &mut PgConnection, order_id: i32) -> Result<(), String> {
+    let query = "UPDATE orders SET prepayment = COALESCE((
+                     SELECT SUM(CASE WHEN p.kind = 'Refund' THEN -p.amount ELSE p.amount END)
+                     FROM payments p
+                     LEFT JOIN payments original ON original.id = p.refund_of
+                     WHERE p.order_id = $1 AND (p.kind = 'Prepayment' OR original.kind = 'Prepayment')
+                 ), 0)
+                 WHERE id = $1";
+    sqlx::query(query)
+        .bind(order_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+struct NewPayment<'a> {
+    order_id: i32,
+    kind: &'a str,
+    method: &'a str,
+    amount: Money,
+    refund_of: Option<i32>, // Платёж, по которому оформлен возврат
+    cashier_id: i32,
+    comment: Option<String>,
+}
+
+async fn insert_payment(conn: &mut PgConnection, payment: NewPayment<'_>) -> Result<i32, String> {
+    let query = "INSERT INTO payments (order_id, kind, method, amount, refund_of, cashier_id, comment)
+                 VALUES ($1, $2::payment_kind, $3::payment_method, $4, $5, $6, $7) RETURNING id";
+    let row = sqlx::query(query)
+        .bind(payment.order_id)
+        .bind(payment.kind)
+        .bind(payment.method)
+        .bind(payment.amount)
+        .bind(payment.refund_of)
+        .bind(payment.cashier_id)
+        .bind(payment.comment)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(row.get("id"))
+}
+
+fn normalize_comment(comment: Option<String>) -> Option<String> {
+    comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+#[tauri::command]
+pub async fn get_order_payments(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<OrderPayments, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_payments").await?;
+
+    let query = "SELECT p.id, p.order_id, p.kind::text AS kind, p.method::text AS method, p.amount, p.refund_of,
+                        COALESCE((SELECT SUM(r.amount) FROM payments r WHERE r.refund_of = p.id), 0) AS refunded,
+                        u.full_name AS cashier_name, p.comment, p.created_at::text AS created_at
+                 FROM payments p
+                 LEFT JOIN users u ON u.id = p.cashier_id
+                 WHERE p.order_id = $1
+                 ORDER BY p.created_at, p.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let payments = rows.iter().map(|row| Payment {
+        id: row.get("id"),
+        order_id: row.get("order_id"),
+        kind: row.get("kind"),
+        method: row.get("method"),
+        amount: row.get("amount"),
+        refund_of: row.get("refund_of"),
+        refunded: row.get("refunded"),
+        cashier_name: row.get("cashier_name"),
+        comment: row.get("comment"),
+        created_at: row.get("created_at"),
+    }).collect();
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    let balance = outstanding_balance(&mut conn, order_id).await?;
+
+    let totals = sqlx::query("SELECT COALESCE(total_amount, 0) AS total_amount FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let total_amount: Money = totals.get("total_amount");
+
+    Ok(OrderPayments {
+        order_id,
+        total_amount,
+        paid: total_amount - balance,
+        balance,
+        payments,
+    })
+}
+
+// Предоплата при приёмке автомобиля (или позже, пока заказ не выдан)
+#[tauri::command]
+pub async fn register_prepayment(
+    session_token: String,
+    order_id: i32,
+    method: String,
+    amount: Money,
+    comment: Option<String>,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManagePayments, "register_prepayment").await?;
+
+    check_method(&method)?;
+    check_amount(&amount)?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let status = lock_order(&mut tx, order_id).await?;
+    if FINAL_STATUSES.contains(&status.as_str()) || PAYABLE_STATUSES.contains(&status.as_str()) {
+        return Err(format!("Предоплата по заказу {} в статусе '{}' не принимается", order_id, status));
+    }
+
+    let payment_id = insert_payment(&mut tx, NewPayment {
+        order_id,
+        kind: KIND_PREPAYMENT,
+        method: &method,
+        amount,
+        refund_of: None,
+        cashier_id: user.id,
+        comment: normalize_comment(comment),
+    }).await?;
+    sync_prepayment(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем предоплату
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Register_Prepayment".to_string(),
+        format!("Принята предоплата {} ({}) по заказу {}", amount, method, order_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging prepayment: {}", e);
+    }
+
+    Ok(payment_id)
+}
+
+// Оплата при выдаче автомобиля; переплата не принимается
+#[tauri::command]
+pub async fn register_payment(
+    session_token: String,
+    order_id: i32,
+    method: String,
+    amount: Money,
+    comment: Option<String>,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManagePayments, "register_payment").await?;
+
+    check_method(&method)?;
+    check_amount(&amount)?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let status = lock_order(&mut tx, order_id).await?;
+    if !PAYABLE_STATUSES.contains(&status.as_str()) {
+        return Err(format!("Оплата по заказу {} принимается только после завершения работ (текущий статус '{}')", order_id, status));
+    }
+
+    let balance = outstanding_balance(&mut tx, order_id).await?;
+    if !balance.is_positive() {
+        return Err(format!("Заказ {} уже полностью оплачен", order_id));
+    }
+    if amount > balance {
+        return Err(format!("Сумма оплаты {} превышает остаток к оплате {}", amount, balance));
+    }
+
+    let payment_id = insert_payment(&mut tx, NewPayment {
+        order_id,
+        kind: KIND_PAYMENT,
+        method: &method,
+        amount,
+        refund_of: None,
+        cashier_id: user.id,
+        comment: normalize_comment(comment),
+    }).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем оплату
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Register_Payment".to_string(),
+        format!("Принята оплата {} ({}) по заказу {}, остаток {}", amount, method, order_id, balance - amount),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging payment: {}", e);
+    }
+
+    Ok(payment_id)
+}
+
+// Возврат денег клиенту тем же способом, которым был принят платёж
+#[tauri::command]
+pub async fn refund_payment(
+    session_token: String,
+    payment_id: i32,
+    amount: Money,
+    reason: String,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManagePayments, "refund_payment").await?;
+
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err("Необходимо указать причину возврата".to_string());
+    }
+    check_amount(&amount)?;
+
+    let order_row = sqlx::query("SELECT order_id FROM payments WHERE id = $1")
+        .bind(payment_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Платёж {} не найден", payment_id))?;
+    let order_id: i32 = order_row.get("order_id");
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Возврат после отмены заказа допустим (возврат предоплаты), а закрытый заказ должен остаться оплаченным полностью
+    let status = lock_order(&mut tx, order_id).await?;
+    if status == "Closed" {
+        return Err(format!("Заказ {} закрыт, возврат по нему не оформляется", order_id));
+    }
+
+    let query = "SELECT p.kind::text AS kind, p.method::text AS method, p.amount,
+                        COALESCE((SELECT SUM(r.amount) FROM payments r WHERE r.refund_of = p.id), 0) AS refunded
+                 FROM payments p WHERE p.id = $1";
+    let payment = sqlx::query(query)
+        .bind(payment_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let kind: String = payment.get("kind");
+    if kind == KIND_REFUND {
+        return Err("Нельзя оформить возврат по возврату".to_string());
+    }
+    let method: String = payment.get("method");
+    let paid: Money = payment.get("amount");
+    let refunded: Money = payment.get("refunded");
+    let refundable = paid - refunded;
+    if amount > refundable {
+        return Err(format!("Сумма возврата {} превышает доступную к возврату {}", amount, refundable));
+    }
+
+    let refund_id = insert_payment(&mut tx, NewPayment {
+        order_id,
+        kind: KIND_REFUND,
+        method: &method,
+        amount,
+        refund_of: Some(payment_id),
+        cashier_id: user.id,
+        comment: Some(reason.clone()),
+    }).await?;
+    sync_prepayment(&mut tx, order_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем возврат
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Refund_Payment".to_string(),
+        format!("Оформлен возврат {} ({}) по платежу {} заказа {}: {}", amount, method, payment_id, order_id, reason),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging refund: {}", e);
+    }
+
+    Ok(refund_id)
+}
@@ -0,0 +1,513 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::order_totals::WORK_AMOUNT_SQL;
+use crate::settings;
+
+// Схемы оплаты (соответствует ENUM pay_scheme в БД)
+const SCHEME_LABOUR_SHARE: &str = "Labour_Share";
+const SCHEME_NORM_HOUR_RATE: &str = "Norm_Hour_Rate";
+const SCHEME_SALARY_BONUS: &str = "Salary_Bonus";
+const PAY_SCHEMES: &[&str] = &[SCHEME_LABOUR_SHARE, SCHEME_NORM_HOUR_RATE, SCHEME_SALARY_BONUS];
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkerPayScheme {
+    worker_id: i32,
+    worker_name: String,
+    role: String,
+    scheme: Option<String>, // null - схема оплаты не задана
+    labour_share_percent: String,
+    norm_hour_rate: Money,
+    salary: Money, // Оклад за месяц
+    bonus_percent: String,
+    updated_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PayrollWork {
+    work_id: i32,
+    order_id: i32,
+    service_name: String,
+    finished_at: String,
+    norm_hours: String,
+    actual_hours: String, // По интервалам учёта времени
+    efficiency_percent: Option<String>, // null - время по работе не учитывалось
+    labour_amount: Money, // Стоимость работы с учётом скидки по строке
+    earnings: Money,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkerPayroll {
+    worker_id: i32,
+    worker_name: String,
+    scheme: Option<String>,
+    works: Vec<PayrollWork>,
+    norm_hours: String,
+    actual_hours: String,
+    efficiency_percent: Option<String>,
+    labour_amount: Money,
+    salary: Money, // Оклад, пропорциональный дням периода
+    earnings: Money, // Начисления по работам и оклад
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PayrollStatement {
+    date_from: String,
+    date_to: String,
+    workers: Vec<WorkerPayroll>,
+    total_earnings: Money,
+}
+
+// Параметры схемы, по которым считаются начисления
+struct SchemeRates {
+    scheme: Option<String>,
+    labour_share_percent: Decimal,
+    norm_hour_rate: Money,
+    salary: Money,
+    bonus_percent: Decimal,
+}
+
+impl SchemeRates {
+    fn work_earnings(&self, labour_amount: Money, norm_hours: Decimal) -> Money {
+        match self.scheme.as_deref() {
+            Some(SCHEME_LABOUR_SHARE) => labour_amount.percent(self.labour_share_percent),
+            Some(SCHEME_NORM_HOUR_RATE) => self.norm_hour_rate.multiply(norm_hours),
+            Some(SCHEME_SALARY_BONUS) => labour_amount.percent(self.bonus_percent),
+            _ => Money::zero(),
+        }
+    }
+}
+
+// Доля от 0 до 100%, не больше двух знаков после запятой
+fn parse_share(value: &str, label: &str) -> Result<Decimal, String> {
+    let value = Decimal::from_str(&value.trim().replace(',', "."))
+        .map_err(|_| format!("{}: некорректное число '{}'", label, value))?;
+    if value < Decimal::ZERO || value > Decimal::ONE_HUNDRED {
+        return Err(format!("{} должен быть от 0 до 100%", label));
+    }
+    if value.scale() > 2 {
+        return Err(format!("{} указывается не более чем с двумя знаками после запятой", label));
+    }
+    Ok(value)
+}
+
+fn parse_period(date_from: &str, date_to: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let from = NaiveDate::parse_from_str(date_from.trim(), DATE_FORMAT).map_err(|_| format!("Некорректная дата начала периода: '{}'", date_from))?;
+    let to = NaiveDate::parse_from_str(date_to.trim(), DATE_FORMAT).map_err(|_| format!("Некорректная дата окончания периода: '{}'", date_to))?;
+    if from > to {
+        return Err("Дата начала периода позже даты окончания".to_string());
+    }
+    Ok((from, to))
+}
+
+fn days_in_month(date: NaiveDate) -> i64 {
+    let first = date.with_day(1).unwrap_or(date);
+    let next = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    };
+    next.map(|next| (next - first).num_days()).unwrap_or(30)
+}
+
+// Доля месячного оклада за период: по каждому месяцу - число дней периода в нём к длине месяца
+fn salary_share(from: NaiveDate, to: NaiveDate) -> Decimal {
+    let mut share = Decimal::ZERO;
+    let mut day = from;
+    while day <= to {
+        let month_days = days_in_month(day);
+        let month_end = day.with_day(month_days as u32).unwrap_or(day);
+        let last = month_end.min(to);
+        share += Decimal::from((last - day).num_days() + 1) / Decimal::from(month_days);
+        day = last + Duration::days(1);
+    }
+    share
+}
+
+fn hours(seconds: i64) -> Decimal {
+    Decimal::from(seconds) / Decimal::from(3600)
+}
+
+fn hours_text(hours: Decimal) -> String {
+    format!("{:.2}", hours.round_dp(2))
+}
+
+// Выработка: нормо-часы к фактически затраченным часам, %
+fn efficiency(norm_hours: Decimal, actual_hours: Decimal) -> Option<String> {
+    if actual_hours.is_zero() {
+        return None;
+    }
+    Some(format!("{:.1}", (norm_hours * Decimal::from(100) / actual_hours).round_dp(1)))
+}
+
+fn scheme_from_row(row: &sqlx::postgres::PgRow) -> (WorkerPayScheme, SchemeRates) {
+    let scheme = WorkerPayScheme {
+        worker_id: row.get("id"),
+        worker_name: row.get("full_name"),
+        role: row.get("role"),
+        scheme: row.get("scheme"),
+        labour_share_percent: row.get::<Decimal, _>("labour_share_percent").to_string(),
+        norm_hour_rate: row.get("norm_hour_rate"),
+        salary: row.get("salary"),
+        bonus_percent: row.get::<Decimal, _>("bonus_percent").to_string(),
+        updated_at: row.get("updated_at"),
+    };
+    let rates = SchemeRates {
+        scheme: scheme.scheme.clone(),
+        labour_share_percent: row.get("labour_share_percent"),
+        norm_hour_rate: scheme.norm_hour_rate,
+        salary: scheme.salary,
+        bonus_percent: row.get("bonus_percent"),
+    };
+    (scheme, rates)
+}
+
+// Действующие слесари, сотрудники с заданной схемой оплаты и дополнительно указанные пользователи
+async fn load_schemes(conn: &mut PgConnection, extra_user_ids: &[i32]) -> Result<Vec<(WorkerPayScheme, SchemeRates)>, String> {
+    let query = "SELECT u.id, u.full_name, u.role::text AS role, s.scheme::text AS scheme,
+                        COALESCE(s.labour_share_percent, 0) AS labour_share_percent,
+                        COALESCE(s.norm_hour_rate, 0) AS norm_hour_rate,
+                        COALESCE(s.salary, 0) AS salary,
+                        COALESCE(s.bonus_percent, 0) AS bonus_percent,
+                        s.updated_at::text AS updated_at
+                 FROM users u
+                 LEFT JOIN worker_pay_schemes s ON s.worker_id = u.id
+                 WHERE (u.role = 'Worker' AND u.status = 'Active') OR s.id IS NOT NULL OR u.id = ANY($1)
+                 ORDER BY u.full_name, u.id";
+    let rows = sqlx::query(query)
+        .bind(extra_user_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(scheme_from_row).collect())
+}
+
+struct CompletedWork {
+    worker_id: i32,
+    work: PayrollWork,
+    norm_hours: Decimal,
+    actual_hours: Decimal,
+}
+
+// Работы, завершённые в периоде; исполнитель по умолчанию - основной слесарь заказа
+// Работа оплачивается один раз - в периоде первого завершения и исполнителю, который её тогда завершил.
+// Возврат на переделку по контролю качества (qc.rs) не переносит её в другой период и не оплачивается повторно.
+async fn load_completed_works(conn: &mut PgConnection, from: NaiveDate, to: NaiveDate) -> Result<Vec<CompletedWork>, String> {
+    let query = format!(
+        "SELECT id, order_id, COALESCE(service_name_snapshot, '') AS service_name, completed_at::text AS finished_at,
+                COALESCE(first_worker_id, worker_id, (SELECT o.worker_id FROM orders o WHERE o.id = order_works.order_id)) AS worker_id,
+                COALESCE(norm_hours, 0) AS norm_hours, {} AS labour_amount,
+                (SELECT COALESCE(SUM(EXTRACT(EPOCH FROM t.finished_at - t.started_at)), 0)::bigint
+                 FROM work_time_intervals t WHERE t.work_id = order_works.id AND t.finished_at IS NOT NULL) AS actual_seconds
+         FROM (SELECT *, COALESCE(first_finished_at, finished_at) AS completed_at FROM order_works) order_works
+         WHERE status IS DISTINCT FROM 'Cancelled' AND completed_at >= $1 AND completed_at < $2
+         ORDER BY completed_at, id",
+        WORK_AMOUNT_SQL
+    );
+    let rows = sqlx::query(&query)
+        .bind(from)
+        .bind(to + Duration::days(1))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut works = Vec::new();
+    for row in rows {
+        let Some(worker_id) = row.get::<Option<i32>, _>("worker_id") else {
+            continue;
+        };
+        let norm_hours: Decimal = row.get("norm_hours");
+        let actual_hours = hours(row.get("actual_seconds"));
+        works.push(CompletedWork {
+            worker_id,
+            work: PayrollWork {
+                work_id: row.get("id"),
+                order_id: row.get("order_id"),
+                service_name: row.get("service_name"),
+                finished_at: row.get("finished_at"),
+                norm_hours: hours_text(norm_hours),
+                actual_hours: hours_text(actual_hours),
+                efficiency_percent: efficiency(norm_hours, actual_hours),
+                labour_amount: row.get("labour_amount"),
+                earnings: Money::zero(),
+            },
+            norm_hours,
+            actual_hours,
+        });
+    }
+
+    Ok(works)
+}
+
+async fn build_statement(conn: &mut PgConnection, from: NaiveDate, to: NaiveDate) -> Result<PayrollStatement, String> {
+    let completed = load_completed_works(conn, from, to).await?;
+    let worker_ids: Vec<i32> = completed.iter().map(|w| w.worker_id).collect();
+    let schemes = load_schemes(conn, &worker_ids).await?;
+    let salary_share = salary_share(from, to);
+
+    let mut works_by_worker: BTreeMap<i32, Vec<CompletedWork>> = BTreeMap::new();
+    for work in completed {
+        works_by_worker.entry(work.worker_id).or_default().push(work);
+    }
+
+    let mut workers = Vec::new();
+    let mut total_earnings = Money::zero();
+    for (scheme, rates) in schemes {
+        let completed = works_by_worker.remove(&scheme.worker_id).unwrap_or_default();
+        let salary = match rates.scheme.as_deref() {
+            Some(SCHEME_SALARY_BONUS) => rates.salary.multiply(salary_share),
+            _ => Money::zero(),
+        };
+
+        // Сотрудники без работ и без оклада в ведомость не попадают
+        if completed.is_empty() && salary.is_zero() {
+            continue;
+        }
+
+        let mut norm_hours = Decimal::ZERO;
+        let mut actual_hours = Decimal::ZERO;
+        let mut labour_amount = Money::zero();
+        let mut earnings = salary;
+        let mut works = Vec::new();
+        for CompletedWork { mut work, norm_hours: work_norm, actual_hours: work_actual, .. } in completed {
+            work.earnings = rates.work_earnings(work.labour_amount, work_norm);
+            norm_hours += work_norm;
+            actual_hours += work_actual;
+            labour_amount = labour_amount + work.labour_amount;
+            earnings = earnings + work.earnings;
+            works.push(work);
+        }
+
+        total_earnings = total_earnings + earnings;
+        workers.push(WorkerPayroll {
+            worker_id: scheme.worker_id,
+            worker_name: scheme.worker_name,
+            scheme: rates.scheme,
+            works,
+            norm_hours: hours_text(norm_hours),
+            actual_hours: hours_text(actual_hours),
+            efficiency_percent: efficiency(norm_hours, actual_hours),
+            labour_amount,
+            salary,
+            earnings,
+        });
+    }
+
+    Ok(PayrollStatement {
+        date_from: from.format(DATE_FORMAT).to_string(),
+        date_to: to.format(DATE_FORMAT).to_string(),
+        workers,
+        total_earnings,
+    })
+}
+
+fn scheme_title(scheme: Option<&str>) -> &'static str {
+    match scheme {
+        Some(SCHEME_LABOUR_SHARE) => "Процент от работ",
+        Some(SCHEME_NORM_HOUR_RATE) => "Ставка за нормо-час",
+        Some(SCHEME_SALARY_BONUS) => "Оклад + премия",
+        _ => "Не задана",
+    }
+}
+
+// CSV для Excel: разделитель ';' и BOM, чтобы кириллица открывалась без настройки кодировки
+fn statement_csv(statement: &PayrollStatement) -> Result<Vec<u8>, String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .from_writer(b"\xEF\xBB\xBF".to_vec());
+
+    let write_error = |e: csv::Error| format!("Ошибка формирования CSV: {}", e);
+    writer.write_record([
+        "Сотрудник", "Схема оплаты", "Заказ", "Работа", "Завершена", "Нормо-часы", "Факт. часы",
+        "Выработка, %", "Стоимость работ", "Начислено",
+    ]).map_err(write_error)?;
+
+    for worker in &statement.workers {
+        let scheme = scheme_title(worker.scheme.as_deref());
+        for work in &worker.works {
+            writer.write_record([
+                worker.worker_name.as_str(),
+                scheme,
+                &work.order_id.to_string(),
+                &work.service_name,
+                &work.finished_at,
+                &work.norm_hours,
+                &work.actual_hours,
+                work.efficiency_percent.as_deref().unwrap_or(""),
+                &work.labour_amount.amount_text(),
+                &work.earnings.amount_text(),
+            ]).map_err(write_error)?;
+        }
+        if !worker.salary.is_zero() {
+            writer.write_record([
+                worker.worker_name.as_str(), scheme, "", "Оклад за период", "", "", "", "", "", &worker.salary.amount_text(),
+            ]).map_err(write_error)?;
+        }
+        writer.write_record([
+            worker.worker_name.as_str(),
+            scheme,
+            "",
+            "ИТОГО",
+            "",
+            &worker.norm_hours,
+            &worker.actual_hours,
+            worker.efficiency_percent.as_deref().unwrap_or(""),
+            &worker.labour_amount.amount_text(),
+            &worker.earnings.amount_text(),
+        ]).map_err(write_error)?;
+    }
+
+    writer.write_record(["ВСЕГО К ВЫПЛАТЕ", "", "", "", "", "", "", "", "", &statement.total_earnings.amount_text()])
+        .map_err(write_error)?;
+
+    writer.into_inner().map_err(|e| format!("Ошибка формирования CSV: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_pay_schemes(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<WorkerPayScheme>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManagePayroll, "get_pay_schemes").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    let schemes = load_schemes(&mut conn, &[]).await?;
+
+    Ok(schemes.into_iter().map(|(scheme, _)| scheme).collect())
+}
+
+// Проценты передаются строкой, чтобы не терять точность при разборе
+#[derive(Deserialize)]
+pub struct PaySchemeRequest {
+    scheme: String,
+    #[serde(rename = "labourSharePercent")]
+    labour_share_percent: String,
+    #[serde(rename = "normHourRate")]
+    norm_hour_rate: Money,
+    salary: Money,
+    #[serde(rename = "bonusPercent")]
+    bonus_percent: String,
+}
+
+#[tauri::command]
+pub async fn set_pay_scheme(
+    session_token: String,
+    worker_id: i32,
+    request: PaySchemeRequest,
+    state: tauri::State<'_, Database>
+) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManagePayroll, "set_pay_scheme").await?;
+
+    let PaySchemeRequest { scheme, labour_share_percent, norm_hour_rate, salary, bonus_percent } = request;
+    if !PAY_SCHEMES.contains(&scheme.as_str()) {
+        return Err(format!("Неизвестная схема оплаты: {}", scheme));
+    }
+    let labour_share = parse_share(&labour_share_percent, "Процент от стоимости работ")?;
+    let bonus = parse_share(&bonus_percent, "Процент премии")?;
+
+    let worker = sqlx::query("SELECT full_name FROM users WHERE id = $1")
+        .bind(worker_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("User {} not found", worker_id))?;
+    let worker_name: String = worker.get("full_name");
+
+    let query = "INSERT INTO worker_pay_schemes (worker_id, scheme, labour_share_percent, norm_hour_rate, salary, bonus_percent, updated_at, updated_by)
+                 VALUES ($1, $2::pay_scheme, $3, $4, $5, $6, NOW(), $7)
+                 ON CONFLICT (worker_id) DO UPDATE
+                 SET scheme = EXCLUDED.scheme, labour_share_percent = EXCLUDED.labour_share_percent,
+                     norm_hour_rate = EXCLUDED.norm_hour_rate, salary = EXCLUDED.salary,
+                     bonus_percent = EXCLUDED.bonus_percent, updated_at = NOW(), updated_by = EXCLUDED.updated_by";
+    sqlx::query(query)
+        .bind(worker_id)
+        .bind(&scheme)
+        .bind(labour_share)
+        .bind(norm_hour_rate)
+        .bind(salary)
+        .bind(bonus)
+        .bind(user.id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    // Логируем изменение схемы оплаты
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Set_Pay_Scheme".to_string(),
+        format!("Изменена схема оплаты сотрудника '{}' ({}): {}, доля работ {}%, нормо-час {}, оклад {}, премия {}%",
+                worker_name, worker_id, scheme, labour_share, norm_hour_rate, salary, bonus),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging pay scheme change: {}", e);
+    }
+
+    Ok(())
+}
+
+// Расчётная ведомость за период (даты включительно, формат ГГГГ-ММ-ДД)
+#[tauri::command]
+pub async fn get_payroll_statement(session_token: String, date_from: String, date_to: String, state: tauri::State<'_, Database>) -> Result<PayrollStatement, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManagePayroll, "get_payroll_statement").await?;
+
+    let (from, to) = parse_period(&date_from, &date_to)?;
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    build_statement(&mut conn, from, to).await
+}
+
+// Сохраняет ведомость в CSV в папку документов; возвращает путь к файлу
+#[tauri::command]
+pub async fn export_payroll_csv(session_token: String, date_from: String, date_to: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManagePayroll, "export_payroll_csv").await?;
+
+    let (from, to) = parse_period(&date_from, &date_to)?;
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    let company = settings::load(&mut *conn).await?;
+    let statement = build_statement(&mut conn, from, to).await?;
+    drop(conn);
+    let content = statement_csv(&statement)?;
+
+    let dir = settings::documents_dir(&company).join("payroll");
+    fs::create_dir_all(&dir).map_err(|e| format!("Ошибка создания папки {}: {}", dir.display(), e))?;
+    let file_name = format!(
+        "payroll_{}_{}_{}.csv",
+        statement.date_from,
+        statement.date_to,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let path = dir.join(file_name);
+    fs::write(&path, content).map_err(|e| format!("Ошибка сохранения ведомости {}: {}", path.display(), e))?;
+    let path = fs::canonicalize(&path).unwrap_or(path);
+
+    // Логируем выгрузку ведомости
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Export_Payroll".to_string(),
+        format!("Выгружена ведомость заработной платы за {} - {} (итого {}): {}",
+                statement.date_from, statement.date_to, statement.total_earnings, path.display()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging payroll export: {}", e);
+    }
+
+    Ok(path.display().to_string())
+}
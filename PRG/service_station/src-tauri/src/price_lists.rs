@@ -0,0 +1,430 @@
+use calamine::{Data, Reader};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::parts_providers::normalize_article;
+
+// Максимальный размер загружаемого файла прайс-листа
+const MAX_FILE_SIZE: usize = 20 * 1024 * 1024;
+// Сколько отклонённых строк возвращается в отчёте импорта (общее количество возвращается всегда)
+pub(crate) const MAX_REPORTED_REJECTIONS: usize = 500;
+// Размер пакета при вставке импортируемых строк
+pub(crate) const INSERT_BATCH_SIZE: usize = 1000;
+// Длины колонок таблицы supplier_price_items
+const MAX_BRAND_LENGTH: usize = 50;
+const MAX_ARTICLE_LENGTH: usize = 50;
+const MAX_NAME_LENGTH: usize = 150;
+
+// Сопоставление колонок прайс-листа. Колонка задаётся названием из строки заголовка,
+// буквой (A, B, ..., AA) или номером, начиная с 1.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PriceListMapping {
+    // Номер строки заголовка, начиная с 1; 0 - заголовка нет и данные начинаются с первой строки
+    #[serde(default = "default_header_row", rename = "headerRow")]
+    header_row: usize,
+    #[serde(default, rename = "brand")]
+    brand: Option<String>,
+    #[serde(rename = "article")]
+    article: String,
+    #[serde(default, rename = "name")]
+    name: Option<String>,
+    #[serde(rename = "price")]
+    price: String,
+    #[serde(default, rename = "deliveryDays")]
+    delivery_days: Option<String>,
+    #[serde(default, rename = "quantity")]
+    quantity: Option<String>,
+}
+
+fn default_header_row() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RejectedRow {
+    pub row: usize, // Номер строки в файле, начиная с 1
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PriceListImportReport {
+    supplier_id: i32,
+    total_rows: usize,
+    imported: usize,
+    rejected_count: usize,
+    rejected: Vec<RejectedRow>,
+}
+
+// Строка прайс-листа, прошедшая проверку
+struct PriceItem {
+    brand: Option<String>,
+    article: String,
+    article_normalized: String,
+    name: Option<String>,
+    price: Money,
+    delivery_days: i32,
+    stock_quantity: Option<i32>,
+}
+
+// Индексы колонок файла для полей прайс-листа
+struct ColumnMap {
+    brand: Option<usize>,
+    article: usize,
+    name: Option<usize>,
+    price: usize,
+    delivery_days: Option<usize>,
+    quantity: Option<usize>,
+}
+
+// Буквенное обозначение колонки в индекс: A -> 0, Z -> 25, AA -> 26
+fn column_letter_index(reference: &str) -> Option<usize> {
+    if reference.is_empty() || reference.len() > 3 || !reference.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let index = reference
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0usize, |acc, b| acc * 26 + (b - b'A' + 1) as usize);
+    Some(index - 1)
+}
+
+fn resolve_column(reference: &str, headers: &[String]) -> Result<usize, String> {
+    let reference = reference.trim();
+    let lower = reference.to_lowercase();
+
+    // Название колонки из заголовка имеет приоритет над буквой: колонка может называться, например, "A"
+    if let Some(index) = headers.iter().position(|h| h.trim().to_lowercase() == lower) {
+        return Ok(index);
+    }
+    if let Ok(number) = reference.parse::<usize>() {
+        if number >= 1 {
+            return Ok(number - 1);
+        }
+    }
+    column_letter_index(reference).ok_or(format!("Колонка '{}' не найдена в прайс-листе", reference))
+}
+
+fn resolve_optional_column(reference: &Option<String>, headers: &[String]) -> Result<Option<usize>, String> {
+    match reference.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        Some(reference) => resolve_column(reference, headers).map(Some),
+        None => Ok(None),
+    }
+}
+
+impl PriceListMapping {
+    fn resolve(&self, headers: &[String]) -> Result<ColumnMap, String> {
+        Ok(ColumnMap {
+            brand: resolve_optional_column(&self.brand, headers)?,
+            article: resolve_column(&self.article, headers)?,
+            name: resolve_optional_column(&self.name, headers)?,
+            price: resolve_column(&self.price, headers)?,
+            delivery_days: resolve_optional_column(&self.delivery_days, headers)?,
+            quantity: resolve_optional_column(&self.quantity, headers)?,
+        })
+    }
+}
+
+// Прайс-листы в CSV часто выгружаются в Windows-1251
+fn decode_text(content: &[u8]) -> String {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    match std::str::from_utf8(content) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1251.decode(content).0.into_owned(),
+    }
+}
+
+fn read_csv(content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let text = decode_text(content);
+
+    // Разделитель - самый частый из ';', табуляции и ',' в первой строке
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = [b';', b'\t', b',']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d as char).count())
+        .unwrap_or(b';');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Ошибка чтения CSV: {}", e))?;
+        rows.push(record.iter().map(|v| v.trim().to_string()).collect());
+    }
+    Ok(rows)
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+// Первый лист книги Excel (XLSX, XLS) или ODS
+fn read_workbook(content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook = calamine::open_workbook_auto_from_rs(Cursor::new(content.to_vec()))
+        .map_err(|e| format!("Ошибка чтения книги Excel: {}", e))?;
+
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("В книге нет ни одного листа".to_string())?
+        .map_err(|e| format!("Ошибка чтения листа: {}", e))?;
+
+    Ok(range.rows().map(|row| row.iter().map(cell_to_string).collect()).collect())
+}
+
+// Проверяет, что загружаемый файл не пуст и не превышает допустимый размер.
+// kind - название файла в родительном падеже для текста ошибки ("прайс-листа")
+pub(crate) fn check_file_size(content: &[u8], kind: &str) -> Result<(), String> {
+    if content.is_empty() {
+        return Err(format!("Файл {} пуст", kind));
+    }
+    if content.len() > MAX_FILE_SIZE {
+        return Err(format!("Файл {} больше {} МБ", kind, MAX_FILE_SIZE / 1024 / 1024));
+    }
+    Ok(())
+}
+
+// Строки первого листа файла CSV или книги Excel в виде текста
+pub fn read_rows(file_name: &str, content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let extension = file_name.rsplit('.').next().unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "csv" | "txt" => read_csv(content),
+        "xlsx" | "xlsm" | "xls" | "ods" => read_workbook(content),
+        _ => Err(format!("Неподдерживаемый формат файла '{}': ожидается CSV или XLSX", file_name)),
+    }
+}
+
+fn parse_price(value: &str) -> Result<Money, String> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+    Money::parse(&cleaned)
+}
+
+// Наибольшее число в строке: "3-5 дн." -> 5, ">10" -> 10
+fn parse_max_number(value: &str) -> Option<i32> {
+    value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse::<i32>().ok())
+        .max()
+}
+
+fn parse_row(row: &[String], columns: &ColumnMap, default_delivery_days: i32) -> Result<PriceItem, String> {
+    let get = |col: Option<usize>| col.and_then(|c| row.get(c)).map(|v| v.trim()).unwrap_or("");
+
+    let article = get(Some(columns.article));
+    if article.is_empty() {
+        return Err("Пустой артикул".to_string());
+    }
+    let article_normalized = normalize_article(article);
+    if article_normalized.is_empty() {
+        return Err(format!("Артикул '{}' не содержит букв и цифр", article));
+    }
+    if article.chars().count() > MAX_ARTICLE_LENGTH {
+        return Err(format!("Артикул длиннее {} символов", MAX_ARTICLE_LENGTH));
+    }
+
+    let brand = get(columns.brand);
+    if brand.chars().count() > MAX_BRAND_LENGTH {
+        return Err(format!("Бренд длиннее {} символов", MAX_BRAND_LENGTH));
+    }
+
+    let price_text = get(Some(columns.price));
+    let price = parse_price(price_text)?;
+
+    let days_text = get(columns.delivery_days);
+    let delivery_days = if days_text.is_empty() {
+        default_delivery_days
+    } else {
+        parse_max_number(days_text).ok_or(format!("Некорректный срок поставки: '{}'", days_text))?
+    };
+
+    // Наличие у поставщика необязательно: нераспознанное значение не отклоняет строку
+    let stock_quantity = parse_max_number(get(columns.quantity));
+
+    let name = get(columns.name);
+    Ok(PriceItem {
+        brand: Some(brand.to_string()).filter(|b| !b.is_empty()),
+        article: article.to_string(),
+        article_normalized,
+        name: Some(name.chars().take(MAX_NAME_LENGTH).collect::<String>()).filter(|n| !n.is_empty()),
+        price,
+        delivery_days,
+        stock_quantity,
+    })
+}
+
+#[tauri::command]
+pub async fn get_price_list_mapping(session_token: String, supplier_id: i32, state: tauri::State<'_, Database>) -> Result<Option<PriceListMapping>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_price_list_mapping").await?;
+
+    let row = sqlx::query("SELECT price_list_mapping::text AS mapping FROM suppliers WHERE id = $1")
+        .bind(supplier_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Supplier {} not found", supplier_id))?;
+
+    let mapping: Option<String> = row.get("mapping");
+    match mapping {
+        Some(mapping) => serde_json::from_str(&mapping).map(Some).map_err(|e| format!("Ошибка чтения сопоставления колонок: {}", e)),
+        None => Ok(None),
+    }
+}
+
+// Загружает прайс-лист поставщика, полностью заменяя предыдущий.
+// Если сопоставление колонок не передано, используется сохранённое при прошлом импорте.
+#[tauri::command]
+pub async fn import_price_list(
+    session_token: String,
+    supplier_id: i32,
+    file_name: String,
+    content: Vec<u8>,
+    mapping: Option<PriceListMapping>,
+    default_delivery_days: Option<i32>,
+    state: tauri::State<'_, Database>
+) -> Result<PriceListImportReport, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "import_price_list").await?;
+
+    check_file_size(&content, "прайс-листа")?;
+    let default_delivery_days = default_delivery_days.unwrap_or(0);
+    if default_delivery_days < 0 {
+        return Err("Срок поставки по умолчанию не может быть отрицательным".to_string());
+    }
+
+    let mapping = match mapping {
+        Some(mapping) => mapping,
+        None => get_price_list_mapping(session_token.clone(), supplier_id, state.clone())
+            .await?
+            .ok_or("Укажите сопоставление колонок прайс-листа".to_string())?,
+    };
+
+    let rows = read_rows(&file_name, &content)?;
+    if rows.len() < mapping.header_row {
+        return Err(format!("В прайс-листе нет строки заголовка {}", mapping.header_row));
+    }
+
+    let headers: &[String] = if mapping.header_row > 0 { &rows[mapping.header_row - 1] } else { &[] };
+    let columns = mapping.resolve(headers)?;
+
+    let mut items: Vec<PriceItem> = Vec::new();
+    let mut rejected: Vec<RejectedRow> = Vec::new();
+    let mut total_rows = 0;
+    // Первая строка файла для каждой пары бренд+артикул
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+
+    for (index, row) in rows.iter().enumerate().skip(mapping.header_row) {
+        let row_number = index + 1;
+        if row.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        total_rows += 1;
+
+        let item = match parse_row(row, &columns, default_delivery_days) {
+            Ok(item) => item,
+            Err(reason) => {
+                rejected.push(RejectedRow { row: row_number, reason });
+                continue;
+            }
+        };
+
+        let key = (item.brand.clone().unwrap_or_default().to_uppercase(), item.article_normalized.clone());
+        if let Some(first_row) = seen.get(&key) {
+            rejected.push(RejectedRow { row: row_number, reason: format!("Повтор позиции из строки {}", first_row) });
+            continue;
+        }
+        seen.insert(key, row_number);
+        items.push(item);
+    }
+
+    // Прайс-лист без единой корректной строки скорее всего загружен с неверным сопоставлением колонок:
+    // прежний прайс-лист в этом случае сохраняется
+    if items.is_empty() {
+        let reason = rejected.first().map(|r| format!(" (строка {}: {})", r.row, r.reason)).unwrap_or_default();
+        return Err(format!("Ни одна строка прайс-листа не прошла проверку{}", reason));
+    }
+
+    let mapping_json = serde_json::to_string(&mapping).map_err(|e| format!("Serialization error: {}", e))?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let supplier_name: String = sqlx::query("UPDATE suppliers SET price_list_mapping = $2::jsonb, price_list_imported_at = NOW() WHERE id = $1 RETURNING name")
+        .bind(supplier_id)
+        .bind(&mapping_json)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Supplier {} not found", supplier_id))?
+        .get("name");
+
+    sqlx::query("DELETE FROM supplier_price_items WHERE supplier_id = $1")
+        .bind(supplier_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error clearing price list: {}", e))?;
+
+    for batch in items.chunks(INSERT_BATCH_SIZE) {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO supplier_price_items (supplier_id, brand, article, article_normalized, name, price, delivery_days, stock_quantity) "
+        );
+        builder.push_values(batch, |mut b, item| {
+            b.push_bind(supplier_id)
+                .push_bind(&item.brand)
+                .push_bind(&item.article)
+                .push_bind(&item.article_normalized)
+                .push_bind(&item.name)
+                .push_bind(item.price)
+                .push_bind(item.delivery_days)
+                .push_bind(item.stock_quantity);
+        });
+        builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error importing price list: {}", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    let rejected_count = rejected.len();
+    rejected.truncate(MAX_REPORTED_REJECTIONS);
+
+    // Логируем импорт прайс-листа
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Import_Price_List".to_string(),
+        format!("Импортирован прайс-лист поставщика '{}' из файла '{}': загружено {}, отклонено {}",
+                supplier_name, file_name, items.len(), rejected_count),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging price list import: {}", e);
+    }
+
+    Ok(PriceListImportReport {
+        supplier_id,
+        total_rows,
+        imported: items.len(),
+        rejected_count,
+        rejected,
+    })
+}
@@ -0,0 +1,731 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::reservations;
+use crate::stock::{self, MovementType, NewMovement};
+
+// Статусы заказа поставщику (соответствует ENUM purchase_order_status в БД)
+const STATUS_DRAFT: &str = "Draft";
+const STATUS_SENT: &str = "Sent";
+const STATUS_PARTIALLY_RECEIVED: &str = "Partially_Received";
+const STATUS_RECEIVED: &str = "Received";
+const PURCHASE_ORDER_STATUSES: &[&str] = &[STATUS_DRAFT, STATUS_SENT, STATUS_PARTIALLY_RECEIVED, STATUS_RECEIVED];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Supplier {
+    id: i32,
+    name: String,
+    contact_person: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+    notes: Option<String>,
+    is_active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SupplierRequest {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "contactPerson")]
+    contact_person: Option<String>,
+    #[serde(rename = "phone")]
+    phone: Option<String>,
+    #[serde(rename = "email")]
+    email: Option<String>,
+    #[serde(rename = "notes")]
+    notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PurchaseOrder {
+    id: i32,
+    supplier_id: i32,
+    supplier_name: String,
+    status: String,
+    expected_date: Option<String>,
+    comment: Option<String>,
+    created_by_name: Option<String>,
+    created_at: String,
+    sent_at: Option<String>,
+    received_at: Option<String>,
+    total: Money,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PurchaseOrderLine {
+    id: i32,
+    warehouse_item_id: Option<i32>,
+    order_part_id: Option<i32>,
+    order_id: Option<i32>, // Заказ клиента, под который заказана запчасть
+    name: String,
+    brand: Option<String>,
+    article: Option<String>,
+    quantity: i32,
+    received_quantity: i32,
+    unit_price: Option<Money>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PurchaseOrderDetails {
+    order: PurchaseOrder,
+    lines: Vec<PurchaseOrderLine>,
+}
+
+// Строка нового заказа поставщику: позиция склада, запчасть заказа клиента или новая позиция по названию
+#[derive(Deserialize)]
+pub struct NewPurchaseOrderLine {
+    #[serde(default, rename = "warehouseItemId")]
+    warehouse_item_id: Option<i32>,
+    #[serde(default, rename = "orderPartId")]
+    order_part_id: Option<i32>,
+    #[serde(default, rename = "name")]
+    name: Option<String>,
+    #[serde(default, rename = "brand")]
+    brand: Option<String>,
+    #[serde(default, rename = "article")]
+    article: Option<String>,
+    #[serde(rename = "quantity")]
+    quantity: i32,
+    #[serde(default, rename = "unitPrice")]
+    unit_price: Option<Money>,
+}
+
+#[derive(Deserialize)]
+pub struct ReceiptItem {
+    #[serde(rename = "lineId")]
+    line_id: i32,
+    #[serde(rename = "quantity")]
+    quantity: i32,
+    // Ячейка для позиции, которая заводится на склад впервые
+    #[serde(default, rename = "locationCell")]
+    location_cell: Option<String>,
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+fn map_supplier_error(e: sqlx::Error, name: &str) -> String {
+    match &e {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => format!("Поставщик '{}' уже существует", name),
+        _ => format!("Database error: {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn get_suppliers(session_token: String, include_inactive: Option<bool>, state: tauri::State<'_, Database>) -> Result<Vec<Supplier>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "get_suppliers").await?;
+
+    let query = "SELECT id, name, contact_person, phone, email, notes, COALESCE(is_active, true) AS is_active
+                 FROM suppliers
+                 WHERE $1 OR COALESCE(is_active, true)
+                 ORDER BY name";
+    let rows = sqlx::query(query)
+        .bind(include_inactive.unwrap_or(false))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut suppliers = Vec::new();
+    for row in rows {
+        suppliers.push(Supplier {
+            id: row.get("id"),
+            name: row.get("name"),
+            contact_person: row.get("contact_person"),
+            phone: row.get("phone"),
+            email: row.get("email"),
+            notes: row.get("notes"),
+            is_active: row.get("is_active"),
+        });
+    }
+
+    Ok(suppliers)
+}
+
+#[tauri::command]
+pub async fn create_supplier(session_token: String, request: SupplierRequest, state: tauri::State<'_, Database>) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "create_supplier").await?;
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Название поставщика не может быть пустым".to_string());
+    }
+
+    let query = "INSERT INTO suppliers (name, contact_person, phone, email, notes) VALUES ($1, $2, $3, $4, $5) RETURNING id";
+    let supplier_id: i32 = sqlx::query(query)
+        .bind(&name)
+        .bind(non_empty(&request.contact_person))
+        .bind(non_empty(&request.phone))
+        .bind(non_empty(&request.email))
+        .bind(non_empty(&request.notes))
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| map_supplier_error(e, &name))?
+        .get("id");
+
+    // Логируем добавление поставщика
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Create_Supplier".to_string(),
+        format!("Добавлен поставщик '{}' (ID {})", name, supplier_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging supplier creation: {}", e);
+    }
+
+    Ok(supplier_id)
+}
+
+#[tauri::command]
+pub async fn update_supplier(
+    session_token: String,
+    supplier_id: i32,
+    request: SupplierRequest,
+    is_active: bool,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "update_supplier").await?;
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Название поставщика не может быть пустым".to_string());
+    }
+
+    let query = "UPDATE suppliers SET name = $2, contact_person = $3, phone = $4, email = $5, notes = $6, is_active = $7 WHERE id = $1";
+    let result = sqlx::query(query)
+        .bind(supplier_id)
+        .bind(&name)
+        .bind(non_empty(&request.contact_person))
+        .bind(non_empty(&request.phone))
+        .bind(non_empty(&request.email))
+        .bind(non_empty(&request.notes))
+        .bind(is_active)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| map_supplier_error(e, &name))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Supplier {} not found", supplier_id));
+    }
+
+    // Логируем изменение поставщика
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Update_Supplier".to_string(),
+        format!("Изменён поставщик '{}' (ID {}), активен: {}", name, supplier_id, is_active),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging supplier update: {}", e);
+    }
+
+    Ok(format!("Supplier {} updated", supplier_id))
+}
+
+const PURCHASE_ORDER_COLUMNS: &str = "po.id, po.supplier_id, s.name AS supplier_name, po.status::text AS status,
+        po.expected_date::text AS expected_date, po.comment, u.full_name AS created_by_name, po.created_at::text AS created_at,
+        po.sent_at::text AS sent_at, po.received_at::text AS received_at,
+        (SELECT COALESCE(SUM(l.quantity * COALESCE(l.unit_price, 0)), 0) FROM purchase_order_lines l WHERE l.purchase_order_id = po.id) AS total
+     FROM purchase_orders po
+     JOIN suppliers s ON po.supplier_id = s.id
+     LEFT JOIN users u ON po.created_by = u.id";
+
+fn purchase_order_from_row(row: &sqlx::postgres::PgRow) -> PurchaseOrder {
+    PurchaseOrder {
+        id: row.get("id"),
+        supplier_id: row.get("supplier_id"),
+        supplier_name: row.get("supplier_name"),
+        status: row.get("status"),
+        expected_date: row.get("expected_date"),
+        comment: row.get("comment"),
+        created_by_name: row.get("created_by_name"),
+        created_at: row.get("created_at"),
+        sent_at: row.get("sent_at"),
+        received_at: row.get("received_at"),
+        total: row.get("total"),
+    }
+}
+
+#[tauri::command]
+pub async fn get_purchase_orders(session_token: String, status: Option<String>, state: tauri::State<'_, Database>) -> Result<Vec<PurchaseOrder>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_purchase_orders").await?;
+
+    let status = non_empty(&status);
+    if let Some(status) = &status {
+        if !PURCHASE_ORDER_STATUSES.contains(&status.as_str()) {
+            return Err(format!("Неизвестный статус заказа поставщику: {}", status));
+        }
+    }
+
+    let query = format!("SELECT {} WHERE $1::text IS NULL OR po.status::text = $1 ORDER BY po.created_at DESC", PURCHASE_ORDER_COLUMNS);
+    let rows = sqlx::query(&query)
+        .bind(status)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(purchase_order_from_row).collect())
+}
+
+#[tauri::command]
+pub async fn get_purchase_order(session_token: String, purchase_order_id: i32, state: tauri::State<'_, Database>) -> Result<PurchaseOrderDetails, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_purchase_order").await?;
+
+    let query = format!("SELECT {} WHERE po.id = $1", PURCHASE_ORDER_COLUMNS);
+    let order_row = sqlx::query(&query)
+        .bind(purchase_order_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Purchase order {} not found", purchase_order_id))?;
+
+    let lines_query = "SELECT l.id, l.warehouse_item_id, l.order_part_id, p.order_id, l.name, l.brand, l.article,
+                              l.quantity, l.received_quantity, l.unit_price
+                       FROM purchase_order_lines l
+                       LEFT JOIN order_parts p ON l.order_part_id = p.id
+                       WHERE l.purchase_order_id = $1
+                       ORDER BY l.id";
+    let rows = sqlx::query(lines_query)
+        .bind(purchase_order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.push(PurchaseOrderLine {
+            id: row.get("id"),
+            warehouse_item_id: row.get("warehouse_item_id"),
+            order_part_id: row.get("order_part_id"),
+            order_id: row.get("order_id"),
+            name: row.get("name"),
+            brand: row.get("brand"),
+            article: row.get("article"),
+            quantity: row.get("quantity"),
+            received_quantity: row.get("received_quantity"),
+            unit_price: row.get("unit_price"),
+        });
+    }
+
+    Ok(PurchaseOrderDetails {
+        order: purchase_order_from_row(&order_row),
+        lines,
+    })
+}
+
+// Дополняет строку заказа данными позиции склада или запчасти заказа клиента.
+// Возвращает (warehouse_item_id, order_part_id, название, бренд, артикул, количество, цена).
+async fn resolve_line(
+    conn: &mut PgConnection,
+    line: &NewPurchaseOrderLine
+) -> Result<(Option<i32>, Option<i32>, String, Option<String>, Option<String>, i32, Option<Money>), String> {
+    let mut name = non_empty(&line.name);
+    let mut brand = non_empty(&line.brand);
+    let mut article = non_empty(&line.article);
+    let mut quantity = line.quantity;
+    let mut unit_price = line.unit_price;
+    let mut warehouse_item_id = line.warehouse_item_id;
+
+    if let Some(order_part_id) = line.order_part_id {
+        // Запчасть под заказ клиента заказывается в том количестве, в котором она нужна заказу
+        let query = "SELECT warehouse_item_id, part_name_snapshot, brand, COALESCE(quantity, 1) AS quantity,
+                            COALESCE(source_type, 'Stock') = 'Supplier' AS from_supplier, received_at IS NOT NULL AS received
+                     FROM order_parts WHERE id = $1";
+        let row = sqlx::query(query)
+            .bind(order_part_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or(format!("Order part {} not found", order_part_id))?;
+
+        let part_name: String = row.get("part_name_snapshot");
+        let from_supplier: bool = row.get("from_supplier");
+        let received: bool = row.get("received");
+        if !from_supplier {
+            return Err(format!("Запчасть '{}' берётся со склада и не требует заказа у поставщика", part_name));
+        }
+        if received {
+            return Err(format!("Запчасть '{}' уже поступила на склад", part_name));
+        }
+
+        warehouse_item_id = warehouse_item_id.or(row.get("warehouse_item_id"));
+        name = name.or(Some(part_name));
+        brand = brand.or(row.get("brand"));
+        quantity = row.get("quantity");
+    }
+
+    if let Some(item_id) = warehouse_item_id {
+        let row = sqlx::query("SELECT name, brand, article, purchase_price FROM warehouse WHERE id = $1")
+            .bind(item_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or(format!("Warehouse item {} not found", item_id))?;
+
+        name = name.or(row.get("name"));
+        brand = brand.or(row.get("brand"));
+        article = article.or(row.get("article"));
+        unit_price = unit_price.or(row.get("purchase_price"));
+    }
+
+    let name = name.ok_or("Для новой позиции заказа поставщику укажите название запчасти".to_string())?;
+    if quantity <= 0 {
+        return Err(format!("Количество запчасти '{}' должно быть положительным", name));
+    }
+
+    Ok((warehouse_item_id, line.order_part_id, name, brand, article, quantity, unit_price))
+}
+
+#[tauri::command]
+pub async fn create_purchase_order(
+    session_token: String,
+    supplier_id: i32,
+    expected_date: Option<String>,
+    comment: Option<String>,
+    lines: Vec<NewPurchaseOrderLine>,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "create_purchase_order").await?;
+
+    if lines.is_empty() {
+        return Err("Заказ поставщику должен содержать хотя бы одну позицию".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let supplier_row = sqlx::query("SELECT name, COALESCE(is_active, true) AS is_active FROM suppliers WHERE id = $1")
+        .bind(supplier_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Supplier {} not found", supplier_id))?;
+
+    let supplier_name: String = supplier_row.get("name");
+    let is_active: bool = supplier_row.get("is_active");
+    if !is_active {
+        return Err(format!("Поставщик '{}' отключён", supplier_name));
+    }
+
+    let query = "INSERT INTO purchase_orders (supplier_id, expected_date, comment, created_by) VALUES ($1, $2::date, $3, $4) RETURNING id";
+    let purchase_order_id: i32 = sqlx::query(query)
+        .bind(supplier_id)
+        .bind(non_empty(&expected_date))
+        .bind(non_empty(&comment))
+        .bind(user.id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error creating purchase order: {}", e))?
+        .get("id");
+
+    for line in &lines {
+        let (warehouse_item_id, order_part_id, name, brand, article, quantity, unit_price) = resolve_line(&mut tx, line).await?;
+
+        let line_query = "INSERT INTO purchase_order_lines (purchase_order_id, warehouse_item_id, order_part_id, name, brand, article, quantity, unit_price)
+                          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)";
+        sqlx::query(line_query)
+            .bind(purchase_order_id)
+            .bind(warehouse_item_id)
+            .bind(order_part_id)
+            .bind(&name)
+            .bind(&brand)
+            .bind(&article)
+            .bind(quantity)
+            .bind(unit_price)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error adding purchase order line: {}", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем создание заказа поставщику
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Create_Purchase_Order".to_string(),
+        format!("Создан заказ поставщику {} ('{}'), позиций: {}", purchase_order_id, supplier_name, lines.len()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging purchase order creation: {}", e);
+    }
+
+    Ok(purchase_order_id)
+}
+
+// Блокирует заказ поставщику и возвращает (supplier_id, статус)
+async fn lock_purchase_order(conn: &mut PgConnection, purchase_order_id: i32) -> Result<(i32, String), String> {
+    let row = sqlx::query("SELECT supplier_id, status::text AS status FROM purchase_orders WHERE id = $1 FOR UPDATE")
+        .bind(purchase_order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Purchase order {} not found", purchase_order_id))?;
+
+    Ok((row.get("supplier_id"), row.get("status")))
+}
+
+#[tauri::command]
+pub async fn send_purchase_order(session_token: String, purchase_order_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "send_purchase_order").await?;
+
+    let query = "UPDATE purchase_orders SET status = 'Sent', sent_at = NOW() WHERE id = $1 AND status = 'Draft'";
+    let result = sqlx::query(query)
+        .bind(purchase_order_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Заказ поставщику {} не найден или уже отправлен", purchase_order_id));
+    }
+
+    // Логируем отправку заказа поставщику
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Send_Purchase_Order".to_string(),
+        format!("Заказ поставщику {} отправлен", purchase_order_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging purchase order sending: {}", e);
+    }
+
+    Ok(format!("Purchase order {} sent", purchase_order_id))
+}
+
+#[tauri::command]
+pub async fn delete_purchase_order(session_token: String, purchase_order_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "delete_purchase_order").await?;
+
+    // Удалить можно только черновик: отправленный заказ уже существует у поставщика
+    let result = sqlx::query("DELETE FROM purchase_orders WHERE id = $1 AND status = 'Draft'")
+        .bind(purchase_order_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Заказ поставщику {} не найден или уже отправлен", purchase_order_id));
+    }
+
+    // Логируем удаление черновика заказа поставщику
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Delete_Purchase_Order".to_string(),
+        format!("Удалён черновик заказа поставщику {}", purchase_order_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging purchase order deletion: {}", e);
+    }
+
+    Ok(format!("Purchase order {} deleted", purchase_order_id))
+}
+
+// Находит позицию склада для строки без привязки (по артикулу и бренду) или заводит новую
+async fn ensure_warehouse_item(
+    conn: &mut PgConnection,
+    name: &str,
+    brand: &Option<String>,
+    article: &Option<String>,
+    location_cell: Option<String>,
+    unit_price: Option<Money>
+) -> Result<i32, String> {
+    if let Some(article) = article {
+        let query = "SELECT id FROM warehouse
+                     WHERE LOWER(TRIM(article)) = LOWER(TRIM($1)) AND LOWER(COALESCE(brand, '')) = LOWER(COALESCE($2, ''))
+                       AND COALESCE(is_archived, false) = false
+                     ORDER BY id LIMIT 1";
+        let existing = sqlx::query(query)
+            .bind(article)
+            .bind(brand)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        if let Some(row) = existing {
+            return Ok(row.get("id"));
+        }
+    }
+
+    // Новая позиция заводится с нулевым остатком, остаток появляется движением поступления
+    let query = "INSERT INTO warehouse (name, brand, article, location_cell, quantity, min_quantity, purchase_price)
+                 VALUES ($1, $2, $3, $4, 0, 0, $5) RETURNING id";
+    let row = sqlx::query(query)
+        .bind(name)
+        .bind(brand)
+        .bind(article)
+        .bind(location_cell)
+        .bind(unit_price)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error creating warehouse item: {}", e))?;
+
+    Ok(row.get("id"))
+}
+
+// Приёмка товара по заказу поставщику: оприходование на склад и передача запчастей в заказы клиентов
+#[tauri::command]
+pub async fn receive_purchase_order(
+    session_token: String,
+    purchase_order_id: i32,
+    items: Vec<ReceiptItem>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "receive_purchase_order").await?;
+
+    if items.is_empty() {
+        return Err("Не указаны позиции для приёмки".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let (supplier_id, status) = lock_purchase_order(&mut tx, purchase_order_id).await?;
+    if status != STATUS_SENT && status != STATUS_PARTIALLY_RECEIVED {
+        return Err(format!("Заказ поставщику {} в статусе '{}': приёмка недоступна", purchase_order_id, status));
+    }
+
+    let reason = format!("Приёмка по заказу поставщику {}", purchase_order_id);
+    let mut received = Vec::new();
+    for item in &items {
+        let query = "SELECT warehouse_item_id, order_part_id, name, brand, article, quantity, received_quantity,
+                            unit_price
+                     FROM purchase_order_lines WHERE id = $1 AND purchase_order_id = $2 FOR UPDATE";
+        let line = sqlx::query(query)
+            .bind(item.line_id)
+            .bind(purchase_order_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or(format!("Строка {} не найдена в заказе поставщику {}", item.line_id, purchase_order_id))?;
+
+        let name: String = line.get("name");
+        let quantity: i32 = line.get("quantity");
+        let received_quantity: i32 = line.get("received_quantity");
+        let unit_price: Option<Money> = line.get("unit_price");
+
+        if item.quantity <= 0 {
+            return Err(format!("Количество к приёмке запчасти '{}' должно быть положительным", name));
+        }
+        if received_quantity + item.quantity > quantity {
+            return Err(format!("Запчасть '{}': к приёмке осталось {} шт., указано {}", name, quantity - received_quantity, item.quantity));
+        }
+
+        let warehouse_item_id: Option<i32> = line.get("warehouse_item_id");
+        let warehouse_item_id = match warehouse_item_id {
+            Some(id) => id,
+            None => {
+                let brand: Option<String> = line.get("brand");
+                let article: Option<String> = line.get("article");
+                ensure_warehouse_item(&mut tx, &name, &brand, &article, non_empty(&item.location_cell), unit_price).await?
+            }
+        };
+
+        stock::record_movement(&mut tx, &NewMovement {
+            warehouse_item_id,
+            movement_type: MovementType::Receipt,
+            quantity: item.quantity,
+            unit_cost: unit_price,
+            user_id: Some(user.id),
+            order_part_id: line.get("order_part_id"),
+            reason: Some(&reason),
+        }).await?;
+
+        // Запоминаем последнюю закупочную цену и поставщика позиции
+        sqlx::query("UPDATE warehouse SET purchase_price = COALESCE($2, purchase_price), supplier_id = $3 WHERE id = $1")
+            .bind(warehouse_item_id)
+            .bind(unit_price)
+            .bind(supplier_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error updating warehouse item: {}", e))?;
+
+        sqlx::query("UPDATE purchase_order_lines SET received_quantity = received_quantity + $2, warehouse_item_id = $3 WHERE id = $1")
+            .bind(item.line_id)
+            .bind(item.quantity)
+            .bind(warehouse_item_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error updating purchase order line: {}", e))?;
+
+        // Полностью поступившая запчасть под заказ клиента связывается со складом и становится доступна для выдачи
+        let order_part_id: Option<i32> = line.get("order_part_id");
+        if let Some(order_part_id) = order_part_id.filter(|_| received_quantity + item.quantity == quantity) {
+            let part_query = "UPDATE order_parts SET warehouse_item_id = $2, received_at = NOW(), supplier_id = COALESCE(supplier_id, $3)
+                              WHERE id = $1
+                              RETURNING is_confirmed";
+            let part_row = sqlx::query(part_query)
+                .bind(order_part_id)
+                .bind(warehouse_item_id)
+                .bind(supplier_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| format!("Database error updating order part: {}", e))?;
+
+            let is_confirmed = part_row.map(|row| row.get::<bool, _>("is_confirmed")).unwrap_or(false);
+            if is_confirmed {
+                reservations::reserve_part(&mut tx, order_part_id, user.id).await?;
+            }
+        }
+
+        received.push(format!("'{}' x{}", name, item.quantity));
+    }
+
+    let status_query = "UPDATE purchase_orders
+                        SET status = CASE WHEN fully_received THEN 'Received' ELSE 'Partially_Received' END::purchase_order_status,
+                            received_at = CASE WHEN fully_received THEN NOW() END
+                        FROM (SELECT NOT EXISTS (SELECT 1 FROM purchase_order_lines
+                                                 WHERE purchase_order_id = $1 AND received_quantity < quantity) AS fully_received) lines
+                        WHERE id = $1
+                        RETURNING status::text AS status";
+    let new_status: String = sqlx::query(status_query)
+        .bind(purchase_order_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error updating purchase order status: {}", e))?
+        .get("status");
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем приёмку товара
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Receive_Goods".to_string(),
+        format!("Приёмка по заказу поставщику {} (статус: {}): {}", purchase_order_id, new_status, received.join(", ")),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging goods receipt: {}", e);
+    }
+
+    Ok(format!("Принято позиций по заказу поставщику {}: {}", purchase_order_id, received.len()))
+}
@@ -0,0 +1,543 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission, UserRole};
+use crate::database::Database;
+use crate::order_status::{self, OrderStatus};
+
+// Итог проверки (соответствует ENUM qc_result в БД)
+const RESULT_PASSED: &str = "Passed";
+const RESULT_FAILED: &str = "Failed";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QcChecklistItem {
+    id: i32,
+    title: String,
+    service_id: Option<i32>,
+    service_name: Option<String>,
+    defect_node_id: Option<i32>,
+    defect_node_name: Option<String>,
+    sort_order: i32,
+}
+
+// Строка чек-листа конкретного заказа: пункт и работа, к которой он относится
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QcCheck {
+    checklist_item_id: i32,
+    title: String,
+    work_id: Option<i32>, // null - общая проверка по заказу
+    work_name: Option<String>,
+    worker_id: Option<i32>,
+    worker_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QcResultInput {
+    checklist_item_id: Option<i32>, // null - замечание мастера вне чек-листа
+    title: String,
+    work_id: Option<i32>,
+    passed: bool,
+    comment: Option<String>,
+    rework_worker_id: Option<i32>, // Кому передать работу на переделку; по умолчанию - прежнему исполнителю
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QcInspectionResult {
+    id: i32,
+    checklist_item_id: Option<i32>,
+    title: String,
+    work_id: Option<i32>,
+    work_name: Option<String>,
+    passed: bool,
+    comment: Option<String>,
+    rework_worker_id: Option<i32>,
+    rework_worker_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QcInspection {
+    id: i32,
+    order_id: i32,
+    inspector_name: Option<String>,
+    result: String,
+    comment: Option<String>,
+    created_at: String,
+    results: Vec<QcInspectionResult>,
+}
+
+fn normalize_text(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+// Исполнитель переделки должен быть действующим слесарем
+async fn check_worker(conn: &mut PgConnection, worker_id: i32) -> Result<(), String> {
+    let row = sqlx::query("SELECT role::text AS role, status::text AS status FROM users WHERE id = $1")
+        .bind(worker_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("User {} not found", worker_id))?;
+
+    let role: String = row.get("role");
+    let status: String = row.get("status");
+    if role != UserRole::Worker.as_str() || status != "Active" {
+        return Err(format!("Пользователь {} не является действующим слесарем", worker_id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_qc_checklist_items(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<QcChecklistItem>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewCatalog, "get_qc_checklist_items").await?;
+
+    let query = "SELECT i.id, i.title, i.service_id, s.name AS service_name, i.defect_node_id, n.name AS defect_node_name, i.sort_order
+                 FROM qc_checklist_items i
+                 LEFT JOIN services_reference s ON s.id = i.service_id
+                 LEFT JOIN defect_nodes n ON n.id = i.defect_node_id
+                 ORDER BY s.name NULLS FIRST, n.name NULLS FIRST, i.sort_order, i.id";
+    let rows = sqlx::query(query)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| QcChecklistItem {
+        id: row.get("id"),
+        title: row.get("title"),
+        service_id: row.get("service_id"),
+        service_name: row.get("service_name"),
+        defect_node_id: row.get("defect_node_id"),
+        defect_node_name: row.get("defect_node_name"),
+        sort_order: row.get("sort_order"),
+    }).collect())
+}
+
+// Пункт чек-листа для услуги, для узла неисправностей или (без привязки) для всех заказов
+#[tauri::command]
+pub async fn add_qc_checklist_item(
+    session_token: String,
+    title: String,
+    service_id: Option<i32>,
+    defect_node_id: Option<i32>,
+    sort_order: Option<i32>,
+    state: tauri::State<'_, Database>
+) -> Result<i32, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "add_qc_checklist_item").await?;
+
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("Укажите, что проверяется".to_string());
+    }
+    if service_id.is_some() && defect_node_id.is_some() {
+        return Err("Пункт чек-листа относится либо к услуге, либо к узлу неисправностей".to_string());
+    }
+
+    let query = "INSERT INTO qc_checklist_items (title, service_id, defect_node_id, sort_order) VALUES ($1, $2, $3, $4) RETURNING id";
+    let row = sqlx::query(query)
+        .bind(&title)
+        .bind(service_id)
+        .bind(defect_node_id)
+        .bind(sort_order.unwrap_or(0))
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let item_id: i32 = row.get("id");
+
+    // Логируем добавление пункта чек-листа
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Add_QC_Checklist_Item".to_string(),
+        format!("Добавлен пункт чек-листа контроля качества {} '{}' (услуга: {:?}, узел: {:?})", item_id, title, service_id, defect_node_id),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging QC checklist change: {}", e);
+    }
+
+    Ok(item_id)
+}
+
+// Результаты прошлых проверок хранят название пункта, поэтому пункт можно удалять
+#[tauri::command]
+pub async fn delete_qc_checklist_item(session_token: String, item_id: i32, state: tauri::State<'_, Database>) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageCatalog, "delete_qc_checklist_item").await?;
+
+    let row = sqlx::query("DELETE FROM qc_checklist_items WHERE id = $1 RETURNING title")
+        .bind(item_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Checklist item {} not found", item_id))?;
+    let title: String = row.get("title");
+
+    // Логируем удаление пункта чек-листа
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Delete_QC_Checklist_Item".to_string(),
+        format!("Удалён пункт чек-листа контроля качества {} '{}'", item_id, title),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging QC checklist change: {}", e);
+    }
+
+    Ok(())
+}
+
+// Пункты чек-листа заказа: общие проверки и проверки по каждой подтверждённой работе
+async fn load_order_checklist(conn: &mut PgConnection, order_id: i32) -> Result<Vec<QcCheck>, String> {
+    let query = "SELECT c.item_id, c.title, c.work_id, w.service_name_snapshot AS work_name,
+                        COALESCE(w.worker_id, o.worker_id) AS worker_id, u.full_name AS worker_name
+                 FROM (
+                     SELECT i.id AS item_id, i.title, NULL::integer AS work_id, i.sort_order
+                     FROM qc_checklist_items i
+                     WHERE i.service_id IS NULL AND i.defect_node_id IS NULL
+                     UNION
+                     SELECT i.id, i.title, w.id, i.sort_order
+                     FROM qc_checklist_items i
+                     JOIN order_works w ON w.service_id = i.service_id
+                     WHERE w.order_id = $1 AND w.is_confirmed AND w.status <> 'Cancelled'
+                     UNION
+                     SELECT i.id, i.title, w.id, i.sort_order
+                     FROM qc_checklist_items i
+                     JOIN defect_types dt ON dt.node_id = i.defect_node_id
+                     JOIN order_defects d ON d.defect_type_id = dt.id AND d.order_id = $1
+                     LEFT JOIN order_works_defects wd ON wd.defect_id = d.id
+                     LEFT JOIN order_works w ON w.id = wd.work_id AND w.is_confirmed AND w.status <> 'Cancelled'
+                 ) c
+                 LEFT JOIN order_works w ON w.id = c.work_id
+                 LEFT JOIN orders o ON o.id = w.order_id
+                 LEFT JOIN users u ON u.id = COALESCE(w.worker_id, o.worker_id)
+                 ORDER BY c.work_id NULLS FIRST, c.sort_order, c.item_id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rows.iter().map(|row| QcCheck {
+        checklist_item_id: row.get("item_id"),
+        title: row.get("title"),
+        work_id: row.get("work_id"),
+        work_name: row.get("work_name"),
+        worker_id: row.get("worker_id"),
+        worker_name: row.get("worker_name"),
+    }).collect())
+}
+
+// Пункты чек-листа, для которых не передан результат (пара пункт + работа)
+fn missing_checks<'a>(checklist: &'a [QcCheck], results: &[QcResultInput]) -> Vec<&'a QcCheck> {
+    let answered: HashSet<(i32, Option<i32>)> = results
+        .iter()
+        .filter_map(|r| r.checklist_item_id.map(|item_id| (item_id, r.work_id)))
+        .collect();
+    checklist
+        .iter()
+        .filter(|check| !answered.contains(&(check.checklist_item_id, check.work_id)))
+        .collect()
+}
+
+// Чек-лист заказа: общие пункты, пункты по услугам заказа и по узлам выявленных неисправностей
+#[tauri::command]
+pub async fn get_order_qc_checklist(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<QcCheck>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::InspectQuality, "get_order_qc_checklist").await?;
+
+    let mut conn = state.pool.acquire().await.map_err(|e| format!("Database connection error: {}", e))?;
+    load_order_checklist(&mut conn, order_id).await
+}
+
+// Приёмка работ мастером: результат нужен по каждому пункту чек-листа заказа.
+// При всех пройденных проверках заказ готов к выдаче, иначе отмеченные работы возвращаются на переделку,
+// а заказ - в работу. Работа на переделке получает статус Pending, а не In_Progress: слесарь начинает её
+// командой start_work, которая открывает интервал учёта времени (work_time.rs)
+#[tauri::command]
+pub async fn submit_qc_inspection(
+    session_token: String,
+    order_id: i32,
+    results: Vec<QcResultInput>,
+    comment: Option<String>,
+    state: tauri::State<'_, Database>
+) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::InspectQuality, "submit_qc_inspection").await?;
+
+    if results.is_empty() {
+        return Err("Отметьте результат хотя бы одной проверки".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let order = sqlx::query("SELECT status::text AS status, worker_id FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order {} not found", order_id))?;
+    let current_status: String = order.get("status");
+    let main_worker_id: Option<i32> = order.get("worker_id");
+
+    if current_status != OrderStatus::QualityControl.as_str() {
+        return Err(format!("Заказ {} не находится на контроле качества (статус '{}')", order_id, current_status));
+    }
+
+    let checklist = load_order_checklist(&mut tx, order_id).await?;
+    let missing = missing_checks(&checklist, &results);
+    if !missing.is_empty() {
+        let titles: Vec<String> = missing
+            .iter()
+            .map(|check| match &check.work_name {
+                Some(work_name) => format!("'{}' ({})", check.title, work_name),
+                None => format!("'{}'", check.title),
+            })
+            .collect();
+        return Err(format!("Не отмечены проверки чек-листа: {}", titles.join(", ")));
+    }
+
+    let failed = results.iter().any(|r| !r.passed);
+    let (new_status, result) = if failed {
+        (OrderStatus::InWork, RESULT_FAILED)
+    } else {
+        (OrderStatus::Ready, RESULT_PASSED)
+    };
+    order_status::check_transition_str(&current_status, new_status.as_str(), &user.role).map_err(|e| e.to_string())?;
+
+    // Проверяем пункты и определяем, кому уходит каждая работа на переделку
+    let mut rework: BTreeMap<i32, i32> = BTreeMap::new();
+    let mut checked = Vec::new();
+    for input in results {
+        let title = input.title.trim().to_string();
+        if title.is_empty() {
+            return Err("У каждой проверки должно быть название".to_string());
+        }
+
+        let mut rework_worker_id = None;
+        match input.work_id {
+            Some(work_id) => {
+                let work = sqlx::query("SELECT worker_id, status::text AS status FROM order_works WHERE id = $1 AND order_id = $2")
+                    .bind(work_id)
+                    .bind(order_id)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Database error: {}", e))?
+                    .ok_or(format!("Работа {} не относится к заказу {}", work_id, order_id))?;
+                let status: String = work.get("status");
+                if status == "Cancelled" {
+                    return Err(format!("Работа {} отменена и не проверяется", work_id));
+                }
+
+                if !input.passed {
+                    let worker_id = input.rework_worker_id
+                        .or(work.get::<Option<i32>, _>("worker_id"))
+                        .or(main_worker_id)
+                        .ok_or(format!("Укажите слесаря для переделки работы {}", work_id))?;
+                    if let Some(previous) = rework.insert(work_id, worker_id) {
+                        if previous != worker_id {
+                            return Err(format!("Работа {} передана на переделку разным слесарям", work_id));
+                        }
+                    }
+                    rework_worker_id = Some(worker_id);
+                }
+            }
+            None if !input.passed => {
+                return Err(format!("Для непройденной проверки '{}' укажите работу на переделку", title));
+            }
+            None => {}
+        }
+
+        checked.push((input, title, rework_worker_id));
+    }
+
+    for worker_id in rework.values() {
+        check_worker(&mut tx, *worker_id).await?;
+    }
+
+    let comment = normalize_text(comment);
+    let inspection = sqlx::query("INSERT INTO qc_inspections (order_id, inspector_id, result, comment) VALUES ($1, $2, $3::qc_result, $4) RETURNING id")
+        .bind(order_id)
+        .bind(user.id)
+        .bind(result)
+        .bind(&comment)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let inspection_id: i32 = inspection.get("id");
+
+    for (input, title, rework_worker_id) in checked {
+        let query = "INSERT INTO qc_inspection_results (inspection_id, checklist_item_id, title, work_id, passed, comment, rework_worker_id)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7)";
+        sqlx::query(query)
+            .bind(inspection_id)
+            .bind(input.checklist_item_id)
+            .bind(&title)
+            .bind(input.work_id)
+            .bind(input.passed)
+            .bind(normalize_text(input.comment))
+            .bind(rework_worker_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+
+    // Работа на переделке снова ждёт исполнителя; время переделки учитывается с момента, когда слесарь её начнёт.
+    // Первое завершение (first_finished_at) сохраняется, поэтому в зарплате переделка повторно не оплачивается
+    for (work_id, worker_id) in &rework {
+        sqlx::query("UPDATE order_works SET status = 'Pending', worker_id = $2, finished_at = NULL WHERE id = $1")
+            .bind(work_id)
+            .bind(worker_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+
+    sqlx::query("UPDATE orders SET status = $1::order_status WHERE id = $2")
+        .bind(new_status.as_str())
+        .bind(order_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем результат контроля качества
+    let details = if failed {
+        format!("Заказ {} не прошёл контроль качества (проверка {}), работы на переделку: {:?}", order_id, inspection_id, rework)
+    } else {
+        format!("Заказ {} прошёл контроль качества (проверка {}) и готов к выдаче", order_id, inspection_id)
+    };
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Quality_Control".to_string(),
+        details,
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging quality control: {}", e);
+    }
+
+    Ok(result.to_string())
+}
+
+// История проверок заказа, по которой видно, какие работы и сколько раз уходили на переделку
+#[tauri::command]
+pub async fn get_order_qc_history(session_token: String, order_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<QcInspection>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ViewOrderDetails, "get_order_qc_history").await?;
+
+    let query = "SELECT i.id, i.order_id, u.full_name AS inspector_name, i.result::text AS result, i.comment, i.created_at::text AS created_at
+                 FROM qc_inspections i
+                 LEFT JOIN users u ON u.id = i.inspector_id
+                 WHERE i.order_id = $1
+                 ORDER BY i.created_at, i.id";
+    let rows = sqlx::query(query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let results_query = "SELECT r.id, r.inspection_id, r.checklist_item_id, r.title, r.work_id, w.service_name_snapshot AS work_name,
+                                r.passed, r.comment, r.rework_worker_id, u.full_name AS rework_worker_name
+                         FROM qc_inspection_results r
+                         JOIN qc_inspections i ON i.id = r.inspection_id
+                         LEFT JOIN order_works w ON w.id = r.work_id
+                         LEFT JOIN users u ON u.id = r.rework_worker_id
+                         WHERE i.order_id = $1
+                         ORDER BY r.id";
+    let result_rows = sqlx::query(results_query)
+        .bind(order_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut results_by_inspection: BTreeMap<i32, Vec<QcInspectionResult>> = BTreeMap::new();
+    for row in result_rows {
+        results_by_inspection.entry(row.get("inspection_id")).or_default().push(QcInspectionResult {
+            id: row.get("id"),
+            checklist_item_id: row.get("checklist_item_id"),
+            title: row.get("title"),
+            work_id: row.get("work_id"),
+            work_name: row.get("work_name"),
+            passed: row.get("passed"),
+            comment: row.get("comment"),
+            rework_worker_id: row.get("rework_worker_id"),
+            rework_worker_name: row.get("rework_worker_name"),
+        });
+    }
+
+    Ok(rows.iter().map(|row| {
+        let id: i32 = row.get("id");
+        QcInspection {
+            id,
+            order_id: row.get("order_id"),
+            inspector_name: row.get("inspector_name"),
+            result: row.get("result"),
+            comment: row.get("comment"),
+            created_at: row.get("created_at"),
+            results: results_by_inspection.remove(&id).unwrap_or_default(),
+        }
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(item_id: i32, work_id: Option<i32>) -> QcCheck {
+        QcCheck {
+            checklist_item_id: item_id,
+            title: format!("Проверка {}", item_id),
+            work_id,
+            work_name: None,
+            worker_id: None,
+            worker_name: None,
+        }
+    }
+
+    fn result(item_id: Option<i32>, work_id: Option<i32>, passed: bool) -> QcResultInput {
+        QcResultInput {
+            checklist_item_id: item_id,
+            title: "Проверка".to_string(),
+            work_id,
+            passed,
+            comment: None,
+            rework_worker_id: None,
+        }
+    }
+
+    #[test]
+    fn all_checks_answered() {
+        let checklist = vec![check(1, None), check(2, Some(10)), check(2, Some(11))];
+        let results = vec![
+            result(Some(1), None, true),
+            result(Some(2), Some(10), true),
+            result(Some(2), Some(11), false),
+            // Замечание мастера вне чек-листа
+            result(None, Some(10), false),
+        ];
+
+        assert!(missing_checks(&checklist, &results).is_empty());
+    }
+
+    #[test]
+    fn check_for_another_work_does_not_count() {
+        let checklist = vec![check(1, None), check(2, Some(10)), check(2, Some(11))];
+        let results = vec![result(Some(1), None, true), result(Some(2), Some(10), true), result(None, Some(11), true)];
+
+        let missing = missing_checks(&checklist, &results);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!((missing[0].checklist_item_id, missing[0].work_id), (2, Some(11)));
+    }
+}
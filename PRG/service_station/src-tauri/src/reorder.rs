@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+use crate::reservations::RESERVED_QUANTITY_SQL;
+
+// Период, за который считается расход запчастей
+const CONSUMPTION_WINDOW_DAYS: i32 = 90;
+// На сколько дней расхода сверх минимального остатка рассчитывается предлагаемый заказ
+const COVERAGE_DAYS: i32 = 30;
+// Группа для позиций, по которым поставщик ещё ни разу не указывался
+const UNKNOWN_SUPPLIER: &str = "Поставщик не указан";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReorderLine {
+    warehouse_item_id: i32,
+    name: String,
+    brand: Option<String>,
+    article: Option<String>,
+    quantity: i32,
+    reserved: i32,
+    available: i32,
+    on_order: i32, // Заказано у поставщиков и ещё не получено
+    min_quantity: i32,
+    consumed: i32, // Выдано в заказы за период CONSUMPTION_WINDOW_DAYS
+    suggested_quantity: i32,
+    purchase_price: Option<Money>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReorderProposal {
+    supplier_id: Option<i32>, // Поставщик из справочника, если он известен; по нему можно создать заказ поставщику
+    supplier: String,
+    lines: Vec<ReorderLine>,
+}
+
+// Предлагаемое количество: довести свободный остаток с учётом уже заказанного до минимума плюс расход за COVERAGE_DAYS
+fn suggested_quantity(available: i32, on_order: i32, min_quantity: i32, consumed: i32) -> i32 {
+    let expected_consumption = (consumed * COVERAGE_DAYS + CONSUMPTION_WINDOW_DAYS - 1) / CONSUMPTION_WINDOW_DAYS;
+    (min_quantity + expected_consumption - available - on_order).max(1)
+}
+
+// Позиции, свободный остаток которых (с учётом резервов и открытых заказов поставщикам) опустился ниже минимального,
+// сгруппированные по поставщику
+#[tauri::command]
+pub async fn get_reorder_proposals(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<ReorderProposal>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_reorder_proposals").await?;
+
+    // Поставщиком позиции считается поставщик последней приёмки, а если приёмок не было -
+    // поставщик, указанный в последней запчасти заказа по этой позиции
+    // Строки заказов поставщикам под запчасти заказов клиентов не пополняют свободный остаток и не учитываются
+    let query = format!("WITH consumption AS (
+                             SELECT warehouse_item_id, SUM(issued_quantity)::integer AS consumed
+                             FROM order_parts
+                             WHERE warehouse_item_id IS NOT NULL AND issued_at >= NOW() - make_interval(days => $1)
+                             GROUP BY warehouse_item_id
+                         ), last_supplier AS (
+                             SELECT DISTINCT ON (warehouse_item_id) warehouse_item_id, supplier_id, TRIM(supplier) AS supplier
+                             FROM order_parts
+                             WHERE warehouse_item_id IS NOT NULL AND NULLIF(TRIM(supplier), '') IS NOT NULL
+                             ORDER BY warehouse_item_id, id DESC
+                         ), on_order AS (
+                             SELECT l.warehouse_item_id, SUM(l.quantity - l.received_quantity)::integer AS on_order
+                             FROM purchase_order_lines l
+                             JOIN purchase_orders po ON l.purchase_order_id = po.id
+                             WHERE l.warehouse_item_id IS NOT NULL AND l.order_part_id IS NULL
+                               AND po.status IN ('Draft', 'Sent', 'Partially_Received')
+                               AND l.quantity > l.received_quantity
+                             GROUP BY l.warehouse_item_id
+                         )
+                         SELECT * FROM (
+                             SELECT w.id, w.name, w.brand, w.article, COALESCE(w.quantity, 0) AS quantity,
+                                    {reserved} AS reserved, COALESCE(w.min_quantity, 0) AS min_quantity,
+                                    w.purchase_price, COALESCE(c.consumed, 0) AS consumed, COALESCE(oo.on_order, 0) AS on_order,
+                                    sp.id AS supplier_id, COALESCE(sp.name, s.supplier) AS supplier
+                             FROM warehouse w
+                             LEFT JOIN consumption c ON c.warehouse_item_id = w.id
+                             LEFT JOIN last_supplier s ON s.warehouse_item_id = w.id
+                             LEFT JOIN on_order oo ON oo.warehouse_item_id = w.id
+                             LEFT JOIN suppliers sp ON sp.id = COALESCE(w.supplier_id, s.supplier_id)
+                             WHERE COALESCE(w.is_archived, false) = false AND COALESCE(w.min_quantity, 0) > 0
+                         ) items
+                         WHERE quantity - reserved + on_order < min_quantity
+                         ORDER BY supplier NULLS LAST, name",
+                        reserved = RESERVED_QUANTITY_SQL);
+    let rows = sqlx::query(&query)
+        .bind(CONSUMPTION_WINDOW_DAYS)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut proposals: Vec<ReorderProposal> = Vec::new();
+    for row in rows {
+        let supplier_id: Option<i32> = row.get("supplier_id");
+        let supplier: Option<String> = row.get("supplier");
+        let supplier = supplier.unwrap_or_else(|| UNKNOWN_SUPPLIER.to_string());
+
+        let quantity: i32 = row.get("quantity");
+        let reserved: i32 = row.get("reserved");
+        let min_quantity: i32 = row.get("min_quantity");
+        let consumed: i32 = row.get("consumed");
+        let on_order: i32 = row.get("on_order");
+        let available = quantity - reserved;
+
+        let line = ReorderLine {
+            warehouse_item_id: row.get("id"),
+            name: row.get("name"),
+            brand: row.get("brand"),
+            article: row.get("article"),
+            quantity,
+            reserved,
+            available,
+            on_order,
+            min_quantity,
+            consumed,
+            suggested_quantity: suggested_quantity(available, on_order, min_quantity, consumed),
+            purchase_price: row.get("purchase_price"),
+        };
+
+        // Строки отсортированы по поставщику, поэтому достаточно сравнить с последней группой
+        match proposals.last_mut() {
+            Some(proposal) if proposal.supplier == supplier => proposal.lines.push(line),
+            _ => proposals.push(ReorderProposal { supplier_id, supplier, lines: vec![line] }),
+        }
+    }
+
+    Ok(proposals)
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+
+// Зарезервированное количество позиции склада w: по активным резервам учитывается только ещё не выданное
+pub const RESERVED_QUANTITY_SQL: &str = "(SELECT COALESCE(SUM(GREATEST(r.quantity - p.issued_quantity, 0)), 0)::integer
+     FROM stock_reservations r
+     JOIN order_parts p ON r.order_part_id = p.id
+     WHERE r.warehouse_item_id = w.id AND r.released_at IS NULL)";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ItemReservation {
+    id: i32,
+    order_id: i32,
+    order_status: String,
+    order_part_id: i32,
+    quantity: i32,
+    issued_quantity: i32,
+    created_by_name: Option<String>,
+    created_at: String,
+}
+
+// Свободный остаток позиции для запчасти заказа: остаток минус резервы других запчастей.
+// Строка склада блокируется до конца транзакции.
+pub async fn available_for_part(conn: &mut PgConnection, warehouse_item_id: i32, order_part_id: i32) -> Result<i32, String> {
+    let on_hand: i32 = sqlx::query("SELECT COALESCE(quantity, 0) AS quantity FROM warehouse WHERE id = $1 FOR UPDATE")
+        .bind(warehouse_item_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Warehouse item {} not found", warehouse_item_id))?
+        .get("quantity");
+
+    let query = "SELECT COALESCE(SUM(GREATEST(r.quantity - p.issued_quantity, 0)), 0)::integer AS reserved
+                 FROM stock_reservations r
+                 JOIN order_parts p ON r.order_part_id = p.id
+                 WHERE r.warehouse_item_id = $1 AND r.released_at IS NULL AND r.order_part_id <> $2";
+    let reserved: i32 = sqlx::query(query)
+        .bind(warehouse_item_id)
+        .bind(order_part_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .get("reserved");
+
+    Ok(on_hand - reserved)
+}
+
+// Резервирует под запчасть заказа ещё не выданное количество.
+// Запчасти, ещё не связанные со складом (заказанные у поставщика и не поступившие), и уже зарезервированные пропускаются.
+pub async fn reserve_part(conn: &mut PgConnection, order_part_id: i32, user_id: i32) -> Result<(), String> {
+    let query = "SELECT p.warehouse_item_id, COALESCE(p.quantity, 1) AS quantity, p.issued_quantity, p.part_name_snapshot,
+                        EXISTS (SELECT 1 FROM stock_reservations r WHERE r.order_part_id = p.id AND r.released_at IS NULL) AS reserved
+                 FROM order_parts p
+                 WHERE p.id = $1
+                 FOR UPDATE OF p";
+    let row = sqlx::query(query)
+        .bind(order_part_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Order part {} not found", order_part_id))?;
+
+    let warehouse_item_id: Option<i32> = row.get("warehouse_item_id");
+    let reserved: bool = row.get("reserved");
+
+    let warehouse_item_id = match warehouse_item_id {
+        Some(id) if !reserved => id,
+        _ => return Ok(()),
+    };
+
+    let quantity: i32 = row.get("quantity");
+    let issued_quantity: i32 = row.get("issued_quantity");
+    let needed = quantity - issued_quantity;
+    if needed <= 0 {
+        return Ok(());
+    }
+
+    let available = available_for_part(conn, warehouse_item_id, order_part_id).await?;
+    if available < needed {
+        let name: String = row.get("part_name_snapshot");
+        return Err(format!("Недостаточно свободного остатка для запчасти '{}': требуется {} шт., доступно {} шт.",
+                           name, needed, available.max(0)));
+    }
+
+    sqlx::query("INSERT INTO stock_reservations (warehouse_item_id, order_part_id, quantity, created_by) VALUES ($1, $2, $3, $4)")
+        .bind(warehouse_item_id)
+        .bind(order_part_id)
+        .bind(quantity)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error creating reservation: {}", e))?;
+
+    Ok(())
+}
+
+// Снимает резерв с запчасти заказа
+pub async fn release_part(conn: &mut PgConnection, order_part_id: i32, reason: &str) -> Result<u64, String> {
+    let query = "UPDATE stock_reservations SET released_at = NOW(), release_reason = $2 WHERE order_part_id = $1 AND released_at IS NULL";
+    let result = sqlx::query(query)
+        .bind(order_part_id)
+        .bind(reason)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error releasing reservation: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+// Снимает все резервы заказа, возвращает количество снятых резервов
+pub async fn release_order(conn: &mut PgConnection, order_id: i32, reason: &str) -> Result<u64, String> {
+    let query = "UPDATE stock_reservations r SET released_at = NOW(), release_reason = $2
+                 FROM order_parts p
+                 WHERE r.order_part_id = p.id AND p.order_id = $1 AND r.released_at IS NULL";
+    let result = sqlx::query(query)
+        .bind(order_id)
+        .bind(reason)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error releasing reservations: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+#[tauri::command]
+pub async fn get_item_reservations(session_token: String, warehouse_item_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<ItemReservation>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "get_item_reservations").await?;
+
+    let query = "SELECT r.id, p.order_id, o.status::text AS order_status, r.order_part_id, r.quantity, p.issued_quantity,
+                        u.full_name AS created_by_name, r.created_at::text AS created_at
+                 FROM stock_reservations r
+                 JOIN order_parts p ON r.order_part_id = p.id
+                 JOIN orders o ON p.order_id = o.id
+                 LEFT JOIN users u ON r.created_by = u.id
+                 WHERE r.warehouse_item_id = $1 AND r.released_at IS NULL
+                 ORDER BY r.created_at";
+    let rows = sqlx::query(query)
+        .bind(warehouse_item_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut reservations = Vec::new();
+    for row in rows {
+        reservations.push(ItemReservation {
+            id: row.get("id"),
+            order_id: row.get("order_id"),
+            order_status: row.get("order_status"),
+            order_part_id: row.get("order_part_id"),
+            quantity: row.get("quantity"),
+            issued_quantity: row.get("issued_quantity"),
+            created_by_name: row.get("created_by_name"),
+            created_at: row.get("created_at"),
+        });
+    }
+
+    Ok(reservations)
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::User;
+
+// Таймауты сессий по умолчанию (можно переопределить через .env)
+const DEFAULT_IDLE_TIMEOUT_MINUTES: i32 = 60;
+const DEFAULT_ABSOLUTE_TIMEOUT_HOURS: i32 = 12;
+
+fn env_timeout(name: &str, default: i32) -> i32 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse::<i32>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+// Сессия без обращений дольше этого времени считается истёкшей
+fn idle_timeout_minutes() -> i32 {
+    env_timeout("SESSION_IDLE_TIMEOUT_MINUTES", DEFAULT_IDLE_TIMEOUT_MINUTES)
+}
+
+// Максимальное время жизни сессии независимо от активности
+fn absolute_timeout_hours() -> i32 {
+    env_timeout("SESSION_ABSOLUTE_TIMEOUT_HOURS", DEFAULT_ABSOLUTE_TIMEOUT_HOURS)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ActiveSession {
+    id: i32,
+    user_id: i32,
+    user_name: String,
+    role: String,
+    created_at: String,
+    last_seen_at: String,
+    expires_at: String,
+}
+
+// Создаёт новую сессию для пользователя и возвращает её токен
+pub async fn create_session(pool: &PgPool, user_id: i32) -> Result<String, String> {
+    let token = Uuid::new_v4().to_string();
+
+    let query = "INSERT INTO sessions (token, user_id, expires_at) VALUES ($1, $2, NOW() + make_interval(hours => $3))";
+    sqlx::query(query)
+        .bind(&token)
+        .bind(user_id)
+        .bind(absolute_timeout_hours())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Database error creating session: {}", e))?;
+
+    Ok(token)
+}
+
+// Возвращает пользователя по токену, если сессия действительна, и продлевает её по активности
+pub async fn get_session_user(pool: &PgPool, token: &str) -> Result<Option<User>, String> {
+    let query = "UPDATE sessions s SET last_seen_at = NOW()
+                 FROM users u
+                 WHERE s.token = $1
+                   AND s.user_id = u.id
+                   AND s.revoked_at IS NULL
+                   AND s.expires_at > NOW()
+                   AND s.last_seen_at > NOW() - make_interval(mins => $2)
+                   AND u.status = 'Active'
+                 RETURNING u.id, u.full_name, u.role::text AS role, u.login, u.password_hash, u.pin_code, u.status::text AS status";
+    let row = sqlx::query(query)
+        .bind(token)
+        .bind(idle_timeout_minutes())
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error reading session: {}", e))?;
+
+    Ok(row.map(|row| User {
+        id: row.get("id"),
+        full_name: row.get("full_name"),
+        role: row.get("role"),
+        login: row.get("login"),
+        password_hash: row.get("password_hash"),
+        pin_code: row.get("pin_code"),
+        status: row.get("status"),
+    }))
+}
+
+// То же, что get_session_user, но отсутствие действующей сессии считается ошибкой
+pub async fn require_user(pool: &PgPool, token: &str) -> Result<User, String> {
+    get_session_user(pool, token)
+        .await?
+        .ok_or_else(|| "Invalid session token".to_string())
+}
+
+// Отзывает одну сессию (выход пользователя)
+pub async fn revoke_session(pool: &PgPool, token: &str) -> Result<Option<i32>, String> {
+    let query = "UPDATE sessions SET revoked_at = NOW() WHERE token = $1 AND revoked_at IS NULL RETURNING user_id";
+    let row = sqlx::query(query)
+        .bind(token)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error revoking session: {}", e))?;
+
+    Ok(row.map(|row| row.get("user_id")))
+}
+
+// Отзывает все действующие сессии пользователя, возвращает их количество
+pub async fn revoke_user_sessions(pool: &PgPool, user_id: i32, revoked_by: Option<i32>) -> Result<u64, String> {
+    let query = "UPDATE sessions SET revoked_at = NOW(), revoked_by = $2 WHERE user_id = $1 AND revoked_at IS NULL";
+    let result = sqlx::query(query)
+        .bind(user_id)
+        .bind(revoked_by)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Database error revoking sessions: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
+#[tauri::command]
+pub async fn get_active_sessions(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<ActiveSession>, String> {
+    auth::authorize(&state, &session_token, Permission::ManageUsers, "get_active_sessions").await?;
+
+    let query = "SELECT s.id, s.user_id, u.full_name, u.role::text AS role, s.created_at::text AS created_at,
+                        s.last_seen_at::text AS last_seen_at, s.expires_at::text AS expires_at
+                 FROM sessions s
+                 JOIN users u ON s.user_id = u.id
+                 WHERE s.revoked_at IS NULL
+                   AND s.expires_at > NOW()
+                   AND s.last_seen_at > NOW() - make_interval(mins => $1)
+                 ORDER BY s.last_seen_at DESC";
+    let rows = sqlx::query(query)
+        .bind(idle_timeout_minutes())
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        sessions.push(ActiveSession {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            user_name: row.get("full_name"),
+            role: row.get("role"),
+            created_at: row.get("created_at"),
+            last_seen_at: row.get("last_seen_at"),
+            expires_at: row.get("expires_at"),
+        });
+    }
+
+    Ok(sessions)
+}
+
+#[tauri::command]
+pub async fn force_logout_user(session_token: String, user_id: i32, state: tauri::State<'_, Database>) -> Result<String, String> {
+    let admin = auth::authorize(&state, &session_token, Permission::ManageUsers, "force_logout_user").await?;
+
+    let revoked = revoke_user_sessions(&state.pool, user_id, Some(admin.id)).await?;
+
+    // Логируем принудительное завершение сессий
+    let log_result = crate::log_event(
+        Some(admin.id),
+        "Force_Logout".to_string(),
+        format!("Принудительно завершены сессии пользователя с ID {} (сессий: {})", user_id, revoked),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging forced logout: {}", e);
+    }
+
+    Ok(format!("Завершено сессий пользователя {}: {}", user_id, revoked))
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+use sqlx::{PgExecutor, PgPool, Row};
+
+// Папка для документов, если она не указана ни в настройках, ни в DOCUMENTS_DIR
+const DEFAULT_DOCUMENTS_DIR: &str = "documents";
+
+// Значения по умолчанию: используются, пока администратор не сохранил настройки
+fn default_settings() -> Value {
+    serde_json::json!({
+        "company_name": "ООО 'АвтоСервис Про'",
+        "legal_name": "",
+        "tax_id": "",
+        "address": "г. Минск, ул. Ленина, 1",
+        "phone": "+375 () ___-__-__",
+        "bank_details": "",
+        "diagnostics_cost": 500,
+        "vat_rate": 20,
+        "prices_include_vat": true,
+        "documents_dir": "",
+        "pdf_font_path": "",
+        "work_schedule": {
+            "mon_to_fri": "09:00 - 18:00",
+            "saturday": "10:00 - 15:00",
+            "sunday": "Выходной"
+        }
+    })
+}
+
+// Сохранённые настройки поверх значений по умолчанию (новые поля появляются и в старых записях)
+pub async fn load<'e, E: PgExecutor<'e>>(executor: E) -> Result<Value, String> {
+    let row = sqlx::query("SELECT settings::text AS settings FROM system_settings WHERE id = 1")
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut settings = default_settings();
+    if let Some(row) = row {
+        let saved: Value = serde_json::from_str(&row.get::<String, _>("settings"))
+            .map_err(|e| format!("Invalid stored settings: {}", e))?;
+        if let (Some(target), Value::Object(saved)) = (settings.as_object_mut(), saved) {
+            for (key, value) in saved {
+                target.insert(key, value);
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+pub async fn save(pool: &PgPool, settings: &str, user_id: i32) -> Result<(), String> {
+    let settings: Map<String, Value> = serde_json::from_str(settings)
+        .map_err(|e| format!("Invalid settings JSON: {}", e))?;
+
+    sqlx::query(
+        "INSERT INTO system_settings (id, settings, updated_at, updated_by) VALUES (1, $1::jsonb, CURRENT_TIMESTAMP, $2)
+         ON CONFLICT (id) DO UPDATE SET settings = EXCLUDED.settings, updated_at = EXCLUDED.updated_at, updated_by = EXCLUDED.updated_by"
+    )
+        .bind(Value::Object(settings).to_string())
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+// Строковое значение настройки; пустая строка считается незаполненной
+pub fn text(settings: &Value, key: &str) -> Option<String> {
+    settings.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+// Папка для документов: настройки, затем переменная окружения DOCUMENTS_DIR
+pub fn documents_dir(settings: &Value) -> PathBuf {
+    text(settings, "documents_dir")
+        .or_else(|| std::env::var("DOCUMENTS_DIR").ok().filter(|s| !s.trim().is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DOCUMENTS_DIR))
+}
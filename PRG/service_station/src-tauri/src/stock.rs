@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use std::fmt;
+
+use crate::auth::{self, Permission};
+use crate::database::Database;
+use crate::money::Money;
+
+// Типы движений по складу (соответствует ENUM stock_movement_type в БД)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementType {
+    Receipt,
+    Issue,
+    Return,
+    #[serde(rename = "Write_Off")]
+    WriteOff,
+    Correction,
+}
+
+impl MovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementType::Receipt => "Receipt",
+            MovementType::Issue => "Issue",
+            MovementType::Return => "Return",
+            MovementType::WriteOff => "Write_Off",
+            MovementType::Correction => "Correction",
+        }
+    }
+}
+
+impl fmt::Display for MovementType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Движение, которое нужно провести по складу
+pub struct NewMovement<'a> {
+    pub warehouse_item_id: i32,
+    pub movement_type: MovementType,
+    pub quantity: i32,             // Со знаком: приход положительный, расход отрицательный
+    pub unit_cost: Option<Money>,  // Если не указана, берётся закупочная цена позиции
+    pub user_id: Option<i32>,
+    pub order_part_id: Option<i32>,
+    pub reason: Option<&'a str>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StockMovement {
+    id: i32,
+    movement_type: String,
+    quantity: i32,
+    balance_after: i32,
+    unit_cost: Option<Money>,
+    user_id: Option<i32>,
+    user_name: Option<String>,
+    order_part_id: Option<i32>,
+    order_id: Option<i32>,
+    reason: Option<String>,
+    created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StockDiscrepancy {
+    warehouse_item_id: i32,
+    name: String,
+    quantity: i32,
+    ledger_quantity: i32,
+}
+
+// Проводит движение по складу: меняет остаток позиции и записывает движение в журнал.
+// Все изменения warehouse.quantity должны выполняться только через эту функцию,
+// в той же транзакции, что и связанные с ними изменения заказа.
+pub async fn record_movement(conn: &mut PgConnection, movement: &NewMovement<'_>) -> Result<i32, String> {
+    if movement.quantity == 0 {
+        return Err("Количество в движении по складу не может быть нулевым".to_string());
+    }
+
+    let row = sqlx::query("UPDATE warehouse SET quantity = COALESCE(quantity, 0) + $2 WHERE id = $1 RETURNING quantity, name")
+        .bind(movement.warehouse_item_id)
+        .bind(movement.quantity)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error updating warehouse quantity: {}", e))?
+        .ok_or(format!("Warehouse item {} not found", movement.warehouse_item_id))?;
+
+    let balance_after: i32 = row.get("quantity");
+    if balance_after < 0 {
+        let name: String = row.get("name");
+        return Err(format!("Недостаточно остатка позиции '{}' на складе: не хватает {} шт.", name, -balance_after));
+    }
+
+    let query = "INSERT INTO stock_movements (warehouse_item_id, movement_type, quantity, balance_after, unit_cost, user_id, order_part_id, reason)
+                 VALUES ($1, $2::stock_movement_type, $3, $4,
+                         COALESCE($5, (SELECT purchase_price FROM warehouse WHERE id = $1)), $6, $7, $8)";
+    sqlx::query(query)
+        .bind(movement.warehouse_item_id)
+        .bind(movement.movement_type.as_str())
+        .bind(movement.quantity)
+        .bind(balance_after)
+        .bind(movement.unit_cost)
+        .bind(movement.user_id)
+        .bind(movement.order_part_id)
+        .bind(movement.reason)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error recording stock movement: {}", e))?;
+
+    Ok(balance_after)
+}
+
+#[tauri::command]
+pub async fn get_stock_movements(session_token: String, warehouse_item_id: i32, state: tauri::State<'_, Database>) -> Result<Vec<StockMovement>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_stock_movements").await?;
+
+    let query = "SELECT m.id, m.movement_type::text AS movement_type, m.quantity, m.balance_after, m.unit_cost,
+                        m.user_id, u.full_name AS user_name, m.order_part_id, op.order_id, m.reason, m.created_at::text AS created_at
+                 FROM stock_movements m
+                 LEFT JOIN users u ON m.user_id = u.id
+                 LEFT JOIN order_parts op ON m.order_part_id = op.id
+                 WHERE m.warehouse_item_id = $1
+                 ORDER BY m.created_at, m.id";
+    let rows = sqlx::query(query)
+        .bind(warehouse_item_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut movements = Vec::new();
+    for row in rows {
+        movements.push(StockMovement {
+            id: row.get("id"),
+            movement_type: row.get("movement_type"),
+            quantity: row.get("quantity"),
+            balance_after: row.get("balance_after"),
+            unit_cost: row.get("unit_cost"),
+            user_id: row.get("user_id"),
+            user_name: row.get("user_name"),
+            order_part_id: row.get("order_part_id"),
+            order_id: row.get("order_id"),
+            reason: row.get("reason"),
+            created_at: row.get("created_at"),
+        });
+    }
+
+    Ok(movements)
+}
+
+// Позиции, у которых остаток не совпадает с суммой движений по журналу
+#[tauri::command]
+pub async fn get_stock_discrepancies(session_token: String, state: tauri::State<'_, Database>) -> Result<Vec<StockDiscrepancy>, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ManageWarehouse, "get_stock_discrepancies").await?;
+
+    let query = "SELECT w.id, w.name, COALESCE(w.quantity, 0) AS quantity, COALESCE(SUM(m.quantity), 0)::integer AS ledger_quantity
+                 FROM warehouse w
+                 LEFT JOIN stock_movements m ON m.warehouse_item_id = w.id
+                 GROUP BY w.id, w.name, w.quantity
+                 HAVING COALESCE(w.quantity, 0) <> COALESCE(SUM(m.quantity), 0)
+                 ORDER BY w.name";
+    let rows = sqlx::query(query)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut discrepancies = Vec::new();
+    for row in rows {
+        discrepancies.push(StockDiscrepancy {
+            warehouse_item_id: row.get("id"),
+            name: row.get("name"),
+            quantity: row.get("quantity"),
+            ledger_quantity: row.get("ledger_quantity"),
+        });
+    }
+
+    Ok(discrepancies)
+}
+
+// Выравнивает журнал по фактическим остаткам: для каждого расхождения записывается корректирующее движение.
+// Используется и для ввода начальных остатков позиций, созданных до появления журнала.
+#[tauri::command]
+pub async fn reconcile_stock_ledger(session_token: String, state: tauri::State<'_, Database>) -> Result<String, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "reconcile_stock_ledger").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Блокируем склад, чтобы между подсчётом и записью корректировок не прошли другие движения
+    sqlx::query("LOCK TABLE warehouse IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let query = "INSERT INTO stock_movements (warehouse_item_id, movement_type, quantity, balance_after, unit_cost, user_id, reason)
+                 SELECT w.id, 'Correction', COALESCE(w.quantity, 0) - COALESCE(l.quantity, 0), COALESCE(w.quantity, 0),
+                        w.purchase_price, $1, 'Сверка остатка с журналом движений'
+                 FROM warehouse w
+                 LEFT JOIN (SELECT warehouse_item_id, SUM(quantity)::integer AS quantity
+                            FROM stock_movements GROUP BY warehouse_item_id) l ON l.warehouse_item_id = w.id
+                 WHERE COALESCE(w.quantity, 0) <> COALESCE(l.quantity, 0)";
+    let result = sqlx::query(query)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Логируем сверку журнала
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Reconcile_Stock_Ledger".to_string(),
+        format!("Выполнена сверка журнала движений по складу, записано корректировок: {}", result.rows_affected()),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging stock ledger reconciliation: {}", e);
+    }
+
+    Ok(format!("Записано корректировок: {}", result.rows_affected()))
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder, Row};
+
+use crate::auth::{self, Permission};
+use crate::database::{escape_like, Database};
+use crate::money::Money;
+use crate::reservations::RESERVED_QUANTITY_SQL;
+use crate::stock::{self, MovementType, NewMovement};
+
+// Размер страницы по умолчанию и максимальный размер страницы списка склада
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WarehouseItem {
+    id: i32,
+    name: String,
+    brand: Option<String>,
+    article: Option<String>,
+    location_cell: Option<String>,
+    quantity: i32,  // Остаток на складе
+    reserved: i32,  // Зарезервировано под подтверждённые запчасти заказов
+    available: i32, // Свободный остаток
+    min_quantity: i32,
+    purchase_price: Option<Money>,
+    selling_price: Option<Money>,
+    is_archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WarehouseItemsPage {
+    items: Vec<WarehouseItem>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+}
+
+// Фильтры списка склада. Пустые строки считаются отсутствием фильтра.
+#[derive(Deserialize, Default)]
+pub struct WarehouseFilter {
+    // Поиск сразу по названию, бренду, артикулу и ячейке
+    #[serde(default)]
+    search: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    brand: Option<String>,
+    #[serde(default)]
+    article: Option<String>,
+    #[serde(default, rename = "locationCell")]
+    location_cell: Option<String>,
+    // Только позиции, свободный остаток которых (с учётом резервов) ниже минимального
+    #[serde(default, rename = "belowMin")]
+    below_min: bool,
+    #[serde(default, rename = "includeArchived")]
+    include_archived: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateWarehouseItemRequest {
+    #[serde(rename = "locationCell")]
+    location_cell: Option<String>,
+    #[serde(rename = "minQuantity")]
+    min_quantity: i32,
+    #[serde(rename = "purchasePrice")]
+    purchase_price: Option<Money>,
+    #[serde(rename = "sellingPrice")]
+    selling_price: Option<Money>,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn push_like(builder: &mut QueryBuilder<'_, Postgres>, column: &str, value: &str) {
+    builder.push(format!(" AND {} ILIKE ", column));
+    builder.push_bind(format!("%{}%", escape_like(value)));
+}
+
+// Добавляет условия фильтра к запросу, уже содержащему WHERE
+fn push_warehouse_filters(builder: &mut QueryBuilder<'_, Postgres>, filter: &WarehouseFilter) {
+    if !filter.include_archived {
+        builder.push(" AND w.is_archived = false");
+    }
+
+    if let Some(search) = non_empty(&filter.search) {
+        let pattern = format!("%{}%", escape_like(search));
+        builder.push(" AND (w.name ILIKE ");
+        builder.push_bind(pattern.clone());
+        builder.push(" OR w.brand ILIKE ");
+        builder.push_bind(pattern.clone());
+        builder.push(" OR w.article ILIKE ");
+        builder.push_bind(pattern.clone());
+        builder.push(" OR w.location_cell ILIKE ");
+        builder.push_bind(pattern);
+        builder.push(")");
+    }
+
+    if let Some(name) = non_empty(&filter.name) {
+        push_like(builder, "w.name", name);
+    }
+    if let Some(brand) = non_empty(&filter.brand) {
+        push_like(builder, "w.brand", brand);
+    }
+    if let Some(article) = non_empty(&filter.article) {
+        push_like(builder, "w.article", article);
+    }
+    if let Some(location_cell) = non_empty(&filter.location_cell) {
+        push_like(builder, "w.location_cell", location_cell);
+    }
+
+    if filter.below_min {
+        builder.push(format!(" AND COALESCE(w.quantity, 0) - {} < COALESCE(w.min_quantity, 0)", RESERVED_QUANTITY_SQL));
+    }
+}
+
+// Колонки позиции склада; запрос должен обращаться к таблице warehouse под псевдонимом w
+fn warehouse_item_columns() -> String {
+    format!("w.id, w.name, w.brand, w.article, w.location_cell, COALESCE(w.quantity, 0) AS quantity,
+             {reserved} AS reserved, COALESCE(w.quantity, 0) - {reserved} AS available,
+             COALESCE(w.min_quantity, 0) AS min_quantity, w.purchase_price,
+             w.selling_price, COALESCE(w.is_archived, false) AS is_archived",
+            reserved = RESERVED_QUANTITY_SQL)
+}
+
+fn warehouse_item_from_row(row: &sqlx::postgres::PgRow) -> WarehouseItem {
+    WarehouseItem {
+        id: row.get("id"),
+        name: row.get("name"),
+        brand: row.get("brand"),
+        article: row.get("article"),
+        location_cell: row.get("location_cell"),
+        quantity: row.get("quantity"),
+        reserved: row.get("reserved"),
+        available: row.get("available"),
+        min_quantity: row.get("min_quantity"),
+        purchase_price: row.get("purchase_price"),
+        selling_price: row.get("selling_price"),
+        is_archived: row.get("is_archived"),
+    }
+}
+
+async fn load_warehouse_item<'e, E>(executor: E, item_id: i32) -> Result<WarehouseItem, String>
+where
+    E: sqlx::Executor<'e, Database = Postgres>,
+{
+    let query = format!("SELECT {} FROM warehouse w WHERE w.id = $1", warehouse_item_columns());
+    let row = sqlx::query(&query)
+        .bind(item_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Warehouse item {} not found", item_id))?;
+
+    Ok(warehouse_item_from_row(&row))
+}
+
+#[tauri::command]
+pub async fn get_warehouse_items(
+    session_token: String,
+    filter: Option<WarehouseFilter>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+    state: tauri::State<'_, Database>
+) -> Result<WarehouseItemsPage, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::SelectParts, "get_warehouse_items").await?;
+
+    let filter = filter.unwrap_or_default();
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) AS total FROM warehouse w WHERE 1 = 1");
+    push_warehouse_filters(&mut count_builder, &filter);
+    let total: i64 = count_builder
+        .build()
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .get("total");
+
+    let mut query_builder = QueryBuilder::new(format!("SELECT {} FROM warehouse w WHERE 1 = 1", warehouse_item_columns()));
+    push_warehouse_filters(&mut query_builder, &filter);
+    query_builder.push(" ORDER BY w.name, w.id LIMIT ");
+    query_builder.push_bind(page_size);
+    query_builder.push(" OFFSET ");
+    query_builder.push_bind((page - 1) * page_size);
+
+    let rows = query_builder
+        .build()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(WarehouseItemsPage {
+        items: rows.iter().map(warehouse_item_from_row).collect(),
+        total,
+        page,
+        page_size,
+    })
+}
+
+#[tauri::command]
+pub async fn update_warehouse_item(
+    session_token: String,
+    item_id: i32,
+    request: UpdateWarehouseItemRequest,
+    state: tauri::State<'_, Database>
+) -> Result<WarehouseItem, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "update_warehouse_item").await?;
+
+    if request.min_quantity < 0 {
+        return Err("Минимальный остаток не может быть отрицательным".to_string());
+    }
+
+    let before = load_warehouse_item(&state.pool, item_id).await?;
+
+    let location_cell = non_empty(&request.location_cell).map(str::to_string);
+    let query = "UPDATE warehouse SET location_cell = $2, min_quantity = $3, purchase_price = $4, selling_price = $5 WHERE id = $1";
+    sqlx::query(query)
+        .bind(item_id)
+        .bind(&location_cell)
+        .bind(request.min_quantity)
+        .bind(request.purchase_price)
+        .bind(request.selling_price)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let after = load_warehouse_item(&state.pool, item_id).await?;
+
+    // Логируем изменение карточки позиции склада
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Update_Warehouse_Item".to_string(),
+        format!("Изменена позиция склада {} '{}': ячейка {:?} -> {:?}, мин. остаток {} -> {}, закупочная цена {:?} -> {:?}, цена продажи {:?} -> {:?}",
+                item_id, after.name, before.location_cell, after.location_cell, before.min_quantity, after.min_quantity,
+                before.purchase_price, after.purchase_price, before.selling_price, after.selling_price),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging warehouse item update: {}", e);
+    }
+
+    Ok(after)
+}
+
+#[tauri::command]
+pub async fn adjust_warehouse_quantity(
+    session_token: String,
+    item_id: i32,
+    new_quantity: i32,
+    reason: String,
+    state: tauri::State<'_, Database>
+) -> Result<WarehouseItem, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "adjust_warehouse_quantity").await?;
+
+    if new_quantity < 0 {
+        return Err("Остаток не может быть отрицательным".to_string());
+    }
+
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err("Необходимо указать причину изменения остатка".to_string());
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    // Блокируем позицию, чтобы старое значение в журнале соответствовало действительности
+    let old_quantity: i32 = sqlx::query("SELECT COALESCE(quantity, 0) AS quantity FROM warehouse WHERE id = $1 FOR UPDATE")
+        .bind(item_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Warehouse item {} not found", item_id))?
+        .get("quantity");
+
+    if old_quantity == new_quantity {
+        return Err("Остаток позиции не изменился".to_string());
+    }
+
+    // Изменение проводится как корректировка по журналу движений
+    stock::record_movement(&mut tx, &NewMovement {
+        warehouse_item_id: item_id,
+        movement_type: MovementType::Correction,
+        quantity: new_quantity - old_quantity,
+        unit_cost: None,
+        user_id: Some(user.id),
+        order_part_id: None,
+        reason: Some(&reason),
+    }).await?;
+
+    let item = load_warehouse_item(&mut *tx, item_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    // Каждое изменение остатка записывается в журнал событий
+    let log_result = crate::log_event(
+        Some(user.id),
+        "Warehouse_Quantity_Change".to_string(),
+        format!("Изменён остаток позиции склада {} '{}': {} -> {} (причина: {})",
+                item_id, item.name, old_quantity, new_quantity, reason),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging warehouse quantity change: {}", e);
+    }
+
+    Ok(item)
+}
+
+#[tauri::command]
+pub async fn set_warehouse_item_archived(
+    session_token: String,
+    item_id: i32,
+    archived: bool,
+    state: tauri::State<'_, Database>
+) -> Result<WarehouseItem, String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ManageWarehouse, "set_warehouse_item_archived").await?;
+
+    // Позиции не удаляются, так как на них ссылаются запчасти заказов
+    let query = "UPDATE warehouse SET is_archived = $2, archived_at = CASE WHEN $2 THEN NOW() ELSE NULL END WHERE id = $1";
+    let result = sqlx::query(query)
+        .bind(item_id)
+        .bind(archived)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Warehouse item {} not found", item_id));
+    }
+
+    let item = load_warehouse_item(&state.pool, item_id).await?;
+
+    // Логируем перевод позиции в архив или возврат из архива
+    let log_result = crate::log_event(
+        Some(user.id),
+        if archived { "Archive_Warehouse_Item" } else { "Restore_Warehouse_Item" }.to_string(),
+        format!("Позиция склада {} '{}' {} (остаток: {})",
+                item_id, item.name, if archived { "переведена в архив" } else { "возвращена из архива" }, item.quantity),
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging warehouse item archival: {}", e);
+    }
+
+    Ok(item)
+}
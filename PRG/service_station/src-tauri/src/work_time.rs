@@ -0,0 +1,358 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::auth::{self, Permission, UserRole};
+use crate::database::Database;
+use crate::order_status::OrderStatus;
+
+// Статусы работы (соответствует ENUM work_status в БД)
+const STATUS_PENDING: &str = "Pending";
+const STATUS_IN_PROGRESS: &str = "In_Progress";
+const STATUS_PAUSED: &str = "Paused";
+const STATUS_DONE: &str = "Done";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkTimeInterval {
+    id: i32,
+    work_id: i32,
+    worker_id: i32,
+    worker_name: Option<String>,
+    started_at: String,
+    finished_at: Option<String>, // null - интервал ещё идёт
+    duration_seconds: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorkTime {
+    work_id: i32,
+    status: String,
+    total_seconds: i64, // Сумма всех интервалов, включая текущий
+    intervals: Vec<WorkTimeInterval>,
+}
+
+// Работа, заблокированная до конца транзакции вместе с данными заказа
+struct LockedWork {
+    order_id: i32,
+    name: String,
+    status: String,
+    is_confirmed: bool,
+    performer_id: Option<i32>, // Исполнитель работы, по умолчанию - основной слесарь заказа
+    order_status: String,
+}
+
+async fn lock_work(conn: &mut PgConnection, work_id: i32) -> Result<LockedWork, String> {
+    let query = "SELECT w.order_id, COALESCE(w.service_name_snapshot, '') AS name, w.status::text AS status,
+                        COALESCE(w.is_confirmed, false) AS is_confirmed,
+                        COALESCE(w.worker_id, o.worker_id) AS performer_id, o.status::text AS order_status
+                 FROM order_works w
+                 JOIN orders o ON o.id = w.order_id
+                 WHERE w.id = $1
+                 FOR UPDATE OF w";
+    let row = sqlx::query(query)
+        .bind(work_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Work {} not found", work_id))?;
+
+    Ok(LockedWork {
+        order_id: row.get("order_id"),
+        name: row.get("name"),
+        status: row.get("status"),
+        is_confirmed: row.get("is_confirmed"),
+        performer_id: row.get("performer_id"),
+        order_status: row.get("order_status"),
+    })
+}
+
+// Проверяет, что работу можно вести сейчас, и возвращает ID слесаря, на которого пишется время
+async fn check_work(
+    state: &tauri::State<'_, Database>,
+    user: &crate::User,
+    work_id: i32,
+    work: &LockedWork,
+    command: &str,
+    allowed_statuses: &[&str],
+) -> Result<i32, String> {
+    let performer_id = work.performer_id.ok_or(format!("Работа '{}' не назначена слесарю", work.name))?;
+
+    // Слесарь ведёт только свои работы, администратор может отметить время за исполнителя
+    if user.role == UserRole::Worker.as_str() && user.id != performer_id {
+        return Err(auth::deny(state, user, command, "работа другого работника").await);
+    }
+
+    if work.order_status != OrderStatus::InWork.as_str() {
+        return Err(format!("Заказ {} не находится в работе (статус '{}')", work.order_id, work.order_status));
+    }
+
+    if !work.is_confirmed {
+        return Err(format!("Работа '{}' не согласована с клиентом", work.name));
+    }
+
+    if !allowed_statuses.contains(&work.status.as_str()) {
+        return Err(format!("Работа {} '{}' в статусе '{}': действие недоступно", work_id, work.name, work.status));
+    }
+
+    Ok(performer_id)
+}
+
+// Слесарь не может вести две работы одновременно; параллельные запуски дополнительно
+// отсекает уникальный индекс work_time_intervals_running_worker_idx
+async fn check_worker_free(conn: &mut PgConnection, worker_id: i32) -> Result<(), String> {
+    let query = "SELECT w.id, w.order_id, COALESCE(w.service_name_snapshot, '') AS name
+                 FROM work_time_intervals t
+                 JOIN order_works w ON w.id = t.work_id
+                 WHERE t.worker_id = $1 AND t.finished_at IS NULL";
+    let running = sqlx::query(query)
+        .bind(worker_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    match running {
+        Some(row) => {
+            let name: String = row.get("name");
+            let order_id: i32 = row.get("order_id");
+            Err(format!("Слесарь уже выполняет работу '{}' по заказу {}. Приостановите или завершите её", name, order_id))
+        }
+        None => Ok(()),
+    }
+}
+
+async fn open_interval(conn: &mut PgConnection, work_id: i32, worker_id: i32) -> Result<(), String> {
+    sqlx::query("INSERT INTO work_time_intervals (work_id, worker_id, started_at) VALUES ($1, $2, NOW())")
+        .bind(work_id)
+        .bind(worker_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+async fn close_interval(conn: &mut PgConnection, work_id: i32) -> Result<(), String> {
+    sqlx::query("UPDATE work_time_intervals SET finished_at = NOW() WHERE work_id = $1 AND finished_at IS NULL")
+        .bind(work_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+async fn set_status(conn: &mut PgConnection, work_id: i32, status: &str) -> Result<(), String> {
+    // Первый запуск фиксирует начало работы и исполнителя, завершение - время окончания.
+    // Первое завершение и его исполнитель запоминаются отдельно: по ним работа оплачивается, в том числе после переделки
+    let query = "UPDATE order_works
+                 SET status = $2::work_status,
+                     started_at = COALESCE(started_at, NOW()),
+                     finished_at = CASE WHEN $2 = 'Done' THEN NOW() ELSE finished_at END,
+                     first_finished_at = CASE WHEN $2 = 'Done' THEN COALESCE(first_finished_at, NOW()) ELSE first_finished_at END,
+                     first_worker_id = CASE WHEN $2 = 'Done' AND first_finished_at IS NULL THEN worker_id ELSE first_worker_id END
+                 WHERE id = $1";
+    sqlx::query(query)
+        .bind(work_id)
+        .bind(status)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+// Закрывает открытые интервалы по заказу, например при его отмене
+pub async fn stop_order_timers(conn: &mut PgConnection, order_id: i32) -> Result<(), String> {
+    let query = "UPDATE work_time_intervals SET finished_at = NOW()
+                 WHERE finished_at IS NULL AND work_id IN (SELECT id FROM order_works WHERE order_id = $1)";
+    sqlx::query(query)
+        .bind(order_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+// Заказ нельзя передавать дальше, пока по нему идёт учёт времени
+pub async fn check_no_running_works(conn: &mut PgConnection, order_id: i32) -> Result<(), String> {
+    let query = "SELECT COALESCE(w.service_name_snapshot, '') AS name
+                 FROM work_time_intervals t
+                 JOIN order_works w ON w.id = t.work_id
+                 WHERE w.order_id = $1 AND t.finished_at IS NULL
+                 LIMIT 1";
+    let running = sqlx::query(query)
+        .bind(order_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    match running {
+        Some(row) => {
+            let name: String = row.get("name");
+            Err(format!("По заказу {} ещё выполняется работа '{}'. Приостановите или завершите её", order_id, name))
+        }
+        None => Ok(()),
+    }
+}
+
+async fn log_work_event(state: &tauri::State<'_, Database>, user_id: i32, event_type: &str, details: String) {
+    let log_result = crate::log_event(
+        Some(user_id),
+        event_type.to_string(),
+        details,
+        None, // IP-адрес пока не реализован
+        state.clone()
+    ).await;
+
+    if let Err(e) = log_result {
+        eprintln!("Error logging work time event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_work_time(session_token: String, work_id: i32, state: tauri::State<'_, Database>) -> Result<WorkTime, String> {
+    // Проверяем права доступа
+    auth::authorize(&state, &session_token, Permission::ExecuteWorks, "get_work_time").await?;
+
+    let status_row = sqlx::query("SELECT status::text AS status FROM order_works WHERE id = $1")
+        .bind(work_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or(format!("Work {} not found", work_id))?;
+
+    let query = "SELECT t.id, t.work_id, t.worker_id, u.full_name AS worker_name,
+                        t.started_at::text AS started_at, t.finished_at::text AS finished_at,
+                        EXTRACT(EPOCH FROM COALESCE(t.finished_at, LOCALTIMESTAMP) - t.started_at)::bigint AS duration_seconds
+                 FROM work_time_intervals t
+                 LEFT JOIN users u ON u.id = t.worker_id
+                 WHERE t.work_id = $1
+                 ORDER BY t.started_at, t.id";
+    let rows = sqlx::query(query)
+        .bind(work_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let intervals: Vec<WorkTimeInterval> = rows.iter().map(|row| WorkTimeInterval {
+        id: row.get("id"),
+        work_id: row.get("work_id"),
+        worker_id: row.get("worker_id"),
+        worker_name: row.get("worker_name"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+        duration_seconds: row.get("duration_seconds"),
+    }).collect();
+
+    Ok(WorkTime {
+        work_id,
+        status: status_row.get("status"),
+        total_seconds: intervals.iter().map(|i| i.duration_seconds).sum(),
+        intervals,
+    })
+}
+
+// Начало работы: Pending -> In_Progress, открывается первый интервал
+#[tauri::command]
+pub async fn start_work(session_token: String, work_id: i32, state: tauri::State<'_, Database>) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "start_work").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let work = lock_work(&mut tx, work_id).await?;
+    let worker_id = check_work(&state, &user, work_id, &work, "start_work", &[STATUS_PENDING]).await?;
+    check_worker_free(&mut tx, worker_id).await?;
+
+    // Работа, выполняемая основным слесарем заказа, закрепляется за ним
+    sqlx::query("UPDATE order_works SET worker_id = $2 WHERE id = $1 AND worker_id IS NULL")
+        .bind(work_id)
+        .bind(worker_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    set_status(&mut tx, work_id, STATUS_IN_PROGRESS).await?;
+    open_interval(&mut tx, work_id, worker_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    log_work_event(&state, user.id, "Start_Work",
+                   format!("Начата работа {} '{}' по заказу {} (слесарь {})", work_id, work.name, work.order_id, worker_id)).await;
+
+    Ok(())
+}
+
+// Приостановка: In_Progress -> Paused, текущий интервал закрывается
+#[tauri::command]
+pub async fn pause_work(session_token: String, work_id: i32, state: tauri::State<'_, Database>) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "pause_work").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let work = lock_work(&mut tx, work_id).await?;
+    let worker_id = check_work(&state, &user, work_id, &work, "pause_work", &[STATUS_IN_PROGRESS]).await?;
+
+    close_interval(&mut tx, work_id).await?;
+    set_status(&mut tx, work_id, STATUS_PAUSED).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    log_work_event(&state, user.id, "Pause_Work",
+                   format!("Приостановлена работа {} '{}' по заказу {} (слесарь {})", work_id, work.name, work.order_id, worker_id)).await;
+
+    Ok(())
+}
+
+// Возобновление: Paused -> In_Progress, открывается новый интервал
+#[tauri::command]
+pub async fn resume_work(session_token: String, work_id: i32, state: tauri::State<'_, Database>) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "resume_work").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let work = lock_work(&mut tx, work_id).await?;
+    let worker_id = check_work(&state, &user, work_id, &work, "resume_work", &[STATUS_PAUSED]).await?;
+    check_worker_free(&mut tx, worker_id).await?;
+
+    set_status(&mut tx, work_id, STATUS_IN_PROGRESS).await?;
+    open_interval(&mut tx, work_id, worker_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    log_work_event(&state, user.id, "Resume_Work",
+                   format!("Возобновлена работа {} '{}' по заказу {} (слесарь {})", work_id, work.name, work.order_id, worker_id)).await;
+
+    Ok(())
+}
+
+// Завершение: In_Progress или Paused -> Done, открытый интервал закрывается
+#[tauri::command]
+pub async fn finish_work(session_token: String, work_id: i32, state: tauri::State<'_, Database>) -> Result<(), String> {
+    // Получаем информацию о пользователе из сессии и проверяем права доступа
+    let user = auth::authorize(&state, &session_token, Permission::ExecuteWorks, "finish_work").await?;
+
+    let mut tx = state.pool.begin().await.map_err(|e| format!("Database transaction error: {}", e))?;
+
+    let work = lock_work(&mut tx, work_id).await?;
+    let worker_id = check_work(&state, &user, work_id, &work, "finish_work", &[STATUS_IN_PROGRESS, STATUS_PAUSED]).await?;
+
+    close_interval(&mut tx, work_id).await?;
+    set_status(&mut tx, work_id, STATUS_DONE).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database transaction commit error: {}", e))?;
+
+    log_work_event(&state, user.id, "Finish_Work",
+                   format!("Завершена работа {} '{}' по заказу {} (слесарь {})", work_id, work.name, work.order_id, worker_id)).await;
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './AddPartToWarehouseModal.css';
+import { moneyInput } from '../types/money';
 
 interface AddPartToWarehouseModalProps {
   isOpen: boolean;
@@ -15,8 +16,8 @@ interface NewPart {
   location_cell: string;
   quantity: number;
   min_quantity: number;
-  purchase_price: number;
-  selling_price: number;
+  purchase_price: string; // Цены хранятся строкой из поля ввода, точность проверяет бэкенд
+  selling_price: string;
 }
 
 const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpen, onClose, onPartAdded }) => {
@@ -27,8 +28,8 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
     location_cell: '',
     quantity: 1,
     min_quantity: 2,
-    purchase_price: 0,
-    selling_price: 0
+    purchase_price: '0',
+    selling_price: '0'
   });
   const [loading, setLoading] = useState(false);
 
@@ -40,7 +41,7 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
       return;
     }
 
-    if (newPart.quantity < 0 || newPart.min_quantity < 0 || newPart.purchase_price < 0 || newPart.selling_price < 0) {
+    if (newPart.quantity < 0 || newPart.min_quantity < 0 || parseFloat(newPart.purchase_price) < 0 || parseFloat(newPart.selling_price) < 0) {
       alert('Значения не могут быть отрицательными');
       return;
     }
@@ -65,8 +66,8 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
           locationCell: newPart.location_cell,
           quantity: newPart.quantity,
           minQuantity: newPart.min_quantity,
-          purchasePrice: newPart.purchase_price,
-          sellingPrice: newPart.selling_price
+          purchasePrice: moneyInput(newPart.purchase_price),
+          sellingPrice: moneyInput(newPart.selling_price)
         }
       });
 
@@ -81,8 +82,8 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
         location_cell: '',
         quantity: 1,
         min_quantity: 2,
-        purchase_price: 0,
-        selling_price: 0
+        purchase_price: '0',
+        selling_price: '0'
       });
       onClose();
 
@@ -187,7 +188,7 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
                 type="number"
                 step="0.01"
                 value={newPart.purchase_price}
-                onChange={(e) => setNewPart({...newPart, purchase_price: parseFloat(e.target.value) || 0})}
+                onChange={(e) => setNewPart({...newPart, purchase_price: e.target.value})}
                 min="0"
                 required
               />
@@ -200,7 +201,7 @@ const AddPartToWarehouseModal: React.FC<AddPartToWarehouseModalProps> = ({ isOpe
                 type="number"
                 step="0.01"
                 value={newPart.selling_price}
-                onChange={(e) => setNewPart({...newPart, selling_price: parseFloat(e.target.value) || 0})}
+                onChange={(e) => setNewPart({...newPart, selling_price: e.target.value})}
                 min="0"
                 required
               />
@@ -1,6 +1,7 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './AddWarehouseItemModal.css';
+import { moneyInput } from '../types/money';
 
 interface AddWarehouseItemModalProps {
   isOpen: boolean;
@@ -16,8 +17,8 @@ const AddWarehouseItemModal: React.FC<AddWarehouseItemModalProps> = ({ isOpen, o
     location_cell: '',
     quantity: 1,
     min_quantity: 2,
-    purchase_price: 0,
-    selling_price: 0
+    purchase_price: '0',
+    selling_price: '0'
   });
   const [loading, setLoading] = useState(false);
 
@@ -25,7 +26,7 @@ const AddWarehouseItemModal: React.FC<AddWarehouseItemModalProps> = ({ isOpen, o
     const { name, value } = e.target;
     setFormData(prev => ({
       ...prev,
-      [name]: name.includes('price') || name.includes('quantity') ? parseFloat(value) || 0 : value
+      [name]: name.includes('quantity') ? parseFloat(value) || 0 : value
     }));
   };
 
@@ -49,8 +50,8 @@ const AddWarehouseItemModal: React.FC<AddWarehouseItemModalProps> = ({ isOpen, o
         locationCell: formData.location_cell,
         quantity: formData.quantity,
         minQuantity: formData.min_quantity,
-        purchasePrice: formData.purchase_price,
-        sellingPrice: formData.selling_price
+        purchasePrice: moneyInput(formData.purchase_price),
+        sellingPrice: moneyInput(formData.selling_price)
       });
 
       alert('Новая позиция успешно добавлена на склад!');
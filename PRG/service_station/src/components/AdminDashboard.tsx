@@ -5,6 +5,8 @@ import { User } from '../types/user';
 import './AdminDashboard.css';
 import ServicesReferenceModal from './ServicesReferenceModal';
 import ReportGenerator from './ReportGenerator';
+import PayrollStatement from './PayrollStatement';
+import QcChecklistManager from './QcChecklistManager';
 
 interface AdminDashboardProps {
   user: User;
@@ -12,7 +14,7 @@ interface AdminDashboardProps {
 }
 
 const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
-  const [activeTab, setActiveTab] = useState<'users' | 'settings' | 'logs' | 'reports' | 'services'>('users');
+  const [activeTab, setActiveTab] = useState<'users' | 'settings' | 'logs' | 'reports' | 'payroll' | 'services' | 'qc'>('users');
   const [users, setUsers] = useState<User[]>([]);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
@@ -41,14 +43,14 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
         setError(null);
 
         if (activeTab === 'users') {
-          const response: User[] = await invoke('get_all_users');
+          const response: User[] = await invoke('get_all_users', { sessionToken: localStorage.getItem('sessionToken') });
           setUsers(response);
         } else if (activeTab === 'settings') {
-          const settings: string = await invoke('get_system_settings');
+          const settings: string = await invoke('get_system_settings', { sessionToken: localStorage.getItem('sessionToken') });
           setSystemSettings(JSON.parse(settings));
         } else if (activeTab === 'logs') {
           setLogsLoading(true);
-          const logsData: string = await invoke('get_system_logs', { filters: '{}' });
+          const logsData: string = await invoke('get_system_logs', { sessionToken: localStorage.getItem('sessionToken'), filters: '{}' });
           setLogs(JSON.parse(logsData));
           setLogsLoading(false);
         }
@@ -106,7 +108,7 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
           userId
         });
         // Обновляем список пользователей
-        const response: User[] = await invoke('get_all_users');
+        const response: User[] = await invoke('get_all_users', { sessionToken: localStorage.getItem('sessionToken') });
         setUsers(response);
       } catch (err) {
         console.error('Error deleting user:', err);
@@ -144,7 +146,7 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
       }
 
       // Обновляем список пользователей
-      const response: User[] = await invoke('get_all_users');
+      const response: User[] = await invoke('get_all_users', { sessionToken: localStorage.getItem('sessionToken') });
       setUsers(response);
       setShowUserForm(false);
     } catch (err) {
@@ -200,12 +202,24 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
             >
               Генерация отчетов
             </button>
+            <button
+              className={activeTab === 'payroll' ? 'active' : ''}
+              onClick={() => setActiveTab('payroll')}
+            >
+              Заработная плата
+            </button>
             <button
               className={activeTab === 'services' ? 'active' : ''}
               onClick={() => setActiveTab('services')}
             >
               Справочник услуг
             </button>
+            <button
+              className={activeTab === 'qc' ? 'active' : ''}
+              onClick={() => setActiveTab('qc')}
+            >
+              Чек-лист контроля качества
+            </button>
 
             <div className="role-preview">
               <h3>Просмотр ролей:</h3>
@@ -283,6 +297,24 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                     />
                   </div>
 
+                  <div className="form-group">
+                    <label>Юридическое наименование:</label>
+                    <input
+                      type="text"
+                      defaultValue={systemSettings.legal_name}
+                      id="company-legal-name"
+                    />
+                  </div>
+
+                  <div className="form-group">
+                    <label>УНП:</label>
+                    <input
+                      type="text"
+                      defaultValue={systemSettings.tax_id}
+                      id="company-tax-id"
+                    />
+                  </div>
+
                   <div className="form-group">
                     <label>Адрес:</label>
                     <input
@@ -310,6 +342,54 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                     /> $
                   </div>
 
+                  <div className="form-group">
+                    <label>Банковские реквизиты:</label>
+                    <input
+                      type="text"
+                      defaultValue={systemSettings.bank_details}
+                      id="company-bank-details"
+                    />
+                  </div>
+
+                  <h3>Налоги</h3>
+                  <div className="form-group">
+                    <label>Ставка НДС, %:</label>
+                    <input
+                      type="number"
+                      defaultValue={systemSettings.vat_rate}
+                      id="vat-rate"
+                    />
+                  </div>
+
+                  <div className="form-group">
+                    <label>
+                      <input
+                        type="checkbox"
+                        defaultChecked={systemSettings.prices_include_vat}
+                        id="prices-include-vat"
+                      /> Цены указаны с НДС
+                    </label>
+                  </div>
+
+                  <h3>Документы</h3>
+                  <div className="form-group">
+                    <label>Папка для документов:</label>
+                    <input
+                      type="text"
+                      defaultValue={systemSettings.documents_dir}
+                      id="documents-dir"
+                    />
+                  </div>
+
+                  <div className="form-group">
+                    <label>Шрифт для PDF (путь к TTF-файлу):</label>
+                    <input
+                      type="text"
+                      defaultValue={systemSettings.pdf_font_path}
+                      id="pdf-font-path"
+                    />
+                  </div>
+
                   <h3>График работы</h3>
                   <div className="schedule-container">
                     <div className="form-group">
@@ -347,7 +427,15 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                         try {
                           // Собираем данные из формы
                           const updatedSettings = {
+                            ...systemSettings,
                             company_name: (document.getElementById('company-name') as HTMLInputElement).value,
+                            legal_name: (document.getElementById('company-legal-name') as HTMLInputElement).value,
+                            tax_id: (document.getElementById('company-tax-id') as HTMLInputElement).value,
+                            bank_details: (document.getElementById('company-bank-details') as HTMLInputElement).value,
+                            documents_dir: (document.getElementById('documents-dir') as HTMLInputElement).value,
+                            pdf_font_path: (document.getElementById('pdf-font-path') as HTMLInputElement).value,
+                            vat_rate: parseFloat((document.getElementById('vat-rate') as HTMLInputElement).value) || 0,
+                            prices_include_vat: (document.getElementById('prices-include-vat') as HTMLInputElement).checked,
                             address: (document.getElementById('company-address') as HTMLInputElement).value,
                             phone: (document.getElementById('company-phone') as HTMLInputElement).value,
                             diagnostics_cost: (document.getElementById('diagnostics-cost') as HTMLInputElement).value,
@@ -359,6 +447,7 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                           };
 
                           await invoke('save_system_settings', {
+                            sessionToken: localStorage.getItem('sessionToken'),
                             settings: JSON.stringify(updatedSettings)
                           });
                           alert('Настройки успешно сохранены');
@@ -404,6 +493,7 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                         search: (document.getElementById('log-search') as HTMLInputElement).value
                       };
                       const logsData: string = await invoke('get_system_logs', {
+                        sessionToken: localStorage.getItem('sessionToken'),
                         filters: JSON.stringify(filters)
                       });
                       setLogs(JSON.parse(logsData));
@@ -422,6 +512,7 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
                         search: (document.getElementById('log-search') as HTMLInputElement).value
                       };
                       const logsData: string = await invoke('get_system_logs', {
+                        sessionToken: localStorage.getItem('sessionToken'),
                         filters: JSON.stringify(filters)
                       });
                       const logsJson = JSON.parse(logsData);
@@ -488,6 +579,13 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
             </div>
           )}
 
+          {activeTab === 'payroll' && (
+            <div className="reports-section">
+              <h2>Заработная плата</h2>
+              <PayrollStatement />
+            </div>
+          )}
+
           {activeTab === 'services' && (
             <div className="services-section">
               <h2>Справочник услуг</h2>
@@ -497,6 +595,13 @@ const AdminDashboard: React.FC<AdminDashboardProps> = ({ user, onLogout }) => {
               />
             </div>
           )}
+
+          {activeTab === 'qc' && (
+            <div className="reports-section">
+              <h2>Чек-лист контроля качества</h2>
+              <QcChecklistManager />
+            </div>
+          )}
         </div>
       </div>
     </div>
@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './ArchivedOrderModal.css';
+import { Money, formatMoney, multiplyMoney } from '../types/money';
 
 interface Order {
   id: number;
@@ -11,7 +12,7 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  total_amount: string | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -33,7 +34,7 @@ interface Car {
 interface Work {
   id: number;
   service_name_snapshot: string;
-  price: string;
+  price: Money;
   status: string;
 }
 
@@ -42,7 +43,7 @@ interface Part {
   part_name_snapshot: string;
   brand: string;
   quantity: number;
-  price_per_unit: string;
+  price_per_unit: Money;
 }
 
 interface ArchivedOrderModalProps {
@@ -75,29 +76,33 @@ const ArchivedOrderModal: React.FC<ArchivedOrderModalProps> = ({
     setLoading(true);
     try {
       // Загружаем данные заказа
-      const orderData = await invoke<Order>('get_order_by_id', { orderId });
+      const orderData = await invoke<Order>('get_order_by_id', { sessionToken: localStorage.getItem('sessionToken'), orderId });
       setOrder(orderData);
 
       // Загружаем данные клиента
       const clientData = await invoke<Client>('get_client_by_id', { 
+        sessionToken: localStorage.getItem('sessionToken'),
         clientId: orderData.client_id 
       });
       setClient(clientData);
 
       // Загружаем данные автомобиля
       const carData = await invoke<Car>('get_car_by_id', { 
+        sessionToken: localStorage.getItem('sessionToken'),
         carId: orderData.car_id 
       });
       setCar(carData);
 
       // Загружаем выполненные работы
       const worksData = await invoke<Work[]>('get_order_works_by_order_id', { 
+        sessionToken: localStorage.getItem('sessionToken'),
         orderId 
       });
       setWorks(worksData.filter(work => work.status === 'Done'));
 
       // Загружаем использованные запчасти
       const partsData = await invoke<Part[]>('get_order_parts_by_order_id', { 
+        sessionToken: localStorage.getItem('sessionToken'),
         orderId 
       });
       setParts(partsData);
@@ -114,15 +119,6 @@ const ArchivedOrderModal: React.FC<ArchivedOrderModalProps> = ({
     return new Date(dateString).toLocaleDateString('ru-RU');
   };
 
-  const calculateWorksTotal = () => {
-    return works.reduce((sum, work) => sum + parseFloat(work.price), 0);
-  };
-
-  const calculatePartsTotal = () => {
-    return parts.reduce((sum, part) => 
-      sum + (parseFloat(part.price_per_unit) * part.quantity), 0
-    );
-  };
 
   if (!isOpen) return null;
 
@@ -174,7 +170,7 @@ const ArchivedOrderModal: React.FC<ArchivedOrderModalProps> = ({
                         <div key={work.id} className="item-row">
                           <span className="item-name">{work.service_name_snapshot}</span>
                           <span className="item-price">
-                            {parseFloat(work.price).toFixed(2)} $
+                            {formatMoney(work.price)}
                           </span>
                         </div>
                       ))}
@@ -192,7 +188,7 @@ const ArchivedOrderModal: React.FC<ArchivedOrderModalProps> = ({
                             {part.part_name_snapshot} ({part.brand}) x{part.quantity}
                           </span>
                           <span className="item-price">
-                            {(parseFloat(part.price_per_unit) * part.quantity).toFixed(2)} $
+                            {formatMoney(multiplyMoney(part.price_per_unit, part.quantity))}
                           </span>
                         </div>
                       ))}
@@ -217,7 +213,7 @@ const ArchivedOrderModal: React.FC<ArchivedOrderModalProps> = ({
         <div className="modal-footer">
           {order && (
             <div className="total-amount">
-              ИТОГО ОПЛАЧЕНО: {order.total_amount ? `${parseFloat(order.total_amount).toFixed(2)} $` : '0.00 $'}
+              ИТОГО ОПЛАЧЕНО: {formatMoney(order.total_amount, '0.00')}
             </div>
           )}
         </div>
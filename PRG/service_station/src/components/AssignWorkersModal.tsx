@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './AssignWorkersModal.css';
+import { Money, formatMoney } from '../types/money';
 
 interface Worker {
   id: number;
@@ -18,8 +19,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -29,7 +30,7 @@ interface Work {
   order_id: number;
   service_id?: number;
   service_name_snapshot: string;
-  price: string; // Changed to string for DECIMAL compatibility
+  price: Money;
   worker_id?: number | null;
   status: string;
   is_confirmed: boolean;
@@ -70,7 +71,7 @@ const AssignWorkersModal: React.FC<AssignWorkersModalProps> = ({
   const loadWorkers = async () => {
     setLoading(true);
     try {
-      const workersData = await invoke<Worker[]>('get_available_workers');
+      const workersData = await invoke<Worker[]>('get_available_workers', { sessionToken: localStorage.getItem('sessionToken') });
       setWorkers(workersData);
     } catch (error) {
       console.error('Error loading workers:', error);
@@ -177,7 +178,7 @@ const AssignWorkersModal: React.FC<AssignWorkersModalProps> = ({
                   <ul>
                     {works.filter(work => work.is_confirmed).map(work => (
                       <li key={work.id}>
-                        {work.service_name_snapshot} - {parseFloat(work.price || '0').toFixed(2)} $
+                        {work.service_name_snapshot} - {formatMoney(work.price)}
                       </li>
                     ))}
                   </ul>
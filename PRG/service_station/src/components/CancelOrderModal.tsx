@@ -1,6 +1,7 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './CancelOrderModal.css';
+import { Money } from '../types/money';
 
 interface Order {
   id: number;
@@ -11,8 +12,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -30,6 +31,15 @@ interface Car {
   license_plate: string | null;
 }
 
+// Коды причин отмены (соответствуют ENUM cancel_reason в БД)
+const CANCEL_REASONS: { value: string; label: string }[] = [
+  { value: 'Client_Refused', label: 'Клиент отказался от ремонта' },
+  { value: 'Too_Expensive', label: 'Клиента не устроила стоимость' },
+  { value: 'Parts_Unavailable', label: 'Нет возможности получить запчасти' },
+  { value: 'Duplicate', label: 'Заказ создан ошибочно или повторно' },
+  { value: 'Other', label: 'Другое' }
+];
+
 interface CancelOrderModalProps {
   isOpen: boolean;
   order: Order | null;
@@ -48,21 +58,29 @@ const CancelOrderModal: React.FC<CancelOrderModalProps> = ({
   onCancelled
 }) => {
   const [cancelReason, setCancelReason] = useState('');
+  const [cancelComment, setCancelComment] = useState('');
   const [isProcessing, setIsProcessing] = useState(false);
 
   if (!isOpen || !order) return null;
 
   const handleConfirmCancel = async () => {
-    if (!cancelReason.trim()) {
+    if (!cancelReason) {
       alert('Пожалуйста, укажите причину отмены');
       return;
     }
 
+    if (cancelReason === 'Other' && !cancelComment.trim()) {
+      alert('Пожалуйста, опишите причину отмены в комментарии');
+      return;
+    }
+
     setIsProcessing(true);
     try {
       await invoke('cancel_order', {
+        sessionToken: localStorage.getItem('sessionToken'),
         orderId: order.id,
-        reason: cancelReason.trim()
+        reason: cancelReason,
+        comment: cancelComment.trim() || null
       });
 
       alert('Заказ успешно отменен');
@@ -109,11 +127,24 @@ const CancelOrderModal: React.FC<CancelOrderModalProps> = ({
 
           <div className="cancel-reason-section">
             <label htmlFor="cancelReason">ПРИЧИНА ОТМЕНЫ:</label>
-            <textarea
+            <select
               id="cancelReason"
               value={cancelReason}
               onChange={(e) => setCancelReason(e.target.value)}
-              placeholder="Укажите причину отмены заказа..."
+              disabled={isProcessing}
+            >
+              <option value="">-- Выберите причину --</option>
+              {CANCEL_REASONS.map(r => (
+                <option key={r.value} value={r.value}>{r.label}</option>
+              ))}
+            </select>
+
+            <label htmlFor="cancelComment">КОММЕНТАРИЙ:</label>
+            <textarea
+              id="cancelComment"
+              value={cancelComment}
+              onChange={(e) => setCancelComment(e.target.value)}
+              placeholder="Опишите подробности отмены заказа..."
               rows={4}
               disabled={isProcessing}
             />
@@ -131,7 +162,7 @@ const CancelOrderModal: React.FC<CancelOrderModalProps> = ({
           <button
             className="confirm-cancel-btn"
             onClick={handleConfirmCancel}
-            disabled={isProcessing || !cancelReason.trim()}
+            disabled={isProcessing || !cancelReason || (cancelReason === 'Other' && !cancelComment.trim())}
           >
             {isProcessing ? 'ОБРАБОТКА...' : '❌ ПОДТВЕРДИТЬ ОТМЕНУ'}
           </button>
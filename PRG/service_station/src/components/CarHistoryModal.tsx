@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './CarHistoryModal.css';
+import { Money, formatMoney, sumMoney } from '../types/money';
 
 interface Car {
   id: number;
@@ -10,14 +11,40 @@ interface Car {
   vin: string | null;
 }
 
+interface HistoryDefect {
+  id: number;
+  defect_description: string;
+  diagnostician_comment: string | null;
+}
+
+interface HistoryWork {
+  id: number;
+  service_name_snapshot: string;
+  price: Money;
+  status: string;
+}
+
+interface HistoryPart {
+  id: number;
+  part_name_snapshot: string;
+  brand: string;
+  price_per_unit: Money;
+  quantity: number;
+}
+
 interface HistoryOrder {
   id: number;
   created_at: string;
   completed_at: string | null;
   current_mileage: number | null;
   complaint: string | null;
-  total_amount: string | null;
+  total_amount: Money | null;
   status: string;
+  defects: HistoryDefect[];
+  works: HistoryWork[];
+  parts: HistoryPart[];
+  works_total: Money;
+  parts_total: Money;
 }
 
 interface CarHistoryModalProps {
@@ -35,6 +62,7 @@ const CarHistoryModal: React.FC<CarHistoryModalProps> = ({
 }) => {
   const [historyOrders, setHistoryOrders] = useState<HistoryOrder[]>([]);
   const [loading, setLoading] = useState(false);
+  const [expandedOrderId, setExpandedOrderId] = useState<number | null>(null);
 
   useEffect(() => {
     if (isOpen && car) {
@@ -48,6 +76,7 @@ const CarHistoryModal: React.FC<CarHistoryModalProps> = ({
     setLoading(true);
     try {
       const history = await invoke<HistoryOrder[]>('get_car_service_history', {
+        sessionToken: localStorage.getItem('sessionToken'),
         carId: car.id
       });
       setHistoryOrders(history);
@@ -73,8 +102,17 @@ const CarHistoryModal: React.FC<CarHistoryModalProps> = ({
     }
   };
 
+  const handleRowClick = (orderId: number) => {
+    setExpandedOrderId(expandedOrderId === orderId ? null : orderId);
+    if (onOrderSelect) onOrderSelect(orderId);
+  };
+
   if (!isOpen || !car) return null;
 
+  // Общие суммы по всем визитам
+  const totalWorks = sumMoney(historyOrders.map(o => o.works_total));
+  const totalParts = sumMoney(historyOrders.map(o => o.parts_total));
+
   return (
     <div className="modal-overlay" onClick={onClose}>
       <div className="modal-content" onClick={(e) => e.stopPropagation()}>
@@ -112,37 +150,85 @@ const CarHistoryModal: React.FC<CarHistoryModalProps> = ({
                 </thead>
                 <tbody>
                   {historyOrders.map((order) => (
-                    <tr
-                      key={order.id}
-                      className="history-row"
-                      onClick={() => onOrderSelect && onOrderSelect(order.id)}
-                    >
-                      <td>{order.id}</td>
-                      <td>{formatDate(order.created_at)}</td>
-                      <td>
-                        {order.current_mileage 
-                          ? `${order.current_mileage.toLocaleString()} км`
-                          : '—'
-                        }
-                      </td>
-                      <td className="complaint-cell">
-                        {order.complaint || 'Не указана'}
-                      </td>
-                      <td>
-                        <span className="status-badge">
-                          {getStatusEmoji(order.status)} {order.status}
-                        </span>
-                      </td>
-                      <td className="amount-cell">
-                        {order.total_amount 
-                          ? `${parseFloat(order.total_amount).toFixed(2)} $`
-                          : '—'
-                        }
-                      </td>
-                    </tr>
+                    <React.Fragment key={order.id}>
+                      <tr
+                        className="history-row"
+                        onClick={() => handleRowClick(order.id)}
+                      >
+                        <td>{order.id}</td>
+                        <td>{formatDate(order.created_at)}</td>
+                        <td>
+                          {order.current_mileage 
+                            ? `${order.current_mileage.toLocaleString()} км`
+                            : '—'
+                          }
+                        </td>
+                        <td className="complaint-cell">
+                          {order.complaint || 'Не указана'}
+                        </td>
+                        <td>
+                          <span className="status-badge">
+                            {getStatusEmoji(order.status)} {order.status}
+                          </span>
+                        </td>
+                        <td className="amount-cell">
+                          {formatMoney(order.total_amount)}
+                        </td>
+                      </tr>
+                      {expandedOrderId === order.id && (
+                        <tr className="history-details-row">
+                          <td colSpan={6}>
+                            <div className="history-details">
+                              <div className="history-section">
+                                <h4>Неисправности</h4>
+                                {order.defects.length > 0 ? (
+                                  <ul>
+                                    {order.defects.map(defect => (
+                                      <li key={defect.id}>
+                                        {defect.defect_description}
+                                        {defect.diagnostician_comment && ` — ${defect.diagnostician_comment}`}
+                                      </li>
+                                    ))}
+                                  </ul>
+                                ) : <p>Нет подтверждённых неисправностей</p>}
+                              </div>
+                              <div className="history-section">
+                                <h4>Работы ({formatMoney(order.works_total)})</h4>
+                                {order.works.length > 0 ? (
+                                  <ul>
+                                    {order.works.map(work => (
+                                      <li key={work.id}>
+                                        {work.service_name_snapshot} — {formatMoney(work.price)} ({work.status})
+                                      </li>
+                                    ))}
+                                  </ul>
+                                ) : <p>Работы не выполнялись</p>}
+                              </div>
+                              <div className="history-section">
+                                <h4>Запчасти ({formatMoney(order.parts_total)})</h4>
+                                {order.parts.length > 0 ? (
+                                  <ul>
+                                    {order.parts.map(part => (
+                                      <li key={part.id}>
+                                        {part.part_name_snapshot} {part.brand && `(${part.brand})`} — {part.quantity} × {formatMoney(part.price_per_unit)}
+                                      </li>
+                                    ))}
+                                  </ul>
+                                ) : <p>Запчасти не устанавливались</p>}
+                              </div>
+                            </div>
+                          </td>
+                        </tr>
+                      )}
+                    </React.Fragment>
                   ))}
                 </tbody>
               </table>
+              <div className="history-totals">
+                <span>Визитов: {historyOrders.length}</span>
+                <span>Работы: {formatMoney(totalWorks)}</span>
+                <span>Запчасти: {formatMoney(totalParts)}</span>
+              </div>
             </div>
           ) : (
             <div className="no-history">
@@ -1,15 +1,17 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './ClientApprovalModal.css';
+import { Money, formatMoney, moneyValue, multiplyMoney } from '../types/money';
 
 interface Work {
   id: number;
   service_id?: number;
   service_name_snapshot: string;
-  price: string; // Changed to string for DECIMAL compatibility
+  price: Money;
   worker_id?: number | null;
   status: string;
   is_confirmed: boolean;
+  discount_percent: string;
 }
 
 interface Part {
@@ -17,9 +19,21 @@ interface Part {
   warehouse_item_id?: number | null;
   part_name_snapshot: string;
   brand: string;
-  price_per_unit: string; // Changed to string for DECIMAL compatibility
+  price_per_unit: Money;
   quantity: number;
   is_confirmed: boolean;
+  discount_percent: string;
+}
+
+// Итоги заказа, рассчитанные на стороне Rust
+interface OrderTotals {
+  subtotal: Money;
+  discount_percent: string;
+  discount_amount: Money;
+  vat_rate: string;
+  prices_include_vat: boolean;
+  vat_amount: Money;
+  total_amount: Money;
 }
 
 interface Defect {
@@ -29,10 +43,21 @@ interface Defect {
   is_confirmed: boolean;
 }
 
+// Рекомендуемая или дополнительная работа по неисправности из справочника связей
+interface WorkSuggestion {
+  defect_id: number;
+  defect_description: string;
+  link_id: number;
+  service_name: string;
+  role: 'Recommended' | 'Optional';
+  price: Money;
+  parts: { article: string; part_name: string | null; quantity: number }[];
+}
+
 interface Order {
   id: number;
   complaint: string | null;
-  total_amount: string | null;
+  total_amount: Money | null;
 }
 
 interface ClientApprovalModalProps {
@@ -62,8 +87,10 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
 }) => {
   const [localWorks, setLocalWorks] = useState<Work[]>([]);
   const [localParts, setLocalParts] = useState<Part[]>([]);
-  const [totalAmount, setTotalAmount] = useState<number>(0);
+  const [totals, setTotals] = useState<OrderTotals | null>(null);
+  const [orderDiscount, setOrderDiscount] = useState('0');
   const [isProcessing, setIsProcessing] = useState(false);
+  const [suggestions, setSuggestions] = useState<WorkSuggestion[]>([]);
 
   useEffect(() => {
     // Initialize local state with the incoming data
@@ -72,16 +99,102 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
   }, [works, parts]);
 
   useEffect(() => {
-    // Calculate the total amount when works or parts change
-    const total = localWorks
-      .filter(work => work.is_confirmed)
-      .reduce((sum, work) => sum + parseFloat(work.price || '0'), 0) +
-      localParts
-        .filter(part => part.is_confirmed)
-        .reduce((sum, part) => sum + (parseFloat(part.price_per_unit || '0') * part.quantity), 0);
+    if (isOpen) {
+      loadTotals();
+    }
+  }, [isOpen, localWorks, localParts]);
 
-    setTotalAmount(total);
-  }, [localWorks, localParts]);
+  useEffect(() => {
+    if (isOpen) {
+      loadSuggestions();
+    }
+  }, [isOpen, order.id]);
+
+  const loadSuggestions = async () => {
+    try {
+      const result = await invoke<WorkSuggestion[]>('get_order_work_suggestions', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id
+      });
+      setSuggestions(result);
+    } catch (error) {
+      console.error('Error loading work suggestions:', error);
+    }
+  };
+
+  // Добавленная работа и её запчасти подгружаются в список, отметки у остальных строк сохраняются
+  const handleAddSuggestion = async (suggestion: WorkSuggestion) => {
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+      await invoke('add_suggested_work', {
+        sessionToken,
+        orderId: order.id,
+        defectId: suggestion.defect_id,
+        linkId: suggestion.link_id
+      });
+      const [worksData, partsData] = await Promise.all([
+        invoke<Work[]>('get_order_works_by_order_id', { sessionToken, orderId: order.id }),
+        invoke<Part[]>('get_order_parts_by_order_id', { sessionToken, orderId: order.id })
+      ]);
+      setLocalWorks(prev => worksData.map(work => prev.find(w => w.id === work.id) || { ...work, is_confirmed: true }));
+      setLocalParts(prev => partsData.map(part => prev.find(p => p.id === part.id) || { ...part, is_confirmed: true }));
+      loadSuggestions();
+    } catch (error) {
+      console.error('Error adding suggested work:', error);
+      alert('Ошибка при добавлении работы: ' + error);
+    }
+  };
+
+  // Итог для отмеченных строк считает бэкенд (скидки, НДС)
+  const loadTotals = async () => {
+    try {
+      const result = await invoke<OrderTotals>('preview_order_totals', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        works: localWorks.filter(work => work.is_confirmed).map(work => work.id),
+        parts: localParts.filter(part => part.is_confirmed).map(part => part.id)
+      });
+      setTotals(result);
+      setOrderDiscount(result.discount_percent);
+    } catch (error) {
+      console.error('Error calculating order totals:', error);
+      setTotals(null);
+    }
+  };
+
+  const handleLineDiscount = async (lineType: 'work' | 'part', lineId: number, value: string) => {
+    try {
+      await invoke('set_order_line_discount', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        lineType,
+        lineId,
+        discountPercent: value.trim() || '0'
+      });
+      if (lineType === 'work') {
+        setLocalWorks(prev => prev.map(work => work.id === lineId ? { ...work, discount_percent: value || '0' } : work));
+      } else {
+        setLocalParts(prev => prev.map(part => part.id === lineId ? { ...part, discount_percent: value || '0' } : part));
+      }
+    } catch (error) {
+      console.error('Error setting line discount:', error);
+      alert('Ошибка при установке скидки: ' + error);
+    }
+  };
+
+  const handleOrderDiscount = async () => {
+    try {
+      await invoke('set_order_discount', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        discountPercent: orderDiscount.trim() || '0'
+      });
+      loadTotals();
+    } catch (error) {
+      console.error('Error setting order discount:', error);
+      alert('Ошибка при установке скидки: ' + error);
+    }
+  };
 
   const toggleWorkConfirmed = (id: number) => {
     setLocalWorks(prevWorks =>
@@ -113,6 +226,7 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
 
       // Вызываем команду подтверждения в Rust
       const result = await invoke('confirm_order_parts_and_works', {
+        sessionToken: localStorage.getItem('sessionToken'),
         orderId: order.id,
         confirmedWorks: confirmedWorkIds,
         confirmedParts: confirmedPartIds
@@ -172,7 +286,36 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
                       {work.service_name_snapshot}
                     </div>
                     <div className="work-price">
-                      {parseFloat(work.price || '0').toFixed(2)} $
+                      {formatMoney(work.price)}
+                      <input
+                        type="number"
+                        className="discount-input"
+                        min="0"
+                        max="100"
+                        step="0.01"
+                        defaultValue={work.discount_percent}
+                        onBlur={(e) => e.target.value !== work.discount_percent && handleLineDiscount('work', work.id, e.target.value)}
+                        title="Скидка, %"
+                      /> %
+                    </div>
+                  </div>
+                ))}
+              </div>
+            )}
+
+            {suggestions.length > 0 && (
+              <div className="works-group">
+                <h4>💡 ПРЕДЛОЖЕНИЯ ПО НЕИСПРАВНОСТЯМ:</h4>
+                {suggestions.map(suggestion => (
+                  <div key={`${suggestion.defect_id}-${suggestion.link_id}`} className="work-item">
+                    <div>
+                      {suggestion.service_name}
+                      {' '}({suggestion.role === 'Recommended' ? 'рекомендуется' : 'дополнительно'}: {suggestion.defect_description})
+                      {suggestion.parts.length > 0 && ` + ${suggestion.parts.map(part => `${part.part_name || part.article} x${part.quantity}`).join(', ')}`}
+                    </div>
+                    <div className="work-price">
+                      {formatMoney(suggestion.price)}
+                      <button type="button" onClick={() => handleAddSuggestion(suggestion)}>➕</button>
                     </div>
                   </div>
                 ))}
@@ -193,7 +336,17 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
                       {part.part_name_snapshot} ({part.brand}) x{part.quantity}
                     </div>
                     <div className="part-price">
-                      {(parseFloat(part.price_per_unit || '0') * part.quantity).toFixed(2)} $
+                      {formatMoney(multiplyMoney(part.price_per_unit, part.quantity))}
+                      <input
+                        type="number"
+                        className="discount-input"
+                        min="0"
+                        max="100"
+                        step="0.01"
+                        defaultValue={part.discount_percent}
+                        onBlur={(e) => e.target.value !== part.discount_percent && handleLineDiscount('part', part.id, e.target.value)}
+                        title="Скидка, %"
+                      /> %
                     </div>
                   </div>
                 ))}
@@ -203,8 +356,29 @@ const ClientApprovalModal: React.FC<ClientApprovalModalProps> = ({
         </div>
 
         <div className="modal-footer">
+          <div className="order-discount">
+            СКИДКА НА ЗАКАЗ:
+            <input
+              type="number"
+              className="discount-input"
+              min="0"
+              max="100"
+              step="0.01"
+              value={orderDiscount}
+              onChange={(e) => setOrderDiscount(e.target.value)}
+              onBlur={handleOrderDiscount}
+            /> %
+          </div>
           <div className="total-amount">
-            ИТОГО К ОПЛАТЕ: {totalAmount.toFixed(2)} $ (Всего: {(totalAmount + 10000).toFixed(2)} $.)
+            {totals ? (
+              <>
+                ИТОГО К ОПЛАТЕ: {formatMoney(totals.total_amount)}
+                {moneyValue(totals.discount_amount) > 0 && ` (скидка ${formatMoney(totals.discount_amount)})`}
+                {parseFloat(totals.vat_rate) > 0 && ` (${totals.prices_include_vat ? 'в т.ч. ' : ''}НДС ${totals.vat_rate}%: ${formatMoney(totals.vat_amount)})`}
+              </>
+            ) : (
+              'ИТОГО К ОПЛАТЕ: —'
+            )}
           </div>
           <div className="modal-actions">
             <button
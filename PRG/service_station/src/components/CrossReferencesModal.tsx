@@ -0,0 +1,214 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './WarehouseStockModal.css';
+import './AddWarehouseItemModal.css';
+
+interface CrossReference {
+  id: number;
+  brand_a: string;
+  article_a: string;
+  brand_b: string;
+  article_b: string;
+  created_by_name: string | null;
+  created_at: string;
+}
+
+interface ImportReport {
+  total_rows: number;
+  imported: number;
+  duplicates: number;
+  rejected_count: number;
+  rejected: { row: number; reason: string }[];
+}
+
+const EMPTY_FORM = { brandA: '', articleA: '', brandB: '', articleB: '' };
+
+interface CrossReferencesModalProps {
+  isOpen: boolean;
+  onClose: () => void;
+}
+
+const CrossReferencesModal: React.FC<CrossReferencesModalProps> = ({ isOpen, onClose }) => {
+  const [references, setReferences] = useState<CrossReference[]>([]);
+  const [search, setSearch] = useState('');
+  const [form, setForm] = useState(EMPTY_FORM);
+  const [report, setReport] = useState<ImportReport | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    if (isOpen) {
+      loadReferences();
+    }
+  }, [isOpen]);
+
+  const loadReferences = async () => {
+    try {
+      setLoading(true);
+      const result = await invoke<CrossReference[]>('get_cross_references', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        search
+      });
+      setReferences(result);
+    } catch (error) {
+      console.error('Error loading cross references:', error);
+      setReferences([]);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleFormChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+    const { name, value } = e.target;
+    setForm(prev => ({ ...prev, [name]: value }));
+  };
+
+  const handleAdd = async (e: React.FormEvent) => {
+    e.preventDefault();
+    try {
+      await invoke('add_cross_reference', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        request: form
+      });
+      setForm(EMPTY_FORM);
+      loadReferences();
+    } catch (error) {
+      console.error('Error adding cross reference:', error);
+      alert('Ошибка при добавлении аналога: ' + error);
+    }
+  };
+
+  const handleDelete = async (reference: CrossReference) => {
+    if (!confirm(`Удалить связь ${reference.brand_a} ${reference.article_a} = ${reference.brand_b} ${reference.article_b}?`)) return;
+
+    try {
+      await invoke('delete_cross_reference', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        crossReferenceId: reference.id
+      });
+      loadReferences();
+    } catch (error) {
+      console.error('Error deleting cross reference:', error);
+      alert('Ошибка при удалении аналога: ' + error);
+    }
+  };
+
+  const handleImport = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = '';
+    if (!file) return;
+
+    try {
+      const buffer = await file.arrayBuffer();
+      const result = await invoke<ImportReport>('import_cross_references', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        fileName: file.name,
+        content: Array.from(new Uint8Array(buffer))
+      });
+      setReport(result);
+      loadReferences();
+    } catch (error) {
+      console.error('Error importing cross references:', error);
+      alert('Ошибка при импорте аналогов: ' + error);
+    }
+  };
+
+  if (!isOpen) return null;
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>🔁 АНАЛОГИ (КРОСС-НОМЕРА)</h2>
+          <button className="close-btn" onClick={onClose}>✖ ЗАКРЫТЬ</button>
+        </div>
+
+        <div className="modal-body">
+          <div className="search-section">
+            <div className="search-input-container">
+              <input
+                type="text"
+                placeholder="Бренд или артикул"
+                value={search}
+                onChange={(e) => setSearch(e.target.value)}
+                onKeyDown={(e) => e.key === 'Enter' && loadReferences()}
+              />
+              <button className="search-btn" onClick={loadReferences}>🔎 НАЙТИ</button>
+            </div>
+          </div>
+
+          <form onSubmit={handleAdd}>
+            <div className="form-row">
+              <div className="form-group">
+                <label htmlFor="brandA">Бренд:</label>
+                <input type="text" id="brandA" name="brandA" value={form.brandA} onChange={handleFormChange} placeholder="Lemforder" required />
+              </div>
+              <div className="form-group">
+                <label htmlFor="articleA">Артикул:</label>
+                <input type="text" id="articleA" name="articleA" value={form.articleA} onChange={handleFormChange} placeholder="30333 01" required />
+              </div>
+              <div className="form-group">
+                <label htmlFor="brandB">Аналог, бренд:</label>
+                <input type="text" id="brandB" name="brandB" value={form.brandB} onChange={handleFormChange} placeholder="TRW" required />
+              </div>
+              <div className="form-group">
+                <label htmlFor="articleB">Аналог, артикул:</label>
+                <input type="text" id="articleB" name="articleB" value={form.articleB} onChange={handleFormChange} placeholder="JTC1001" required />
+              </div>
+            </div>
+            <div className="modal-actions">
+              <label className="secondary-btn">
+                📄 ИМПОРТ ИЗ ФАЙЛА
+                <input type="file" accept=".csv,.txt,.xlsx,.xls,.ods" onChange={handleImport} hidden />
+              </label>
+              <button type="submit" className="save-btn">➕ ДОБАВИТЬ АНАЛОГ</button>
+            </div>
+          </form>
+
+          {report && (
+            <p className="item-brand">
+              Импорт: строк {report.total_rows}, добавлено {report.imported}, уже были {report.duplicates}, отклонено {report.rejected_count}
+              {report.rejected.length > 0 && ` (первая ошибка: строка ${report.rejected[0].row} - ${report.rejected[0].reason})`}
+            </p>
+          )}
+
+          <div className="warehouse-stock-table">
+            <table>
+              <thead>
+                <tr>
+                  <th>Бренд</th>
+                  <th>Артикул</th>
+                  <th>Аналог, бренд</th>
+                  <th>Аналог, артикул</th>
+                  <th>Добавил</th>
+                  <th></th>
+                </tr>
+              </thead>
+              <tbody>
+                {loading ? (
+                  <tr><td colSpan={6}>Загрузка справочника аналогов...</td></tr>
+                ) : references.length === 0 ? (
+                  <tr><td colSpan={6}>Аналоги не найдены</td></tr>
+                ) : (
+                  references.map(reference => (
+                    <tr key={reference.id}>
+                      <td>{reference.brand_a}</td>
+                      <td>{reference.article_a}</td>
+                      <td>{reference.brand_b}</td>
+                      <td>{reference.article_b}</td>
+                      <td>{reference.created_by_name || '—'}</td>
+                      <td>
+                        <button className="cancel-btn" onClick={() => handleDelete(reference)}>🗑️</button>
+                      </td>
+                    </tr>
+                  ))
+                )}
+              </tbody>
+            </table>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default CrossReferencesModal;
@@ -36,7 +36,7 @@ const DefectNodeDefectTypeSelector: React.FC<Props> = ({
   useEffect(() => {
     const loadNodes = async () => {
       try {
-        const nodes = await invoke<DefectNode[]>('get_defect_nodes');
+        const nodes = await invoke<DefectNode[]>('get_defect_nodes', { sessionToken: localStorage.getItem('sessionToken') });
         setDefectNodes(nodes);
       } catch (error) {
         console.error('Error loading defect nodes:', error);
@@ -54,7 +54,7 @@ const DefectNodeDefectTypeSelector: React.FC<Props> = ({
       if (selectedNodeId > 0) {
         try {
           setLoading(true);
-          const types = await invoke<DefectType[]>('get_defect_types_by_node', { nodeId: selectedNodeId });
+          const types = await invoke<DefectType[]>('get_defect_types_by_node', { sessionToken: localStorage.getItem('sessionToken'), nodeId: selectedNodeId });
           setDefectTypes(types);
         } catch (error) {
           console.error('Error loading defect types:', error);
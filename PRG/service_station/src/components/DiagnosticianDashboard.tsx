@@ -2,6 +2,7 @@ import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './DiagnosticianDashboard.css';
 import DiagnosticsModal from './DiagnosticsModal';
+import { Money } from '../types/money';
 
 // Типы данных для заказа и автомобиля
 type Order = {
@@ -13,8 +14,8 @@ type Order = {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null; // Decimal as string
-  total_amount: string | null; // Decimal as string
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 };
@@ -108,12 +109,12 @@ const DiagnosticianDashboard: React.FC = () => {
   // Функция для получения заказов для диагноста
   const fetchOrders = async () => {
     try {
-      const fetchedOrders: Order[] = await invoke('get_orders_for_diagnostician');
+      const fetchedOrders: Order[] = await invoke('get_orders_for_diagnostician', { sessionToken: localStorage.getItem('sessionToken') });
       setOrders(fetchedOrders);
 
       // Fetch car details for each order
       const carDetailsPromises = fetchedOrders.map(order =>
-        invoke<Car>('get_car_by_id', { carId: order.car_id })
+        invoke<Car>('get_car_by_id', { sessionToken: localStorage.getItem('sessionToken'), carId: order.car_id })
       );
 
       const carDetailsList = await Promise.all(carDetailsPromises);
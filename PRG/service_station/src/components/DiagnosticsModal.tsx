@@ -4,15 +4,106 @@ import './DiagnosticsModal.css';
 import AddFaultModal from './AddFaultModal';
 import { DefectNode, DefectType } from '../types/defect';
 
+// Шаблон замера для типа неисправности
+type MeasurementTemplate = {
+  id: number;
+  name: string;
+  value_type: 'Number' | 'Text';
+  unit: string | null;
+  min_value: string | null;
+  max_value: string | null;
+  flag_if_present: boolean;
+};
+
+type FaultMeasurement = MeasurementTemplate & {
+  value: string;
+};
+
+// Сохранённый замер по неисправности заказа
+type DefectMeasurement = {
+  order_defect_id: number;
+  template_id: number | null;
+  value: string;
+};
+
 type Fault = {
-  id: number; // Это будет ID типа неисправности
+  key: string;
+  defect_id: number | null; // ID сохранённой неисправности заказа; null - ещё не сохранена
   node_id: number;
   type_id: number;
   node_name: string;
   type_name: string;
   comment: string;
+  measurements: FaultMeasurement[];
+};
+
+// Сохранённая неисправность заказа
+type OrderDefect = {
+  id: number;
+  defect_type_id: number | null;
+  defect_description: string;
+  diagnostician_comment: string | null;
+};
+
+// Запись истории изменений дефектовочной ведомости
+type DefectChange = {
+  id: number;
+  action: 'Added' | 'Updated' | 'Removed';
+  details: string;
+  changed_by_name: string | null;
+  changed_at: string;
+};
+
+// Тип неисправности, предлагаемый по коду ошибки
+type DtcDefectType = {
+  id: number;
+  node_name: string;
+  name: string;
+};
+
+// Код из справочника OBD-II
+type DtcCode = {
+  id: number;
+  code: string;
+  description: string;
+  defect_types: DtcDefectType[];
+};
+
+// Код ошибки, считанный сканером и прикреплённый к заказу
+type OrderDtcCode = {
+  id: number;
+  code: string;
+  description: string | null;
+  added_by_name: string | null;
+  added_at: string;
+  defect_types: DtcDefectType[];
+};
+
+// Подсветка значения вне диапазона до сохранения; окончательно отклонение определяет бэкенд
+const isOutOfRange = (measurement: FaultMeasurement) => {
+  const value = measurement.value.trim();
+  if (!value) return false;
+  if (measurement.value_type === 'Text') return measurement.flag_if_present;
+  const number = parseFloat(value.replace(',', '.'));
+  if (isNaN(number)) return false;
+  return (measurement.min_value !== null && number < parseFloat(measurement.min_value))
+    || (measurement.max_value !== null && number > parseFloat(measurement.max_value));
+};
+
+const rangeText = (measurement: MeasurementTemplate) => {
+  const unit = measurement.unit ? ` ${measurement.unit}` : '';
+  if (measurement.min_value !== null && measurement.max_value !== null) return `норма ${measurement.min_value}–${measurement.max_value}${unit}`;
+  if (measurement.min_value !== null) return `норма от ${measurement.min_value}${unit}`;
+  if (measurement.max_value !== null) return `норма до ${measurement.max_value}${unit}`;
+  return measurement.unit || '';
 };
 
+const loadTemplates = async (typeId: number) =>
+  invoke<MeasurementTemplate[]>('get_defect_type_measurements', {
+    sessionToken: localStorage.getItem('sessionToken'),
+    defectTypeId: typeId
+  });
+
 type Props = {
   orderId: number;
   clientComplaint: string;
@@ -31,68 +122,216 @@ const DiagnosticsModal: React.FC<Props> = ({
   // Состояния для списка неисправностей
   const [faults, setFaults] = useState<Fault[]>([]);
   const [allDefectTypes, setAllDefectTypes] = useState<Record<number, DefectType>>({});
+  const [selectedKey, setSelectedKey] = useState<string | null>(null);
+  const [changes, setChanges] = useState<DefectChange[]>([]);
+  const [isSaving, setIsSaving] = useState(false);
+
+  // Коды ошибок OBD
+  const [dtcCodes, setDtcCodes] = useState<OrderDtcCode[]>([]);
+  const [dtcInput, setDtcInput] = useState('');
+  const [dtcMatches, setDtcMatches] = useState<DtcCode[]>([]);
+  const [dtcDescription, setDtcDescription] = useState('');
 
   // Состояние для управления видимостью модального окна добавления неисправности
   const [showAddFaultModal, setShowAddFaultModal] = useState(false);
 
-  // Загрузка справочника типов неисправностей при монтировании
-  useEffect(() => {
-    const loadDefectTypes = async () => {
-      try {
-        const defectTypes = await invoke<DefectType[]>('get_all_defect_types');
-        const typesMap: Record<number, DefectType> = {};
-        defectTypes.forEach(type => {
-          typesMap[type.id] = type;
-        });
-        setAllDefectTypes(typesMap);
-      } catch (error) {
-        console.error('Error loading defect types:', error);
-        alert('Ошибка загрузки справочника неисправностей: ' + error);
-      }
-    };
+  // Загрузка справочника типов неисправностей и уже сохранённой дефектовки заказа
+  const loadDiagnostics = async () => {
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+      const [defectTypes, defects, history, measurements, orderDtcCodes] = await Promise.all([
+        invoke<DefectType[]>('get_all_defect_types', { sessionToken }),
+        invoke<OrderDefect[]>('get_diagnostic_results_by_order_id', { sessionToken, orderId }),
+        invoke<DefectChange[]>('get_order_defect_changes', { sessionToken, orderId }),
+        invoke<DefectMeasurement[]>('get_order_defect_measurements', { sessionToken, orderId }),
+        invoke<OrderDtcCode[]>('get_order_dtc_codes', { sessionToken, orderId })
+      ]);
+      const typesMap: Record<number, DefectType> = {};
+      defectTypes.forEach(type => {
+        typesMap[type.id] = type;
+      });
+      setAllDefectTypes(typesMap);
+
+      // Шаблоны замеров для типов уже выявленных неисправностей
+      const typeIds = [...new Set(defects.flatMap(defect => (defect.defect_type_id !== null ? [defect.defect_type_id] : [])))];
+      const templatesByType: Record<number, MeasurementTemplate[]> = {};
+      await Promise.all(typeIds.map(async typeId => {
+        templatesByType[typeId] = await loadTemplates(typeId);
+      }));
+
+      // Неисправности без типа из справочника (старые записи) в форме не редактируются
+      setFaults(defects.flatMap(defect => {
+        const defectType = defect.defect_type_id !== null ? typesMap[defect.defect_type_id] : undefined;
+        if (!defectType) return [];
+        return [{
+          key: `defect-${defect.id}`,
+          defect_id: defect.id,
+          node_id: defectType.node_id,
+          type_id: defectType.id,
+          node_name: defectType.node_name,
+          type_name: defectType.name,
+          comment: defect.diagnostician_comment || '',
+          measurements: (templatesByType[defectType.id] || []).map(template => ({
+            ...template,
+            value: measurements.find(m => m.order_defect_id === defect.id && m.template_id === template.id)?.value || ''
+          }))
+        }];
+      }));
+      setChanges(history);
+      setDtcCodes(orderDtcCodes);
+    } catch (error) {
+      console.error('Error loading diagnostics:', error);
+      alert('Ошибка загрузки результатов диагностики: ' + error);
+    }
+  };
 
-    loadDefectTypes();
-  }, []);
+  useEffect(() => {
+    loadDiagnostics();
+  }, [orderId]);
 
   // Функция для добавления новой неисправности
-  const handleAddFault = (fault: { node_id: number; type_id: number; comment: string }) => {
+  const handleAddFault = async (fault: { node_id: number; type_id: number; comment: string }) => {
     const defectType = allDefectTypes[fault.type_id];
     if (!defectType) {
       console.error(`Defect type with ID ${fault.type_id} not found in cache`);
       return;
     }
 
+    let templates: MeasurementTemplate[] = [];
+    try {
+      templates = await loadTemplates(fault.type_id);
+    } catch (error) {
+      console.error('Error loading measurement templates:', error);
+    }
+
     const newFault: Fault = {
-      id: fault.type_id,
+      key: `new-${Date.now()}`,
+      defect_id: null,
       node_id: fault.node_id,
       type_id: fault.type_id,
       node_name: defectType.node_name,
       type_name: defectType.name,
-      comment: fault.comment
+      comment: fault.comment,
+      measurements: templates.map(template => ({ ...template, value: '' }))
     };
-    setFaults([...faults, newFault]);
+    setFaults(prev => [...prev, newFault]);
   };
 
-  // Функция для удаления неисправности
-  const handleRemoveFault = (id: number) => {
-    setFaults(faults.filter(fault => fault.id !== id));
+  // Подсказки из справочника по мере ввода кода
+  useEffect(() => {
+    const query = dtcInput.trim();
+    if (query.length < 2) {
+      setDtcMatches([]);
+      return;
+    }
+    invoke<DtcCode[]>('search_dtc_codes', {
+      sessionToken: localStorage.getItem('sessionToken'),
+      query
+    })
+      .then(setDtcMatches)
+      .catch(error => console.error('Error searching DTC codes:', error));
+  }, [dtcInput]);
+
+  const reloadDtcCodes = async () => {
+    setDtcCodes(await invoke<OrderDtcCode[]>('get_order_dtc_codes', {
+      sessionToken: localStorage.getItem('sessionToken'),
+      orderId
+    }));
   };
 
-  // Функция для завершения диагностики
-  const handleCompleteDiagnosis = async () => {
-    // Получаем ID типов неисправностей для передачи в бэкенд
-    const defectTypeIds = faults.map(fault => fault.type_id);
+  const handleAddDtcCode = async (code: string) => {
+    try {
+      await invoke('add_order_dtc_code', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId,
+        code,
+        description: dtcDescription.trim() || null
+      });
+      setDtcInput('');
+      setDtcDescription('');
+      await reloadDtcCodes();
+    } catch (error) {
+      console.error('Error adding DTC code:', error);
+      alert('Ошибка добавления кода ошибки: ' + error);
+    }
+  };
 
+  const handleRemoveDtcCode = async (orderDtcCodeId: number) => {
     try {
-      // Вызываем команду сохранения результатов диагностики
-      const result = await invoke('save_diagnostic_results', {
-        orderId: orderId,
-        diagnosticianId: diagnosticianId,
-        defectTypeIds: defectTypeIds
+      await invoke('remove_order_dtc_code', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderDtcCodeId
       });
+      await reloadDtcCodes();
+    } catch (error) {
+      console.error('Error removing DTC code:', error);
+      alert('Ошибка удаления кода ошибки: ' + error);
+    }
+  };
+
+  // Добавляет предложенную по коду неисправность, если такой ещё нет в списке
+  const handleAddSuggestedFault = (dtc: OrderDtcCode, defectType: DtcDefectType) => {
+    const known = allDefectTypes[defectType.id];
+    if (!known || faults.some(fault => fault.type_id === defectType.id)) return;
+    handleAddFault({
+      node_id: known.node_id,
+      type_id: defectType.id,
+      comment: `Код ${dtc.code}${dtc.description ? `: ${dtc.description}` : ''}`
+    });
+  };
+
+  // Функция для удаления неисправности
+  const handleRemoveFault = (key: string) => {
+    setFaults(faults.filter(fault => fault.key !== key));
+    setSelectedKey(null);
+  };
+
+  const handleCommentChange = (key: string, comment: string) => {
+    setFaults(faults.map(fault => (fault.key === key ? { ...fault, comment } : fault)));
+  };
 
+  const handleMeasurementChange = (key: string, templateId: number, value: string) => {
+    setFaults(faults.map(fault => (fault.key === key
+      ? { ...fault, measurements: fault.measurements.map(m => (m.id === templateId ? { ...m, value } : m)) }
+      : fault)));
+  };
+
+  // Сохраняет текущий список неисправностей целиком: бэкенд сам добавит, изменит или удалит записи
+  const saveFaults = async () => {
+    const result = await invoke('save_diagnostic_results', {
+      sessionToken: localStorage.getItem('sessionToken'),
+      orderId: orderId,
+      diagnosticianId: diagnosticianId,
+      defects: faults.map(fault => ({
+        id: fault.defect_id,
+        defect_type_id: fault.type_id,
+        comment: fault.comment || null,
+        measurements: fault.measurements.map(m => ({ template_id: m.id, value: m.value || null }))
+      }))
+    });
+    console.log('Результат:', result);
+  };
+
+  // Промежуточное сохранение без передачи заказа на подбор запчастей
+  const handleSave = async () => {
+    setIsSaving(true);
+    try {
+      await saveFaults();
+      await loadDiagnostics();
+    } catch (error) {
+      console.error('Error saving diagnostic results:', error);
+      alert('Ошибка при сохранении результатов диагностики: ' + error);
+    } finally {
+      setIsSaving(false);
+    }
+  };
+
+  // Функция для завершения диагностики
+  const handleCompleteDiagnosis = async () => {
+    setIsSaving(true);
+    try {
+      await saveFaults();
       console.log(`Завершаем диагностику для заказа #${orderId}`);
-      console.log('Результат:', result);
 
       // Получаем токен сессии из localStorage
       const sessionToken = localStorage.getItem('sessionToken');
@@ -114,6 +353,8 @@ const DiagnosticsModal: React.FC<Props> = ({
     } catch (error) {
       console.error('Error saving diagnostic results or updating order status:', error);
       alert('Ошибка при сохранении результатов диагностики или обновлении статуса заказа: ' + error);
+    } finally {
+      setIsSaving(false);
     }
   };
 
@@ -130,6 +371,71 @@ const DiagnosticsModal: React.FC<Props> = ({
             <strong>ЖАЛОБА КЛИЕНТА:</strong> {clientComplaint}
           </div>
 
+          <div className="dtc-codes">
+            <h3>КОДЫ ОШИБОК OBD:</h3>
+            <div className="dtc-input">
+              <input
+                type="text"
+                value={dtcInput}
+                onChange={(e) => setDtcInput(e.target.value.toUpperCase())}
+                placeholder="Код или текст, например P0300"
+              />
+              <input
+                type="text"
+                value={dtcDescription}
+                onChange={(e) => setDtcDescription(e.target.value)}
+                placeholder="Расшифровка (если кода нет в справочнике)"
+              />
+              <button onClick={() => handleAddDtcCode(dtcInput)} disabled={!dtcInput.trim()}>
+                ➕ ДОБАВИТЬ КОД
+              </button>
+            </div>
+            {dtcMatches.length > 0 && (
+              <ul className="dtc-matches">
+                {dtcMatches.map(match => (
+                  <li key={match.id} onClick={() => handleAddDtcCode(match.code)}>
+                    <strong>{match.code}</strong> — {match.description}
+                  </li>
+                ))}
+              </ul>
+            )}
+            {dtcCodes.length > 0 && (
+              <table className="faults-table">
+                <thead>
+                  <tr>
+                    <th>Код</th>
+                    <th>Расшифровка</th>
+                    <th>Возможные неисправности</th>
+                    <th></th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {dtcCodes.map(dtc => (
+                    <tr key={dtc.id}>
+                      <td>{dtc.code}</td>
+                      <td>{dtc.description || '—'}</td>
+                      <td>
+                        {dtc.defect_types.map(defectType => (
+                          <button
+                            key={defectType.id}
+                            className="dtc-suggestion"
+                            onClick={() => handleAddSuggestedFault(dtc, defectType)}
+                            disabled={faults.some(fault => fault.type_id === defectType.id)}
+                          >
+                            ➕ {defectType.node_name} / {defectType.name}
+                          </button>
+                        ))}
+                      </td>
+                      <td>
+                        <button onClick={() => handleRemoveDtcCode(dtc.id)}>✖</button>
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            )}
+          </div>
+
           <div className="diagnostics-list">
             <h3>СПИСОК ВЫЯВЛЕННЫХ НЕИСПРАВНОСТЕЙ:</h3>
             <table className="faults-table">
@@ -142,11 +448,45 @@ const DiagnosticsModal: React.FC<Props> = ({
               </thead>
               <tbody>
                 {faults.map((fault, index) => (
-                  <tr key={fault.id}>
-                    <td>{index + 1}</td>
-                    <td>{fault.node_name} / {fault.type_name}</td>
-                    <td>{fault.comment}</td>
-                  </tr>
+                  <React.Fragment key={fault.key}>
+                    <tr
+                      className={fault.key === selectedKey ? 'selected-fault' : ''}
+                      onClick={() => setSelectedKey(fault.key)}
+                    >
+                      <td>{index + 1}</td>
+                      <td>{fault.node_name} / {fault.type_name}</td>
+                      <td>
+                        <input
+                          type="text"
+                          value={fault.comment}
+                          onChange={(e) => handleCommentChange(fault.key, e.target.value)}
+                          placeholder="Заключение диагноста"
+                        />
+                      </td>
+                    </tr>
+                    {fault.measurements.length > 0 && (
+                      <tr className="measurements-row">
+                        <td></td>
+                        <td colSpan={2}>
+                          {fault.measurements.map(measurement => (
+                            <label
+                              key={measurement.id}
+                              className={`measurement ${isOutOfRange(measurement) ? 'out-of-range' : ''}`}
+                            >
+                              {measurement.name}:
+                              <input
+                                type="text"
+                                value={measurement.value}
+                                onChange={(e) => handleMeasurementChange(fault.key, measurement.id, e.target.value)}
+                              />
+                              <span className="measurement-range">{rangeText(measurement)}</span>
+                              {isOutOfRange(measurement) && ' ⚠️'}
+                            </label>
+                          ))}
+                        </td>
+                      </tr>
+                    )}
+                  </React.Fragment>
                 ))}
               </tbody>
             </table>
@@ -161,21 +501,34 @@ const DiagnosticsModal: React.FC<Props> = ({
             </button>
             <button
               className="remove-fault-button"
-              onClick={() => {
-                if (faults.length > 0) {
-                  handleRemoveFault(faults[faults.length - 1].id);
-                }
-              }}
-              disabled={faults.length === 0}
+              onClick={() => selectedKey && handleRemoveFault(selectedKey)}
+              disabled={selectedKey === null}
             >
               ➖ УДАЛИТЬ
             </button>
           </div>
+
+          {changes.length > 0 && (
+            <div className="defect-changes">
+              <h3>ИСТОРИЯ ИЗМЕНЕНИЙ:</h3>
+              <ul>
+                {changes.map(change => (
+                  <li key={change.id}>
+                    {new Date(change.changed_at).toLocaleString()}
+                    {change.changed_by_name ? ` (${change.changed_by_name})` : ''}: {change.details}
+                  </li>
+                ))}
+              </ul>
+            </div>
+          )}
         </div>
 
         <div className="modal-footer">
-          <button className="complete-diagnosis-button" onClick={handleCompleteDiagnosis}>
-            💾 ЗАВЕРШИТЬ
+          <button className="save-diagnosis-button" onClick={handleSave} disabled={isSaving}>
+            💾 СОХРАНИТЬ
+          </button>
+          <button className="complete-diagnosis-button" onClick={handleCompleteDiagnosis} disabled={isSaving}>
+            ✅ ЗАВЕРШИТЬ
           </button>
         </div>
       </div>
@@ -0,0 +1,293 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './AddWarehouseItemModal.css';
+import { Money, formatMoney, moneyInput } from '../types/money';
+
+export interface WarehouseItem {
+  id: number;
+  name: string;
+  brand: string | null;
+  article: string | null;
+  location_cell: string | null;
+  quantity: number;
+  reserved: number;
+  available: number;
+  min_quantity: number;
+  purchase_price: Money | null;
+  selling_price: Money | null;
+  is_archived: boolean;
+}
+
+interface StockMovement {
+  id: number;
+  movement_type: string;
+  quantity: number;
+  balance_after: number;
+  unit_cost: Money | null;
+  user_name: string | null;
+  order_id: number | null;
+  reason: string | null;
+  created_at: string;
+}
+
+const MOVEMENT_TYPE_LABELS: Record<string, string> = {
+  Receipt: 'Поступление',
+  Issue: 'Выдача в заказ',
+  Return: 'Возврат',
+  Write_Off: 'Списание',
+  Correction: 'Корректировка'
+};
+
+interface EditWarehouseItemModalProps {
+  isOpen: boolean;
+  item: WarehouseItem | null;
+  onClose: () => void;
+  onItemUpdated: () => void;
+}
+
+const EditWarehouseItemModal: React.FC<EditWarehouseItemModalProps> = ({ isOpen, item, onClose, onItemUpdated }) => {
+  const [formData, setFormData] = useState({
+    location_cell: item?.location_cell || '',
+    min_quantity: item?.min_quantity ?? 0,
+    purchase_price: item?.purchase_price?.amount ?? '',
+    selling_price: item?.selling_price?.amount ?? ''
+  });
+  const [newQuantity, setNewQuantity] = useState(item?.quantity ?? 0);
+  const [quantityReason, setQuantityReason] = useState('');
+  const [loading, setLoading] = useState(false);
+  const [movements, setMovements] = useState<StockMovement[] | null>(null);
+
+  if (!isOpen || !item) return null;
+
+  const loadMovements = async () => {
+    try {
+      const result = await invoke<StockMovement[]>('get_stock_movements', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        warehouseItemId: item.id
+      });
+      setMovements(result);
+    } catch (error) {
+      console.error('Error loading stock movements:', error);
+      alert('Ошибка при загрузке движений по складу: ' + error);
+    }
+  };
+
+  const handleInputChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+    const { name, value } = e.target;
+    setFormData(prev => ({
+      ...prev,
+      [name]: name.includes('quantity') ? parseFloat(value) || 0 : value
+    }));
+  };
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+
+    const quantityChanged = newQuantity !== item.quantity;
+    if (quantityChanged && !quantityReason.trim()) {
+      alert('Укажите причину изменения остатка');
+      return;
+    }
+
+    setLoading(true);
+
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+
+      await invoke('update_warehouse_item', {
+        sessionToken,
+        itemId: item.id,
+        request: {
+          locationCell: formData.location_cell,
+          minQuantity: formData.min_quantity,
+          // Пустое поле снимает цену с позиции
+          purchasePrice: formData.purchase_price.trim() ? moneyInput(formData.purchase_price) : null,
+          sellingPrice: formData.selling_price.trim() ? moneyInput(formData.selling_price) : null
+        }
+      });
+
+      // Остаток меняется отдельной командой, так как каждое изменение требует причины
+      if (quantityChanged) {
+        await invoke('adjust_warehouse_quantity', {
+          sessionToken,
+          itemId: item.id,
+          newQuantity,
+          reason: quantityReason.trim()
+        });
+      }
+
+      alert('Позиция склада обновлена');
+      onItemUpdated();
+      onClose();
+    } catch (error) {
+      console.error('Error updating warehouse item:', error);
+      alert('Ошибка при обновлении позиции склада: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleToggleArchived = async () => {
+    const message = item.is_archived
+      ? 'Вернуть позицию из архива?'
+      : 'Перевести позицию в архив? Она перестанет отображаться в списке склада.';
+    if (!confirm(message)) return;
+
+    setLoading(true);
+    try {
+      await invoke('set_warehouse_item_archived', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        itemId: item.id,
+        archived: !item.is_archived
+      });
+
+      onItemUpdated();
+      onClose();
+    } catch (error) {
+      console.error('Error archiving warehouse item:', error);
+      alert('Ошибка при изменении архивного статуса: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>✏️ {item.name} {item.brand && `(${item.brand})`}</h2>
+          <button className="close-btn" onClick={onClose}>✖ ЗАКРЫТЬ</button>
+        </div>
+
+        <form onSubmit={handleSubmit} className="modal-body">
+          <div className="form-row">
+            <div className="form-group">
+              <label htmlFor="location_cell">Ячейка на складе:</label>
+              <input
+                type="text"
+                id="location_cell"
+                name="location_cell"
+                value={formData.location_cell}
+                onChange={handleInputChange}
+                placeholder="A-01-01"
+              />
+            </div>
+            <div className="form-group">
+              <label htmlFor="min_quantity">Мин. остаток:</label>
+              <input
+                type="number"
+                id="min_quantity"
+                name="min_quantity"
+                value={formData.min_quantity}
+                onChange={handleInputChange}
+                min="0"
+                required
+              />
+            </div>
+          </div>
+
+          <div className="form-row">
+            <div className="form-group">
+              <label htmlFor="purchase_price">Закупочная цена:</label>
+              <input
+                type="number"
+                id="purchase_price"
+                name="purchase_price"
+                value={formData.purchase_price}
+                onChange={handleInputChange}
+                min="0"
+                step="0.01"
+              />
+            </div>
+            <div className="form-group">
+              <label htmlFor="selling_price">Цена продажи:</label>
+              <input
+                type="number"
+                id="selling_price"
+                name="selling_price"
+                value={formData.selling_price}
+                onChange={handleInputChange}
+                min="0"
+                step="0.01"
+              />
+            </div>
+          </div>
+
+          <div className="form-row">
+            <div className="form-group">
+              <label htmlFor="quantity">Остаток (сейчас {item.quantity} шт.):</label>
+              <input
+                type="number"
+                id="quantity"
+                value={newQuantity}
+                onChange={(e) => setNewQuantity(parseInt(e.target.value) || 0)}
+                min="0"
+              />
+            </div>
+            <div className="form-group">
+              <label htmlFor="quantity_reason">Причина изменения остатка:</label>
+              <input
+                type="text"
+                id="quantity_reason"
+                value={quantityReason}
+                onChange={(e) => setQuantityReason(e.target.value)}
+                placeholder="Инвентаризация, пересорт..."
+                disabled={newQuantity === item.quantity}
+              />
+            </div>
+          </div>
+
+          <div className="stock-movements">
+            {movements === null ? (
+              <button type="button" className="cancel-btn" onClick={loadMovements}>
+                📜 ИСТОРИЯ ДВИЖЕНИЙ
+              </button>
+            ) : movements.length > 0 ? (
+              <table>
+                <thead>
+                  <tr>
+                    <th>Дата</th>
+                    <th>Операция</th>
+                    <th>Кол-во</th>
+                    <th>Остаток</th>
+                    <th>Цена</th>
+                    <th>Сотрудник</th>
+                    <th>Заказ</th>
+                    <th>Причина</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {movements.map(m => (
+                    <tr key={m.id}>
+                      <td>{new Date(m.created_at).toLocaleString('ru-RU')}</td>
+                      <td>{MOVEMENT_TYPE_LABELS[m.movement_type] || m.movement_type}</td>
+                      <td>{m.quantity > 0 ? `+${m.quantity}` : m.quantity}</td>
+                      <td>{m.balance_after}</td>
+                      <td>{formatMoney(m.unit_cost)}</td>
+                      <td>{m.user_name || '—'}</td>
+                      <td>{m.order_id ? `#${m.order_id}` : '—'}</td>
+                      <td>{m.reason || '—'}</td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            ) : (
+              <p>Движений по позиции нет</p>
+            )}
+          </div>
+
+          <div className="modal-actions">
+            <button type="button" className="cancel-btn" onClick={handleToggleArchived} disabled={loading}>
+              {item.is_archived ? '♻️ ВЕРНУТЬ ИЗ АРХИВА' : '🗄️ В АРХИВ'}
+            </button>
+            <button type="submit" className="save-btn" disabled={loading}>
+              {loading ? 'Сохранение...' : '✅ СОХРАНИТЬ'}
+            </button>
+          </div>
+        </form>
+      </div>
+    </div>
+  );
+};
+
+export default EditWarehouseItemModal;
@@ -1,11 +1,12 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './FinalProcessingModal.css';
+import { Money, formatMoney, moneyInput, moneyValue, subtractMoney } from '../types/money';
 
 interface Order {
   id: number;
-  total_amount: string | null;
-  prepayment: string | null;
+  total_amount: Money | null;
+  prepayment: Money | null;
   client_id: number;
   car_id: number;
   worker_id: number | null; // Main worker assigned to the entire order
@@ -32,6 +33,37 @@ interface Car {
   // другие поля автомобиля
 }
 
+interface Payment {
+  id: number;
+  kind: 'Prepayment' | 'Payment' | 'Refund';
+  method: 'Cash' | 'Card' | 'Transfer';
+  amount: Money;
+  refund_of: number | null;
+  refunded: Money;
+  cashier_name: string | null;
+  comment: string | null;
+  created_at: string;
+}
+
+interface OrderPayments {
+  total_amount: Money;
+  paid: Money;
+  balance: Money;
+  payments: Payment[];
+}
+
+const PAYMENT_KIND_LABELS: Record<Payment['kind'], string> = {
+  Prepayment: 'Предоплата',
+  Payment: 'Оплата',
+  Refund: 'Возврат'
+};
+
+const PAYMENT_METHOD_LABELS: Record<Payment['method'], string> = {
+  Cash: 'Наличные',
+  Card: 'Карта',
+  Transfer: 'Безналичный (Р/С)'
+};
+
 interface FinalProcessingModalProps {
   isOpen: boolean;
   order: Order;
@@ -49,23 +81,64 @@ const FinalProcessingModal: React.FC<FinalProcessingModalProps> = ({
   onClose,
   onCompletion
 }) => {
-  const [paymentMethod, setPaymentMethod] = useState<'cash' | 'card' | 'bank_transfer'>('card');
-  const [prepaymentAmount, setPrepaymentAmount] = useState<number>(0);
-  const [finalAmount, setFinalAmount] = useState<number>(0);
+  const [paymentMethod, setPaymentMethod] = useState<Payment['method']>('Card');
+  const [orderPayments, setOrderPayments] = useState<OrderPayments | null>(null);
   const [isProcessing, setIsProcessing] = useState(false);
+  const [documentPath, setDocumentPath] = useState<string | null>(null);
 
   useEffect(() => {
-    if (order && order.total_amount) {
-      const total = parseFloat(order.total_amount);
-      const prepayment = order.prepayment ? parseFloat(order.prepayment) : 0;
-      setPrepaymentAmount(prepayment);
-      setFinalAmount(total - prepayment);
+    if (isOpen && order) {
+      loadPayments();
+    }
+  }, [isOpen, order]);
+
+  const loadPayments = async () => {
+    try {
+      const result = await invoke<OrderPayments>('get_order_payments', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id
+      });
+      setOrderPayments(result);
+    } catch (error) {
+      console.error('Error loading payments:', error);
+      setOrderPayments(null);
+    }
+  };
+
+  const handleRefund = async (payment: Payment) => {
+    const refundable = subtractMoney(payment.amount, payment.refunded);
+    const amountInput = prompt(`Сумма возврата (не больше ${formatMoney(refundable)}):`, refundable.amount);
+    if (!amountInput) return;
+    const reason = prompt('Причина возврата:');
+    if (!reason) return;
+
+    try {
+      await invoke('refund_payment', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        paymentId: payment.id,
+        amount: moneyInput(amountInput),
+        reason
+      });
+      loadPayments();
+    } catch (error) {
+      console.error('Error refunding payment:', error);
+      alert('Ошибка при оформлении возврата: ' + error);
     }
-  }, [order]);
+  };
 
-  const handlePrintReceipt = () => {
-    // В реальной реализации отправляем запрос на печать чека
-    console.log('Печать чека/акта выполненных работ');
+  // Формируем счёт или акт выполненных работ; PDF сохраняется в папку документов из настроек
+  const handlePrintDocument = async (documentType: 'Invoice' | 'AcceptanceCertificate') => {
+    try {
+      const path = await invoke<string>('generate_order_document', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        documentType
+      });
+      setDocumentPath(path);
+    } catch (error) {
+      console.error('Error generating document:', error);
+      alert('Ошибка при формировании документа: ' + error);
+    }
   };
 
   const handleConfirmDelivery = async () => {
@@ -78,7 +151,28 @@ const FinalProcessingModal: React.FC<FinalProcessingModalProps> = ({
         return;
       }
 
-      // Обновляем статус заказа на "Closed" (окончательно закрыт)
+      // Переводим заказ на оплату
+      if (order.status === 'Ready') {
+        await invoke('update_order_status', {
+          sessionToken,
+          orderId: order.id,
+          newStatus: 'Payment'
+        });
+      }
+
+      // Принимаем доплату на весь остаток выбранным способом
+      const balance = orderPayments?.balance;
+      if (balance && moneyValue(balance) > 0) {
+        await invoke('register_payment', {
+          sessionToken,
+          orderId: order.id,
+          method: paymentMethod,
+          amount: balance,
+          comment: null
+        });
+      }
+
+      // Заказ закрывается только при нулевом остатке
       await invoke('update_order_status', {
         sessionToken,
         orderId: order.id,
@@ -90,6 +184,7 @@ const FinalProcessingModal: React.FC<FinalProcessingModalProps> = ({
     } catch (error) {
       console.error('Error updating order status:', error);
       alert('Ошибка при завершении заказа: ' + error);
+      loadPayments();
     } finally {
       setIsProcessing(false);
     }
@@ -118,56 +213,79 @@ const FinalProcessingModal: React.FC<FinalProcessingModalProps> = ({
             <div className="payment-details">
               <div className="payment-line total">
                 <span>ВСЕГО:</span>
-                <span>{order.total_amount || '0'} $</span>
+                <span>{formatMoney(orderPayments?.total_amount || order.total_amount, '0.00')}</span>
               </div>
               <div className="payment-line discount">
-                <span>Оплачено ранее (аванс):</span>
-                <span>- {prepaymentAmount} $</span>
+                <span>Оплачено ранее (с учётом возвратов):</span>
+                <span>- {formatMoney(orderPayments?.paid, '0.00')}</span>
               </div>
               <div className="payment-line final total">
                 <span>К ДОПЛАТЕ:</span>
-                <span>{finalAmount} $</span>
+                <span>{formatMoney(orderPayments?.balance, '0.00')}</span>
               </div>
             </div>
           </div>
 
+          {orderPayments && orderPayments.payments.length > 0 && (
+            <div className="payments-history">
+              <h3>🧾 ПЛАТЕЖИ:</h3>
+              <table>
+                <thead>
+                  <tr>
+                    <th>Дата</th>
+                    <th>Вид</th>
+                    <th>Способ</th>
+                    <th>Сумма</th>
+                    <th>Кассир</th>
+                    <th></th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {orderPayments.payments.map(payment => (
+                    <tr key={payment.id} className={payment.kind === 'Refund' ? 'refund-row' : ''}>
+                      <td>{new Date(payment.created_at).toLocaleString()}</td>
+                      <td>{PAYMENT_KIND_LABELS[payment.kind]}{payment.comment ? ` (${payment.comment})` : ''}</td>
+                      <td>{PAYMENT_METHOD_LABELS[payment.method]}</td>
+                      <td>{payment.kind === 'Refund' ? '- ' : ''}{formatMoney(payment.amount)}</td>
+                      <td>{payment.cashier_name || '—'}</td>
+                      <td>
+                        {payment.kind !== 'Refund' && moneyValue(payment.refunded) < moneyValue(payment.amount) && (
+                          <button className="refund-btn" onClick={() => handleRefund(payment)}>↩️ ВОЗВРАТ</button>
+                        )}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </div>
+          )}
+
           <div className="payment-method">
             <h3>ОПЛАТА:</h3>
             <div className="payment-options">
-              <label>
-                <input
-                  type="radio"
-                  value="cash"
-                  checked={paymentMethod === 'cash'}
-                  onChange={() => setPaymentMethod('cash')}
-                />
-                Наличные
-              </label>
-              <label>
-                <input
-                  type="radio"
-                  value="card"
-                  checked={paymentMethod === 'card'}
-                  onChange={() => setPaymentMethod('card')}
-                />
-                Карта
-              </label>
-              <label>
-                <input
-                  type="radio"
-                  value="bank_transfer"
-                  checked={paymentMethod === 'bank_transfer'}
-                  onChange={() => setPaymentMethod('bank_transfer')}
-                />
-                Безналичный (Р/С)
-              </label>
+              {(Object.keys(PAYMENT_METHOD_LABELS) as Payment['method'][]).map(method => (
+                <label key={method}>
+                  <input
+                    type="radio"
+                    value={method}
+                    checked={paymentMethod === method}
+                    onChange={() => setPaymentMethod(method)}
+                  />
+                  {PAYMENT_METHOD_LABELS[method]}
+                </label>
+              ))}
             </div>
           </div>
         </div>
 
+        {documentPath && <p className="document-path">Документ сохранён: {documentPath}</p>}
+
         <div className="modal-footer">
-          <button className="print-btn" onClick={handlePrintReceipt}>
-            🖨️ ПЕЧАТЬ ЧЕКА
+          <button className="print-btn" onClick={() => handlePrintDocument('Invoice')}>
+            🖨️ СЧЁТ
+          </button>
+          <button className="print-btn" onClick={() => handlePrintDocument('AcceptanceCertificate')}>
+            🖨️ АКТ ВЫПОЛНЕННЫХ РАБОТ
           </button>
           <button
             className="confirm-delivery-btn"
@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './IssuePartsModal.css';
+import { Money } from '../types/money';
 
 interface Order {
   id: number;
@@ -11,27 +12,28 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
 
-interface Worker {
+interface PartToIssue {
   id: number;
-  full_name: string;
-  role: string;
-  login: string | null;
-  status: string;
+  warehouse_item_id: number;
+  part_name_snapshot: string;
+  brand: string | null;
+  quantity: number;
+  issued_quantity: number;
+  location_cell: string | null;
+  on_hand: number;
 }
 
-interface PartItem {
-  id: number;
-  name: string;
-  brand: string;
-  quantity: number;
-  storage_location: string;
-  status: string;
+interface IssueSheet {
+  order_id: number;
+  worker_id: number | null;
+  worker_name: string | null;
+  parts: PartToIssue[];
 }
 
 interface IssuePartsModalProps {
@@ -47,9 +49,11 @@ const IssuePartsModal: React.FC<IssuePartsModalProps> = ({
   onClose, 
   onIssueConfirmed 
 }) => {
-  const [worker, setWorker] = useState<Worker | null>(null);
-  const [partItems, setPartItems] = useState<PartItem[]>([]);
+  const [sheet, setSheet] = useState<IssueSheet | null>(null);
+  // Количество к выдаче (положительное) или к возврату (отрицательное) по каждой запчасти
+  const [amounts, setAmounts] = useState<Record<number, number>>({});
   const [loading, setLoading] = useState(true);
+  const [processing, setProcessing] = useState(false);
 
   useEffect(() => {
     if (isOpen) {
@@ -60,71 +64,73 @@ const IssuePartsModal: React.FC<IssuePartsModalProps> = ({
   const loadOrderDetails = async () => {
     try {
       setLoading(true);
-      // В реальном приложении загружаем данные о назначенном работнике и запчастях к выдаче
-      // Временно используем заглушку
-      const mockWorker: Worker = {
-        id: 1,
-        full_name: "Сидоров С.С.",
-        role: "Worker",
-        login: "sidorov",
-        status: "Active"
-      };
-      
-      const mockParts: PartItem[] = [
-        {
-          id: 1,
-          name: "Рычаг передний лев.",
-          brand: "Lemforder",
-          quantity: 1,
-          storage_location: "A-12-05",
-          status: "В наличии"
-        },
-        {
-          id: 2,
-          name: "Колодки торм.",
-          brand: "Patron",
-          quantity: 1,
-          storage_location: "B-03-11",
-          status: "В наличии"
-        }
-      ];
-      
-      setWorker(mockWorker);
-      setPartItems(mockParts);
+      const result = await invoke<IssueSheet>('get_order_issue_sheet', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id
+      });
+
+      // По умолчанию предлагаем выдать всё, что ещё не выдано
+      const defaults: Record<number, number> = {};
+      result.parts.forEach(part => {
+        defaults[part.id] = part.quantity - part.issued_quantity;
+      });
+
+      setSheet(result);
+      setAmounts(defaults);
     } catch (error) {
       console.error('Error loading order details:', error);
+      alert('Ошибка при загрузке списка к выдаче: ' + error);
     } finally {
       setLoading(false);
     }
   };
 
-  const handleConfirmIssue = async () => {
+  const submit = async (command: 'issue_order_parts' | 'return_order_parts', sign: 1 | -1) => {
+    const items = Object.entries(amounts)
+      .map(([id, amount]) => ({ orderPartId: Number(id), quantity: amount * sign }))
+      .filter(item => item.quantity > 0);
+
+    if (items.length === 0) {
+      alert(sign > 0 ? 'Не указано количество к выдаче' : 'Не указано количество к возврату');
+      return;
+    }
+
+    setProcessing(true);
     try {
-      // В реальном приложении вызываем API для подтверждения выдачи запчастей
-      // и списания их со склада
-      console.log('Confirming part issuance for order:', order.id);
+      const result = await invoke<string>(command, {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        items
+      });
+      alert(result);
       onIssueConfirmed();
       onClose();
     } catch (error) {
-      console.error('Error confirming part issuance:', error);
+      console.error('Error processing part issuance:', error);
+      alert('Ошибка: ' + error);
+    } finally {
+      setProcessing(false);
     }
   };
 
+  const handleConfirmIssue = () => submit('issue_order_parts', 1);
+  const handleReturn = () => submit('return_order_parts', -1);
+
   if (!isOpen) return null;
 
   return (
     <div className="modal-overlay" onClick={onClose}>
       <div className="modal-content" onClick={(e) => e.stopPropagation()}>
         <div className="modal-header">
-          <h2>📤 ВЫДАЧА В ЦЕХ: Заказ #{order.id} ({order.id})</h2>
+          <h2>📤 ВЫДАЧА В ЦЕХ: Заказ #{order.id}</h2>
           <button className="close-btn" onClick={onClose}>✖ ОТМЕНА</button>
         </div>
 
         <div className="modal-body">
           <div className="recipient-section">
             <h3>ПОЛУЧАТЕЛЬ:</h3>
-            {worker ? (
-              <p>{worker.full_name} ({worker.role})</p>
+            {sheet?.worker_name ? (
+              <p>{sheet.worker_name}</p>
             ) : (
               <p>Не назначен</p>
             )}
@@ -132,30 +138,46 @@ const IssuePartsModal: React.FC<IssuePartsModalProps> = ({
 
           <div className="parts-to-issue-section">
             <h3>СПИСОК К ВЫДАЧЕ:</h3>
+            <p className="part-brand">Положительное количество — выдача в цех, отрицательное — возврат неиспользованного на склад.</p>
             {loading ? (
               <p>Загрузка списка запчастей...</p>
-            ) : partItems.length > 0 ? (
+            ) : sheet && sheet.parts.length > 0 ? (
               <table className="parts-issue-table">
                 <thead>
                   <tr>
                     <th>#</th>
                     <th>Деталь / Бренд</th>
+                    <th>Нужно</th>
+                    <th>Выдано</th>
+                    <th>Ячейка</th>
+                    <th>На складе</th>
                     <th>Кол-во</th>
-                    <th>Склад</th>
-                    <th>Статус</th>
                   </tr>
                 </thead>
                 <tbody>
-                  {partItems.map(part => (
+                  {sheet.parts.map(part => (
                     <tr key={part.id}>
                       <td>{part.id}</td>
                       <td>
-                        <div>{part.name}</div>
+                        <div>{part.part_name_snapshot}</div>
                         <div className="part-brand">{part.brand}</div>
                       </td>
                       <td>{part.quantity} шт.</td>
-                      <td>{part.storage_location}</td>
-                      <td>{part.status}</td>
+                      <td>{part.issued_quantity} шт.</td>
+                      <td>{part.location_cell || '—'}</td>
+                      <td className={part.on_hand < part.quantity - part.issued_quantity ? 'low-stock' : ''}>
+                        {part.on_hand} шт.
+                      </td>
+                      <td>
+                        <input
+                          type="number"
+                          className="issue-amount-input"
+                          min={-part.issued_quantity}
+                          max={part.quantity - part.issued_quantity}
+                          value={amounts[part.id] ?? 0}
+                          onChange={(e) => setAmounts({ ...amounts, [part.id]: parseInt(e.target.value) || 0 })}
+                        />
+                      </td>
                     </tr>
                   ))}
                 </tbody>
@@ -167,7 +189,10 @@ const IssuePartsModal: React.FC<IssuePartsModalProps> = ({
         </div>
 
         <div className="modal-actions">
-          <button className="primary-btn" onClick={handleConfirmIssue}>
+          <button className="secondary-btn" onClick={handleReturn} disabled={processing || !sheet}>
+            📥 ВЕРНУТЬ НА СКЛАД
+          </button>
+          <button className="primary-btn" onClick={handleConfirmIssue} disabled={processing || !sheet?.worker_id}>
             📤 ПОДТВЕРДИТЬ ВЫДАЧУ И СПИСАТЬ
           </button>
         </div>
@@ -176,4 +201,4 @@ const IssuePartsModal: React.FC<IssuePartsModalProps> = ({
   );
 };
 
-export default IssuePartsModal;
\ No newline at end of file
+export default IssuePartsModal;
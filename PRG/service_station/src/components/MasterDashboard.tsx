@@ -8,7 +8,9 @@ import './MasterDashboard.css';
 import NewClientModal from './NewClientModal';
 import NewCarModal from './NewCarModal';
 import AssignWorkersModal from './AssignWorkersModal';
+import QualityControlModal from './QualityControlModal';
 import NewOrderModal from './NewOrderModal';
+import { Money, formatMoney } from '../types/money';
 
 interface Order {
   id: number;
@@ -19,8 +21,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null; // Decimal as string
-  total_amount: string | null; // Decimal as string
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -81,6 +83,7 @@ const MasterDashboard: React.FC = () => {
   const [showAssignWorkersModal, setShowAssignWorkersModal] = useState(false);
   const [showClientApprovalModal, setShowClientApprovalModal] = useState(false);
   const [showFinalProcessingModal, setShowFinalProcessingModal] = useState(false);
+  const [showQualityControlModal, setShowQualityControlModal] = useState(false);
   const [selectedClientForFinalProcessing, setSelectedClientForFinalProcessing] = useState<Client | null>(null);
   const [selectedCarForFinalProcessing, setSelectedCarForFinalProcessing] = useState<Car | null>(null);
   const [orderDefects, setOrderDefects] = useState<any[]>([]);
@@ -121,7 +124,7 @@ const MasterDashboard: React.FC = () => {
     try {
       setLoading(true);
       // Загрузим все заказы для мастера
-      const ordersData = await invoke<Order[]>('get_orders_for_master');
+      const ordersData = await invoke<Order[]>('get_orders_for_master', { sessionToken: localStorage.getItem('sessionToken') });
       setOrders(ordersData);
 
       // Загрузим информацию о клиентах и автомобилях для каждого заказа
@@ -132,7 +135,7 @@ const MasterDashboard: React.FC = () => {
       for (const clientId of uniqueClientIds) {
         if (!clients[clientId]) {
           try {
-            const clientData = await invoke<Client | null>('get_client_by_id', { clientId });
+            const clientData = await invoke<Client | null>('get_client_by_id', { sessionToken: localStorage.getItem('sessionToken'), clientId });
             if (clientData) {
               setClients(prev => ({ ...prev, [clientId]: clientData }));
             }
@@ -146,7 +149,7 @@ const MasterDashboard: React.FC = () => {
       for (const carId of uniqueCarIds) {
         if (!cars[carId]) {
           try {
-            const carData = await invoke<Car | null>('get_car_by_id', { carId });
+            const carData = await invoke<Car | null>('get_car_by_id', { sessionToken: localStorage.getItem('sessionToken'), carId });
             if (carData) {
               setCars(prev => ({ ...prev, [carId]: carData }));
             }
@@ -284,7 +287,7 @@ const MasterDashboard: React.FC = () => {
     try {
       const [orders, clients, cars] = await invoke<[Order[], Client[], Car[]]>(
         'search_orders_clients_cars',
-        { query }
+        { sessionToken: localStorage.getItem('sessionToken'), query }
       );
 
       // Combine all results
@@ -322,7 +325,7 @@ const MasterDashboard: React.FC = () => {
     if ('phone' in item) { // Это клиент
       // Загрузим автомобили клиента
       try {
-        const carsForClient = await invoke<Car[]>('get_cars_by_client_id', { clientId: item.id });
+        const carsForClient = await invoke<Car[]>('get_cars_by_client_id', { sessionToken: localStorage.getItem('sessionToken'), clientId: item.id });
         setClientCars(carsForClient);
         setSelectedClientForCars(item as Client);
         setShowClientCarsModal(true);
@@ -334,7 +337,7 @@ const MasterDashboard: React.FC = () => {
     } else if ('license_plate' in item) { // Это автомобиль
       // Загрузим информацию о клиенте напрямую из базы данных
       try {
-        const client = await invoke<Client | null>('get_client_by_id', { clientId: (item as Car).client_id });
+        const client = await invoke<Client | null>('get_client_by_id', { sessionToken: localStorage.getItem('sessionToken'), clientId: (item as Car).client_id });
         handleCreateNewOrder(client, item as Car);
       } catch (error) {
         console.error(`Error loading client for car ${item.id}:`, error);
@@ -374,7 +377,7 @@ const MasterDashboard: React.FC = () => {
   // Загрузка неисправностей для заказа
   const loadOrderDefects = async (orderId: number) => {
     try {
-      const defects = await invoke<any[]>('get_diagnostic_results_by_order_id', { orderId });
+      const defects = await invoke<any[]>('get_diagnostic_results_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId });
       setOrderDefects(defects);
     } catch (error) {
       console.error(`Error loading defects for order ${orderId}:`, error);
@@ -385,7 +388,7 @@ const MasterDashboard: React.FC = () => {
   // Загрузка работ для заказа
   const loadOrderWorks = async (orderId: number) => {
     try {
-      const works = await invoke<any[]>('get_order_works_by_order_id', { orderId });
+      const works = await invoke<any[]>('get_order_works_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId });
       setOrderWorks(works);
     } catch (error) {
       console.error(`Error loading works for order ${orderId}:`, error);
@@ -396,7 +399,7 @@ const MasterDashboard: React.FC = () => {
   // Загрузка запчастей для заказа
   const loadOrderParts = async (orderId: number) => {
     try {
-      const parts = await invoke<any[]>('get_order_parts_by_order_id', { orderId });
+      const parts = await invoke<any[]>('get_order_parts_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId });
       setOrderParts(parts);
     } catch (error) {
       console.error(`Error loading parts for order ${orderId}:`, error);
@@ -412,9 +415,9 @@ const MasterDashboard: React.FC = () => {
       // Загружаем данные для модального окна согласования или назначения работников
       try {
         const [defects, works, parts] = await Promise.all([
-          invoke<any[]>('get_diagnostic_results_by_order_id', { orderId: order.id }),
-          invoke<any[]>('get_order_works_by_order_id', { orderId: order.id }),
-          invoke<any[]>('get_order_parts_by_order_id', { orderId: order.id })
+          invoke<any[]>('get_diagnostic_results_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: order.id }),
+          invoke<any[]>('get_order_works_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: order.id }),
+          invoke<any[]>('get_order_parts_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: order.id })
         ]);
 
         setOrderDefects(defects);
@@ -434,10 +437,20 @@ const MasterDashboard: React.FC = () => {
         console.error('Error loading order data:', error);
         setIsModalOpen(true);
       }
+    } else if (order.status === 'Quality_Control') {
+      // Заказ после выполнения работ - мастер проходит чек-лист и принимает работы или возвращает их на переделку
+      try {
+        const works = await invoke<any[]>('get_order_works_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: order.id });
+        setOrderWorks(works);
+        setShowQualityControlModal(true);
+      } catch (error) {
+        console.error('Error loading order works for quality control:', error);
+        alert('Ошибка при загрузке работ заказа: ' + error);
+      }
     } else if (order.status === 'Ready') {
       // Для заказов в статусе "Ready" открываем модальное окно завершения
-      const client = await invoke<Client | null>('get_client_by_id', { clientId: order.client_id });
-      const car = await invoke<Car | null>('get_car_by_id', { carId: order.car_id });
+      const client = await invoke<Client | null>('get_client_by_id', { sessionToken: localStorage.getItem('sessionToken'), clientId: order.client_id });
+      const car = await invoke<Car | null>('get_car_by_id', { sessionToken: localStorage.getItem('sessionToken'), carId: order.car_id });
 
       setSelectedClientForFinalProcessing(client);
       setSelectedCarForFinalProcessing(car);
@@ -487,6 +500,7 @@ const MasterDashboard: React.FC = () => {
       // Немедленно вызываем с новыми значениями
       try {
         const archivedOrdersData = await invoke<Order[]>('get_archived_orders', {
+          sessionToken: localStorage.getItem('sessionToken'),
           statusFilter: newArchiveFilter.status,
           periodStart: newArchiveFilter.periodStart,
           periodEnd: newArchiveFilter.periodEnd,
@@ -503,6 +517,7 @@ const MasterDashboard: React.FC = () => {
   const loadArchivedOrders = async () => {
     try {
       const archivedOrdersData = await invoke<Order[]>('get_archived_orders', {
+        sessionToken: localStorage.getItem('sessionToken'),
         statusFilter: archiveFilter.status,
         periodStart: archiveFilter.periodStart,
         periodEnd: archiveFilter.periodEnd,
@@ -704,7 +719,7 @@ const MasterDashboard: React.FC = () => {
                             {getStatusEmoji(order.status)} {order.status}
                           </span>
                         </td>
-                        <td>{order.total_amount || '----'}</td>
+                        <td>{formatMoney(order.total_amount, '----')}</td>
                       </tr>
                     );
                   })}
@@ -799,7 +814,7 @@ const MasterDashboard: React.FC = () => {
                           {getStatusEmoji(order.status)} {order.status}
                         </span>
                       </td>
-                      <td>{order.total_amount || '----'}</td>
+                      <td>{formatMoney(order.total_amount, '----')}</td>
                     </tr>
                   );
                 })}
@@ -846,7 +861,7 @@ const MasterDashboard: React.FC = () => {
                 console.log('MasterDashboard: Opening worker assignment modal (works:', confirmedWorks.length, ', parts:', confirmedParts.length, ')');
                 try {
                   // Перезагружаем данные о работах из базы данных
-                  const updatedWorks = await invoke<any[]>('get_order_works_by_order_id', { orderId: selectedOrder!.id });
+                  const updatedWorks = await invoke<any[]>('get_order_works_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: selectedOrder!.id });
                   console.log('MasterDashboard: Updated works from DB:', updatedWorks);
 
                   // Фильтруем только подтвержденные работы
@@ -867,7 +882,7 @@ const MasterDashboard: React.FC = () => {
             }, 100);
           }}
           onRejectAll={async () => {
-            // Обработка отказа от всего - изменяем статус заказа на "Closed"
+            // Обработка отказа от всего - отменяем заказ с причиной "Клиент отказался"
             // В реальной системе, возможно, нужно будет учесть оплату за диагностику
             try {
               // Получаем токен сессии из localStorage
@@ -877,12 +892,13 @@ const MasterDashboard: React.FC = () => {
                 return;
               }
 
-              await invoke('update_order_status', {
+              await invoke('cancel_order', {
                 sessionToken,
                 orderId: selectedOrder.id,
-                newStatus: 'Closed'
+                reason: 'Client_Refused',
+                comment: 'Клиент отказался от всех работ и запчастей при согласовании'
               });
-              console.log('Order rejected by client and status updated to Closed');
+              console.log('Order rejected by client and cancelled');
               setShowClientApprovalModal(false);
               loadOrders(); // Перезагружаем список заказов
             } catch (error) {
@@ -911,6 +927,19 @@ const MasterDashboard: React.FC = () => {
       )}
 
       {/* Модальное окно завершения заказа */}
+      {showQualityControlModal && selectedOrder && (
+        <QualityControlModal
+          isOpen={showQualityControlModal}
+          order={selectedOrder}
+          works={orderWorks}
+          onClose={() => setShowQualityControlModal(false)}
+          onInspectionSaved={() => {
+            setShowQualityControlModal(false);
+            loadOrders();
+          }}
+        />
+      )}
+
       {showFinalProcessingModal && selectedOrder && selectedClientForFinalProcessing && selectedCarForFinalProcessing && (
         <FinalProcessingModal
           isOpen={showFinalProcessingModal}
@@ -50,7 +50,7 @@ const NewCarModal: React.FC<NewCarModalProps> = ({ isOpen, onClose, onCarCreated
   const loadClients = async () => {
     try {
       setLoadingClients(true);
-      const clientList: Client[] = await invoke('get_all_clients');
+      const clientList: Client[] = await invoke('get_all_clients', { sessionToken: localStorage.getItem('sessionToken') });
       setClients(clientList);
     } catch (error) {
       console.error('Error loading clients:', error);
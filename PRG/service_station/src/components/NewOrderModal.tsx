@@ -1,6 +1,7 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './NewOrderModal.css';
+import { moneyInput } from '../types/money';
 
 interface Client {
   id: number;
@@ -40,6 +41,8 @@ const NewOrderModal: React.FC<NewOrderModalProps> = ({
 }) => {
   const [mileage, setMileage] = useState(car?.mileage.toString() || '');
   const [complaint, setComplaint] = useState('');
+  const [prepayment, setPrepayment] = useState('');
+  const [prepaymentMethod, setPrepaymentMethod] = useState<'Cash' | 'Card' | 'Transfer'>('Cash');
   const [previousMileage, setPreviousMileage] = useState(car?.mileage || 0);
   const [dateError, setDateError] = useState('');
   const [loading, setLoading] = useState(false);
@@ -88,6 +91,24 @@ const NewOrderModal: React.FC<NewOrderModalProps> = ({
       });
 
       console.log('Order creation result:', result);
+
+      // Предоплата регистрируется отдельным платежом по созданному заказу
+      const prepaymentAmount = moneyInput(prepayment);
+      const orderId = parseInt(result.match(/ID: (\d+)/)?.[1] || '');
+      if (parseFloat(prepaymentAmount) > 0 && orderId) {
+        try {
+          await invoke('register_prepayment', {
+            sessionToken,
+            orderId,
+            method: prepaymentMethod,
+            amount: prepaymentAmount,
+            comment: null
+          });
+        } catch (error) {
+          alert(`Заказ #${orderId} создан, но предоплата не зарегистрирована: ${error}`);
+        }
+      }
+
       onOrderCreated();
       onClose();
     } catch (error) {
@@ -165,6 +186,27 @@ const NewOrderModal: React.FC<NewOrderModalProps> = ({
             />
           </div>
 
+          <div className="order-prepayment">
+            <label htmlFor="prepayment">💵 ПРЕДОПЛАТА:</label>
+            <input
+              id="prepayment"
+              type="number"
+              min="0"
+              step="0.01"
+              value={prepayment}
+              onChange={(e) => setPrepayment(e.target.value)}
+              placeholder="0.00"
+            />
+            <select
+              value={prepaymentMethod}
+              onChange={(e) => setPrepaymentMethod(e.target.value as 'Cash' | 'Card' | 'Transfer')}
+            >
+              <option value="Cash">Наличные</option>
+              <option value="Card">Карта</option>
+              <option value="Transfer">Безналичный (Р/С)</option>
+            </select>
+          </div>
+
           <div className="modal-actions">
             <button type="submit" className="create-btn" disabled={loading || !client || !car}>
               {loading ? 'СОЗДАНИЕ...' : '🚀 СОЗДАТЬ ЗАКАЗ'}
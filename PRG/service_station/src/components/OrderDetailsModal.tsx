@@ -1,5 +1,7 @@
-import React from 'react';
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import './OrderDetailsModal.css';
+import { Money, formatMoney } from '../types/money';
 
 interface Order {
   id: number;
@@ -10,8 +12,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -52,8 +54,25 @@ const OrderDetailsModal: React.FC<OrderDetailsModalProps> = ({
   isOpen,
   onClose
 }) => {
+  const [documentPath, setDocumentPath] = useState<string | null>(null);
+
   if (!isOpen || !order) return null;
 
+  // Формируем PDF на стороне Rust; файл сохраняется в папку документов из настроек
+  const handleGenerateDocument = async (documentType: 'Intake' | 'WorkOrder') => {
+    try {
+      const path = await invoke<string>('generate_order_document', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        documentType
+      });
+      setDocumentPath(path);
+    } catch (error) {
+      console.error('Error generating document:', error);
+      alert('Ошибка при формировании документа: ' + error);
+    }
+  };
+
   // Статусы заказов с эмодзи
   const getStatusEmoji = (status: string) => {
     switch (status) {
@@ -118,10 +137,17 @@ const OrderDetailsModal: React.FC<OrderDetailsModalProps> = ({
           <div className="order-payment">
             <h3>💰 ФИНАНСОВАЯ ИНФОРМАЦИЯ:</h3>
             <div className="payment-details">
-              <p>Предоплата: {order.prepayment || '0 $'}</p>
-              <p>Итоговая сумма: {order.total_amount || '---- $'}</p>
+              <p>Предоплата: {formatMoney(order.prepayment, '0.00')}</p>
+              <p>Итоговая сумма: {formatMoney(order.total_amount, '----')}</p>
             </div>
           </div>
+
+          <div className="order-documents">
+            <h3>🖨️ ДОКУМЕНТЫ:</h3>
+            <button className="document-btn" onClick={() => handleGenerateDocument('Intake')}>АКТ ПРИЁМКИ</button>
+            <button className="document-btn" onClick={() => handleGenerateDocument('WorkOrder')}>ЗАКАЗ-НАРЯД</button>
+            {documentPath && <p className="document-path">Сохранено: {documentPath}</p>}
+          </div>
         </div>
       </div>
     </div>
@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './OrderExecutionModal.css';
 import { User } from '../types/user';
+import { Money } from '../types/money';
 
 // Define TypeScript interfaces
 interface WorkItem {
@@ -9,10 +10,11 @@ interface WorkItem {
   order_id: number;
   service_id?: number;
   service_name_snapshot: string;
-  price: string; // Changed to string for DECIMAL compatibility
+  price: Money;
   worker_id?: number | null;
   status: string;
   is_confirmed: boolean;
+  worked_seconds: number;
 }
 
 interface Part {
@@ -21,7 +23,7 @@ interface Part {
   warehouse_item_id?: number | null;
   part_name_snapshot: string;
   brand: string;
-  price_per_unit: string; // Changed to string for DECIMAL compatibility
+  price_per_unit: Money;
   quantity: number;
   is_confirmed: boolean;
 }
@@ -44,8 +46,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -65,48 +67,50 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
   const [selectedWork, setSelectedWork] = useState<number | null>(null);
   const [showDiagnosticReport, setShowDiagnosticReport] = useState<boolean>(false);
 
-  useEffect(() => {
-    const fetchOrderDetails = async () => {
-      try {
-        // Получаем ID текущего пользователя из сессии
-        const sessionToken = localStorage.getItem('sessionToken');
-        if (!sessionToken) {
-          throw new Error('Session token not found');
-        }
+  const fetchOrderDetails = async () => {
+    try {
+      // Получаем ID текущего пользователя из сессии
+      const sessionToken = localStorage.getItem('sessionToken');
+      if (!sessionToken) {
+        throw new Error('Session token not found');
+      }
 
-        const userData = await invoke<User | null>('get_user_session', { sessionToken });
-        if (!userData) {
-          throw new Error('User data not found');
-        }
+      const userData = await invoke<User | null>('get_user_session', { sessionToken });
+      if (!userData) {
+        throw new Error('User data not found');
+      }
 
-        const workerId = userData.id;
+      const workerId = userData.id;
 
-        // Получаем детали заказа для работника
-        const [orderData, worksData, partsData, defectsData] =
-          await invoke<[Order, WorkItem[], Part[], Defect[]]>('get_order_details_for_worker', {
-            orderId,
-            workerId
-          });
+      // Получаем детали заказа для работника
+      const [orderData, worksData, partsData, defectsData] =
+        await invoke<[Order, WorkItem[], Part[], Defect[]]>('get_order_details_for_worker', {
+          sessionToken: localStorage.getItem('sessionToken'),
+          orderId,
+          workerId
+        });
 
-        setOrder(orderData);
-        setWorks(worksData);
-        setParts(partsData);
-        setDefects(defectsData);
-      } catch (error) {
-        console.error('Error fetching order details:', error);
-        alert('Ошибка при загрузке данных заказа: ' + error);
-      } finally {
-        setLoading(false);
-      }
-    };
+      setOrder(orderData);
+      setWorks(worksData);
+      setParts(partsData);
+      setDefects(defectsData);
+    } catch (error) {
+      console.error('Error fetching order details:', error);
+      alert('Ошибка при загрузке данных заказа: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
 
+  useEffect(() => {
     fetchOrderDetails();
   }, [orderId]);
 
   const getStatusIcon = (status: string) => {
     const statusMap: Record<string, string> = {
       'Completed': '✅',
-      'In Progress': '🔄',
+      'In_Progress': '🔄',
+      'Paused': '⏸️',
       'Pending': '⏳',
       'Received': '✅',
       'Ordered': '⏳',
@@ -120,7 +124,8 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
   const getStatusText = (status: string) => {
     const statusMap: Record<string, string> = {
       'Completed': 'Готово',
-      'In Progress': 'В работе',
+      'In_Progress': 'В работе',
+      'Paused': 'Приостановлена',
       'Pending': 'Ожидание',
       'Received': 'Получено',
       'Ordered': 'Заказано',
@@ -131,40 +136,34 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
     return statusMap[status] || status;
   };
 
-  const toggleWorkStatus = (id: number) => {
-    setWorks(works.map(work =>
-      work.id === id
-        ? {
-            ...work,
-            status: work.status === 'Done' ? 'Pending' : 'Done'
-          }
-        : work
-    ));
+  // Учтённое время по работе: "1 ч 05 мин"
+  const formatDuration = (seconds: number) => {
+    const minutes = Math.floor(seconds / 60);
+    const hours = Math.floor(minutes / 60);
+    return hours > 0 ? `${hours} ч ${String(minutes % 60).padStart(2, '0')} мин` : `${minutes} мин`;
   };
 
-  const handleStartWork = () => {
-    if (selectedWork !== null) {
-      setWorks(works.map(work =>
-        work.id === selectedWork
-          ? { ...work, status: 'In Progress' }
-          : work
-      ));
+  // Начало, пауза, возобновление и завершение работы фиксируются на бэкенде вместе с интервалами времени
+  const changeWorkState = async (command: 'start_work' | 'pause_work' | 'resume_work' | 'finish_work') => {
+    if (selectedWork === null) return;
+    try {
+      await invoke(command, {
+        sessionToken: localStorage.getItem('sessionToken'),
+        workId: selectedWork
+      });
+      await fetchOrderDetails();
+    } catch (error) {
+      console.error('Error changing work state:', error);
+      alert('Ошибка при изменении статуса работы: ' + error);
     }
   };
 
-  const handleMarkCompleted = () => {
-    if (selectedWork !== null) {
-      setWorks(works.map(work =>
-        work.id === selectedWork
-          ? { ...work, status: 'Done' }
-          : work
-      ));
-    }
-  };
+  const selectedWorkStatus = works.find(work => work.id === selectedWork)?.status;
 
   const handleFinishOrder = async () => {
     // Check if all works are completed
-    const allCompleted = works.every(work => work.status === 'Done' || work.status === 'Completed');
+    // Отменённые работы не выполняются и не мешают сдаче заказа
+    const allCompleted = works.every(work => work.status === 'Done' || work.status === 'Cancelled');
 
     if (allCompleted) {
       try {
@@ -175,13 +174,13 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
           return;
         }
 
-        // Обновляем статус заказа на 'Ready' - готов к выдаче, ожидает мастера
+        // Обновляем статус заказа на 'Quality_Control' - мастер проверяет работу перед выдачей
         await invoke('update_order_status', {
           sessionToken,
           orderId: order!.id,
-          newStatus: 'Ready' // заказ готов к выдаче
+          newStatus: 'Quality_Control' // заказ передан на контроль качества
         });
-        alert(`Заказ ${orderId} завершен и готов к выдаче!`);
+        alert(`Заказ ${orderId} завершен и передан мастеру на контроль качества!`);
 
         // Вызываем обратный вызов для обновления списка заказов в родительском компоненте
         if (onOrderComplete) {
@@ -230,6 +229,7 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
                     <th></th>
                     <th>Наименование работ</th>
                     <th>Статус</th>
+                    <th>Время</th>
                   </tr>
                 </thead>
                 <tbody>
@@ -242,8 +242,8 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
                       <td>
                         <input
                           type="checkbox"
-                          checked={work.status === 'Done' || work.status === 'Completed'}
-                          onChange={() => toggleWorkStatus(work.id)}
+                          checked={work.status === 'Done'}
+                          readOnly
                         />
                       </td>
                       <td>{work.service_name_snapshot}</td>
@@ -252,6 +252,7 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
                           {getStatusIcon(work.status)} {getStatusText(work.status)}
                         </span>
                       </td>
+                      <td>{formatDuration(work.worked_seconds)}</td>
                     </tr>
                   ))}
                 </tbody>
@@ -291,10 +292,23 @@ const OrderExecutionModal: React.FC<OrderExecutionModalProps> = ({ orderId, onCl
         </div>
 
         <div className="modal-actions">
-          <button className="action-button" onClick={handleStartWork} disabled={selectedWork === null}>
-            ▶️ НАЧАТЬ РАБОТУ
+          {selectedWorkStatus === 'Paused' ? (
+            <button className="action-button" onClick={() => changeWorkState('resume_work')}>
+              ▶️ ПРОДОЛЖИТЬ
+            </button>
+          ) : (
+            <button className="action-button" onClick={() => changeWorkState('start_work')} disabled={selectedWorkStatus !== 'Pending'}>
+              ▶️ НАЧАТЬ РАБОТУ
+            </button>
+          )}
+          <button className="action-button" onClick={() => changeWorkState('pause_work')} disabled={selectedWorkStatus !== 'In_Progress'}>
+            ⏸️ ПАУЗА
           </button>
-          <button className="action-button" onClick={handleMarkCompleted} disabled={selectedWork === null}>
+          <button
+            className="action-button"
+            onClick={() => changeWorkState('finish_work')}
+            disabled={selectedWorkStatus !== 'In_Progress' && selectedWorkStatus !== 'Paused'}
+          >
             ✅ ОТМЕТИТЬ ВЫПОЛНЕННЫМ
           </button>
           <button className="action-button" onClick={() => setShowDiagnosticReport(true)}>
@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './PartsSearchModal.css';
+import { Money, formatMoney } from '../types/money';
 
 interface Part {
   id: number;
@@ -8,8 +9,10 @@ interface Part {
   brand: string;
   supplier: string;
   article: string;
-  price: number;
+  price: Money;
   availability: string; // срок поставки
+  is_analog: boolean; // найдено по справочнику кросс-номеров
+  in_stock: boolean; // есть на нашем складе
   selected: boolean;
 }
 
@@ -39,6 +42,7 @@ const PartsSearchModal: React.FC<PartsSearchModalProps> = ({
     try {
       // Вызов команды из Rust для поиска запчастей
       const searchResults: any[] = await invoke('search_parts_by_vin', {
+        sessionToken: localStorage.getItem('sessionToken'),
         vin: vin,
         query: searchQuery
       });
@@ -52,6 +56,8 @@ const PartsSearchModal: React.FC<PartsSearchModalProps> = ({
         article: part.article,
         price: part.price,
         availability: part.availability,
+        is_analog: part.is_analog,
+        in_stock: part.in_stock,
         selected: false
       }));
 
@@ -166,11 +172,15 @@ const PartsSearchModal: React.FC<PartsSearchModalProps> = ({
                       </td>
                       <td>
                         <div>{part.name}</div>
-                        <div className="part-brand">{part.brand}</div>
+                        <div className="part-brand">
+                          {part.brand}
+                          {part.is_analog && <span className="part-badge analog-badge">АНАЛОГ</span>}
+                          {part.in_stock && <span className="part-badge stock-badge">НА СКЛАДЕ</span>}
+                        </div>
                       </td>
                       <td>{part.supplier}</td>
                       <td>{part.article}</td>
-                      <td>{part.price.toFixed(2)}</td>
+                      <td>{formatMoney(part.price)}</td>
                       <td>{part.availability}</td>
                     </tr>
                   ))}
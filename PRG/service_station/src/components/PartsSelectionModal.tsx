@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './PartsSelectionModal.css';
 import PartsSearchModal from './PartsSearchModal';
+import { Money, formatMoney } from '../types/money';
 
 interface Order {
   id: number;
@@ -12,8 +13,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -45,7 +46,7 @@ interface PartSuggestion {
   name: string;
   brand: string;
   supplier: string;
-  price: number;
+  price: Money;
   availability: string;
   part_number: string;
   selected?: boolean;
@@ -71,6 +72,8 @@ const PartsSelectionModal: React.FC<PartsSelectionModalProps> = ({
   const [partSuggestions, setPartSuggestions] = useState<PartSuggestion[]>([]);
   const [loading, setLoading] = useState(false);
   const [showPartsSearchModal, setShowPartsSearchModal] = useState(false);
+  // Альтернативные предложения (другие бренды и поставщики) по ID строки подбора
+  const [alternatives, setAlternatives] = useState<Record<number, PartSuggestion[]>>({});
 
   useEffect(() => {
     if (isOpen) {
@@ -101,8 +104,10 @@ const PartsSelectionModal: React.FC<PartsSelectionModalProps> = ({
   };
 
   const handleAddSelectedParts = (parts: any[]) => {
-    const newParts = parts.map(part => ({
-      id: part.id,
+    // ID результатов поиска уникальны только в пределах одного поиска
+    const baseId = Date.now();
+    const newParts = parts.map((part, index) => ({
+      id: baseId + index,
       name: part.name,
       brand: part.brand,
       supplier: part.supplier,
@@ -124,6 +129,49 @@ const PartsSelectionModal: React.FC<PartsSelectionModalProps> = ({
     setPartSuggestions(prev => prev.filter(part => part.id !== id));
   };
 
+  // Ищем по названию запчасти предложения других брендов и поставщиков на складе и в прайс-листах
+  const handleShowAlternatives = async (part: PartSuggestion) => {
+    if (alternatives[part.id]) {
+      setAlternatives(prev => {
+        const { [part.id]: _, ...rest } = prev;
+        return rest;
+      });
+      return;
+    }
+
+    try {
+      const results: any[] = await invoke('search_parts_by_vin', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        vin: car?.vin || '',
+        query: part.name
+      });
+      const offers = results
+        .filter(offer => !(offer.brand === part.brand && offer.article === part.part_number))
+        .map((offer, index) => ({
+          id: Date.now() + index,
+          name: offer.name,
+          brand: offer.brand,
+          supplier: offer.supplier,
+          price: offer.price,
+          availability: offer.availability,
+          part_number: offer.article,
+          selected: true
+        }));
+      setAlternatives(prev => ({ ...prev, [part.id]: offers }));
+    } catch (error) {
+      console.error('Ошибка при поиске альтернатив:', error);
+      alert('Ошибка при поиске альтернатив: ' + error);
+    }
+  };
+
+  const handleReplacePart = (id: number, replacement: PartSuggestion) => {
+    setPartSuggestions(prev => prev.map(part => (part.id === id ? replacement : part)));
+    setAlternatives(prev => {
+      const { [id]: _, ...rest } = prev;
+      return rest;
+    });
+  };
+
   if (!isOpen) return null;
 
   return (
@@ -163,31 +211,65 @@ const PartsSelectionModal: React.FC<PartsSelectionModalProps> = ({
                 </thead>
                 <tbody>
                   {partSuggestions.map(part => (
-                    <tr key={part.id}>
-                      <td>
-                        <div>{part.name}</div>
-                        <div className="part-brand">{part.brand}</div>
-                      </td>
-                      <td>{part.supplier}</td>
-                      <td>{part.price.toFixed(2)}</td>
-                      <td>{part.availability}</td>
-                      <td>{part.part_number}</td>
-                      <td>
-                        <input
-                          type="checkbox"
-                          checked={!!part.selected}
-                          onChange={() => handlePartToggle(part.id)}
-                        />
-                      </td>
-                      <td>
-                        <button
-                          className="remove-part-btn"
-                          onClick={() => handleRemovePart(part.id)}
-                        >
-                          УДАЛИТЬ
-                        </button>
-                      </td>
-                    </tr>
+                    <React.Fragment key={part.id}>
+                      <tr>
+                        <td>
+                          <div>{part.name}</div>
+                          <div className="part-brand">{part.brand}</div>
+                        </td>
+                        <td>{part.supplier}</td>
+                        <td>{formatMoney(part.price)}</td>
+                        <td>{part.availability}</td>
+                        <td>{part.part_number}</td>
+                        <td>
+                          <input
+                            type="checkbox"
+                            checked={!!part.selected}
+                            onChange={() => handlePartToggle(part.id)}
+                          />
+                        </td>
+                        <td>
+                          <button
+                            className="remove-part-btn"
+                            onClick={() => handleShowAlternatives(part)}
+                          >
+                            АЛЬТЕРНАТИВЫ
+                          </button>
+                          <button
+                            className="remove-part-btn"
+                            onClick={() => handleRemovePart(part.id)}
+                          >
+                            УДАЛИТЬ
+                          </button>
+                        </td>
+                      </tr>
+                      {alternatives[part.id] && (
+                        alternatives[part.id].length > 0 ? (
+                          alternatives[part.id].map(alt => (
+                            <tr key={alt.id} className="alternative-row">
+                              <td>
+                                <div>↳ {alt.name}</div>
+                                <div className="part-brand">{alt.brand}</div>
+                              </td>
+                              <td>{alt.supplier}</td>
+                              <td>{formatMoney(alt.price)}</td>
+                              <td>{alt.availability}</td>
+                              <td>{alt.part_number}</td>
+                              <td></td>
+                              <td>
+                                <button className="remove-part-btn" onClick={() => handleReplacePart(part.id, alt)}>
+                                  ЗАМЕНИТЬ
+                                </button>
+                              </td>
+                            </tr>
+                          ))
+                        ) : (
+                          <tr className="alternative-row">
+                            <td colSpan={7}>Альтернатив не найдено</td>
+                          </tr>
+                        )
+                      )}
+                    </React.Fragment>
                   ))}
                 </tbody>
               </table>
@@ -0,0 +1,367 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './ReportGenerator.css';
+import { Money, formatMoney, moneyInput, moneyValue } from '../types/money';
+
+type PayScheme = 'Labour_Share' | 'Norm_Hour_Rate' | 'Salary_Bonus';
+
+interface WorkerPayScheme {
+  worker_id: number;
+  worker_name: string;
+  role: string;
+  scheme: PayScheme | null;
+  labour_share_percent: string;
+  norm_hour_rate: Money;
+  salary: Money;
+  bonus_percent: string;
+  updated_at: string | null;
+}
+
+interface PayrollWork {
+  work_id: number;
+  order_id: number;
+  service_name: string;
+  finished_at: string;
+  norm_hours: string;
+  actual_hours: string;
+  efficiency_percent: string | null;
+  labour_amount: Money;
+  earnings: Money;
+}
+
+interface WorkerPayroll {
+  worker_id: number;
+  worker_name: string;
+  scheme: PayScheme | null;
+  works: PayrollWork[];
+  norm_hours: string;
+  actual_hours: string;
+  efficiency_percent: string | null;
+  labour_amount: Money;
+  salary: Money;
+  earnings: Money;
+}
+
+interface PayrollStatementData {
+  date_from: string;
+  date_to: string;
+  workers: WorkerPayroll[];
+  total_earnings: Money;
+}
+
+const SCHEME_LABELS: Record<PayScheme, string> = {
+  Labour_Share: 'Процент от работ',
+  Norm_Hour_Rate: 'Ставка за нормо-час',
+  Salary_Bonus: 'Оклад + премия'
+};
+
+// Поля формы схемы оплаты хранятся строками, как их ввёл пользователь
+interface SchemeForm {
+  scheme: PayScheme;
+  labourSharePercent: string;
+  normHourRate: string;
+  salary: string;
+  bonusPercent: string;
+}
+
+const toDateInput = (date: Date) =>
+  `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, '0')}-${String(date.getDate()).padStart(2, '0')}`;
+
+const PayrollStatement: React.FC = () => {
+  const now = new Date();
+  const [dateRange, setDateRange] = useState({
+    startDate: toDateInput(new Date(now.getFullYear(), now.getMonth(), 1)),
+    endDate: toDateInput(new Date(now.getFullYear(), now.getMonth() + 1, 0))
+  });
+  const [schemes, setSchemes] = useState<WorkerPayScheme[]>([]);
+  const [statement, setStatement] = useState<PayrollStatementData | null>(null);
+  const [loading, setLoading] = useState(false);
+  const [editingWorker, setEditingWorker] = useState<WorkerPayScheme | null>(null);
+  const [schemeForm, setSchemeForm] = useState<SchemeForm | null>(null);
+  const [exportPath, setExportPath] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadSchemes();
+  }, []);
+
+  const loadSchemes = async () => {
+    try {
+      const result = await invoke<WorkerPayScheme[]>('get_pay_schemes', {
+        sessionToken: localStorage.getItem('sessionToken')
+      });
+      setSchemes(result);
+    } catch (error) {
+      console.error('Error loading pay schemes:', error);
+      alert('Ошибка при загрузке схем оплаты: ' + error);
+    }
+  };
+
+  const handleEditScheme = (worker: WorkerPayScheme) => {
+    setEditingWorker(worker);
+    setSchemeForm({
+      scheme: worker.scheme || 'Labour_Share',
+      labourSharePercent: worker.labour_share_percent,
+      normHourRate: worker.norm_hour_rate.amount,
+      salary: worker.salary.amount,
+      bonusPercent: worker.bonus_percent
+    });
+  };
+
+  const handleSaveScheme = async () => {
+    if (!editingWorker || !schemeForm) return;
+    try {
+      await invoke('set_pay_scheme', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        workerId: editingWorker.worker_id,
+        request: {
+          scheme: schemeForm.scheme,
+          labourSharePercent: moneyInput(schemeForm.labourSharePercent),
+          normHourRate: moneyInput(schemeForm.normHourRate),
+          salary: moneyInput(schemeForm.salary),
+          bonusPercent: moneyInput(schemeForm.bonusPercent)
+        }
+      });
+      setEditingWorker(null);
+      setSchemeForm(null);
+      loadSchemes();
+    } catch (error) {
+      console.error('Error saving pay scheme:', error);
+      alert('Ошибка при сохранении схемы оплаты: ' + error);
+    }
+  };
+
+  const handleGenerate = async () => {
+    setLoading(true);
+    setExportPath(null);
+    try {
+      const result = await invoke<PayrollStatementData>('get_payroll_statement', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        dateFrom: dateRange.startDate,
+        dateTo: dateRange.endDate
+      });
+      setStatement(result);
+    } catch (error) {
+      console.error('Error generating payroll statement:', error);
+      alert('Ошибка при расчёте ведомости: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // Ведомость сохраняется в CSV в папку документов из настроек
+  const handleExportCSV = async () => {
+    try {
+      const path = await invoke<string>('export_payroll_csv', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        dateFrom: dateRange.startDate,
+        dateTo: dateRange.endDate
+      });
+      setExportPath(path);
+    } catch (error) {
+      console.error('Error exporting payroll statement:', error);
+      alert('Ошибка при выгрузке ведомости: ' + error);
+    }
+  };
+
+  const schemeDetails = (worker: WorkerPayScheme) => {
+    switch (worker.scheme) {
+      case 'Labour_Share':
+        return `${worker.labour_share_percent}% от стоимости работ`;
+      case 'Norm_Hour_Rate':
+        return `${formatMoney(worker.norm_hour_rate)} за нормо-час`;
+      case 'Salary_Bonus':
+        return `${formatMoney(worker.salary)} в месяц + ${worker.bonus_percent}% от работ`;
+      default:
+        return '—';
+    }
+  };
+
+  return (
+    <div className="report-generator">
+      <div className="report-results">
+        <h3>Схемы оплаты</h3>
+        <div className="report-table-container">
+          <table className="report-table">
+            <thead>
+              <tr>
+                <th>Сотрудник</th>
+                <th>Схема</th>
+                <th>Условия</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {schemes.map(worker => (
+                <tr key={worker.worker_id}>
+                  <td>{worker.worker_name}</td>
+                  <td>{worker.scheme ? SCHEME_LABELS[worker.scheme] : 'Не задана'}</td>
+                  <td>{schemeDetails(worker)}</td>
+                  <td>
+                    <button className="export-btn csv-export" onClick={() => handleEditScheme(worker)}>
+                      Изменить
+                    </button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      </div>
+
+      {editingWorker && schemeForm && (
+        <div className="report-controls">
+          <div className="control-group">
+            <label>Схема оплаты ({editingWorker.worker_name}):</label>
+            <select
+              value={schemeForm.scheme}
+              onChange={(e) => setSchemeForm({ ...schemeForm, scheme: e.target.value as PayScheme })}
+            >
+              {(Object.keys(SCHEME_LABELS) as PayScheme[]).map(scheme => (
+                <option key={scheme} value={scheme}>{SCHEME_LABELS[scheme]}</option>
+              ))}
+            </select>
+          </div>
+          {schemeForm.scheme === 'Labour_Share' && (
+            <div className="control-group">
+              <label>Процент от стоимости работ:</label>
+              <input
+                type="text"
+                value={schemeForm.labourSharePercent}
+                onChange={(e) => setSchemeForm({ ...schemeForm, labourSharePercent: e.target.value })}
+              />
+            </div>
+          )}
+          {schemeForm.scheme === 'Norm_Hour_Rate' && (
+            <div className="control-group">
+              <label>Ставка за нормо-час:</label>
+              <input
+                type="text"
+                value={schemeForm.normHourRate}
+                onChange={(e) => setSchemeForm({ ...schemeForm, normHourRate: e.target.value })}
+              />
+            </div>
+          )}
+          {schemeForm.scheme === 'Salary_Bonus' && (
+            <>
+              <div className="control-group">
+                <label>Оклад за месяц:</label>
+                <input
+                  type="text"
+                  value={schemeForm.salary}
+                  onChange={(e) => setSchemeForm({ ...schemeForm, salary: e.target.value })}
+                />
+              </div>
+              <div className="control-group">
+                <label>Премия, % от стоимости работ:</label>
+                <input
+                  type="text"
+                  value={schemeForm.bonusPercent}
+                  onChange={(e) => setSchemeForm({ ...schemeForm, bonusPercent: e.target.value })}
+                />
+              </div>
+            </>
+          )}
+          <button className="generate-btn" onClick={handleSaveScheme}>Сохранить</button>
+          <button className="export-btn pdf-export" onClick={() => { setEditingWorker(null); setSchemeForm(null); }}>
+            Отмена
+          </button>
+        </div>
+      )}
+
+      <div className="report-controls">
+        <div className="control-group">
+          <label>Период:</label>
+          <div className="date-range">
+            <input
+              type="date"
+              value={dateRange.startDate}
+              onChange={(e) => setDateRange({ ...dateRange, startDate: e.target.value })}
+            />
+            <span> - </span>
+            <input
+              type="date"
+              value={dateRange.endDate}
+              onChange={(e) => setDateRange({ ...dateRange, endDate: e.target.value })}
+            />
+          </div>
+        </div>
+
+        <button className="generate-btn" onClick={handleGenerate} disabled={loading}>
+          {loading ? 'Расчёт...' : 'Рассчитать ведомость'}
+        </button>
+
+        <div className="export-buttons">
+          <button className="export-btn csv-export" onClick={handleExportCSV} disabled={!statement}>
+            Экспорт в CSV
+          </button>
+        </div>
+      </div>
+
+      {exportPath && <p>Ведомость сохранена: {exportPath}</p>}
+
+      {statement && (
+        <div className="report-results">
+          <h3>Ведомость за {statement.date_from} — {statement.date_to}</h3>
+          <div className="report-table-container">
+            <table className="report-table">
+              <thead>
+                <tr>
+                  <th>Заказ</th>
+                  <th>Работа</th>
+                  <th>Завершена</th>
+                  <th>Нормо-часы</th>
+                  <th>Факт. часы</th>
+                  <th>Выработка</th>
+                  <th>Стоимость работ</th>
+                  <th>Начислено</th>
+                </tr>
+              </thead>
+              <tbody>
+                {statement.workers.map(worker => (
+                  <React.Fragment key={worker.worker_id}>
+                    <tr>
+                      <td colSpan={8}>
+                        <strong>{worker.worker_name}</strong> ({worker.scheme ? SCHEME_LABELS[worker.scheme] : 'схема оплаты не задана'})
+                      </td>
+                    </tr>
+                    {worker.works.map(work => (
+                      <tr key={work.work_id}>
+                        <td>#{work.order_id}</td>
+                        <td>{work.service_name}</td>
+                        <td>{new Date(work.finished_at).toLocaleString()}</td>
+                        <td>{work.norm_hours}</td>
+                        <td>{work.actual_hours}</td>
+                        <td>{work.efficiency_percent ? `${work.efficiency_percent}%` : '—'}</td>
+                        <td>{formatMoney(work.labour_amount)}</td>
+                        <td>{formatMoney(work.earnings)}</td>
+                      </tr>
+                    ))}
+                    {moneyValue(worker.salary) > 0 && (
+                      <tr>
+                        <td></td>
+                        <td colSpan={6}>Оклад за период</td>
+                        <td>{formatMoney(worker.salary)}</td>
+                      </tr>
+                    )}
+                    <tr>
+                      <td></td>
+                      <td colSpan={2}><strong>Итого</strong></td>
+                      <td><strong>{worker.norm_hours}</strong></td>
+                      <td><strong>{worker.actual_hours}</strong></td>
+                      <td><strong>{worker.efficiency_percent ? `${worker.efficiency_percent}%` : '—'}</strong></td>
+                      <td><strong>{formatMoney(worker.labour_amount)}</strong></td>
+                      <td><strong>{formatMoney(worker.earnings)}</strong></td>
+                    </tr>
+                  </React.Fragment>
+                ))}
+              </tbody>
+            </table>
+          </div>
+          <h3>Всего к выплате: {formatMoney(statement.total_earnings)}</h3>
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default PayrollStatement;
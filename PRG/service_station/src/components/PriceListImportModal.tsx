@@ -0,0 +1,254 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './WarehouseStockModal.css';
+import './AddWarehouseItemModal.css';
+
+interface Supplier {
+  id: number;
+  name: string;
+}
+
+// Колонка задаётся названием из заголовка, буквой (A, B, ...) или номером
+interface PriceListMapping {
+  headerRow: number;
+  brand: string;
+  article: string;
+  name: string;
+  price: string;
+  deliveryDays: string;
+  quantity: string;
+}
+
+interface RejectedRow {
+  row: number;
+  reason: string;
+}
+
+interface ImportReport {
+  supplier_id: number;
+  total_rows: number;
+  imported: number;
+  rejected_count: number;
+  rejected: RejectedRow[];
+}
+
+const DEFAULT_MAPPING: PriceListMapping = {
+  headerRow: 1,
+  brand: 'Бренд',
+  article: 'Артикул',
+  name: 'Наименование',
+  price: 'Цена',
+  deliveryDays: 'Срок',
+  quantity: ''
+};
+
+const MAPPING_FIELDS: { key: keyof PriceListMapping; label: string; required?: boolean }[] = [
+  { key: 'article', label: 'Артикул', required: true },
+  { key: 'price', label: 'Цена', required: true },
+  { key: 'brand', label: 'Бренд' },
+  { key: 'name', label: 'Наименование' },
+  { key: 'deliveryDays', label: 'Срок поставки (дн.)' },
+  { key: 'quantity', label: 'Наличие у поставщика' }
+];
+
+interface PriceListImportModalProps {
+  isOpen: boolean;
+  onClose: () => void;
+}
+
+const PriceListImportModal: React.FC<PriceListImportModalProps> = ({ isOpen, onClose }) => {
+  const [suppliers, setSuppliers] = useState<Supplier[]>([]);
+  const [supplierId, setSupplierId] = useState<number | null>(null);
+  const [file, setFile] = useState<File | null>(null);
+  const [mapping, setMapping] = useState<PriceListMapping>(DEFAULT_MAPPING);
+  const [defaultDeliveryDays, setDefaultDeliveryDays] = useState(0);
+  const [report, setReport] = useState<ImportReport | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    if (isOpen) {
+      loadSuppliers();
+    }
+  }, [isOpen]);
+
+  const loadSuppliers = async () => {
+    try {
+      const result = await invoke<Supplier[]>('get_suppliers', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        includeInactive: false
+      });
+      setSuppliers(result);
+    } catch (error) {
+      console.error('Error loading suppliers:', error);
+    }
+  };
+
+  // Подставляем сопоставление колонок, сохранённое при прошлом импорте
+  const handleSupplierChange = async (id: number) => {
+    setSupplierId(id);
+    setReport(null);
+    try {
+      const saved = await invoke<Record<string, string | number | null> | null>('get_price_list_mapping', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        supplierId: id
+      });
+      // Необязательные колонки приходят как null
+      const savedFields = saved ? Object.fromEntries(Object.entries(saved).map(([key, value]) => [key, value ?? ''])) : {};
+      setMapping({ ...DEFAULT_MAPPING, ...savedFields } as PriceListMapping);
+    } catch (error) {
+      console.error('Error loading price list mapping:', error);
+      setMapping(DEFAULT_MAPPING);
+    }
+  };
+
+  const handleImport = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!supplierId || !file) {
+      alert('Выберите поставщика и файл прайс-листа');
+      return;
+    }
+
+    setLoading(true);
+    try {
+      const buffer = await file.arrayBuffer();
+      const result = await invoke<ImportReport>('import_price_list', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        supplierId,
+        fileName: file.name,
+        content: Array.from(new Uint8Array(buffer)),
+        mapping: {
+          ...mapping,
+          brand: mapping.brand || null,
+          name: mapping.name || null,
+          deliveryDays: mapping.deliveryDays || null,
+          quantity: mapping.quantity || null
+        },
+        defaultDeliveryDays
+      });
+      setReport(result);
+    } catch (error) {
+      console.error('Error importing price list:', error);
+      alert('Ошибка при импорте прайс-листа: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  if (!isOpen) return null;
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>📄 ИМПОРТ ПРАЙС-ЛИСТА</h2>
+          <button className="close-btn" onClick={onClose}>✖ ЗАКРЫТЬ</button>
+        </div>
+
+        <form onSubmit={handleImport} className="modal-body">
+          <div className="form-row">
+            <div className="form-group">
+              <label htmlFor="price_supplier">Поставщик:</label>
+              <select
+                id="price_supplier"
+                value={supplierId ?? ''}
+                onChange={(e) => handleSupplierChange(parseInt(e.target.value))}
+                required
+              >
+                <option value="" disabled>Выберите поставщика</option>
+                {suppliers.map(s => (
+                  <option key={s.id} value={s.id}>{s.name}</option>
+                ))}
+              </select>
+            </div>
+            <div className="form-group">
+              <label htmlFor="price_file">Файл (CSV, XLSX):</label>
+              <input
+                type="file"
+                id="price_file"
+                accept=".csv,.txt,.xlsx,.xls,.ods"
+                onChange={(e) => setFile(e.target.files?.[0] || null)}
+                required
+              />
+            </div>
+          </div>
+
+          <div className="form-row">
+            <div className="form-group">
+              <label htmlFor="header_row">Строка заголовка (0 - без заголовка):</label>
+              <input
+                type="number"
+                id="header_row"
+                min="0"
+                value={mapping.headerRow}
+                onChange={(e) => setMapping(prev => ({ ...prev, headerRow: parseInt(e.target.value) || 0 }))}
+              />
+            </div>
+            <div className="form-group">
+              <label htmlFor="default_days">Срок поставки по умолчанию (дн.):</label>
+              <input
+                type="number"
+                id="default_days"
+                min="0"
+                value={defaultDeliveryDays}
+                onChange={(e) => setDefaultDeliveryDays(parseInt(e.target.value) || 0)}
+              />
+            </div>
+          </div>
+
+          <p className="item-brand">Колонки указываются названием из заголовка, буквой (A, B, ...) или номером.</p>
+          <div className="form-row">
+            {MAPPING_FIELDS.map(field => (
+              <div className="form-group" key={field.key}>
+                <label htmlFor={`mapping_${field.key}`}>{field.label}:</label>
+                <input
+                  type="text"
+                  id={`mapping_${field.key}`}
+                  value={mapping[field.key]}
+                  onChange={(e) => setMapping(prev => ({ ...prev, [field.key]: e.target.value }))}
+                  required={field.required}
+                />
+              </div>
+            ))}
+          </div>
+
+          {report && (
+            <div className="warehouse-stock-table">
+              <h3>
+                Загружено {report.imported} из {report.total_rows} строк, отклонено {report.rejected_count}
+              </h3>
+              {report.rejected.length > 0 && (
+                <table>
+                  <thead>
+                    <tr>
+                      <th>Строка</th>
+                      <th>Причина</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {report.rejected.map(r => (
+                      <tr key={r.row}>
+                        <td>{r.row}</td>
+                        <td>{r.reason}</td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              )}
+              {report.rejected_count > report.rejected.length && (
+                <p className="item-brand">Показаны первые {report.rejected.length} отклонённых строк</p>
+              )}
+            </div>
+          )}
+
+          <div className="modal-actions">
+            <button type="submit" className="save-btn" disabled={loading}>
+              {loading ? 'Импорт...' : '📥 ИМПОРТИРОВАТЬ'}
+            </button>
+          </div>
+        </form>
+      </div>
+    </div>
+  );
+};
+
+export default PriceListImportModal;
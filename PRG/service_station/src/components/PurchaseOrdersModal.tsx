@@ -0,0 +1,354 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './WarehouseStockModal.css';
+import './AddWarehouseItemModal.css';
+import { Money, formatMoney } from '../types/money';
+
+interface PurchaseOrder {
+  id: number;
+  supplier_id: number;
+  supplier_name: string;
+  status: string;
+  expected_date: string | null;
+  comment: string | null;
+  created_by_name: string | null;
+  created_at: string;
+  sent_at: string | null;
+  received_at: string | null;
+  total: Money;
+}
+
+interface PurchaseOrderLine {
+  id: number;
+  warehouse_item_id: number | null;
+  order_part_id: number | null;
+  order_id: number | null;
+  name: string;
+  brand: string | null;
+  article: string | null;
+  quantity: number;
+  received_quantity: number;
+  unit_price: Money | null;
+}
+
+interface PurchaseOrderDetails {
+  order: PurchaseOrder;
+  lines: PurchaseOrderLine[];
+}
+
+const STATUS_LABELS: Record<string, string> = {
+  Draft: 'Черновик',
+  Sent: 'Отправлен',
+  Partially_Received: 'Получен частично',
+  Received: 'Получен'
+};
+
+interface PurchaseOrdersModalProps {
+  isOpen: boolean;
+  onClose: () => void;
+  onGoodsReceived?: () => void;
+}
+
+const PurchaseOrdersModal: React.FC<PurchaseOrdersModalProps> = ({ isOpen, onClose, onGoodsReceived }) => {
+  const [orders, setOrders] = useState<PurchaseOrder[]>([]);
+  const [statusFilter, setStatusFilter] = useState('');
+  const [details, setDetails] = useState<PurchaseOrderDetails | null>(null);
+  // Количество к приёмке и ячейка для новых позиций по ID строки
+  const [receiptQuantities, setReceiptQuantities] = useState<Record<number, number>>({});
+  const [receiptCells, setReceiptCells] = useState<Record<number, string>>({});
+  const [loading, setLoading] = useState(true);
+  const [showSupplierForm, setShowSupplierForm] = useState(false);
+  const [supplierForm, setSupplierForm] = useState({ name: '', contactPerson: '', phone: '', email: '', notes: '' });
+
+  useEffect(() => {
+    if (isOpen) {
+      loadOrders();
+    }
+  }, [isOpen, statusFilter]);
+
+  const loadOrders = async () => {
+    try {
+      setLoading(true);
+      const result = await invoke<PurchaseOrder[]>('get_purchase_orders', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        status: statusFilter || null
+      });
+      setOrders(result);
+    } catch (error) {
+      console.error('Error loading purchase orders:', error);
+      setOrders([]);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const openOrder = async (purchaseOrderId: number) => {
+    try {
+      const result = await invoke<PurchaseOrderDetails>('get_purchase_order', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        purchaseOrderId
+      });
+      setDetails(result);
+      // По умолчанию принимаем весь остаток по каждой строке
+      const quantities: Record<number, number> = {};
+      result.lines.forEach(line => {
+        quantities[line.id] = line.quantity - line.received_quantity;
+      });
+      setReceiptQuantities(quantities);
+      setReceiptCells({});
+    } catch (error) {
+      console.error('Error loading purchase order:', error);
+      alert('Ошибка при загрузке заказа поставщику: ' + error);
+    }
+  };
+
+  const runAction = async (command: string, confirmMessage?: string) => {
+    if (!details) return;
+    if (confirmMessage && !confirm(confirmMessage)) return;
+
+    try {
+      await invoke(command, {
+        sessionToken: localStorage.getItem('sessionToken'),
+        purchaseOrderId: details.order.id
+      });
+      if (command === 'delete_purchase_order') {
+        setDetails(null);
+      } else {
+        await openOrder(details.order.id);
+      }
+      loadOrders();
+    } catch (error) {
+      console.error(`Error running ${command}:`, error);
+      alert('Ошибка: ' + error);
+    }
+  };
+
+  const handleReceive = async () => {
+    if (!details) return;
+
+    const items = details.lines
+      .filter(line => (receiptQuantities[line.id] || 0) > 0)
+      .map(line => ({
+        lineId: line.id,
+        quantity: receiptQuantities[line.id],
+        locationCell: receiptCells[line.id] || null
+      }));
+
+    if (items.length === 0) {
+      alert('Укажите количество хотя бы для одной позиции');
+      return;
+    }
+
+    try {
+      const result = await invoke<string>('receive_purchase_order', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        purchaseOrderId: details.order.id,
+        items
+      });
+      alert(result);
+      await openOrder(details.order.id);
+      loadOrders();
+      onGoodsReceived?.();
+    } catch (error) {
+      console.error('Error receiving goods:', error);
+      alert('Ошибка при приёмке товара: ' + error);
+    }
+  };
+
+  const handleCreateSupplier = async (e: React.FormEvent) => {
+    e.preventDefault();
+    try {
+      await invoke('create_supplier', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        request: supplierForm
+      });
+      alert(`Поставщик '${supplierForm.name}' добавлен`);
+      setSupplierForm({ name: '', contactPerson: '', phone: '', email: '', notes: '' });
+      setShowSupplierForm(false);
+    } catch (error) {
+      console.error('Error creating supplier:', error);
+      alert('Ошибка при добавлении поставщика: ' + error);
+    }
+  };
+
+  const handleSupplierInput = (e: React.ChangeEvent<HTMLInputElement>) => {
+    const { name, value } = e.target;
+    setSupplierForm(prev => ({ ...prev, [name]: value }));
+  };
+
+  if (!isOpen) return null;
+
+  const canReceive = details && (details.order.status === 'Sent' || details.order.status === 'Partially_Received');
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>📦 ЗАКАЗЫ ПОСТАВЩИКАМ</h2>
+          <button className="close-btn" onClick={onClose}>✖ ЗАКРЫТЬ</button>
+        </div>
+
+        <div className="modal-body">
+          {details ? (
+            <div className="warehouse-stock-table">
+              <h3>
+                Заказ №{details.order.id} — {details.order.supplier_name} ({STATUS_LABELS[details.order.status] || details.order.status})
+              </h3>
+              <p className="item-brand">
+                Ожидается: {details.order.expected_date || '—'} · Сумма: {formatMoney(details.order.total)}
+                {details.order.comment && ` · ${details.order.comment}`}
+              </p>
+              <table>
+                <thead>
+                  <tr>
+                    <th>Деталь / Бренд</th>
+                    <th>Артикул</th>
+                    <th>Для заказа</th>
+                    <th>Заказано</th>
+                    <th>Получено</th>
+                    <th>Цена</th>
+                    {canReceive && <th>Принять</th>}
+                    {canReceive && <th>Ячейка</th>}
+                  </tr>
+                </thead>
+                <tbody>
+                  {details.lines.map(line => (
+                    <tr key={line.id}>
+                      <td>
+                        <div>{line.name}</div>
+                        <div className="item-brand">{line.brand}</div>
+                      </td>
+                      <td>{line.article || '—'}</td>
+                      <td>{line.order_id ? `#${line.order_id}` : 'на склад'}</td>
+                      <td>{line.quantity} шт.</td>
+                      <td>{line.received_quantity} шт.</td>
+                      <td>{formatMoney(line.unit_price)}</td>
+                      {canReceive && (
+                        <td>
+                          <input
+                            type="number"
+                            min="0"
+                            max={line.quantity - line.received_quantity}
+                            value={receiptQuantities[line.id] ?? 0}
+                            onChange={(e) => setReceiptQuantities(prev => ({ ...prev, [line.id]: parseInt(e.target.value) || 0 }))}
+                            disabled={line.received_quantity >= line.quantity}
+                          />
+                        </td>
+                      )}
+                      {canReceive && (
+                        <td>
+                          {line.warehouse_item_id === null && (
+                            <input
+                              type="text"
+                              placeholder="A-01-01"
+                              value={receiptCells[line.id] || ''}
+                              onChange={(e) => setReceiptCells(prev => ({ ...prev, [line.id]: e.target.value }))}
+                            />
+                          )}
+                        </td>
+                      )}
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+
+              <div className="modal-actions">
+                <button className="secondary-btn" onClick={() => setDetails(null)}>◀ К СПИСКУ</button>
+                {details.order.status === 'Draft' && (
+                  <>
+                    <button className="secondary-btn" onClick={() => runAction('delete_purchase_order', 'Удалить черновик заказа?')}>
+                      🗑️ УДАЛИТЬ
+                    </button>
+                    <button className="primary-btn" onClick={() => runAction('send_purchase_order')}>📤 ОТПРАВИТЬ ПОСТАВЩИКУ</button>
+                  </>
+                )}
+                {canReceive && (
+                  <button className="primary-btn" onClick={handleReceive}>📥 ПРИНЯТЬ ТОВАР</button>
+                )}
+              </div>
+            </div>
+          ) : (
+            <>
+              <div className="search-filters">
+                <select value={statusFilter} onChange={(e) => setStatusFilter(e.target.value)}>
+                  <option value="">Все статусы</option>
+                  {Object.entries(STATUS_LABELS).map(([value, label]) => (
+                    <option key={value} value={value}>{label}</option>
+                  ))}
+                </select>
+              </div>
+
+              <div className="warehouse-stock-table">
+                <table>
+                  <thead>
+                    <tr>
+                      <th>№</th>
+                      <th>Поставщик</th>
+                      <th>Статус</th>
+                      <th>Создан</th>
+                      <th>Ожидается</th>
+                      <th>Сумма</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {loading ? (
+                      <tr><td colSpan={6}>Загрузка заказов поставщикам...</td></tr>
+                    ) : orders.length === 0 ? (
+                      <tr><td colSpan={6}>Заказов поставщикам нет</td></tr>
+                    ) : (
+                      orders.map(order => (
+                        <tr key={order.id} className="stock-row" onClick={() => openOrder(order.id)}>
+                          <td>{order.id}</td>
+                          <td>{order.supplier_name}</td>
+                          <td>{STATUS_LABELS[order.status] || order.status}</td>
+                          <td>{new Date(order.created_at).toLocaleDateString('ru-RU')}</td>
+                          <td>{order.expected_date ? new Date(order.expected_date).toLocaleDateString('ru-RU') : '—'}</td>
+                          <td>{formatMoney(order.total)}</td>
+                        </tr>
+                      ))
+                    )}
+                  </tbody>
+                </table>
+              </div>
+
+              {showSupplierForm ? (
+                <form onSubmit={handleCreateSupplier}>
+                  <div className="form-row">
+                    <div className="form-group">
+                      <label htmlFor="supplier_name">Название поставщика:</label>
+                      <input type="text" id="supplier_name" name="name" value={supplierForm.name} onChange={handleSupplierInput} required />
+                    </div>
+                    <div className="form-group">
+                      <label htmlFor="supplier_contact">Контактное лицо:</label>
+                      <input type="text" id="supplier_contact" name="contactPerson" value={supplierForm.contactPerson} onChange={handleSupplierInput} />
+                    </div>
+                  </div>
+                  <div className="form-row">
+                    <div className="form-group">
+                      <label htmlFor="supplier_phone">Телефон:</label>
+                      <input type="text" id="supplier_phone" name="phone" value={supplierForm.phone} onChange={handleSupplierInput} />
+                    </div>
+                    <div className="form-group">
+                      <label htmlFor="supplier_email">E-mail:</label>
+                      <input type="email" id="supplier_email" name="email" value={supplierForm.email} onChange={handleSupplierInput} />
+                    </div>
+                  </div>
+                  <div className="modal-actions">
+                    <button type="button" className="cancel-btn" onClick={() => setShowSupplierForm(false)}>ОТМЕНА</button>
+                    <button type="submit" className="save-btn">✅ СОХРАНИТЬ ПОСТАВЩИКА</button>
+                  </div>
+                </form>
+              ) : (
+                <div className="modal-actions">
+                  <button className="secondary-btn" onClick={() => setShowSupplierForm(true)}>➕ НОВЫЙ ПОСТАВЩИК</button>
+                </div>
+              )}
+            </>
+          )}
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default PurchaseOrdersModal;
@@ -0,0 +1,168 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './ReportGenerator.css';
+
+interface QcChecklistItem {
+  id: number;
+  title: string;
+  service_id: number | null;
+  service_name: string | null;
+  defect_node_id: number | null;
+  defect_node_name: string | null;
+  sort_order: number;
+}
+
+interface NamedItem {
+  id: number;
+  name: string;
+}
+
+// Пункт относится к услуге, к узлу неисправностей или ко всем заказам
+type Scope = 'all' | 'service' | 'node';
+
+const QcChecklistManager: React.FC = () => {
+  const [items, setItems] = useState<QcChecklistItem[]>([]);
+  const [services, setServices] = useState<NamedItem[]>([]);
+  const [nodes, setNodes] = useState<NamedItem[]>([]);
+  const [title, setTitle] = useState('');
+  const [scope, setScope] = useState<Scope>('all');
+  const [targetId, setTargetId] = useState<number | null>(null);
+  const [sortOrder, setSortOrder] = useState('0');
+
+  useEffect(() => {
+    loadData();
+  }, []);
+
+  const loadData = async () => {
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+      const [itemsData, servicesData, nodesData] = await Promise.all([
+        invoke<QcChecklistItem[]>('get_qc_checklist_items', { sessionToken }),
+        invoke<NamedItem[]>('get_all_services', { sessionToken }),
+        invoke<NamedItem[]>('get_defect_nodes', { sessionToken })
+      ]);
+      setItems(itemsData);
+      setServices(servicesData);
+      setNodes(nodesData);
+    } catch (error) {
+      console.error('Error loading QC checklist:', error);
+      alert('Ошибка при загрузке чек-листа: ' + error);
+    }
+  };
+
+  const handleAdd = async () => {
+    if (!title.trim()) {
+      alert('Укажите, что проверяется');
+      return;
+    }
+    if (scope !== 'all' && targetId === null) {
+      alert(scope === 'service' ? 'Выберите услугу' : 'Выберите узел');
+      return;
+    }
+
+    try {
+      await invoke('add_qc_checklist_item', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        title,
+        serviceId: scope === 'service' ? targetId : null,
+        defectNodeId: scope === 'node' ? targetId : null,
+        sortOrder: parseInt(sortOrder) || 0
+      });
+      setTitle('');
+      loadData();
+    } catch (error) {
+      console.error('Error adding QC checklist item:', error);
+      alert('Ошибка при добавлении пункта: ' + error);
+    }
+  };
+
+  const handleDelete = async (item: QcChecklistItem) => {
+    if (!window.confirm(`Удалить пункт "${item.title}"?`)) return;
+    try {
+      await invoke('delete_qc_checklist_item', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        itemId: item.id
+      });
+      loadData();
+    } catch (error) {
+      console.error('Error deleting QC checklist item:', error);
+      alert('Ошибка при удалении пункта: ' + error);
+    }
+  };
+
+  const scopeText = (item: QcChecklistItem) => {
+    if (item.service_name) return `Услуга: ${item.service_name}`;
+    if (item.defect_node_name) return `Узел: ${item.defect_node_name}`;
+    return 'Все заказы';
+  };
+
+  return (
+    <div className="report-generator">
+      <div className="report-controls">
+        <div className="control-group">
+          <label>Проверка:</label>
+          <input type="text" value={title} onChange={(e) => setTitle(e.target.value)} />
+        </div>
+        <div className="control-group">
+          <label>Применяется к:</label>
+          <select
+            value={scope}
+            onChange={(e) => { setScope(e.target.value as Scope); setTargetId(null); }}
+          >
+            <option value="all">Все заказы</option>
+            <option value="service">Услуге</option>
+            <option value="node">Узлу неисправностей</option>
+          </select>
+        </div>
+        {scope !== 'all' && (
+          <div className="control-group">
+            <label>{scope === 'service' ? 'Услуга:' : 'Узел:'}</label>
+            <select
+              value={targetId ?? ''}
+              onChange={(e) => setTargetId(e.target.value ? parseInt(e.target.value) : null)}
+            >
+              <option value="">Выберите...</option>
+              {(scope === 'service' ? services : nodes).map(option => (
+                <option key={option.id} value={option.id}>{option.name}</option>
+              ))}
+            </select>
+          </div>
+        )}
+        <div className="control-group">
+          <label>Порядок:</label>
+          <input type="number" value={sortOrder} onChange={(e) => setSortOrder(e.target.value)} />
+        </div>
+        <button className="generate-btn" onClick={handleAdd}>Добавить пункт</button>
+      </div>
+
+      <div className="report-results">
+        <div className="report-table-container">
+          <table className="report-table">
+            <thead>
+              <tr>
+                <th>Проверка</th>
+                <th>Применяется к</th>
+                <th>Порядок</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {items.map(item => (
+                <tr key={item.id}>
+                  <td>{item.title}</td>
+                  <td>{scopeText(item)}</td>
+                  <td>{item.sort_order}</td>
+                  <td>
+                    <button className="export-btn pdf-export" onClick={() => handleDelete(item)}>Удалить</button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default QcChecklistManager;
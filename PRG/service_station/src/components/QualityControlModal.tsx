@@ -0,0 +1,341 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './AssignWorkersModal.css';
+import './QualityControlModal.css';
+
+interface Order {
+  id: number;
+  worker_id: number | null;
+  status: string;
+  complaint: string | null;
+}
+
+interface Work {
+  id: number;
+  service_name_snapshot: string;
+  worker_id?: number | null;
+  status: string;
+  is_confirmed: boolean;
+}
+
+interface Worker {
+  id: number;
+  full_name: string;
+  status: string;
+}
+
+interface QcCheck {
+  checklist_item_id: number;
+  title: string;
+  work_id: number | null;
+  work_name: string | null;
+  worker_id: number | null;
+  worker_name: string | null;
+}
+
+interface QcInspectionResult {
+  id: number;
+  title: string;
+  work_name: string | null;
+  passed: boolean;
+  comment: string | null;
+  rework_worker_name: string | null;
+}
+
+interface QcInspection {
+  id: number;
+  inspector_name: string | null;
+  result: 'Passed' | 'Failed';
+  comment: string | null;
+  created_at: string;
+  results: QcInspectionResult[];
+}
+
+// Строка проверки в форме: пункт чек-листа или замечание мастера по работе
+interface CheckRow {
+  key: string;
+  checklist_item_id: number | null;
+  title: string;
+  work_id: number | null;
+  passed: boolean;
+  comment: string;
+  rework_worker_id: number | null;
+}
+
+interface QualityControlModalProps {
+  isOpen: boolean;
+  order: Order;
+  works: Work[];
+  onClose: () => void;
+  onInspectionSaved: () => void;
+}
+
+const QualityControlModal: React.FC<QualityControlModalProps> = ({
+  isOpen,
+  order,
+  works,
+  onClose,
+  onInspectionSaved
+}) => {
+  const [rows, setRows] = useState<CheckRow[]>([]);
+  const [history, setHistory] = useState<QcInspection[]>([]);
+  const [workers, setWorkers] = useState<Worker[]>([]);
+  const [comment, setComment] = useState('');
+  const [loading, setLoading] = useState(false);
+  const [isProcessing, setIsProcessing] = useState(false);
+
+  const activeWorks = works.filter(work => work.is_confirmed && work.status !== 'Cancelled');
+
+  useEffect(() => {
+    if (isOpen) {
+      loadData();
+    }
+  }, [isOpen, order]);
+
+  const loadData = async () => {
+    setLoading(true);
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+      const [checklist, inspections, workersData] = await Promise.all([
+        invoke<QcCheck[]>('get_order_qc_checklist', { sessionToken, orderId: order.id }),
+        invoke<QcInspection[]>('get_order_qc_history', { sessionToken, orderId: order.id }),
+        invoke<Worker[]>('get_available_workers', { sessionToken })
+      ]);
+
+      // Если чек-лист для заказа не настроен, мастер проверяет каждую работу целиком
+      const checkRows: CheckRow[] = checklist.length > 0
+        ? checklist.map(check => ({
+            key: `${check.checklist_item_id}-${check.work_id ?? 'order'}`,
+            checklist_item_id: check.checklist_item_id,
+            title: check.title,
+            work_id: check.work_id,
+            passed: true,
+            comment: '',
+            rework_worker_id: null
+          }))
+        : activeWorks.map(work => ({
+            key: `work-${work.id}`,
+            checklist_item_id: null,
+            title: 'Работа выполнена качественно',
+            work_id: work.id,
+            passed: true,
+            comment: '',
+            rework_worker_id: null
+          }));
+
+      setRows(checkRows);
+      setHistory(inspections);
+      setWorkers(workersData.filter(worker => worker.status === 'Active'));
+    } catch (error) {
+      console.error('Error loading quality control data:', error);
+      alert('Ошибка при загрузке чек-листа: ' + error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const updateRow = (key: string, changes: Partial<CheckRow>) => {
+    setRows(prev => prev.map(row => (row.key === key ? { ...row, ...changes } : row)));
+  };
+
+  const handleAddRemark = () => {
+    setRows(prev => [...prev, {
+      key: `remark-${Date.now()}`,
+      checklist_item_id: null,
+      title: '',
+      work_id: activeWorks[0]?.id ?? null,
+      passed: false,
+      comment: '',
+      rework_worker_id: null
+    }]);
+  };
+
+  const workName = (workId: number | null) =>
+    works.find(work => work.id === workId)?.service_name_snapshot || 'Заказ в целом';
+
+  const performerId = (workId: number | null) =>
+    works.find(work => work.id === workId)?.worker_id ?? order.worker_id;
+
+  const failedCount = rows.filter(row => !row.passed).length;
+
+  const handleSubmit = async () => {
+    setIsProcessing(true);
+    try {
+      const result = await invoke<string>('submit_qc_inspection', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        orderId: order.id,
+        results: rows.map(row => ({
+          checklist_item_id: row.checklist_item_id,
+          title: row.title,
+          work_id: row.work_id,
+          passed: row.passed,
+          comment: row.comment || null,
+          rework_worker_id: row.passed ? null : row.rework_worker_id
+        })),
+        comment: comment || null
+      });
+
+      alert(result === 'Passed'
+        ? `Заказ #${order.id} прошёл контроль качества и готов к выдаче`
+        : `Заказ #${order.id} возвращён в работу на переделку`);
+      onInspectionSaved();
+      onClose();
+    } catch (error) {
+      console.error('Error submitting quality control:', error);
+      alert('Ошибка при сохранении результатов контроля качества: ' + error);
+    } finally {
+      setIsProcessing(false);
+    }
+  };
+
+  if (!isOpen) return null;
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>🔍 КОНТРОЛЬ КАЧЕСТВА (Заказ #{order.id})</h2>
+          <button className="close-btn" onClick={onClose}>✖ ОТМЕНА</button>
+        </div>
+
+        <div className="modal-body">
+          <div className="order-info">
+            <p><strong>Жалоба клиента:</strong> {order.complaint || 'Не указана'}</p>
+          </div>
+
+          {loading ? (
+            <div className="loading">Загрузка чек-листа...</div>
+          ) : (
+            <div className="qc-checklist">
+              <h3>ЧЕК-ЛИСТ ПРОВЕРКИ:</h3>
+              <table>
+                <thead>
+                  <tr>
+                    <th>Проверка</th>
+                    <th>Работа</th>
+                    <th>Результат</th>
+                    <th>Замечание</th>
+                    <th>Переделка</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {rows.map(row => (
+                    <tr key={row.key} className={row.passed ? '' : 'qc-failed'}>
+                      <td>
+                        {row.checklist_item_id !== null ? row.title : (
+                          <input
+                            type="text"
+                            value={row.title}
+                            placeholder="Что проверено"
+                            onChange={(e) => updateRow(row.key, { title: e.target.value })}
+                          />
+                        )}
+                      </td>
+                      <td>
+                        {row.checklist_item_id !== null && row.work_id !== null ? workName(row.work_id) : (
+                          <select
+                            value={row.work_id ?? ''}
+                            onChange={(e) => updateRow(row.key, { work_id: e.target.value ? parseInt(e.target.value) : null })}
+                          >
+                            <option value="">Заказ в целом</option>
+                            {activeWorks.map(work => (
+                              <option key={work.id} value={work.id}>{work.service_name_snapshot}</option>
+                            ))}
+                          </select>
+                        )}
+                      </td>
+                      <td>
+                        <label>
+                          <input
+                            type="radio"
+                            checked={row.passed}
+                            onChange={() => updateRow(row.key, { passed: true })}
+                          />
+                          OK
+                        </label>
+                        <label>
+                          <input
+                            type="radio"
+                            checked={!row.passed}
+                            onChange={() => updateRow(row.key, { passed: false })}
+                          />
+                          Брак
+                        </label>
+                      </td>
+                      <td>
+                        <input
+                          type="text"
+                          value={row.comment}
+                          onChange={(e) => updateRow(row.key, { comment: e.target.value })}
+                        />
+                      </td>
+                      <td>
+                        {!row.passed && row.work_id !== null && (
+                          <select
+                            value={row.rework_worker_id ?? performerId(row.work_id) ?? ''}
+                            onChange={(e) => updateRow(row.key, { rework_worker_id: e.target.value ? parseInt(e.target.value) : null })}
+                          >
+                            <option value="">Выберите слесаря</option>
+                            {workers.map(worker => (
+                              <option key={worker.id} value={worker.id}>{worker.full_name}</option>
+                            ))}
+                          </select>
+                        )}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+              <button type="button" className="qc-add-remark" onClick={handleAddRemark}>
+                ➕ ДОБАВИТЬ ЗАМЕЧАНИЕ
+              </button>
+
+              <div className="worker-selection-form">
+                <label htmlFor="qc-comment">КОММЕНТАРИЙ:</label>
+                <input id="qc-comment" type="text" value={comment} onChange={(e) => setComment(e.target.value)} />
+              </div>
+
+              {history.length > 0 && (
+                <div className="qc-history">
+                  <h3>ПРЕДЫДУЩИЕ ПРОВЕРКИ:</h3>
+                  {history.map(inspection => (
+                    <div key={inspection.id} className="qc-history-item">
+                      <p>
+                        <strong>{new Date(inspection.created_at).toLocaleString()}</strong>
+                        {' '}{inspection.result === 'Passed' ? '✅ Принято' : '❌ Возврат на переделку'}
+                        {inspection.inspector_name ? ` (${inspection.inspector_name})` : ''}
+                        {inspection.comment ? `: ${inspection.comment}` : ''}
+                      </p>
+                      <ul>
+                        {inspection.results.filter(result => !result.passed).map(result => (
+                          <li key={result.id}>
+                            {result.title} — {result.work_name || 'заказ в целом'}
+                            {result.comment ? `: ${result.comment}` : ''}
+                            {result.rework_worker_name ? ` → ${result.rework_worker_name}` : ''}
+                          </li>
+                        ))}
+                      </ul>
+                    </div>
+                  ))}
+                </div>
+              )}
+            </div>
+          )}
+
+          <div className="modal-actions">
+            <button
+              type="button"
+              className="save-btn"
+              onClick={handleSubmit}
+              disabled={isProcessing || loading || rows.length === 0}
+            >
+              {isProcessing ? 'Обработка...' : failedCount > 0 ? `🔁 ВЕРНУТЬ НА ПЕРЕДЕЛКУ (${failedCount})` : '✅ ПРИНЯТЬ РАБОТЫ'}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default QualityControlModal;
@@ -0,0 +1,155 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import './WarehouseStockModal.css';
+import { Money, formatMoney, multiplyMoney, sumMoney } from '../types/money';
+
+interface ReorderLine {
+  warehouse_item_id: number;
+  name: string;
+  brand: string | null;
+  article: string | null;
+  quantity: number;
+  reserved: number;
+  available: number;
+  on_order: number; // Заказано у поставщиков и ещё не получено
+  min_quantity: number;
+  consumed: number;
+  suggested_quantity: number;
+  purchase_price: Money | null;
+}
+
+interface ReorderProposal {
+  supplier_id: number | null;
+  supplier: string;
+  lines: ReorderLine[];
+}
+
+interface ReorderProposalsModalProps {
+  isOpen: boolean;
+  onClose: () => void;
+}
+
+const ReorderProposalsModal: React.FC<ReorderProposalsModalProps> = ({ isOpen, onClose }) => {
+  const [proposals, setProposals] = useState<ReorderProposal[]>([]);
+  const [loading, setLoading] = useState(true);
+
+  useEffect(() => {
+    if (isOpen) {
+      loadProposals();
+    }
+  }, [isOpen]);
+
+  const loadProposals = async () => {
+    try {
+      setLoading(true);
+      const result = await invoke<ReorderProposal[]>('get_reorder_proposals', {
+        sessionToken: localStorage.getItem('sessionToken')
+      });
+      setProposals(result);
+    } catch (error) {
+      console.error('Error loading reorder proposals:', error);
+      setProposals([]);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // Черновик заказа поставщику по предложенным количествам; отправка и приёмка - в разделе заказов поставщикам
+  const createPurchaseOrder = async (proposal: ReorderProposal) => {
+    if (proposal.supplier_id === null) return;
+
+    try {
+      const purchaseOrderId = await invoke<number>('create_purchase_order', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        supplierId: proposal.supplier_id,
+        expectedDate: null,
+        comment: 'Дозаказ по минимальным остаткам',
+        lines: proposal.lines.map(line => ({
+          warehouseItemId: line.warehouse_item_id,
+          quantity: line.suggested_quantity
+        }))
+      });
+      alert(`Создан черновик заказа поставщику №${purchaseOrderId}`);
+    } catch (error) {
+      console.error('Error creating purchase order:', error);
+      alert('Ошибка при создании заказа поставщику: ' + error);
+    }
+  };
+
+  const lineCost = (line: ReorderLine) =>
+    line.purchase_price ? multiplyMoney(line.purchase_price, line.suggested_quantity) : null;
+
+  if (!isOpen) return null;
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal-content" onClick={(e) => e.stopPropagation()}>
+        <div className="modal-header">
+          <h2>🛒 К ДОЗАКАЗУ</h2>
+          <button className="close-btn" onClick={onClose}>✖ ЗАКРЫТЬ</button>
+        </div>
+
+        <div className="modal-body">
+          {loading ? (
+            <p>Загрузка списка к дозаказу...</p>
+          ) : proposals.length === 0 ? (
+            <p>Все позиции выше минимального остатка.</p>
+          ) : (
+            proposals.map(proposal => (
+              <div key={proposal.supplier} className="warehouse-stock-table">
+                <h3>{proposal.supplier}</h3>
+                <table>
+                  <thead>
+                    <tr>
+                      <th>Деталь / Бренд</th>
+                      <th>Артикул</th>
+                      <th>Остаток</th>
+                      <th>Резерв</th>
+                      <th>Доступно</th>
+                      <th>В заказе</th>
+                      <th>Мин.</th>
+                      <th>Расход за 90 дн.</th>
+                      <th>Заказать</th>
+                      <th>Сумма</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {proposal.lines.map(line => (
+                      <tr key={line.warehouse_item_id}>
+                        <td>
+                          <div>{line.name}</div>
+                          <div className="item-brand">{line.brand}</div>
+                        </td>
+                        <td>{line.article || '—'}</td>
+                        <td>{line.quantity} шт.</td>
+                        <td>{line.reserved} шт.</td>
+                        <td className="low-stock">{line.available} шт.</td>
+                        <td>{line.on_order} шт.</td>
+                        <td>{line.min_quantity} шт.</td>
+                        <td>{line.consumed} шт.</td>
+                        <td><strong>{line.suggested_quantity} шт.</strong></td>
+                        <td>{formatMoney(lineCost(line))}</td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+                <p className="item-brand">
+                  Итого по поставщику: {formatMoney(sumMoney(proposal.lines.map(lineCost)))}
+                </p>
+                {proposal.supplier_id !== null && (
+                  <div className="modal-actions">
+                    <button className="primary-btn" onClick={() => createPurchaseOrder(proposal)}>
+                      📝 СОЗДАТЬ ЗАКАЗ ПОСТАВЩИКУ
+                    </button>
+                  </div>
+                )}
+              </div>
+            ))
+          )}
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default ReorderProposalsModal;
@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './SearchModal.css';
+import { Money } from '../types/money';
 
 interface Client {
   id: number;
@@ -32,8 +33,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -66,7 +67,7 @@ const SearchModal: React.FC<SearchModalProps> = ({ isOpen, onClose, onResultSele
       setLoading(true);
       const [orders, clients, cars] = await invoke<[Order[], Client[], Car[]]>(
         'search_orders_clients_cars',
-        { query: searchQuery }
+        { sessionToken: localStorage.getItem('sessionToken'), query: searchQuery }
       );
 
       // Combine all results
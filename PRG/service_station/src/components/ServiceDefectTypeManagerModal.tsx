@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import './ServiceDefectTypeManagerModal.css';
 import { Service } from '../types/service';
 import { DefectNode, DefectType } from '../types/defect';
+import { Money, formatMoney, moneyInput } from '../types/money';
 
 interface ServiceDefectTypeManagerModalProps {
   isOpen: boolean;
@@ -11,7 +12,7 @@ interface ServiceDefectTypeManagerModalProps {
 
 interface NewService {
   name: string;
-  basePrice: number;
+  basePrice: string;
   normHours: number;
 }
 
@@ -26,14 +27,95 @@ interface NewDefectType {
   description: string | null;
 }
 
+type ServiceRole = 'Mandatory' | 'Recommended' | 'Optional';
+
+const ROLE_LABELS: Record<ServiceRole, string> = {
+  Mandatory: 'Обязательная',
+  Recommended: 'Рекомендуемая',
+  Optional: 'Дополнительная'
+};
+
+interface PartTemplate {
+  article: string;
+  part_name: string | null;
+  quantity: number;
+}
+
+// Связь типа неисправности с услугой и запчастями по умолчанию
+interface DefectServiceLink {
+  id: number;
+  service_id: number;
+  service_name: string;
+  base_price: Money;
+  norm_hours: string;
+  role: ServiceRole;
+  parts: PartTemplate[];
+}
+
+interface LinkForm {
+  serviceId: number;
+  role: ServiceRole;
+  parts: PartTemplate[];
+}
+
+const emptyLinkForm: LinkForm = { serviceId: 0, role: 'Mandatory', parts: [] };
+
+// Шаблон замера для типа неисправности: единица и допустимый диапазон
+interface MeasurementTemplate {
+  id: number;
+  name: string;
+  value_type: 'Number' | 'Text';
+  unit: string | null;
+  min_value: string | null;
+  max_value: string | null;
+  flag_if_present: boolean;
+  sort_order: number;
+}
+
+interface MeasurementForm {
+  name: string;
+  valueType: 'Number' | 'Text';
+  unit: string;
+  minValue: string;
+  maxValue: string;
+  flagIfPresent: boolean;
+  sortOrder: string;
+}
+
+const emptyMeasurementForm: MeasurementForm = {
+  name: '',
+  valueType: 'Number',
+  unit: '',
+  minValue: '',
+  maxValue: '',
+  flagIfPresent: false,
+  sortOrder: '0'
+};
+
+// Код ошибки OBD-II и предлагаемые по нему типы неисправностей
+interface DtcCode {
+  id: number;
+  code: string;
+  description: string;
+  defect_types: { id: number; node_name: string; name: string }[];
+}
+
+interface DtcImportReport {
+  total_rows: number;
+  imported: number;
+  rejected_count: number;
+  rejected: { row: number; reason: string }[];
+  linked_defect_types: number; // Связи из стартового сопоставления стандартных кодов
+}
+
 const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps> = ({ isOpen, onClose }) => {
-  const [activeTab, setActiveTab] = useState<'services' | 'defectNodes' | 'defectTypes'>('services');
+  const [activeTab, setActiveTab] = useState<'services' | 'defectNodes' | 'defectTypes' | 'links' | 'measurements' | 'dtc'>('services');
   
   // Состояния для услуг
   const [services, setServices] = useState<Service[]>([]);
   const [newService, setNewService] = useState<NewService>({
     name: '',
-    basePrice: 0,
+    basePrice: '',
     normHours: 0
   });
   const [loadingServices, setLoadingServices] = useState(false);
@@ -55,6 +137,21 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
   });
   const [loadingDefectTypes, setLoadingDefectTypes] = useState(false);
 
+  // Состояния для связей типов неисправностей с услугами
+  const [linkDefectTypeId, setLinkDefectTypeId] = useState(0);
+  const [links, setLinks] = useState<DefectServiceLink[]>([]);
+  const [linkForm, setLinkForm] = useState<LinkForm>(emptyLinkForm);
+
+  // Состояния для шаблонов замеров
+  const [measurementDefectTypeId, setMeasurementDefectTypeId] = useState(0);
+  const [measurementTemplates, setMeasurementTemplates] = useState<MeasurementTemplate[]>([]);
+  const [measurementForm, setMeasurementForm] = useState<MeasurementForm>(emptyMeasurementForm);
+
+  // Состояния для справочника кодов OBD
+  const [dtcQuery, setDtcQuery] = useState('');
+  const [dtcCodes, setDtcCodes] = useState<DtcCode[]>([]);
+  const [dtcReport, setDtcReport] = useState<DtcImportReport | null>(null);
+
   // Загрузка данных при открытии модального окна
   useEffect(() => {
     if (isOpen) {
@@ -67,7 +164,7 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
   const loadServices = async () => {
     setLoadingServices(true);
     try {
-      const servicesData: Service[] = await invoke('get_all_services');
+      const servicesData: Service[] = await invoke('get_all_services', { sessionToken: localStorage.getItem('sessionToken') });
       setServices(servicesData);
     } catch (error) {
       console.error('Error loading services:', error);
@@ -80,7 +177,7 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
   const loadDefectNodes = async () => {
     setLoadingDefectNodes(true);
     try {
-      const nodesData: DefectNode[] = await invoke('get_defect_nodes');
+      const nodesData: DefectNode[] = await invoke('get_defect_nodes', { sessionToken: localStorage.getItem('sessionToken') });
       setDefectNodes(nodesData);
     } catch (error) {
       console.error('Error loading defect nodes:', error);
@@ -93,7 +190,7 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
   const loadDefectTypes = async () => {
     setLoadingDefectTypes(true);
     try {
-      const typesData: DefectType[] = await invoke('get_all_defect_types');
+      const typesData: DefectType[] = await invoke('get_all_defect_types', { sessionToken: localStorage.getItem('sessionToken') });
       setDefectTypes(typesData);
     } catch (error) {
       console.error('Error loading defect types:', error);
@@ -103,6 +200,216 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
     }
   };
 
+  const loadLinks = async (defectTypeId: number) => {
+    if (defectTypeId === 0) {
+      setLinks([]);
+      return;
+    }
+    try {
+      const linksData = await invoke<DefectServiceLink[]>('get_defect_type_services', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        defectTypeId
+      });
+      setLinks(linksData);
+    } catch (error) {
+      console.error('Error loading defect type services:', error);
+      alert(`Ошибка загрузки связей с услугами: ${error}`);
+    }
+  };
+
+  const handleSelectLinkDefectType = (defectTypeId: number) => {
+    setLinkDefectTypeId(defectTypeId);
+    setLinkForm(emptyLinkForm);
+    loadLinks(defectTypeId);
+  };
+
+  const handleEditLink = (link: DefectServiceLink) => {
+    setLinkForm({
+      serviceId: link.service_id,
+      role: link.role,
+      parts: link.parts.map(part => ({ ...part }))
+    });
+  };
+
+  const updateLinkPart = (index: number, changes: Partial<PartTemplate>) => {
+    setLinkForm({
+      ...linkForm,
+      parts: linkForm.parts.map((part, i) => (i === index ? { ...part, ...changes } : part))
+    });
+  };
+
+  const handleSaveLink = async () => {
+    if (linkDefectTypeId === 0 || linkForm.serviceId === 0) {
+      alert('Выберите тип неисправности и услугу');
+      return;
+    }
+
+    try {
+      await invoke('set_defect_type_service', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        defectTypeId: linkDefectTypeId,
+        serviceId: linkForm.serviceId,
+        role: linkForm.role,
+        parts: linkForm.parts.filter(part => part.article.trim())
+      });
+      setLinkForm(emptyLinkForm);
+      loadLinks(linkDefectTypeId);
+    } catch (error) {
+      console.error('Error saving defect type service:', error);
+      alert(`Ошибка при сохранении связи: ${error}`);
+    }
+  };
+
+  const handleRemoveLink = async (link: DefectServiceLink) => {
+    if (!window.confirm(`Удалить связь с услугой "${link.service_name}"?`)) return;
+    try {
+      await invoke('remove_defect_type_service', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        linkId: link.id
+      });
+      loadLinks(linkDefectTypeId);
+    } catch (error) {
+      console.error('Error removing defect type service:', error);
+      alert(`Ошибка при удалении связи: ${error}`);
+    }
+  };
+
+  const loadMeasurementTemplates = async (defectTypeId: number) => {
+    if (defectTypeId === 0) {
+      setMeasurementTemplates([]);
+      return;
+    }
+    try {
+      const templates = await invoke<MeasurementTemplate[]>('get_defect_type_measurements', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        defectTypeId
+      });
+      setMeasurementTemplates(templates);
+    } catch (error) {
+      console.error('Error loading measurement templates:', error);
+      alert(`Ошибка загрузки замеров: ${error}`);
+    }
+  };
+
+  const handleSelectMeasurementDefectType = (defectTypeId: number) => {
+    setMeasurementDefectTypeId(defectTypeId);
+    setMeasurementForm(emptyMeasurementForm);
+    loadMeasurementTemplates(defectTypeId);
+  };
+
+  const handleAddMeasurement = async () => {
+    if (measurementDefectTypeId === 0 || !measurementForm.name.trim()) {
+      alert('Выберите тип неисправности и укажите, что измеряется');
+      return;
+    }
+
+    try {
+      const isNumber = measurementForm.valueType === 'Number';
+      await invoke('add_defect_type_measurement', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        defectTypeId: measurementDefectTypeId,
+        template: {
+          name: measurementForm.name,
+          valueType: measurementForm.valueType,
+          unit: measurementForm.unit || null,
+          minValue: isNumber ? measurementForm.minValue || null : null,
+          maxValue: isNumber ? measurementForm.maxValue || null : null,
+          flagIfPresent: !isNumber && measurementForm.flagIfPresent,
+          sortOrder: parseInt(measurementForm.sortOrder) || 0
+        }
+      });
+      setMeasurementForm(emptyMeasurementForm);
+      loadMeasurementTemplates(measurementDefectTypeId);
+    } catch (error) {
+      console.error('Error adding measurement template:', error);
+      alert(`Ошибка при добавлении замера: ${error}`);
+    }
+  };
+
+  const handleDeleteMeasurement = async (template: MeasurementTemplate) => {
+    if (!window.confirm(`Удалить замер "${template.name}"?`)) return;
+    try {
+      await invoke('delete_defect_type_measurement', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        templateId: template.id
+      });
+      loadMeasurementTemplates(measurementDefectTypeId);
+    } catch (error) {
+      console.error('Error deleting measurement template:', error);
+      alert(`Ошибка при удалении замера: ${error}`);
+    }
+  };
+
+  const measurementNorm = (template: MeasurementTemplate) => {
+    if (template.value_type === 'Text') return template.flag_if_present ? 'любое значение - отклонение' : '-';
+    const unit = template.unit ? ` ${template.unit}` : '';
+    if (template.min_value !== null && template.max_value !== null) return `${template.min_value}–${template.max_value}${unit}`;
+    if (template.min_value !== null) return `от ${template.min_value}${unit}`;
+    if (template.max_value !== null) return `до ${template.max_value}${unit}`;
+    return '-';
+  };
+
+  const loadDtcCodes = async (query: string = dtcQuery) => {
+    try {
+      const codes = await invoke<DtcCode[]>('search_dtc_codes', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        query
+      });
+      setDtcCodes(codes);
+    } catch (error) {
+      console.error('Error searching DTC codes:', error);
+      alert(`Ошибка поиска кодов: ${error}`);
+    }
+  };
+
+  const handleLoadBundledDtcCodes = async () => {
+    try {
+      const result = await invoke<DtcImportReport>('load_bundled_dtc_codes', {
+        sessionToken: localStorage.getItem('sessionToken')
+      });
+      setDtcReport(result);
+      loadDtcCodes();
+    } catch (error) {
+      console.error('Error loading bundled DTC codes:', error);
+      alert(`Ошибка загрузки стандартных кодов: ${error}`);
+    }
+  };
+
+  const handleImportDtcCodes = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = '';
+    if (!file) return;
+
+    try {
+      const buffer = await file.arrayBuffer();
+      const result = await invoke<DtcImportReport>('import_dtc_codes', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        fileName: file.name,
+        content: Array.from(new Uint8Array(buffer))
+      });
+      setDtcReport(result);
+      loadDtcCodes();
+    } catch (error) {
+      console.error('Error importing DTC codes:', error);
+      alert(`Ошибка при импорте кодов: ${error}`);
+    }
+  };
+
+  // Сохраняет полный список типов неисправностей, предлагаемых по коду
+  const handleSetDtcDefectTypes = async (code: DtcCode, defectTypeIds: number[]) => {
+    try {
+      await invoke('set_dtc_defect_types', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        dtcCodeId: code.id,
+        defectTypeIds
+      });
+      loadDtcCodes();
+    } catch (error) {
+      console.error('Error setting DTC defect types:', error);
+      alert(`Ошибка при сохранении связей кода: ${error}`);
+    }
+  };
+
   const handleCreateService = async () => {
     if (!newService.name) {
       alert('Название услуги не может быть пустым');
@@ -121,13 +428,13 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
         request: {
           sessionToken,
           name: newService.name,
-          basePrice: newService.basePrice,
+          basePrice: moneyInput(newService.basePrice),
           normHours: newService.normHours
         }
       });
 
       alert(result);
-      setNewService({ name: '', basePrice: 0, normHours: 0 });
+      setNewService({ name: '', basePrice: '', normHours: 0 });
       loadServices(); // Обновляем список услуг
     } catch (error) {
       console.error('Error creating service:', error);
@@ -228,6 +535,24 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
             >
               ТИПЫ НЕИСПРАВНОСТЕЙ
             </button>
+            <button
+              className={`tab ${activeTab === 'links' ? 'active' : ''}`}
+              onClick={() => setActiveTab('links')}
+            >
+              СВЯЗИ С УСЛУГАМИ
+            </button>
+            <button
+              className={`tab ${activeTab === 'measurements' ? 'active' : ''}`}
+              onClick={() => setActiveTab('measurements')}
+            >
+              ЗАМЕРЫ
+            </button>
+            <button
+              className={`tab ${activeTab === 'dtc' ? 'active' : ''}`}
+              onClick={() => { setActiveTab('dtc'); loadDtcCodes(); }}
+            >
+              КОДЫ OBD
+            </button>
           </div>
 
           {activeTab === 'services' && (
@@ -252,7 +577,7 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
                       type="number"
                       step="0.01"
                       value={newService.basePrice}
-                      onChange={(e) => setNewService({...newService, basePrice: parseFloat(e.target.value) || 0})}
+                      onChange={(e) => setNewService({...newService, basePrice: e.target.value})}
                       placeholder="0.00"
                     />
                   </div>
@@ -290,7 +615,7 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
                       {services.map(service => (
                         <tr key={service.id}>
                           <td>{service.name}</td>
-                          <td>{service.base_price}</td>
+                          <td>{formatMoney(service.base_price)}</td>
                           <td>{service.norm_hours}</td>
                         </tr>
                       ))}
@@ -427,6 +752,344 @@ const ServiceDefectTypeManagerModal: React.FC<ServiceDefectTypeManagerModalProps
               </div>
             </div>
           )}
+
+          {activeTab === 'links' && (
+            <div className="links-tab">
+              <div className="form-section">
+                <h3>🔗 УСЛУГИ ПО НЕИСПРАВНОСТИ</h3>
+                <div className="input-group">
+                  <label htmlFor="link-defect-type">Тип неисправности:</label>
+                  <select
+                    id="link-defect-type"
+                    value={linkDefectTypeId}
+                    onChange={(e) => handleSelectLinkDefectType(parseInt(e.target.value))}
+                  >
+                    <option value={0}>Выберите неисправность</option>
+                    {defectTypes.map(type => (
+                      <option key={type.id} value={type.id}>
+                        {type.node_name}: {type.name}
+                      </option>
+                    ))}
+                  </select>
+                </div>
+                {linkDefectTypeId !== 0 && (
+                  <>
+                    <div className="input-grid">
+                      <div className="input-group">
+                        <label htmlFor="link-service">Услуга:</label>
+                        <select
+                          id="link-service"
+                          value={linkForm.serviceId}
+                          onChange={(e) => setLinkForm({...linkForm, serviceId: parseInt(e.target.value)})}
+                        >
+                          <option value={0}>Выберите услугу</option>
+                          {services.map(service => (
+                            <option key={service.id} value={service.id}>{service.name}</option>
+                          ))}
+                        </select>
+                      </div>
+                      <div className="input-group">
+                        <label htmlFor="link-role">Роль:</label>
+                        <select
+                          id="link-role"
+                          value={linkForm.role}
+                          onChange={(e) => setLinkForm({...linkForm, role: e.target.value as ServiceRole})}
+                        >
+                          {(Object.keys(ROLE_LABELS) as ServiceRole[]).map(role => (
+                            <option key={role} value={role}>{ROLE_LABELS[role]}</option>
+                          ))}
+                        </select>
+                      </div>
+                    </div>
+                    <div className="input-group">
+                      <label>Запчасти по умолчанию:</label>
+                      {linkForm.parts.map((part, index) => (
+                        <div key={index} className="part-template-row">
+                          <input
+                            type="text"
+                            value={part.article}
+                            onChange={(e) => updateLinkPart(index, { article: e.target.value })}
+                            placeholder="Артикул"
+                          />
+                          <input
+                            type="text"
+                            value={part.part_name || ''}
+                            onChange={(e) => updateLinkPart(index, { part_name: e.target.value || null })}
+                            placeholder="Название"
+                          />
+                          <input
+                            type="number"
+                            min="1"
+                            value={part.quantity}
+                            onChange={(e) => updateLinkPart(index, { quantity: parseInt(e.target.value) || 1 })}
+                          />
+                          <button
+                            type="button"
+                            onClick={() => setLinkForm({...linkForm, parts: linkForm.parts.filter((_, i) => i !== index)})}
+                          >
+                            ✖
+                          </button>
+                        </div>
+                      ))}
+                      <button
+                        type="button"
+                        onClick={() => setLinkForm({...linkForm, parts: [...linkForm.parts, { article: '', part_name: null, quantity: 1 }]})}
+                      >
+                        ➕ ЗАПЧАСТЬ
+                      </button>
+                    </div>
+                    <button className="primary-btn" onClick={handleSaveLink}>
+                      💾 СОХРАНИТЬ СВЯЗЬ
+                    </button>
+                  </>
+                )}
+              </div>
+
+              {linkDefectTypeId !== 0 && (
+                <div className="list-section">
+                  <h3>🔗 СВЯЗАННЫЕ УСЛУГИ</h3>
+                  <table className="items-table">
+                    <thead>
+                      <tr>
+                        <th>Услуга</th>
+                        <th>Роль</th>
+                        <th>Запчасти</th>
+                        <th></th>
+                      </tr>
+                    </thead>
+                    <tbody>
+                      {links.map(link => (
+                        <tr key={link.id}>
+                          <td>{link.service_name} ({formatMoney(link.base_price)})</td>
+                          <td>{ROLE_LABELS[link.role]}</td>
+                          <td>
+                            {link.parts.length > 0
+                              ? link.parts.map(part => `${part.part_name || part.article} x${part.quantity}`).join(', ')
+                              : '-'}
+                          </td>
+                          <td>
+                            <button onClick={() => handleEditLink(link)}>✏️</button>
+                            <button onClick={() => handleRemoveLink(link)}>🗑</button>
+                          </td>
+                        </tr>
+                      ))}
+                    </tbody>
+                  </table>
+                </div>
+              )}
+            </div>
+          )}
+
+          {activeTab === 'measurements' && (
+            <div className="measurements-tab">
+              <div className="form-section">
+                <h3>📏 ЗАМЕРЫ ПО НЕИСПРАВНОСТИ</h3>
+                <div className="input-group">
+                  <label htmlFor="measurement-defect-type">Тип неисправности:</label>
+                  <select
+                    id="measurement-defect-type"
+                    value={measurementDefectTypeId}
+                    onChange={(e) => handleSelectMeasurementDefectType(parseInt(e.target.value))}
+                  >
+                    <option value={0}>Выберите неисправность</option>
+                    {defectTypes.map(type => (
+                      <option key={type.id} value={type.id}>
+                        {type.node_name}: {type.name}
+                      </option>
+                    ))}
+                  </select>
+                </div>
+                {measurementDefectTypeId !== 0 && (
+                  <>
+                    <div className="input-grid">
+                      <div className="input-group">
+                        <label htmlFor="measurement-name">Что измеряется:</label>
+                        <input
+                          id="measurement-name"
+                          type="text"
+                          value={measurementForm.name}
+                          onChange={(e) => setMeasurementForm({...measurementForm, name: e.target.value})}
+                          placeholder="Например: Толщина колодок"
+                        />
+                      </div>
+                      <div className="input-group">
+                        <label htmlFor="measurement-type">Тип значения:</label>
+                        <select
+                          id="measurement-type"
+                          value={measurementForm.valueType}
+                          onChange={(e) => setMeasurementForm({...measurementForm, valueType: e.target.value as 'Number' | 'Text'})}
+                        >
+                          <option value="Number">Число</option>
+                          <option value="Text">Текст (коды ошибок и т.п.)</option>
+                        </select>
+                      </div>
+                      <div className="input-group">
+                        <label htmlFor="measurement-unit">Единица измерения:</label>
+                        <input
+                          id="measurement-unit"
+                          type="text"
+                          value={measurementForm.unit}
+                          onChange={(e) => setMeasurementForm({...measurementForm, unit: e.target.value})}
+                          placeholder="мм, В, ..."
+                        />
+                      </div>
+                      <div className="input-group">
+                        <label htmlFor="measurement-sort">Порядок:</label>
+                        <input
+                          id="measurement-sort"
+                          type="number"
+                          value={measurementForm.sortOrder}
+                          onChange={(e) => setMeasurementForm({...measurementForm, sortOrder: e.target.value})}
+                        />
+                      </div>
+                      {measurementForm.valueType === 'Number' ? (
+                        <>
+                          <div className="input-group">
+                            <label htmlFor="measurement-min">Минимум нормы:</label>
+                            <input
+                              id="measurement-min"
+                              type="text"
+                              value={measurementForm.minValue}
+                              onChange={(e) => setMeasurementForm({...measurementForm, minValue: e.target.value})}
+                            />
+                          </div>
+                          <div className="input-group">
+                            <label htmlFor="measurement-max">Максимум нормы:</label>
+                            <input
+                              id="measurement-max"
+                              type="text"
+                              value={measurementForm.maxValue}
+                              onChange={(e) => setMeasurementForm({...measurementForm, maxValue: e.target.value})}
+                            />
+                          </div>
+                        </>
+                      ) : (
+                        <div className="input-group">
+                          <label>
+                            <input
+                              type="checkbox"
+                              checked={measurementForm.flagIfPresent}
+                              onChange={(e) => setMeasurementForm({...measurementForm, flagIfPresent: e.target.checked})}
+                            />
+                            {' '}Любое заполненное значение - отклонение
+                          </label>
+                        </div>
+                      )}
+                    </div>
+                    <button className="primary-btn" onClick={handleAddMeasurement}>
+                      ➕ ДОБАВИТЬ ЗАМЕР
+                    </button>
+                  </>
+                )}
+              </div>
+
+              {measurementDefectTypeId !== 0 && (
+                <div className="list-section">
+                  <h3>📏 ЗАМЕРЫ</h3>
+                  <table className="items-table">
+                    <thead>
+                      <tr>
+                        <th>Замер</th>
+                        <th>Норма</th>
+                        <th>Порядок</th>
+                        <th></th>
+                      </tr>
+                    </thead>
+                    <tbody>
+                      {measurementTemplates.map(template => (
+                        <tr key={template.id}>
+                          <td>{template.name}</td>
+                          <td>{measurementNorm(template)}</td>
+                          <td>{template.sort_order}</td>
+                          <td>
+                            <button onClick={() => handleDeleteMeasurement(template)}>🗑</button>
+                          </td>
+                        </tr>
+                      ))}
+                    </tbody>
+                  </table>
+                </div>
+              )}
+            </div>
+          )}
+
+          {activeTab === 'dtc' && (
+            <div className="dtc-tab">
+              <div className="form-section">
+                <h3>🚗 КОДЫ ОШИБОК OBD-II</h3>
+                <div className="input-group">
+                  <label htmlFor="dtc-query">Поиск по коду или расшифровке:</label>
+                  <input
+                    id="dtc-query"
+                    type="text"
+                    value={dtcQuery}
+                    onChange={(e) => { setDtcQuery(e.target.value); loadDtcCodes(e.target.value); }}
+                    placeholder="P0300"
+                  />
+                </div>
+                <button className="primary-btn" onClick={handleLoadBundledDtcCodes}>
+                  📥 ЗАГРУЗИТЬ СТАНДАРТНЫЕ КОДЫ
+                </button>
+                <label className="primary-btn">
+                  📄 ИМПОРТ ИЗ ФАЙЛА
+                  <input type="file" accept=".csv,.txt,.xlsx,.xls,.ods" onChange={handleImportDtcCodes} hidden />
+                </label>
+                {dtcReport && (
+                  <p>
+                    Импорт: строк {dtcReport.total_rows}, добавлено или обновлено {dtcReport.imported}, отклонено {dtcReport.rejected_count}
+                    {dtcReport.linked_defect_types > 0 && `, связано с типами неисправностей: ${dtcReport.linked_defect_types}`}
+                    {dtcReport.rejected.length > 0 && ` (первая ошибка: строка ${dtcReport.rejected[0].row} - ${dtcReport.rejected[0].reason})`}
+                  </p>
+                )}
+              </div>
+
+              <div className="list-section">
+                <h3>📋 КОДЫ ({dtcCodes.length})</h3>
+                <table className="items-table">
+                  <thead>
+                    <tr>
+                      <th>Код</th>
+                      <th>Расшифровка</th>
+                      <th>Предлагаемые неисправности</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {dtcCodes.map(code => (
+                      <tr key={code.id}>
+                        <td>{code.code}</td>
+                        <td>{code.description}</td>
+                        <td>
+                          {code.defect_types.map(type => (
+                            <div key={type.id}>
+                              {type.node_name}: {type.name}{' '}
+                              <button
+                                onClick={() => handleSetDtcDefectTypes(code, code.defect_types.filter(t => t.id !== type.id).map(t => t.id))}
+                              >
+                                ✖
+                              </button>
+                            </div>
+                          ))}
+                          <select
+                            value={0}
+                            onChange={(e) => handleSetDtcDefectTypes(code, [...code.defect_types.map(t => t.id), parseInt(e.target.value)])}
+                          >
+                            <option value={0}>➕ Добавить неисправность</option>
+                            {defectTypes
+                              .filter(type => !code.defect_types.some(t => t.id === type.id))
+                              .map(type => (
+                                <option key={type.id} value={type.id}>
+                                  {type.node_name}: {type.name}
+                                </option>
+                              ))}
+                          </select>
+                        </td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              </div>
+            </div>
+          )}
         </div>
       </div>
     </div>
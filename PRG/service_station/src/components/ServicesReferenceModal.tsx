@@ -1,11 +1,12 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './ServicesReferenceModal.css';
+import { Money, formatMoney, moneyInput } from '../types/money';
 
 interface Service {
   id: number;
   name: string;
-  base_price: string; // DECIMAL as string
+  base_price: Money;
   norm_hours: string; // DECIMAL as string
 }
 
@@ -75,7 +76,7 @@ const ServicesReferenceModal: React.FC<ServicesReferenceModalProps> = ({ isOpen,
   const loadDefectNodes = async () => {
     try {
       setLoadingServices(true); // Используем то же состояние, что и для загрузки услуг
-      const nodes = await invoke<DefectNode[]>('get_defect_nodes');
+      const nodes = await invoke<DefectNode[]>('get_defect_nodes', { sessionToken: localStorage.getItem('sessionToken') });
       setDefectNodes(nodes);
     } catch (error) {
       console.error('Error loading defect nodes:', error);
@@ -87,7 +88,7 @@ const ServicesReferenceModal: React.FC<ServicesReferenceModalProps> = ({ isOpen,
   const loadServices = async () => {
     try {
       setLoadingServices(true);
-      const servicesData = await invoke<Service[]>('get_all_services');
+      const servicesData = await invoke<Service[]>('get_all_services', { sessionToken: localStorage.getItem('sessionToken') });
       setServices(servicesData);
     } catch (error) {
       console.error('Error loading services:', error);
@@ -115,8 +116,8 @@ const ServicesReferenceModal: React.FC<ServicesReferenceModalProps> = ({ isOpen,
       await invoke('create_service', {
         sessionToken,
         name: newService.name,
-        basePrice: parseFloat(newService.base_price) || 0,
-        normHours: parseFloat(newService.norm_hours) || 0
+        basePrice: moneyInput(newService.base_price),
+        normHours: newService.norm_hours.trim() || '0'
       });
 
       // Reset form
@@ -438,7 +439,7 @@ const ServicesReferenceModal: React.FC<ServicesReferenceModalProps> = ({ isOpen,
                     {services.map(service => (
                       <tr key={service.id}>
                         <td>{service.name}</td>
-                        <td>{parseFloat(service.base_price).toFixed(2)}</td>
+                        <td>{formatMoney(service.base_price)}</td>
                         <td>{parseFloat(service.norm_hours).toFixed(2)}</td>
                       </tr>
                     ))}
@@ -6,6 +6,11 @@ import PartsSelectionModal from './PartsSelectionModal';
 import IssuePartsModal from './IssuePartsModal';
 import WarehouseStockModal from './WarehouseStockModal';
 import AddPartToWarehouseModal from './AddPartToWarehouseModal';
+import ReorderProposalsModal from './ReorderProposalsModal';
+import PurchaseOrdersModal from './PurchaseOrdersModal';
+import PriceListImportModal from './PriceListImportModal';
+import CrossReferencesModal from './CrossReferencesModal';
+import { Money } from '../types/money';
 
 interface Order {
   id: number;
@@ -16,8 +21,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null; // Decimal as string
-  total_amount: string | null; // Decimal as string
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -57,7 +62,7 @@ interface PartSuggestion {
   name: string;
   brand: string;
   supplier: string;
-  price: number;
+  price: Money;
   availability: string;
   part_number: string;
 }
@@ -75,6 +80,10 @@ const StorekeeperDashboard: React.FC = () => {
   const [showPartsSelectionModal, setShowPartsSelectionModal] = useState(false);
   const [showIssuePartsModal, setShowIssuePartsModal] = useState(false);
   const [showWarehouseStockModal, setShowWarehouseStockModal] = useState(false);
+  const [showReorderModal, setShowReorderModal] = useState(false);
+  const [showPurchaseOrdersModal, setShowPurchaseOrdersModal] = useState(false);
+  const [showPriceListImportModal, setShowPriceListImportModal] = useState(false);
+  const [showCrossReferencesModal, setShowCrossReferencesModal] = useState(false);
   const [showAddPartModal, setShowAddPartModal] = useState(false);
   const [selectedOrderForParts, setSelectedOrderForParts] = useState<Order | null>(null);
   const [selectedOrderForIssuance, setSelectedOrderForIssuance] = useState<Order | null>(null);
@@ -114,7 +123,7 @@ const StorekeeperDashboard: React.FC = () => {
       setLoading(true);
 
       // Загрузим все заказы для кладовщика (всё, что не закрыто и не отменено)
-      const ordersData = await invoke<Order[]>('get_orders_for_storekeeper');
+      const ordersData = await invoke<Order[]>('get_orders_for_storekeeper', { sessionToken: localStorage.getItem('sessionToken') });
       setOrders(ordersData);
 
       // Загрузим информацию о клиентах и автомобилях для каждого заказа
@@ -125,7 +134,7 @@ const StorekeeperDashboard: React.FC = () => {
       for (const clientId of uniqueClientIds) {
         if (!clients[clientId]) {
           try {
-            const clientData = await invoke<Client | null>('get_client_by_id', { clientId });
+            const clientData = await invoke<Client | null>('get_client_by_id', { sessionToken: localStorage.getItem('sessionToken'), clientId });
             if (clientData) {
               setClients(prev => ({ ...prev, [clientId]: clientData }));
             }
@@ -139,7 +148,7 @@ const StorekeeperDashboard: React.FC = () => {
       for (const carId of uniqueCarIds) {
         if (!cars[carId]) {
           try {
-            const carData = await invoke<Car | null>('get_car_by_id', { carId });
+            const carData = await invoke<Car | null>('get_car_by_id', { sessionToken: localStorage.getItem('sessionToken'), carId });
             if (carData) {
               setCars(prev => ({ ...prev, [carId]: carData }));
             }
@@ -152,7 +161,7 @@ const StorekeeperDashboard: React.FC = () => {
       // Загрузим результаты диагностики
       for (const order of ordersData) {
         try {
-          const results = await invoke<OrderDefect[]>('get_diagnostic_results_by_order_id', { orderId: order.id });
+          const results = await invoke<OrderDefect[]>('get_diagnostic_results_by_order_id', { sessionToken: localStorage.getItem('sessionToken'), orderId: order.id });
           setOrderDefects(prev => ({ ...prev, [order.id]: results }));
         } catch (error) {
           console.error(`Error loading diagnostic results for order ${order.id}:`, error);
@@ -324,6 +333,30 @@ const StorekeeperDashboard: React.FC = () => {
           >
             🔍 СКЛАД (Остатки)
           </button>
+          <button
+            className="secondary-btn"
+            onClick={() => setShowReorderModal(true)}
+          >
+            🛒 К ДОЗАКАЗУ
+          </button>
+          <button
+            className="secondary-btn"
+            onClick={() => setShowPurchaseOrdersModal(true)}
+          >
+            📦 ЗАКАЗЫ ПОСТАВЩИКАМ
+          </button>
+          <button
+            className="secondary-btn"
+            onClick={() => setShowPriceListImportModal(true)}
+          >
+            📄 ИМПОРТ ПРАЙС-ЛИСТА
+          </button>
+          <button
+            className="secondary-btn"
+            onClick={() => setShowCrossReferencesModal(true)}
+          >
+            🔁 АНАЛОГИ
+          </button>
           <button
             className="primary-btn"
             onClick={() => setShowAddPartModal(true)}
@@ -487,6 +520,35 @@ const StorekeeperDashboard: React.FC = () => {
         />
       )}
 
+      {showReorderModal && (
+        <ReorderProposalsModal
+          isOpen={showReorderModal}
+          onClose={() => setShowReorderModal(false)}
+        />
+      )}
+
+      {showPurchaseOrdersModal && (
+        <PurchaseOrdersModal
+          isOpen={showPurchaseOrdersModal}
+          onClose={() => setShowPurchaseOrdersModal(false)}
+          onGoodsReceived={loadOrders}
+        />
+      )}
+
+      {showPriceListImportModal && (
+        <PriceListImportModal
+          isOpen={showPriceListImportModal}
+          onClose={() => setShowPriceListImportModal(false)}
+        />
+      )}
+
+      {showCrossReferencesModal && (
+        <CrossReferencesModal
+          isOpen={showCrossReferencesModal}
+          onClose={() => setShowCrossReferencesModal(false)}
+        />
+      )}
+
       {showAddPartModal && (
         <AddPartToWarehouseModal
           isOpen={showAddPartModal}
@@ -1,17 +1,15 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import AddWarehouseItemModal from './AddWarehouseItemModal';
+import PurchaseOrdersModal from './PurchaseOrdersModal';
+import EditWarehouseItemModal, { WarehouseItem } from './EditWarehouseItemModal';
 import './WarehouseStockModal.css';
 
-interface WarehouseItem {
-  id: number;
-  name: string;
-  brand: string;
-  part_number: string;
-  storage_location: string;
-  quantity: number;
-  min_quantity: number;
-  status: string;
+interface WarehouseItemsPage {
+  items: WarehouseItem[];
+  total: number;
+  page: number;
+  page_size: number;
 }
 
 interface WarehouseStockModalProps {
@@ -19,86 +17,87 @@ interface WarehouseStockModalProps {
   onClose: () => void;
 }
 
+const PAGE_SIZE = 50;
+
 const WarehouseStockModal: React.FC<WarehouseStockModalProps> = ({ isOpen, onClose }) => {
   const [searchQuery, setSearchQuery] = useState('');
+  const [belowMinOnly, setBelowMinOnly] = useState(false);
+  const [includeArchived, setIncludeArchived] = useState(false);
+  const [page, setPage] = useState(1);
   const [warehouseItems, setWarehouseItems] = useState<WarehouseItem[]>([]);
-  const [filteredItems, setFilteredItems] = useState<WarehouseItem[]>([]);
+  const [total, setTotal] = useState(0);
   const [loading, setLoading] = useState(true);
   const [showAddItemModal, setShowAddItemModal] = useState(false);
+  const [editingItem, setEditingItem] = useState<WarehouseItem | null>(null);
+  const [showPurchaseOrdersModal, setShowPurchaseOrdersModal] = useState(false);
 
   useEffect(() => {
     if (isOpen) {
       loadWarehouseStock();
     }
-  }, [isOpen]);
-
-  useEffect(() => {
-    if (warehouseItems.length > 0) {
-      const filtered = warehouseItems.filter(item =>
-        item.name.toLowerCase().includes(searchQuery.toLowerCase()) ||
-        item.brand.toLowerCase().includes(searchQuery.toLowerCase()) ||
-        item.part_number.toLowerCase().includes(searchQuery.toLowerCase())
-      );
-      setFilteredItems(filtered);
-    }
-  }, [searchQuery, warehouseItems]);
+  }, [isOpen, page, belowMinOnly, includeArchived]);
 
   const loadWarehouseStock = async () => {
     try {
       setLoading(true);
-      // В реальном приложении вызываем API для получения остатков на складе
-      // Временно используем заглушку
-      const mockItems: WarehouseItem[] = [
-        {
-          id: 1,
-          name: "Масло Shell 5W30",
-          brand: "Shell",
-          part_number: "SH-5W30-4L",
-          storage_location: "A-05-12",
-          quantity: 15,
-          min_quantity: 5,
-          status: "Достаточно"
-        },
-        {
-          id: 2,
-          name: "Рычаг передний",
-          brand: "Lemforder",
-          part_number: "LEM-30333",
-          storage_location: "B-12-03",
-          quantity: 2,
-          min_quantity: 2,
-          status: "⚠️ На минимуме"
+      const result = await invoke<WarehouseItemsPage>('get_warehouse_items', {
+        sessionToken: localStorage.getItem('sessionToken'),
+        filter: {
+          search: searchQuery,
+          belowMin: belowMinOnly,
+          includeArchived
         },
-        {
-          id: 3,
-          name: "Фильтр масляный",
-          brand: "Mann",
-          part_number: "MW-68/3",
-          storage_location: "C-01-07",
-          quantity: 1,
-          min_quantity: 5,
-          status: "⚠️ Низкий остаток"
-        },
-        {
-          id: 4,
-          name: "Колодки торм.",
-          brand: "Patron",
-          part_number: "PTR-PS5005",
-          storage_location: "A-08-15",
-          quantity: 8,
-          min_quantity: 3,
-          status: "Достаточно"
-        }
-      ];
-      setWarehouseItems(mockItems);
-      setFilteredItems(mockItems);
+        page,
+        pageSize: PAGE_SIZE
+      });
+      setWarehouseItems(result.items);
+      setTotal(result.total);
     } catch (error) {
       console.error('Error loading warehouse stock:', error);
+      setWarehouseItems([]);
+      setTotal(0);
     } finally {
       setLoading(false);
     }
   };
 
+  const handleSearch = () => {
+    // Поиск всегда начинается с первой страницы
+    if (page === 1) {
+      loadWarehouseStock();
+    } else {
+      setPage(1);
+    }
+  };
+
+  // Сверка остатков с журналом движений по складу
+  const handleReconcile = async () => {
+    try {
+      const sessionToken = localStorage.getItem('sessionToken');
+      const discrepancies = await invoke<{ warehouse_item_id: number; name: string; quantity: number; ledger_quantity: number }[]>(
+        'get_stock_discrepancies', { sessionToken }
+      );
+
+      if (discrepancies.length === 0) {
+        alert('Остатки совпадают с журналом движений');
+        return;
+      }
+
+      const details = discrepancies
+        .map(d => `${d.name}: остаток ${d.quantity}, по журналу ${d.ledger_quantity}`)
+        .join('\n');
+      if (!confirm(`Найдены расхождения:\n${details}\n\nЗаписать корректировки по фактическим остаткам?`)) return;
+
+      const result = await invoke<string>('reconcile_stock_ledger', { sessionToken });
+      alert(result);
+    } catch (error) {
+      console.error('Error reconciling stock ledger:', error);
+      alert('Ошибка при сверке остатков: ' + error);
+    }
+  };
+
+  const totalPages = Math.max(1, Math.ceil(total / PAGE_SIZE));
+
   if (!isOpen) return null;
 
   return (
@@ -115,11 +114,30 @@ const WarehouseStockModal: React.FC<WarehouseStockModalProps> = ({ isOpen, onClo
               <div className="search-input-container">
                 <input
                   type="text"
-                  placeholder="Поиск: деталь, бренд, артикул..."
+                  placeholder="Поиск: деталь, бренд, артикул, ячейка..."
                   value={searchQuery}
                   onChange={(e) => setSearchQuery(e.target.value)}
+                  onKeyDown={(e) => e.key === 'Enter' && handleSearch()}
                 />
-                <button className="search-btn">🔍</button>
+                <button className="search-btn" onClick={handleSearch}>🔍</button>
+              </div>
+              <div className="search-filters">
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={belowMinOnly}
+                    onChange={(e) => { setBelowMinOnly(e.target.checked); setPage(1); }}
+                  />
+                  Только ниже минимального остатка
+                </label>
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={includeArchived}
+                    onChange={(e) => { setIncludeArchived(e.target.checked); setPage(1); }}
+                  />
+                  Показывать архивные позиции
+                </label>
               </div>
             </div>
 
@@ -131,6 +149,8 @@ const WarehouseStockModal: React.FC<WarehouseStockModalProps> = ({ isOpen, onClo
                     <th>Артикул</th>
                     <th>Ячейка</th>
                     <th>Остаток</th>
+                    <th>Резерв</th>
+                    <th>Доступно</th>
                     <th>Мин.</th>
                     <th>Статус</th>
                   </tr>
@@ -138,41 +158,60 @@ const WarehouseStockModal: React.FC<WarehouseStockModalProps> = ({ isOpen, onClo
                 <tbody>
                   {loading ? (
                     <tr>
-                      <td colSpan={6}>Загрузка остатков...</td>
+                      <td colSpan={8}>Загрузка остатков...</td>
                     </tr>
-                  ) : filteredItems.length > 0 ? (
-                    filteredItems.map(item => (
-                      <tr key={item.id}>
+                  ) : warehouseItems.length > 0 ? (
+                    warehouseItems.map(item => (
+                      <tr key={item.id} className="stock-row" onClick={() => setEditingItem(item)}>
                         <td>
                           <div>{item.name}</div>
                           <div className="item-brand">{item.brand}</div>
                         </td>
-                        <td>{item.part_number}</td>
-                        <td>{item.storage_location}</td>
+                        <td>{item.article || '—'}</td>
+                        <td>{item.location_cell || '—'}</td>
                         <td>{item.quantity} шт.</td>
+                        <td>{item.reserved} шт.</td>
+                        <td className={item.available <= 0 ? 'low-stock' : ''}>{item.available} шт.</td>
                         <td>{item.min_quantity} шт.</td>
-                        <td className={item.quantity <= item.min_quantity ? 'low-stock' : ''}>
-                          {item.quantity <= item.min_quantity ? '⚠️ Низкий остаток' : 'Норма'}
+                        <td className={item.available < item.min_quantity ? 'low-stock' : ''}>
+                          {item.is_archived
+                            ? '🗄️ В архиве'
+                            : item.available < item.min_quantity ? '⚠️ Низкий остаток' : 'Норма'}
                         </td>
                       </tr>
                     ))
                   ) : (
                     <tr>
-                      <td colSpan={6}>Ничего не найдено</td>
+                      <td colSpan={8}>Ничего не найдено</td>
                     </tr>
                   )}
                 </tbody>
               </table>
             </div>
+
+            <div className="pagination">
+              <button disabled={page <= 1 || loading} onClick={() => setPage(page - 1)}>◀</button>
+              <span>Страница {page} из {totalPages} (позиций: {total})</span>
+              <button disabled={page >= totalPages || loading} onClick={() => setPage(page + 1)}>▶</button>
+            </div>
           </div>
 
           <div className="modal-actions">
             <button className="secondary-btn" onClick={() => setShowAddItemModal(true)}>➕ ДОБАВИТЬ ПОЗИЦИЮ</button>
-            <button className="primary-btn">➕ ЗАКАЗАТЬ У ПОСТАВЩИКА</button>
+            <button className="secondary-btn" onClick={handleReconcile}>🧮 СВЕРКА ОСТАТКОВ</button>
+            <button className="primary-btn" onClick={() => setShowPurchaseOrdersModal(true)}>➕ ЗАКАЗАТЬ У ПОСТАВЩИКА</button>
           </div>
         </div>
       </div>
 
+      {showPurchaseOrdersModal && (
+        <PurchaseOrdersModal
+          isOpen={showPurchaseOrdersModal}
+          onClose={() => setShowPurchaseOrdersModal(false)}
+          onGoodsReceived={() => loadWarehouseStock()}
+        />
+      )}
+
       {showAddItemModal && (
         <AddWarehouseItemModal
           isOpen={showAddItemModal}
@@ -180,6 +219,15 @@ const WarehouseStockModal: React.FC<WarehouseStockModalProps> = ({ isOpen, onClo
           onItemAdded={() => loadWarehouseStock()} // Перезагружаем список после добавления
         />
       )}
+
+      {editingItem && (
+        <EditWarehouseItemModal
+          isOpen={!!editingItem}
+          item={editingItem}
+          onClose={() => setEditingItem(null)}
+          onItemUpdated={() => loadWarehouseStock()}
+        />
+      )}
     </>
   );
 };
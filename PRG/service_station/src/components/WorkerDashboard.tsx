@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import './WorkerDashboard.css';
 import OrderExecutionModal from './OrderExecutionModal';
+import { Money } from '../types/money';
 
 // Define TypeScript interfaces
 interface Order {
@@ -13,8 +14,8 @@ interface Order {
   status: string;
   complaint: string | null;
   current_mileage: number | null;
-  prepayment: string | null;
-  total_amount: string | null;
+  prepayment: Money | null;
+  total_amount: Money | null;
   created_at: string;
   completed_at: string | null;
 }
@@ -103,7 +104,7 @@ const WorkerDashboard: React.FC = () => {
 
   const loadWorkerOrders = async (workerId: number) => {
     try {
-      const ordersData = await invoke<Order[]>('get_orders_for_worker', { workerId });
+      const ordersData = await invoke<Order[]>('get_orders_for_worker', { sessionToken: localStorage.getItem('sessionToken'), workerId });
       setOrders(ordersData);
 
       // Загрузим информацию об автомобилях для отображения
@@ -111,7 +112,7 @@ const WorkerDashboard: React.FC = () => {
       for (const carId of uniqueCarIds) {
         if (!cars[carId]) {
           try {
-            const carData = await invoke<any>('get_car_by_id', { carId });
+            const carData = await invoke<any>('get_car_by_id', { sessionToken: localStorage.getItem('sessionToken'), carId });
             if (carData) {
               setCars(prev => ({ ...prev, [carId]: {
                 make: carData.make,
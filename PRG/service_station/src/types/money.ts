@@ -0,0 +1,36 @@
+// Денежная сумма в том виде, в котором её передаёт бэкенд: точное значение строкой и код валюты
+export interface Money {
+  amount: string; // Всегда с двумя знаками после запятой
+  currency: string;
+}
+
+// Сумма для отображения: "12.50 BYN"
+export const formatMoney = (money: Money | null | undefined, empty = '—'): string =>
+  money ? `${money.amount} ${money.currency}` : empty;
+
+// Числовое значение только для сравнений в интерфейсе, итоги считает бэкенд
+export const moneyValue = (money: Money | null | undefined): number =>
+  money ? parseFloat(money.amount) : 0;
+
+// Арифметика для отображения ведётся в целых копейках, чтобы не накапливать ошибки float
+const toCents = (money: Money | null | undefined): number =>
+  money ? Math.round(parseFloat(money.amount) * 100) : 0;
+
+const fromCents = (cents: number, currency: string): Money => ({
+  amount: (cents / 100).toFixed(2),
+  currency
+});
+
+// Стоимость строки: цена за единицу × количество
+export const multiplyMoney = (money: Money, quantity: number): Money =>
+  fromCents(toCents(money) * quantity, money.currency);
+
+export const sumMoney = (values: (Money | null | undefined)[], currency = 'BYN'): Money =>
+  fromCents(values.reduce((sum, value) => sum + toCents(value), 0), values.find(v => v)?.currency ?? currency);
+
+export const subtractMoney = (money: Money, other: Money): Money =>
+  fromCents(toCents(money) - toCents(other), money.currency);
+
+// Сумма из поля ввода передаётся строкой, чтобы не терять точность на float
+export const moneyInput = (value: string | number): string =>
+  String(value).trim().replace(',', '.') || '0';
@@ -1,9 +1,11 @@
+import { Money } from './money';
+
 export interface OrderWork {
   id: number;
   order_id: number;
   service_id?: number;
   service_name_snapshot: string;
-  price: number;
+  price: Money;
   worker_id?: number | null;
   status: string;
   is_confirmed: boolean;
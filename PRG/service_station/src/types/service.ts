@@ -1,6 +1,8 @@
+import { Money } from './money';
+
 export interface Service {
   id: number;
   name: string;
-  base_price: string; // DECIMAL as string
+  base_price: Money;
   norm_hours: string; // DECIMAL as string
 }
\ No newline at end of file